@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sensor_common::Request;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(request) = Request::read(&mut &data[..]) {
+        let mut encoded = Vec::new();
+        request.write(&mut encoded).unwrap();
+        assert_eq!(Ok(request), Request::read(&mut &encoded[..]));
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sensor_common::Response;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(response) = Response::read(&mut &data[..]) {
+        let mut encoded = Vec::new();
+        response.write(&mut encoded).unwrap();
+        assert_eq!(Ok(response), Response::read(&mut &encoded[..]));
+    }
+});
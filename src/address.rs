@@ -0,0 +1,203 @@
+//! The list of device addresses in the payload of a [`crate::Request::ReadSpecified`].
+//!
+//! Addresses of buses with a fixed address length (see [`Bus::address_len`]) are concatenated
+//! without separator, addresses of [`Bus::Custom`] buses are prefixed by their length as `u8`.
+
+use crate::hex::Hex;
+use crate::{Bus, Error, Write};
+
+impl Bus {
+    /// The length in bytes of the address of a device on this bus, `None` if it is not fixed
+    pub const fn address_len(&self) -> Option<usize> {
+        match self {
+            Bus::OneWire => Some(8),
+            Bus::I2C => Some(1),
+            Bus::I2cN { ten_bit, .. } => Some(if *ten_bit { 2 } else { 1 }),
+            Bus::ModbusRtu { .. } => Some(crate::modbus::Register::ADDRESS_LEN),
+            Bus::Can { extended } => Some(if *extended { 4 } else { 2 }),
+            Bus::Analog => Some(1),
+            Bus::Custom(_) => None,
+        }
+    }
+}
+
+/// Writes the addresses, fails with [`Error::UnknownTypeIdentifier`] if an address does not
+/// have the length required by the bus
+pub fn encode_address_list<'a>(
+    bus: Bus,
+    addresses: impl Iterator<Item = &'a [u8]>,
+    writer: &mut impl Write,
+) -> Result<usize, Error> {
+    let mut len = 0;
+    for address in addresses {
+        len += match bus.address_len() {
+            Some(address_len) if address_len == address.len() => writer.write_all(address)?,
+            Some(_) => return Err(Error::UnknownTypeIdentifier),
+            None => writer.write_dyn_bytes(address)?,
+        };
+    }
+    Ok(len)
+}
+
+/// Decodes the addresses of a [`encode_address_list`] payload. Stops after the first malformed
+/// address.
+pub struct AddressListIter<'a> {
+    bus: Bus,
+    remaining: &'a [u8],
+}
+
+impl<'a> AddressListIter<'a> {
+    pub const fn new(bus: Bus, payload: &'a [u8]) -> Self {
+        Self {
+            bus,
+            remaining: payload,
+        }
+    }
+}
+
+impl<'a> Iterator for AddressListIter<'a> {
+    type Item = Result<&'a [u8], Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (len, offset) = match self.bus.address_len() {
+            _ if self.remaining.is_empty() => return None,
+            Some(len) => (len, 0),
+            None => (usize::from(self.remaining[0]), 1),
+        };
+
+        if self.remaining.len() < offset + len {
+            self.remaining = &[];
+            Some(Err(Error::UnexpectedEOF))
+        } else {
+            let (address, remaining) = self.remaining[offset..].split_at(len);
+            self.remaining = remaining;
+            Some(Ok(address))
+        }
+    }
+}
+
+/// The 64-bit ROM code of a OneWire device, formatted as colon separated hex bytes
+/// (`"28:ff:4c:6e:71:16:04:2a"`)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct OneWireAddress(pub [u8; 8]);
+
+impl OneWireAddress {
+    /// The family code of the device, for example `0x28` for a DS18B20
+    pub const fn family(&self) -> u8 {
+        self.0[0]
+    }
+
+    /// Whether the last byte is the Dallas/Maxim CRC-8 of the first seven bytes
+    pub const fn is_crc_valid(&self) -> bool {
+        let mut crc = 0u8;
+        let mut i = 0;
+        while i < 7 {
+            let mut byte = self.0[i];
+            let mut bit = 0;
+            while bit < 8 {
+                let mix = (crc ^ byte) & 0x01;
+                crc >>= 1;
+                if mix != 0 {
+                    crc ^= 0x8C;
+                }
+                byte >>= 1;
+                bit += 1;
+            }
+            i += 1;
+        }
+        crc == self.0[7]
+    }
+}
+
+impl AsRef<[u8]> for OneWireAddress {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<[u8; 8]> for OneWireAddress {
+    fn from(address: [u8; 8]) -> Self {
+        OneWireAddress(address)
+    }
+}
+
+impl From<OneWireAddress> for [u8; 8] {
+    fn from(address: OneWireAddress) -> Self {
+        address.0
+    }
+}
+
+impl core::str::FromStr for OneWireAddress {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut address = [0u8; 8];
+        let mut segments = s.split(':');
+        for byte in address.iter_mut() {
+            *byte = segments
+                .next()
+                .and_then(|segment| u8::from_str_radix(segment, 16).ok())
+                .ok_or(Error::Unparsable)?;
+        }
+        if segments.next().is_some() {
+            return Err(Error::Unparsable);
+        }
+        Ok(OneWireAddress(address))
+    }
+}
+
+impl core::fmt::Display for OneWireAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Hex::colons(&self.0).fmt(f)
+    }
+}
+
+#[cfg(feature = "onewire")]
+impl From<onewire::Device> for OneWireAddress {
+    fn from(device: onewire::Device) -> Self {
+        OneWireAddress(device.address)
+    }
+}
+
+#[cfg(feature = "onewire")]
+impl From<&onewire::Device> for OneWireAddress {
+    fn from(device: &onewire::Device) -> Self {
+        OneWireAddress(device.address)
+    }
+}
+
+#[cfg(feature = "onewire")]
+impl From<OneWireAddress> for onewire::Device {
+    fn from(address: OneWireAddress) -> Self {
+        onewire::Device { address: address.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn onewire_address_crc() {
+        // the example of the Maxim application note 27
+        let address: OneWireAddress = "02:1c:b8:01:00:00:00:a2".parse().unwrap();
+        assert!(address.is_crc_valid());
+        assert!(!OneWireAddress([0x02, 0x1C, 0xB8, 0x01, 0x00, 0x00, 0x00, 0xA3]).is_crc_valid());
+    }
+
+    #[test]
+    fn onewire_address_parse_errors() {
+        let address = OneWireAddress([0x28, 0xFF, 0x4C, 0x01, 0x00, 0x00, 0x00, 0x0A]);
+        assert_eq!(Ok(address), "28:ff:4c:01:00:00:00:0a".parse());
+
+        assert_eq!(Err(Error::Unparsable), "28:ff:4c".parse::<OneWireAddress>());
+        assert_eq!(
+            Err(Error::Unparsable),
+            "28:ff:4c:01:00:00:00:0a:00".parse::<OneWireAddress>()
+        );
+        assert_eq!(
+            Err(Error::Unparsable),
+            "28:ff:4c:01:00:00:00:zz".parse::<OneWireAddress>()
+        );
+    }
+}
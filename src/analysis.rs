@@ -0,0 +1,538 @@
+//! Offline analysis of captured traffic.
+//!
+//! [`PcapReader`] extracts the UDP datagrams of a classic pcap capture (as written by tcpdump
+//! or Wireshark) of Ethernet, Linux cooked or raw IP frames. [`analyze`] pairs the requests to
+//! a port with the responses from that port by their request id.
+
+use crate::compression::{decompress_response, COMPRESSED_RESPONSE_MARKER};
+use crate::headers::skip_request_headers;
+use crate::routing::RoutingHeader;
+use crate::{Request, Response};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_IPV4: u32 = 228;
+const LINKTYPE_IPV6: u32 = 229;
+
+/// The largest packet a capture record may claim to contain, the default snapshot length of
+/// tcpdump and Wireshark
+pub const MAX_CAPTURED_LEN: usize = 256 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UdpDatagram {
+    /// The capture time since the unix epoch
+    pub timestamp: Duration,
+    pub source: SocketAddr,
+    pub destination: SocketAddr,
+    pub payload: Vec<u8>,
+}
+
+/// Reads the UDP datagrams of a pcap capture, other packets are skipped
+pub struct PcapReader<R: io::Read> {
+    reader: R,
+    big_endian: bool,
+    nanos: bool,
+    snaplen: usize,
+    link_type: u32,
+}
+
+impl<R: io::Read> PcapReader<R> {
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut header = [0u8; 24];
+        reader.read_exact(&mut header)?;
+        let (big_endian, nanos) = match [header[0], header[1], header[2], header[3]] {
+            [0xA1, 0xB2, 0xC3, 0xD4] => (true, false),
+            [0xD4, 0xC3, 0xB2, 0xA1] => (false, false),
+            [0xA1, 0xB2, 0x3C, 0x4D] => (true, true),
+            [0x4D, 0x3C, 0xB2, 0xA1] => (false, true),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "not a pcap capture",
+                ))
+            }
+        };
+        let snaplen = u32_at(&header, 16, big_endian) as usize;
+        let link_type = u32_at(&header, 20, big_endian);
+        Ok(Self {
+            reader,
+            big_endian,
+            nanos,
+            snaplen,
+            link_type,
+        })
+    }
+
+    /// The next UDP datagram, `None` at the end of the capture. Fails for records longer than
+    /// the snapshot length of the capture or [`MAX_CAPTURED_LEN`].
+    pub fn next_udp(&mut self) -> io::Result<Option<UdpDatagram>> {
+        loop {
+            let mut header = [0u8; 16];
+            match self.reader.read_exact(&mut header) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e),
+            }
+
+            let seconds = u32_at(&header, 0, self.big_endian);
+            let fraction = u32_at(&header, 4, self.big_endian);
+            let captured_len = u32_at(&header, 8, self.big_endian) as usize;
+            if captured_len > self.snaplen || captured_len > MAX_CAPTURED_LEN {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "captured length exceeds the snapshot length",
+                ));
+            }
+
+            let mut packet = vec![0u8; captured_len];
+            self.reader.read_exact(&mut packet)?;
+
+            let timestamp = Duration::from_secs(u64::from(seconds))
+                + if self.nanos {
+                    Duration::from_nanos(u64::from(fraction))
+                } else {
+                    Duration::from_micros(u64::from(fraction))
+                };
+
+            if let Some(datagram) = self.decode(timestamp, &packet) {
+                return Ok(Some(datagram));
+            }
+        }
+    }
+
+    fn decode(&self, timestamp: Duration, packet: &[u8]) -> Option<UdpDatagram> {
+        let ip = match self.link_type {
+            LINKTYPE_ETHERNET => {
+                let mut offset = 12;
+                // skip VLAN tags
+                while matches!(packet.get(offset..offset + 2)?, [0x81, 0x00] | [0x88, 0xA8]) {
+                    offset += 4;
+                }
+                match packet.get(offset..offset + 2)? {
+                    [0x08, 0x00] | [0x86, 0xDD] => packet.get(offset + 2..)?,
+                    _ => return None,
+                }
+            }
+            LINKTYPE_LINUX_SLL => packet.get(16..)?,
+            LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => packet,
+            _ => return None,
+        };
+
+        let (source, destination, udp) = match ip.first()? >> 4 {
+            4 => {
+                let header_len = usize::from(ip[0] & 0x0F) * 4;
+                if header_len < 20 || *ip.get(9)? != 17 {
+                    return None;
+                }
+                let addresses = ip.get(12..20)?;
+                (
+                    IpAddr::V4(Ipv4Addr::new(
+                        addresses[0],
+                        addresses[1],
+                        addresses[2],
+                        addresses[3],
+                    )),
+                    IpAddr::V4(Ipv4Addr::new(
+                        addresses[4],
+                        addresses[5],
+                        addresses[6],
+                        addresses[7],
+                    )),
+                    ip.get(header_len..)?,
+                )
+            }
+            6 => {
+                if *ip.get(6)? != 17 {
+                    return None;
+                }
+                let mut source = [0u8; 16];
+                let mut destination = [0u8; 16];
+                source.copy_from_slice(ip.get(8..24)?);
+                destination.copy_from_slice(ip.get(24..40)?);
+                (
+                    IpAddr::V6(Ipv6Addr::from(source)),
+                    IpAddr::V6(Ipv6Addr::from(destination)),
+                    ip.get(40..)?,
+                )
+            }
+            _ => return None,
+        };
+
+        let source_port = u16::from_be_bytes([*udp.first()?, *udp.get(1)?]);
+        let destination_port = u16::from_be_bytes([*udp.get(2)?, *udp.get(3)?]);
+        let len = usize::from(u16::from_be_bytes([*udp.get(4)?, *udp.get(5)?]));
+
+        Some(UdpDatagram {
+            timestamp,
+            source: SocketAddr::new(source, source_port),
+            destination: SocketAddr::new(destination, destination_port),
+            payload: udp.get(8..len.max(8).min(udp.len()))?.to_vec(),
+        })
+    }
+}
+
+fn u32_at(bytes: &[u8], offset: usize, big_endian: bool) -> u32 {
+    let value = [
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ];
+    if big_endian {
+        u32::from_be_bytes(value)
+    } else {
+        u32::from_le_bytes(value)
+    }
+}
+
+/// A request and its response, if any
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exchange {
+    pub request: Request,
+    pub request_datagram: UdpDatagram,
+    pub response: Option<(Response, UdpDatagram)>,
+}
+
+impl Exchange {
+    /// The time between the request and its response
+    pub fn rtt(&self) -> Option<Duration> {
+        self.response.as_ref().and_then(|(_, response)| {
+            response
+                .timestamp
+                .checked_sub(self.request_datagram.timestamp)
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    /// The exchanges in the order of their requests
+    pub exchanges: Vec<Exchange>,
+    /// Responses without a preceding request with the same id
+    pub unmatched_responses: Vec<(Response, UdpDatagram)>,
+    /// Datagrams on the port that could not be decoded
+    pub malformed: Vec<(crate::Error, UdpDatagram)>,
+}
+
+/// Pairs the requests sent to the given port with the responses sent from the given port. A
+/// response is assigned to the most recent unanswered request with the same id between the
+/// same endpoints.
+pub fn analyze<R: io::Read>(reader: &mut PcapReader<R>, port: u16) -> io::Result<Analysis> {
+    let mut analysis = Analysis {
+        exchanges: Vec::new(),
+        unmatched_responses: Vec::new(),
+        malformed: Vec::new(),
+    };
+
+    while let Some(datagram) = reader.next_udp()? {
+        if datagram.destination.port() == port {
+            let decoded = skip_request_headers(&datagram.payload)
+                .and_then(|mut request| Request::read(&mut request));
+            match decoded {
+                Ok(request) => analysis.exchanges.push(Exchange {
+                    request,
+                    request_datagram: datagram,
+                    response: None,
+                }),
+                Err(e) => analysis.malformed.push((e, datagram)),
+            }
+        } else if datagram.source.port() == port {
+            let response = match read_response(&datagram.payload) {
+                Ok(response) => response,
+                Err(e) => {
+                    analysis.malformed.push((e, datagram));
+                    continue;
+                }
+            };
+            let exchange = analysis.exchanges.iter_mut().rev().find(|exchange| {
+                exchange.response.is_none()
+                    && exchange.request.id() == response.id()
+                    && exchange.request_datagram.source == datagram.destination
+                    && exchange.request_datagram.destination == datagram.source
+            });
+            match exchange {
+                Some(exchange) => exchange.response = Some((response, datagram)),
+                None => analysis.unmatched_responses.push((response, datagram)),
+            }
+        }
+    }
+
+    Ok(analysis)
+}
+
+/// Reads the response behind an optional [`RoutingHeader`], decompressing it if necessary
+fn read_response(datagram: &[u8]) -> Result<Response, crate::Error> {
+    let (_, datagram) = RoutingHeader::read_optional(datagram)?;
+    if datagram.first() == Some(&COMPRESSED_RESPONSE_MARKER) {
+        let mut decompressed = Vec::new();
+        decompress_response(datagram, &mut decompressed)?;
+        Response::read(&mut &decompressed[..])
+    } else {
+        Response::read(&mut &*datagram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(
+        seconds: u32,
+        source: [u8; 4],
+        destination: [u8; 4],
+        ports: [u16; 2],
+        payload: &[u8],
+    ) -> Vec<u8> {
+        let mut packet = vec![0u8; 12];
+        packet.extend_from_slice(&[0x08, 0x00]);
+        packet.extend_from_slice(&[0x45, 0, 0, 0, 0, 0, 0, 0, 64, 17, 0, 0]);
+        packet.extend_from_slice(&source);
+        packet.extend_from_slice(&destination);
+        packet.extend_from_slice(&ports[0].to_be_bytes());
+        packet.extend_from_slice(&ports[1].to_be_bytes());
+        packet.extend_from_slice(&(8 + payload.len() as u16).to_be_bytes());
+        packet.extend_from_slice(&[0, 0]);
+        packet.extend_from_slice(payload);
+
+        let mut record = Vec::new();
+        record.extend_from_slice(&seconds.to_le_bytes());
+        record.extend_from_slice(&0u32.to_le_bytes());
+        record.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+        record.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+        record.extend_from_slice(&packet);
+        record
+    }
+
+    fn header(snaplen: u32) -> Vec<u8> {
+        let mut header = vec![0xD4, 0xC3, 0xB2, 0xA1, 2, 0, 4, 0];
+        header.extend_from_slice(&[0u8; 8]);
+        header.extend_from_slice(&snaplen.to_le_bytes());
+        header.extend_from_slice(&LINKTYPE_ETHERNET.to_le_bytes());
+        header
+    }
+
+    #[test]
+    fn rejects_records_longer_than_the_snaplen() {
+        let mut response = Vec::new();
+        Response::NotAvailable(9).write(&mut response).unwrap();
+        let record = record(
+            1,
+            [192, 168, 0, 51],
+            [192, 168, 0, 2],
+            [51, 40000],
+            &response,
+        );
+
+        let mut capture = header(record.len() as u32 - 16);
+        capture.extend_from_slice(&record);
+        let mut reader = PcapReader::new(&capture[..]).unwrap();
+        assert_eq!(response, reader.next_udp().unwrap().unwrap().payload);
+        assert_eq!(None, reader.next_udp().unwrap());
+
+        let mut capture = header(record.len() as u32 - 17);
+        capture.extend_from_slice(&record);
+        let mut reader = PcapReader::new(&capture[..]).unwrap();
+        assert_eq!(
+            io::ErrorKind::InvalidData,
+            reader.next_udp().unwrap_err().kind()
+        );
+
+        let mut capture = header(u32::MAX);
+        capture.extend_from_slice(&[0u8; 8]);
+        capture.extend_from_slice(&u32::MAX.to_le_bytes());
+        capture.extend_from_slice(&u32::MAX.to_le_bytes());
+        let mut reader = PcapReader::new(&capture[..]).unwrap();
+        assert_eq!(
+            io::ErrorKind::InvalidData,
+            reader.next_udp().unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn skips_truncated_ip_packets() {
+        let mut truncated = record(1, [192, 168, 0, 2], [192, 168, 0, 51], [40000, 51], &[]);
+        for len in [19, 12, 10] {
+            // the Ethernet header, followed by the first `len` bytes of the IPv4 header
+            let packet_len = 14 + len;
+            truncated.truncate(16 + packet_len);
+            truncated[8..12].copy_from_slice(&(packet_len as u32).to_le_bytes());
+            truncated[12..16].copy_from_slice(&(packet_len as u32).to_le_bytes());
+
+            let mut capture = header(65535);
+            capture.extend_from_slice(&truncated);
+            let mut reader = PcapReader::new(&capture[..]).unwrap();
+            assert_eq!(None, reader.next_udp().unwrap());
+        }
+
+        // an IPv4 header length below the minimum of 20 bytes
+        let mut packet = record(1, [192, 168, 0, 2], [192, 168, 0, 51], [40000, 51], &[1]);
+        packet[16 + 14] = 0x44;
+        let mut capture = header(65535);
+        capture.extend_from_slice(&packet);
+        let mut reader = PcapReader::new(&capture[..]).unwrap();
+        assert_eq!(None, reader.next_udp().unwrap());
+    }
+
+    #[test]
+    fn pairs_requests_with_responses() {
+        let client = [192, 168, 0, 2];
+        let device = [192, 168, 0, 51];
+
+        let mut request = Vec::new();
+        Request::ReadAll(9).write(&mut request).unwrap();
+        let mut response = Vec::new();
+        Response::NotAvailable(9).write(&mut response).unwrap();
+
+        let mut capture = header(65535);
+        capture.extend(record(1, client, device, [40000, 51], &request));
+        capture.extend(record(2, device, client, [51, 40000], &response));
+        capture.extend(record(3, device, client, [51, 40000], &response));
+
+        let mut reader = PcapReader::new(&capture[..]).unwrap();
+        let analysis = analyze(&mut reader, 51).unwrap();
+
+        assert_eq!(1, analysis.exchanges.len());
+        assert_eq!(Request::ReadAll(9), analysis.exchanges[0].request);
+        assert_eq!(Some(Duration::from_secs(1)), analysis.exchanges[0].rtt());
+        assert_eq!(1, analysis.unmatched_responses.len());
+    }
+
+    /// Analyzes a capture of the request to and the response from a device, which must pair
+    fn analyze_exchange(request: &[u8], response: &[u8]) -> Exchange {
+        let client = [192, 168, 0, 2];
+        let device = [192, 168, 0, 51];
+        let mut capture = header(65535);
+        capture.extend(record(1, client, device, [40000, 51], request));
+        capture.extend(record(2, device, client, [51, 40000], response));
+
+        let mut reader = PcapReader::new(&capture[..]).unwrap();
+        let mut analysis = analyze(&mut reader, 51).unwrap();
+        assert_eq!(
+            Vec::<(crate::Error, UdpDatagram)>::new(),
+            analysis.malformed
+        );
+        assert!(analysis.unmatched_responses.is_empty());
+        assert_eq!(1, analysis.exchanges.len());
+        analysis.exchanges.remove(0)
+    }
+
+    #[test]
+    fn skips_request_headers_and_decompresses_responses() {
+        use crate::compression::{compress_response, AcceptCompressionHeader, Algorithm};
+        use crate::pairing::SessionHeader;
+        use crate::priority::{Priority, PriorityHeader};
+        use crate::replay::SequenceHeader;
+        use crate::schedule::ScheduleHeader;
+
+        let routing = RoutingHeader::new(&[4]).unwrap();
+
+        let mut request = Vec::new();
+        routing.write(&mut request).unwrap();
+        SequenceHeader(17).write(&mut request).unwrap();
+        SessionHeader([5; 8]).write(&mut request).unwrap();
+        ScheduleHeader::Delay(30).write(&mut request).unwrap();
+        AcceptCompressionHeader(Algorithm::Heatshrink.id())
+            .write(&mut request)
+            .unwrap();
+        PriorityHeader(Priority::HIGHEST)
+            .write(&mut request)
+            .unwrap();
+        Request::ReadAll(7).write(&mut request).unwrap();
+
+        let mut payload = [0u8; 200];
+        payload[..4].copy_from_slice(&[0x00, 7, 0x00, 0xC3]);
+        for (index, byte) in payload[4..].iter_mut().enumerate() {
+            *byte = (index % 12) as u8;
+        }
+        let mut compressed = [0u8; 200];
+        let len = compress_response(&payload, &mut compressed).unwrap();
+        let mut response = Vec::new();
+        routing.write(&mut response).unwrap();
+        response.extend_from_slice(&compressed[..len]);
+
+        let exchange = analyze_exchange(&request, &response);
+        assert_eq!(Request::ReadAll(7), exchange.request);
+        let (response, _) = exchange.response.unwrap();
+        assert_eq!(Response::read(&mut &payload[..]).unwrap(), response);
+    }
+
+    #[test]
+    fn skips_schedule_headers() {
+        use crate::schedule::ScheduleHeader;
+
+        for schedule in [ScheduleHeader::Delay(30), ScheduleHeader::At(1_700_000_000)] {
+            let mut request = Vec::new();
+            schedule.write(&mut request).unwrap();
+            Request::ReadAll(7).write(&mut request).unwrap();
+            let mut response = Vec::new();
+            Response::NotAvailable(7).write(&mut response).unwrap();
+
+            let exchange = analyze_exchange(&request, &response);
+            assert_eq!(Request::ReadAll(7), exchange.request);
+            assert!(exchange.response.is_some());
+        }
+    }
+
+    #[test]
+    fn skips_session_headers() {
+        use crate::pairing::SessionHeader;
+
+        let mut request = Vec::new();
+        SessionHeader([5; 8]).write(&mut request).unwrap();
+        Request::ReadAll(7).write(&mut request).unwrap();
+        let mut response = Vec::new();
+        Response::NotAvailable(7).write(&mut response).unwrap();
+
+        let exchange = analyze_exchange(&request, &response);
+        assert_eq!(Request::ReadAll(7), exchange.request);
+        assert!(exchange.response.is_some());
+    }
+
+    #[test]
+    fn decompresses_responses() {
+        use crate::compression::compress_response;
+
+        let mut request = Vec::new();
+        Request::ReadAll(7).write(&mut request).unwrap();
+        let mut payload = [0u8; 200];
+        payload[..4].copy_from_slice(&[0x00, 7, 0x00, 0xC3]);
+        for (index, byte) in payload[4..].iter_mut().enumerate() {
+            *byte = (index % 12) as u8;
+        }
+        let mut compressed = [0u8; 200];
+        let len = compress_response(&payload, &mut compressed).unwrap();
+
+        let exchange = analyze_exchange(&request, &compressed[..len]);
+        let (response, _) = exchange.response.unwrap();
+        assert_eq!(Response::read(&mut &payload[..]).unwrap(), response);
+
+        let client = [192, 168, 0, 2];
+        let device = [192, 168, 0, 51];
+        let mut capture = header(65535);
+        capture.extend(record(1, device, client, [51, 40000], &compressed[..3]));
+        let mut reader = PcapReader::new(&capture[..]).unwrap();
+        let analysis = analyze(&mut reader, 51).unwrap();
+        assert_eq!(1, analysis.malformed.len());
+    }
+
+    #[test]
+    fn skips_priority_headers() {
+        use crate::priority::{Priority, PriorityHeader};
+
+        let mut request = Vec::new();
+        PriorityHeader(Priority::LOWEST)
+            .write(&mut request)
+            .unwrap();
+        Request::ReadAll(7).write(&mut request).unwrap();
+        let mut response = Vec::new();
+        Response::NotAvailable(7).write(&mut response).unwrap();
+
+        let exchange = analyze_exchange(&request, &response);
+        assert_eq!(Request::ReadAll(7), exchange.request);
+        assert!(exchange.response.is_some());
+    }
+}
@@ -0,0 +1,80 @@
+//! Unsolicited reporting of the latest readings to a multicast group.
+//!
+//! Instead of being polled, a device can periodically send its latest readings as
+//! `Response::Announcement(sequence, format)` followed by the payload of that format to
+//! [`MULTICAST_GROUP`]:[`PORT`]. The sequence number is incremented with each announcement,
+//! so that listeners can tell lost announcements. Since the format describes the payload, a
+//! listener needs no knowledge about the device to decode the readings.
+
+use crate::cursor::Rewind;
+use crate::frame::ResponseWriter;
+use crate::{Error, Format};
+
+/// The IPv4 multicast group announcements are sent to, within the organization-local scope
+pub const MULTICAST_GROUP: [u8; 4] = [239, 255, 0, 51];
+
+/// The UDP port announcements are sent to
+pub const PORT: u16 = 52;
+
+/// Decides when the device announces its readings and numbers the announcements
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Announcer {
+    interval_millis: u64,
+    next_millis: u64,
+    sequence: u8,
+}
+
+impl Announcer {
+    /// The first announcement is due right away
+    pub const fn new(interval_millis: u64) -> Self {
+        Self {
+            interval_millis,
+            next_millis: 0,
+            sequence: 0,
+        }
+    }
+
+    /// Writes the header of the next announcement if it is due, the readings then have to be
+    /// pushed to the returned [`ResponseWriter`]
+    pub fn poll<'w, W: Rewind>(
+        &mut self,
+        uptime_millis: u64,
+        writer: &'w mut W,
+        format: Format,
+    ) -> Option<Result<ResponseWriter<'w, W>, Error>> {
+        if uptime_millis < self.next_millis {
+            return None;
+        }
+        self.next_millis = uptime_millis + self.interval_millis;
+        let sequence = self.sequence;
+        self.sequence = self.sequence.wrapping_add(1);
+        Some(ResponseWriter::announcement(writer, sequence, format))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+    use crate::Type;
+
+    #[test]
+    fn announcements_are_numbered_and_spaced() {
+        let mut announcer = Announcer::new(5_000);
+        let format = Format::AddressValuePairs(Type::U8, Type::F32);
+        let mut buffer = [0u8; 16];
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let mut writer = announcer.poll(100, &mut cursor, format).unwrap().unwrap();
+        writer.push_pair(3u8, 21.5f32).unwrap();
+        assert_eq!(
+            &[0x01, 0, 0x02, 0xFE, 0x00, 3, 0x41, 0xAC, 0x00, 0x00],
+            cursor.written()
+        );
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        assert!(announcer.poll(5_099, &mut cursor, format).is_none());
+        announcer.poll(5_100, &mut cursor, format).unwrap().unwrap();
+        assert_eq!(&[0x01, 1, 0x02, 0xFE, 0x00], cursor.written());
+    }
+}
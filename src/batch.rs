@@ -0,0 +1,60 @@
+//! Several requests and their responses in a single datagram.
+//!
+//! A `Request::Batch(id, count)` is followed by `count` entries, each containing an encoded
+//! request including its payload. The device answers with
+//! `Response::Ok(id, Format::ValueOnly(Type::Batch))` followed by one entry per request, each
+//! containing the encoded response including its payload. An entry is prefixed with its length
+//! as big endian `u16`.
+
+use crate::{Error, Read, Write};
+
+/// Writes a single batch entry
+pub fn write_entry(writer: &mut impl Write, frame: &[u8]) -> Result<usize, Error> {
+    if frame.len() > usize::from(u16::MAX) {
+        return Err(Error::BufferToSmall);
+    }
+    if writer.available() < 2 + frame.len() {
+        return Err(Error::BufferToSmall);
+    }
+    Ok(writer.write_all(&(frame.len() as u16).to_be_bytes())? + writer.write_all(frame)?)
+}
+
+/// Iterates over the entries of a batch until the payload is exhausted. Stops after the first
+/// malformed entry.
+pub struct BatchIter<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> BatchIter<'a> {
+    pub const fn new(payload: &'a [u8]) -> Self {
+        Self { remaining: payload }
+    }
+}
+
+impl<'a> Iterator for BatchIter<'a> {
+    type Item = Result<&'a [u8], Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+
+        let mut len = 0u16.to_be_bytes();
+        let len = match self.remaining.read_all(&mut len) {
+            Ok(_) => usize::from(u16::from_be_bytes(len)),
+            Err(e) => {
+                self.remaining = &[];
+                return Some(Err(e));
+            }
+        };
+
+        if self.remaining.len() < len {
+            self.remaining = &[];
+            Some(Err(Error::UnexpectedEOF))
+        } else {
+            let (entry, remaining) = self.remaining.split_at(len);
+            self.remaining = remaining;
+            Some(Ok(entry))
+        }
+    }
+}
@@ -0,0 +1,11 @@
+//! Prints the Lua source of a Wireshark dissector, see [`sensor_common::dissector`].
+//!
+//! Usage: `cargo run --features dissector -- [port] > sensor_common.lua`
+
+fn main() {
+    let port = match std::env::args().nth(1) {
+        Some(port) => port.parse().expect("Invalid port"),
+        None => 51,
+    };
+    print!("{}", sensor_common::dissector::lua_dissector(port));
+}
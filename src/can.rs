@@ -0,0 +1,124 @@
+//! Addressing of the nodes of a [`crate::Bus::Can`].
+//!
+//! Standard 11-bit identifiers are encoded as `u16` big endian (`Type::Bytes(2)`), extended
+//! 29-bit identifiers as `u32` big endian (`Type::Bytes(4)`), in discovery responses as well as
+//! in the `AddressValuePairs` of read responses.
+
+use crate::{Error, Type};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CanId {
+    Standard(u16),
+    Extended(u32),
+}
+
+impl CanId {
+    pub const STANDARD_MAX: u16 = 0x07FF;
+    pub const EXTENDED_MAX: u32 = 0x1FFF_FFFF;
+
+    /// Fails if the id exceeds 11 bits
+    pub const fn standard(id: u16) -> Option<Self> {
+        if id <= Self::STANDARD_MAX {
+            Some(CanId::Standard(id))
+        } else {
+            None
+        }
+    }
+
+    /// Fails if the id exceeds 29 bits
+    pub const fn extended(id: u32) -> Option<Self> {
+        if id <= Self::EXTENDED_MAX {
+            Some(CanId::Extended(id))
+        } else {
+            None
+        }
+    }
+
+    pub const fn address_type(extended: bool) -> Type {
+        if extended {
+            Type::Bytes(4)
+        } else {
+            Type::Bytes(2)
+        }
+    }
+
+    /// The encoded address and the number of used bytes
+    pub const fn to_address(self) -> ([u8; 4], usize) {
+        match self {
+            CanId::Standard(id) => {
+                let id = id.to_be_bytes();
+                ([id[0], id[1], 0, 0], 2)
+            }
+            CanId::Extended(id) => (id.to_be_bytes(), 4),
+        }
+    }
+
+    pub fn from_address(address: &[u8]) -> Result<Self, Error> {
+        match *address {
+            [high, low] => Self::standard(u16::from_be_bytes([high, low])),
+            [a, b, c, d] => Self::extended(u32::from_be_bytes([a, b, c, d])),
+            _ => return Err(Error::UnexpectedEOF),
+        }
+        .ok_or(Error::UnknownTypeIdentifier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_id_address_roundtrip() {
+        for id in [
+            CanId::Standard(0),
+            CanId::Standard(CanId::STANDARD_MAX),
+            CanId::Extended(0),
+            CanId::Extended(u32::from(CanId::STANDARD_MAX) + 1),
+            CanId::Extended(CanId::EXTENDED_MAX),
+        ] {
+            let (address, len) = id.to_address();
+            assert_eq!(Ok(id), CanId::from_address(&address[..len]));
+        }
+        assert_eq!(
+            ([0x07, 0xFF, 0, 0], 2),
+            CanId::Standard(0x07FF).to_address()
+        );
+        assert_eq!(
+            ([0x00, 0x00, 0x08, 0x00], 4),
+            CanId::Extended(0x0800).to_address()
+        );
+    }
+
+    #[test]
+    fn ids_beyond_their_width_are_rejected() {
+        assert_eq!(
+            Some(CanId::Standard(0x07FF)),
+            CanId::standard(CanId::STANDARD_MAX)
+        );
+        assert_eq!(None, CanId::standard(CanId::STANDARD_MAX + 1));
+        assert_eq!(
+            Some(CanId::Extended(0x1FFF_FFFF)),
+            CanId::extended(CanId::EXTENDED_MAX)
+        );
+        assert_eq!(None, CanId::extended(CanId::EXTENDED_MAX + 1));
+
+        assert_eq!(
+            Err(Error::UnknownTypeIdentifier),
+            CanId::from_address(&[0x08, 0x00])
+        );
+        assert_eq!(
+            Err(Error::UnknownTypeIdentifier),
+            CanId::from_address(&[0x20, 0x00, 0x00, 0x00])
+        );
+    }
+
+    #[test]
+    fn addresses_of_invalid_length_are_rejected() {
+        for len in [0, 1, 3, 5] {
+            assert_eq!(
+                Err(Error::UnexpectedEOF),
+                CanId::from_address(&[0u8; 5][..len])
+            );
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::frame::ResponseFrame;
+use crate::value::Value;
+use crate::Format;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+
+/// Joins the multicast group of [`crate::announcement`] and receives the announcements of all
+/// devices reporting to it
+#[derive(Debug)]
+pub struct AnnouncementListener {
+    socket: tokio::net::UdpSocket,
+    buffer: Vec<u8>,
+}
+
+impl AnnouncementListener {
+    /// Joins the group on the network interface with the given address,
+    /// `Ipv4Addr::UNSPECIFIED` lets the operating system choose one
+    pub async fn join(interface: Ipv4Addr) -> io::Result<Self> {
+        let socket = tokio::net::UdpSocket::bind(SocketAddrV4::new(
+            Ipv4Addr::UNSPECIFIED,
+            crate::announcement::PORT,
+        ))
+        .await?;
+        socket.join_multicast_v4(crate::announcement::MULTICAST_GROUP.into(), interface)?;
+        Ok(Self {
+            socket,
+            buffer: vec![0u8; 1024],
+        })
+    }
+
+    /// Waits for the next announcement, datagrams that are not announcements are ignored
+    pub async fn recv(&mut self) -> io::Result<Announcement> {
+        loop {
+            let (len, source) = self.socket.recv_from(&mut self.buffer).await?;
+            let datagram = &self.buffer[..len];
+            match ResponseFrame::decode(datagram) {
+                Ok(frame) => {
+                    if let crate::Response::Announcement(sequence, format) = *frame.response() {
+                        return Ok(Announcement {
+                            source,
+                            sequence,
+                            format,
+                            datagram: datagram.to_vec(),
+                            payload_start: len - frame.payload().len(),
+                        });
+                    }
+                }
+                Err(_e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(%source, error = ?_e, "ignoring malformed announcement");
+                }
+            }
+        }
+    }
+}
+
+/// A received [`crate::Response::Announcement`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Announcement {
+    /// The address of the announcing device
+    pub source: SocketAddr,
+    pub sequence: u8,
+    pub format: Format,
+    datagram: Vec<u8>,
+    payload_start: usize,
+}
+
+impl Announcement {
+    pub fn payload(&self) -> &[u8] {
+        &self.datagram[self.payload_start..]
+    }
+
+    /// The announcement borrowing the received datagram, see [`ResponseFrame`]
+    pub fn frame(&self) -> ResponseFrame<'_> {
+        ResponseFrame::new(
+            crate::Response::Announcement(self.sequence, self.format),
+            self.payload(),
+        )
+    }
+
+    /// The addresses and readings of a `Format::AddressValuePairs` or
+    /// `Format::CountedAddressValuePairs` announcement, up to the first malformed one
+    pub fn readings(&self) -> Option<Vec<(&[u8], Value<'_>)>> {
+        Some(
+            self.frame()
+                .address_values()?
+                .map_while(Result::ok)
+                .collect(),
+        )
+    }
+}
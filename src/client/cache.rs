@@ -0,0 +1,230 @@
+use crate::client::{ConnectionOptions, DispatchError, Response, Runtime};
+use crate::props::PropertyReportV1;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A request whose response rarely changes, so that it can be answered by a [`CachedClient`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Cacheable {
+    /// `Request::RetrieveVersionInformation`, see [`Response::extract_version_information`]
+    VersionInformation,
+    /// `Request::RetrieveDeviceInformation`, see [`Response::extract_device_information`]
+    DeviceInformation,
+    /// `Request::ListBuses`, see [`Response::extract_buses`]
+    Buses,
+}
+
+impl Cacheable {
+    /// The time a response is served from the cache unless configured otherwise. The device
+    /// information contains the uptime, which therefore is only as accurate as this.
+    pub const fn default_ttl(self) -> Duration {
+        match self {
+            Cacheable::VersionInformation => Duration::from_secs(60 * 60),
+            Cacheable::DeviceInformation => Duration::from_secs(60),
+            Cacheable::Buses => Duration::from_secs(10 * 60),
+        }
+    }
+
+    fn request(self) -> fn(u8) -> crate::Request {
+        match self {
+            Cacheable::VersionInformation => crate::Request::RetrieveVersionInformation,
+            Cacheable::DeviceInformation => crate::Request::RetrieveDeviceInformation,
+            Cacheable::Buses => crate::Request::ListBuses,
+        }
+    }
+}
+
+/// Dispatches [`Cacheable`] requests and keeps their successful responses per device, so that
+/// tools enumerating the properties of a device over and over again do not ask a slow device
+/// each time. Responses expire after the TTL of their [`Cacheable`]. The property reports of a
+/// device are listed in pages, see [`CachedClient::components`], and kept as a whole.
+#[derive(Debug)]
+pub struct CachedClient {
+    ttls: HashMap<Cacheable, Duration>,
+    components_ttl: Duration,
+    entries: HashMap<(SocketAddr, Cacheable), (Instant, Arc<Response>)>,
+    components: HashMap<SocketAddr, (Instant, Arc<Vec<PropertyReportV1>>)>,
+}
+
+impl Default for CachedClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CachedClient {
+    /// The time property reports are served from the cache unless configured otherwise
+    pub const DEFAULT_COMPONENTS_TTL: Duration = Duration::from_secs(10 * 60);
+
+    pub fn new() -> Self {
+        Self {
+            ttls: HashMap::new(),
+            components_ttl: Self::DEFAULT_COMPONENTS_TTL,
+            entries: HashMap::new(),
+            components: HashMap::new(),
+        }
+    }
+
+    /// Overrides the [`Cacheable::default_ttl`], a zero TTL disables the cache for it
+    pub fn with_ttl(mut self, cacheable: Cacheable, ttl: Duration) -> Self {
+        self.ttls.insert(cacheable, ttl);
+        self
+    }
+
+    /// Overrides the [`CachedClient::DEFAULT_COMPONENTS_TTL`], a zero TTL disables the cache for
+    /// property reports
+    pub fn with_components_ttl(mut self, ttl: Duration) -> Self {
+        self.components_ttl = ttl;
+        self
+    }
+
+    pub fn ttl(&self, cacheable: Cacheable) -> Duration {
+        self.ttls
+            .get(&cacheable)
+            .copied()
+            .unwrap_or_else(|| cacheable.default_ttl())
+    }
+
+    /// The cached response of the device or, if there is none or it expired, the response to
+    /// a new request dispatched on the given [`Runtime`]. Only `Response::Ok` is cached.
+    pub async fn get<R: Runtime>(
+        &mut self,
+        device: &ConnectionOptions,
+        cacheable: Cacheable,
+    ) -> Result<Arc<Response>, DispatchError> {
+        let key = (device.remote_address(), cacheable);
+        if let Some(response) = self.cached(key, Instant::now()) {
+            return Ok(response);
+        }
+
+        let response = Arc::new(
+            device
+                .new_request(cacheable.request())
+                .map_err(DispatchError::Encoding)?
+                .dispatch_on::<R>()
+                .await?,
+        );
+        if matches!(response.response, crate::Response::Ok(..)) {
+            self.entries
+                .insert(key, (Instant::now(), Arc::clone(&response)));
+        }
+        Ok(response)
+    }
+
+    /// The cached property reports of the device or, if there are none or they expired, the
+    /// reports listed in pages of the given size, see [`ConnectionOptions::list_components`].
+    /// Only complete listings are cached.
+    pub async fn components<R: Runtime>(
+        &mut self,
+        device: &ConnectionOptions,
+        page_size: u8,
+    ) -> Result<Arc<Vec<PropertyReportV1>>, DispatchError> {
+        let key = device.remote_address();
+        if let Some(reports) = cached(
+            &mut self.components,
+            key,
+            self.components_ttl,
+            Instant::now(),
+        ) {
+            return Ok(reports);
+        }
+
+        let reports = Arc::new(device.list_components::<R>(page_size).collect().await?);
+        self.components
+            .insert(key, (Instant::now(), Arc::clone(&reports)));
+        Ok(reports)
+    }
+
+    /// Forgets all responses of the device, for example after it has been reset or upgraded
+    pub fn invalidate(&mut self, device: SocketAddr) {
+        self.entries.retain(|(address, _), _| *address != device);
+        self.components.remove(&device);
+    }
+
+    /// Forgets all responses, including the expired ones
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.components.clear();
+    }
+
+    fn cached(&mut self, key: (SocketAddr, Cacheable), now: Instant) -> Option<Arc<Response>> {
+        let ttl = self.ttl(key.1);
+        cached(&mut self.entries, key, ttl, now)
+    }
+}
+
+/// The entry unless it is older than the TTL, expired entries are removed
+fn cached<K: Eq + Hash, V>(
+    entries: &mut HashMap<K, (Instant, Arc<V>)>,
+    key: K,
+    ttl: Duration,
+    now: Instant,
+) -> Option<Arc<V>> {
+    match entries.get(&key) {
+        Some((cached_at, value)) if now.saturating_duration_since(*cached_at) < ttl => {
+            Some(Arc::clone(value))
+        }
+        Some(_) => {
+            entries.remove(&key);
+            None
+        }
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::DispatchStats;
+    use crate::{Format, Type};
+
+    #[test]
+    fn responses_expire_after_their_ttl() {
+        let device = SocketAddr::from(([192, 168, 0, 2], 51));
+        let key = (device, Cacheable::Buses);
+        let response = Response {
+            request: crate::Request::ListBuses(1),
+            response: crate::Response::Ok(1, Format::ValueOnly(Type::DynListBusInformation)),
+            datagram: Arc::new(vec![0x00, 1, 0x00, 0xC7]),
+            payload_start: 4,
+            payload_end: 4,
+            stats: DispatchStats::default(),
+            duplicates_ignored: 0,
+            batched: Vec::new(),
+        };
+
+        let mut cache = CachedClient::new().with_ttl(Cacheable::Buses, Duration::from_secs(5));
+        let now = Instant::now();
+        cache.entries.insert(key, (now, Arc::new(response)));
+
+        assert!(cache.cached(key, now + Duration::from_secs(4)).is_some());
+        assert!(cache
+            .cached((device, Cacheable::VersionInformation), now)
+            .is_none());
+        assert!(cache.cached(key, now + Duration::from_secs(5)).is_none());
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn reports_expire_and_are_invalidated_per_device() {
+        let device = SocketAddr::from(([192, 168, 0, 2], 51));
+        let other = SocketAddr::from(([192, 168, 0, 3], 51));
+        let mut cache = CachedClient::new().with_components_ttl(Duration::from_secs(5));
+        let now = Instant::now();
+        cache.components.insert(device, (now, Arc::new(Vec::new())));
+        cache.components.insert(other, (now, Arc::new(Vec::new())));
+
+        let later = now + Duration::from_secs(4);
+        assert!(cached(&mut cache.components, device, cache.components_ttl, later).is_some());
+        cache.invalidate(device);
+        assert!(cached(&mut cache.components, device, cache.components_ttl, later).is_none());
+        assert!(cached(&mut cache.components, other, cache.components_ttl, later).is_some());
+
+        let expired = now + Duration::from_secs(5);
+        assert!(cached(&mut cache.components, other, cache.components_ttl, expired).is_none());
+        assert!(cache.components.is_empty());
+    }
+}
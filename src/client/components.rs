@@ -0,0 +1,137 @@
+use crate::client::{ConnectionOptions, DispatchError, Runtime};
+use crate::props::PropertyReportV1;
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+
+/// Pages through the properties of a device with [`crate::Request::ListComponentsRange`], so
+/// that devices with more properties than fit into a single response can be listed. Devices
+/// that do not implement the request are asked for the complete list instead.
+pub struct ComponentStream<'a, R> {
+    device: &'a ConnectionOptions,
+    page_size: u8,
+    offset: u16,
+    page: std::vec::IntoIter<PropertyReportV1>,
+    finished: bool,
+    runtime: PhantomData<fn() -> R>,
+}
+
+impl<'a, R: Runtime> ComponentStream<'a, R> {
+    pub fn new(device: &'a ConnectionOptions, page_size: u8) -> Self {
+        Self {
+            device,
+            page_size: page_size.max(1),
+            offset: 0,
+            page: Vec::new().into_iter(),
+            finished: false,
+            runtime: PhantomData,
+        }
+    }
+
+    /// The next property report, requesting the next page if needed. Returns `None` once all
+    /// properties have been listed or after an error. A device that cannot fit a page into its
+    /// buffers answers with `Response::NotAvailable`, which is returned as
+    /// [`DispatchError::UnexpectedResponse`], a smaller page size might help.
+    pub async fn next(&mut self) -> Option<Result<PropertyReportV1, DispatchError>> {
+        loop {
+            if let Some(report) = self.page.next() {
+                return Some(Ok(report));
+            }
+            if self.finished {
+                return None;
+            }
+            if let Err(e) = self.fetch().await {
+                self.finished = true;
+                return Some(Err(e));
+            }
+        }
+    }
+
+    /// Collects the remaining property reports
+    pub async fn collect(mut self) -> Result<Vec<PropertyReportV1>, DispatchError> {
+        let mut reports = Vec::new();
+        while let Some(report) = self.next().await {
+            reports.push(report?);
+        }
+        Ok(reports)
+    }
+
+    async fn fetch(&mut self) -> Result<(), DispatchError> {
+        let mut response = self
+            .device
+            .new_list_components_range(self.offset, self.page_size)
+            .map_err(DispatchError::Encoding)?
+            .dispatch_on::<R>()
+            .await?;
+        if let crate::Response::NotImplemented(_) = response.response {
+            if self.offset == 0 {
+                self.finished = true;
+                response = self
+                    .device
+                    .new_request(crate::Request::ListComponentsWithReportV1)
+                    .map_err(DispatchError::Encoding)?
+                    .dispatch_on::<R>()
+                    .await?;
+            }
+        }
+
+        match response.extract_property_reports() {
+            Some(reports) => {
+                self.advance(reports);
+                Ok(())
+            }
+            None => Err(DispatchError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// Continues behind the given page, a short page is the last one. Stops at the largest
+    /// offset the request can address.
+    fn advance(&mut self, page: Vec<PropertyReportV1>) {
+        if page.len() < usize::from(self.page_size) {
+            self.finished = true;
+        }
+        match u16::try_from(page.len())
+            .ok()
+            .and_then(|len| self.offset.checked_add(len))
+        {
+            Some(offset) => self.offset = offset,
+            None => self.finished = true,
+        }
+        self.page = page.into_iter();
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::client::{ConnectionOptionsBuilder, Tokio};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn pages_until_a_short_page_or_the_last_offset() {
+        let device = ConnectionOptionsBuilder::default()
+            .remote_ip(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .build()
+            .unwrap();
+        let property = crate::props::Property::<(), ()>::new(&[0x10]);
+        let page = |len| {
+            (0..len)
+                .map(|_| PropertyReportV1::from(&property))
+                .collect()
+        };
+
+        let mut stream = ComponentStream::<Tokio>::new(&device, 2);
+        stream.advance(page(2));
+        assert_eq!((2, false), (stream.offset, stream.finished));
+        stream.advance(page(1));
+        assert_eq!((3, true), (stream.offset, stream.finished));
+        assert_eq!(1, stream.page.len());
+
+        let mut stream = ComponentStream::<Tokio>::new(&device, 2);
+        stream.offset = u16::MAX - 1;
+        stream.advance(page(1));
+        assert_eq!(u16::MAX, stream.offset);
+        stream.finished = false;
+        stream.advance(page(2));
+        assert_eq!((u16::MAX, true), (stream.offset, stream.finished));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::client::Response;
+use crate::frame::ElementIter;
+use crate::hex::Hex;
+use crate::value::Value;
+use crate::Format;
+use serde_json::{json, Map};
+
+impl Response {
+    /// Renders the response as JSON document with the request, the response format, the
+    /// decoded addresses and values (as far as they can be decoded) and the raw payload as hex
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut document = Map::new();
+        document.insert("request".into(), json!(format!("{:?}", self.request)));
+        document.insert("id".into(), json!(self.response.id()));
+
+        match &self.response {
+            crate::Response::NotImplemented(_) => {
+                document.insert("status".into(), json!("NotImplemented"));
+            }
+            crate::Response::NotAvailable(_) => {
+                document.insert("status".into(), json!("NotAvailable"));
+            }
+            crate::Response::Error(_, code) => {
+                document.insert("status".into(), json!("Error"));
+                document.insert("error".into(), json!(format!("{:?}", code)));
+            }
+            crate::Response::Ok(_, format) | crate::Response::Announcement(_, format) => {
+                document.insert("status".into(), json!("Ok"));
+                document.insert("format".into(), json!(format!("{:?}", format)));
+                if let Some(entries) = self.decode_entries(format) {
+                    document.insert("entries".into(), entries);
+                }
+            }
+        }
+
+        document.insert(
+            "payload".into(),
+            json!(Hex::new(self.payload(), "").to_string()),
+        );
+        serde_json::Value::Object(document)
+    }
+
+    fn decode_entries(&self, format: &Format) -> Option<serde_json::Value> {
+        if let Format::Empty = format {
+            return None;
+        }
+        let mut entries = Vec::new();
+        for element in ElementIter::new(*format, self.payload()) {
+            let element = element.ok()?;
+            let mut entry = Map::new();
+            if let Some(address) = element.address {
+                entry.insert("address".into(), json!(Hex::colons(address).to_string()));
+            }
+            if let Some(status) = element.status {
+                entry.insert("status".into(), json!(format!("{:?}", status)));
+            }
+            if let Some(value) = &element.value {
+                let value = match element.status {
+                    Some(status) if !status.is_ok() => serde_json::Value::Null,
+                    _ => self::value(value),
+                };
+                entry.insert("value".into(), value);
+            }
+            if let Some(timestamp) = &element.timestamp {
+                entry.insert("timestamp".into(), value(timestamp));
+            }
+            entries.push(serde_json::Value::Object(entry));
+        }
+        Some(serde_json::Value::Array(entries))
+    }
+}
+
+fn value(value: &Value<'_>) -> serde_json::Value {
+    match value {
+        Value::Bool(value) => json!(value),
+        Value::U128(value) => json!(value.to_string()),
+        Value::I128(value) => json!(value.to_string()),
+        Value::U64(value) | Value::Counter(value) => json!(value),
+        Value::I64(value) => json!(value),
+        Value::TimestampMillis(_) | Value::TimestampSeconds(_) => {
+            json!({ "timestamp_millis": value.timestamp_millis() })
+        }
+        Value::String(value) | Value::DynString(value) => json!(value),
+        Value::Bytes(bytes) | Value::DynBytes(bytes) | Value::PropertyId(bytes) => {
+            json!(Hex::new(bytes, "").to_string())
+        }
+        Value::Uuid(uuid) => json!(Hex::new(uuid, "").to_string()),
+        Value::Tlv(raw) => json!({ "tlv": Hex::new(raw, "").to_string() }),
+        Value::List { .. } => match value.elements() {
+            Some(elements) => serde_json::Value::Array(
+                elements
+                    .map(|element| match element {
+                        Ok(element) => self::value(&element),
+                        Err(e) => json!({ "error": format!("{:?}", e) }),
+                    })
+                    .collect(),
+            ),
+            None => serde_json::Value::Null,
+        },
+        Value::DeviceInformation(_)
+        | Value::NetworkConfiguration(_)
+        | Value::VersionInformation(_)
+        | Value::OutputState(_) => json!(format!("{:?}", value)),
+        Value::SensorDiagnostics(diagnostics) => json!({
+            "crc_failures": diagnostics.crc_failures,
+            "timeouts": diagnostics.timeouts,
+            "last_seen_millis": diagnostics.last_seen_millis,
+        }),
+        _ => value.to_f64().map(|value| json!(value)).unwrap_or_default(),
+    }
+}
@@ -0,0 +1,185 @@
+use crate::client::{ConnectionOptions, DispatchError, Runtime};
+use crate::log::{is_before, LogLevel, LogRecord};
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+/// A [`LogRecord`] that outlives the response it has been received with
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub sequence: u32,
+    pub level: LogLevel,
+    /// The uptime of the device in milliseconds at which the record was logged
+    pub timestamp: u64,
+    pub module: String,
+    pub message: Vec<u8>,
+}
+
+impl LogEntry {
+    /// The message as text, with invalid UTF-8 replaced
+    pub fn message_lossy(&self) -> std::borrow::Cow<'_, str> {
+        String::from_utf8_lossy(&self.message)
+    }
+}
+
+impl From<LogRecord<'_>> for LogEntry {
+    fn from(record: LogRecord<'_>) -> Self {
+        Self {
+            sequence: record.sequence,
+            level: record.level,
+            timestamp: record.timestamp,
+            module: record.module.to_string(),
+            message: record.message.to_vec(),
+        }
+    }
+}
+
+/// Follows the log of a device with [`crate::Request::TailLog`]. The device is asked for new
+/// records right away as long as it answers with any, and once per interval otherwise.
+pub struct LogTail<'a, R> {
+    device: &'a ConnectionOptions,
+    interval: Duration,
+    /// `None` until the first record has been received when tailing from the start
+    since: Option<u32>,
+    last_poll: Option<Instant>,
+    pending: VecDeque<LogEntry>,
+    runtime: PhantomData<fn() -> R>,
+}
+
+impl<'a, R: Runtime> LogTail<'a, R> {
+    /// Tails the log starting at the given sequence number, `0` for all records the device
+    /// still has
+    pub fn new(device: &'a ConnectionOptions, interval: Duration, since: u32) -> Self {
+        Self {
+            device,
+            interval,
+            since: Some(since).filter(|since| *since != 0),
+            last_poll: None,
+            pending: VecDeque::new(),
+            runtime: PhantomData,
+        }
+    }
+
+    /// The sequence number of the next record to ask the device for
+    pub fn since(&self) -> u32 {
+        self.since.unwrap_or(0)
+    }
+
+    /// The next record, waiting for the device to log one. Failing to poll the device does not
+    /// end the tail, the next call polls again.
+    pub async fn next(&mut self) -> Result<LogEntry, DispatchError> {
+        loop {
+            if let Some(entry) = self.pending.pop_front() {
+                return Ok(entry);
+            }
+            if let Some(last_poll) = self.last_poll {
+                // waits until the deadline, the pending future never completes
+                R::timeout_at(last_poll + self.interval, std::future::pending::<()>()).await;
+            }
+            self.last_poll = Some(Instant::now());
+            self.poll().await?;
+            if !self.pending.is_empty() {
+                // more records might be waiting on the device
+                self.last_poll = None;
+            }
+        }
+    }
+
+    async fn poll(&mut self) -> Result<(), DispatchError> {
+        let response = self
+            .device
+            .new_tail_log(self.since())
+            .map_err(DispatchError::Encoding)?
+            .dispatch_on::<R>()
+            .await?;
+        match response.extract_log_records() {
+            Some(records) => {
+                self.receive(records);
+                Ok(())
+            }
+            None => Err(DispatchError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// Queues the records that are not before the next expected sequence number. Once the
+    /// sequence number wrapped around to `0`, the device answers with all of its records again.
+    fn receive<'r>(&mut self, records: impl IntoIterator<Item = LogRecord<'r>>) {
+        for record in records {
+            let expected = match self.since {
+                Some(since) => !is_before(record.sequence, since),
+                None => true,
+            };
+            if expected {
+                self.since = Some(record.sequence.wrapping_add(1));
+                self.pending.push_back(LogEntry::from(record));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "tokio")]
+    use crate::client::{ConnectionOptionsBuilder, Tokio};
+    #[cfg(feature = "tokio")]
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn entry_outlives_the_record() {
+        let record = LogRecord {
+            sequence: 3,
+            level: LogLevel::Warn,
+            timestamp: 1_000,
+            module: "bus",
+            message: b"crc \xFF",
+        };
+        let entry = LogEntry::from(record);
+        assert_eq!("bus", entry.module);
+        assert_eq!("crc \u{FFFD}", entry.message_lossy());
+    }
+
+    #[cfg(feature = "tokio")]
+    fn records(sequences: &[u32]) -> impl Iterator<Item = LogRecord<'static>> + '_ {
+        sequences.iter().map(|&sequence| LogRecord {
+            sequence,
+            level: LogLevel::Info,
+            timestamp: 0,
+            module: "net",
+            message: b"",
+        })
+    }
+
+    #[cfg(feature = "tokio")]
+    fn pending(tail: &mut LogTail<'_, Tokio>) -> Vec<u32> {
+        tail.pending.drain(..).map(|entry| entry.sequence).collect()
+    }
+
+    #[test]
+    #[cfg(feature = "tokio")]
+    fn polls_continue_behind_the_last_record() {
+        let device = ConnectionOptionsBuilder::default()
+            .remote_ip(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .build()
+            .unwrap();
+
+        let mut tail = LogTail::<Tokio>::new(&device, Duration::from_secs(1), 0);
+        tail.receive(records(&[0x8000_0000, 0x8000_0001]));
+        assert_eq!(vec![0x8000_0000, 0x8000_0001], pending(&mut tail));
+        assert_eq!(0x8000_0002, tail.since());
+
+        // records that have already been received are skipped
+        tail.receive(records(&[0x8000_0001, 0x8000_0002]));
+        assert_eq!(vec![0x8000_0002], pending(&mut tail));
+
+        let mut tail = LogTail::<Tokio>::new(&device, Duration::from_secs(1), u32::MAX);
+        tail.receive(records(&[u32::MAX - 1, u32::MAX]));
+        assert_eq!(vec![u32::MAX], pending(&mut tail));
+        assert_eq!(0, tail.since());
+
+        // after the wrap around, the device answers with all records again
+        tail.receive(records(&[u32::MAX, 0, 1]));
+        assert_eq!(vec![0, 1], pending(&mut tail));
+        assert_eq!(2, tail.since());
+    }
+}
@@ -0,0 +1,226 @@
+use crate::dns_sd::{SERVICE_TYPE, TXT_LOCATION, TXT_NAME, TXT_VERSION};
+use simple_dns::rdata::RData;
+use simple_dns::{Name, Packet, Question, CLASS, QCLASS, QTYPE, TYPE};
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+
+const MDNS_ADDRESS: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(224, 0, 0, 251)), 5353);
+
+/// A device found by [`discover_mdns`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MdnsDevice {
+    /// The name of the service instance, for example `sensor-4a2f._sensor._udp.local`
+    pub instance: String,
+    pub address: SocketAddr,
+    /// The attributes of the TXT record, see [`crate::dns_sd`]
+    pub attributes: HashMap<String, String>,
+}
+
+impl MdnsDevice {
+    pub fn version(&self) -> Option<&str> {
+        self.attributes.get(TXT_VERSION).map(String::as_str)
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.attributes.get(TXT_NAME).map(String::as_str)
+    }
+
+    pub fn location(&self) -> Option<&str> {
+        self.attributes.get(TXT_LOCATION).map(String::as_str)
+    }
+}
+
+/// The records of a service instance collected from the answers
+#[derive(Debug, core::default::Default)]
+struct Instance {
+    /// The source of the answer, for devices that do not include their address record
+    source: Option<IpAddr>,
+    target: Option<String>,
+    port: Option<u16>,
+    attributes: HashMap<String, String>,
+}
+
+/// Browses for devices advertising [`crate::dns_sd::SERVICE_TYPE`] over mDNS and collects the
+/// answers that arrive within the given duration
+pub async fn discover_mdns(duration: Duration) -> io::Result<Vec<MdnsDevice>> {
+    let socket = tokio::net::UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    let mut query = Packet::new_query(0);
+    query.questions.push(Question::new(
+        Name::new_unchecked(SERVICE_TYPE),
+        QTYPE::TYPE(TYPE::PTR),
+        QCLASS::CLASS(CLASS::IN),
+        true,
+    ));
+    let query = query
+        .build_bytes_vec()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    socket.send_to(&query, MDNS_ADDRESS).await?;
+
+    let deadline = tokio::time::Instant::from_std(Instant::now() + duration);
+    let mut instances = HashMap::<String, Instance>::new();
+    let mut hosts = HashMap::<String, Ipv4Addr>::new();
+    let mut buffer = [0u8; 9000];
+    while let Ok(received) = tokio::time::timeout_at(deadline, socket.recv_from(&mut buffer)).await
+    {
+        let (len, source) = received?;
+        if let Ok(packet) = Packet::parse(&buffer[..len]) {
+            collect(&packet, source.ip(), &mut instances, &mut hosts);
+        }
+    }
+
+    Ok(devices(instances, &hosts))
+}
+
+/// The instances whose port is known, at the address of their target host or, if its address
+/// is unknown, at the source of their answer
+fn devices(
+    instances: HashMap<String, Instance>,
+    hosts: &HashMap<String, Ipv4Addr>,
+) -> Vec<MdnsDevice> {
+    instances
+        .into_iter()
+        .filter_map(|(instance, records)| {
+            let ip = records
+                .target
+                .and_then(|target| hosts.get(&target).copied().map(IpAddr::V4))
+                .or(records.source)?;
+            Some(MdnsDevice {
+                instance,
+                address: SocketAddr::new(ip, records.port?),
+                attributes: records.attributes,
+            })
+        })
+        .collect()
+}
+
+fn collect(
+    packet: &Packet,
+    source: IpAddr,
+    instances: &mut HashMap<String, Instance>,
+    hosts: &mut HashMap<String, Ipv4Addr>,
+) {
+    let key = |name: &Name| name.to_string().to_ascii_lowercase();
+    let records = packet.answers.iter().chain(&packet.additional_records);
+
+    for record in records.clone() {
+        if let RData::PTR(ptr) = &record.rdata {
+            if key(&record.name) == SERVICE_TYPE {
+                instances.entry(key(&ptr.0)).or_default().source = Some(source);
+            }
+        }
+    }
+    for record in records {
+        match &record.rdata {
+            RData::A(a) => {
+                hosts.insert(key(&record.name), Ipv4Addr::from(a.address));
+            }
+            RData::SRV(srv) => {
+                if let Some(instance) = instances.get_mut(&key(&record.name)) {
+                    instance.target = Some(key(&srv.target));
+                    instance.port = Some(srv.port);
+                }
+            }
+            RData::TXT(txt) => {
+                if let Some(instance) = instances.get_mut(&key(&record.name)) {
+                    instance.attributes = txt
+                        .attributes()
+                        .into_iter()
+                        .map(|(key, value)| (key, value.unwrap_or_default()))
+                        .collect();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simple_dns::rdata::{PTR, SRV, TXT};
+    use simple_dns::ResourceRecord;
+
+    fn record<'a>(name: &'a str, rdata: RData<'a>) -> ResourceRecord<'a> {
+        ResourceRecord::new(Name::new_unchecked(name), CLASS::IN, 120, rdata)
+    }
+
+    fn ptr(instance: &str) -> ResourceRecord<'_> {
+        record(SERVICE_TYPE, RData::PTR(PTR(Name::new_unchecked(instance))))
+    }
+
+    fn srv(instance: &str, port: u16) -> ResourceRecord<'_> {
+        record(
+            instance,
+            RData::SRV(SRV {
+                priority: 0,
+                weight: 0,
+                port,
+                target: Name::new_unchecked("Sensor-Host.local"),
+            }),
+        )
+    }
+
+    #[test]
+    fn collects_the_records_of_announced_instances() {
+        let mut packet = Packet::new_reply(0);
+        packet.answers.push(ptr("Sensor-4A2F._sensor._udp.local"));
+        packet
+            .answers
+            .push(srv("sensor-4a2f._sensor._udp.local", 51));
+        packet.answers.push(record(
+            "sensor-4a2f._sensor._udp.local",
+            RData::TXT(
+                TXT::new()
+                    .with_string("version=1.4.2")
+                    .and_then(|txt| txt.with_string("name=kitchen"))
+                    .and_then(|txt| txt.with_string("location=ground floor"))
+                    .and_then(|txt| txt.with_string("flag"))
+                    .unwrap(),
+            ),
+        ));
+        // not announced by a PTR record
+        packet.answers.push(srv("other._sensor._udp.local", 52));
+        packet.additional_records.push(record(
+            "sensor-host.local",
+            RData::A(Ipv4Addr::new(192, 168, 0, 7).into()),
+        ));
+
+        let source = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
+        let mut instances = HashMap::new();
+        let mut hosts = HashMap::new();
+        collect(&packet, source, &mut instances, &mut hosts);
+        let devices = devices(instances, &hosts);
+
+        assert_eq!(1, devices.len());
+        let device = &devices[0];
+        assert_eq!("sensor-4a2f._sensor._udp.local", device.instance);
+        assert_eq!(SocketAddr::from(([192, 168, 0, 7], 51)), device.address);
+        assert_eq!(Some("1.4.2"), device.version());
+        assert_eq!(Some("kitchen"), device.name());
+        assert_eq!(Some("ground floor"), device.location());
+        assert_eq!(Some(""), device.attributes.get("flag").map(String::as_str));
+    }
+
+    #[test]
+    fn unknown_hosts_fall_back_to_the_source() {
+        let mut packet = Packet::new_reply(0);
+        packet.answers.push(ptr("a._sensor._udp.local"));
+        packet.answers.push(srv("a._sensor._udp.local", 51));
+        // without a port, the instance cannot be reached
+        packet.answers.push(ptr("b._sensor._udp.local"));
+
+        let source = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 2));
+        let mut instances = HashMap::new();
+        let mut hosts = HashMap::new();
+        collect(&packet, source, &mut instances, &mut hosts);
+        let devices = devices(instances, &hosts);
+
+        assert_eq!(1, devices.len());
+        assert_eq!("a._sensor._udp.local", devices[0].instance);
+        assert_eq!(SocketAddr::new(source, 51), devices[0].address);
+        assert_eq!(None, devices[0].version());
+        assert_eq!(None, devices[0].location());
+    }
+}
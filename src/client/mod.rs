@@ -1,10 +1,82 @@
-use crate::{Format, Read, Type};
+use crate::address::{encode_address_list, OneWireAddress};
+use crate::can::CanId;
+use crate::compression::{
+    decompress_response, AcceptCompressionHeader, COMPRESSED_RESPONSE_MARKER,
+};
+use crate::counted::ReadFlags;
+use crate::error_dump::{ErrorDumpEntry, ErrorDumpIter};
+use crate::frame::ResponseFrame;
+use crate::headers::{HeaderKind, RequestHeaderIter};
+use crate::info::{
+    BusInformation, Capabilities, DeviceInformation, NetworkConfiguration, ResetReason,
+    VersionInformation,
+};
+use crate::log::{LogRecord, LogRecordIter};
+use crate::modbus::Register;
+use crate::output::OutputState;
+use crate::pairing::SessionHeader;
+use crate::priority::{Priority, PriorityHeader};
+use crate::props::well_known;
+use crate::props::{PropertyReportIter, PropertyReportV1, QueryComplexity};
+use crate::routing::RoutingHeader;
+use crate::schedule::ScheduleHeader;
+use crate::status::{SensorDiagnostics, SensorStatus};
+use crate::value::Value;
+use crate::{Format, Read, Type, Write};
 use random::Source;
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::num::NonZeroU8;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-#[derive(Debug, Clone, derive_builder::Builder)]
+#[cfg(feature = "tokio")]
+mod announcement;
+mod cache;
+mod components;
+#[cfg(feature = "serde_json")]
+mod json;
+mod log_tail;
+#[cfg(feature = "mdns")]
+mod mdns;
+#[cfg(feature = "tokio")]
+mod poll;
+#[cfg(feature = "tokio")]
+mod poller;
+pub mod profile;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+mod property;
+mod retry;
+mod runtime;
+mod transport;
+
+#[cfg(feature = "tokio")]
+pub use announcement::{Announcement, AnnouncementListener};
+pub use cache::{Cacheable, CachedClient};
+pub use components::ComponentStream;
+pub use log_tail::{LogEntry, LogTail};
+#[cfg(feature = "mdns")]
+pub use mdns::{discover_mdns, MdnsDevice};
+#[cfg(feature = "tokio")]
+pub use poll::{poll_many, poll_many_on};
+#[cfg(feature = "tokio")]
+pub use poller::{PollId, PollResult, Poller};
+pub use property::{PropertyError, PropertyType, TypedPropertyHandle};
+pub use retry::RetryPolicy;
+pub use runtime::Runtime;
+#[cfg(feature = "tokio")]
+pub use runtime::Tokio;
+#[cfg(feature = "async-io")]
+pub use runtime::{AsyncIo, AsyncIoUdpTransport};
+#[cfg(feature = "client-encryption")]
+pub use transport::EncryptedTransport;
+pub use transport::Transport;
+#[cfg(feature = "tokio")]
+pub use transport::{TcpTransport, UdpTransport};
+
+#[derive(Clone, derive_builder::Builder)]
 pub struct ConnectionOptions {
     #[builder(setter(into, strip_option), default)]
     local_ip: Option<IpAddr>,
@@ -18,8 +90,22 @@ pub struct ConnectionOptions {
     timeout: Duration,
     #[builder(default = "NonZeroU8::new(3).unwrap()")]
     resend_attempts: NonZeroU8,
+    #[builder(default)]
+    retry_policy: RetryPolicy,
     #[builder(default = "1024")]
     rx_buffer_size: usize,
+    /// Whether to send an [`AcceptCompressionHeader`] in front of each request, so that the
+    /// device may compress large responses, see [`crate::compression`]
+    #[builder(default)]
+    accept_compression: bool,
+    /// Sent in a [`crate::priority::PriorityHeader`] in front of each request, if set
+    #[builder(setter(strip_option), default)]
+    priority: Option<Priority>,
+    /// The pre-shared key to seal the datagrams with, plain UDP if not set, see
+    /// [`crate::encryption`]
+    #[cfg(feature = "client-encryption")]
+    #[builder(setter(into, strip_option), default)]
+    psk: Option<[u8; 32]>,
 }
 
 impl ConnectionOptionsBuilder {
@@ -33,6 +119,7 @@ impl ConnectionOptionsBuilder {
         self
     }
 
+    #[cfg(feature = "tokio")]
     pub async fn remote_host_async(mut self, host: &str) -> Self {
         self.remote_ip = match host.parse::<IpAddr>() {
             Ok(ip) => Some(ip),
@@ -47,7 +134,360 @@ impl ConnectionOptionsBuilder {
     }
 }
 
+impl core::fmt::Debug for ConnectionOptions {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut debug = f.debug_struct("ConnectionOptions");
+        debug
+            .field("local_ip", &self.local_ip)
+            .field("local_port", &self.local_port)
+            .field("remote_ip", &self.remote_ip)
+            .field("remote_port", &self.remote_port)
+            .field("timeout", &self.timeout)
+            .field("resend_attempts", &self.resend_attempts)
+            .field("retry_policy", &self.retry_policy)
+            .field("rx_buffer_size", &self.rx_buffer_size)
+            .field("accept_compression", &self.accept_compression)
+            .field("priority", &self.priority);
+        #[cfg(feature = "client-encryption")]
+        debug.field("psk", &self.psk.map(|_| "<redacted>"));
+        debug.finish()
+    }
+}
+
 impl ConnectionOptions {
+    /// Creates a new [`Request`] with a random request id, for example
+    /// `options.new_request(crate::Request::RetrieveDeviceInformation)`.
+    pub fn new_request(
+        &self,
+        request: impl FnOnce(u8) -> crate::Request,
+    ) -> Result<Request, crate::Error> {
+        self.new_request_with_payload(request, &[])
+    }
+
+    /// Creates a new [`Request`] with a random request id, which is followed by the given payload
+    pub fn new_request_with_payload(
+        &self,
+        request: impl FnOnce(u8) -> crate::Request,
+        payload: &[u8],
+    ) -> Result<Request, crate::Error> {
+        let request = request(random::default().read());
+
+        let serialized = {
+            let mut binary = Vec::new();
+            self.write_request(&request, &mut binary)?;
+            binary.extend_from_slice(payload);
+            binary
+        };
+
+        Ok(Request {
+            connection_options: self.clone(),
+            request,
+            serialized,
+            batched: Vec::new(),
+        })
+    }
+
+    /// Writes the request preceded by the priority header, if configured
+    fn write_request(
+        &self,
+        request: &crate::Request,
+        binary: &mut Vec<u8>,
+    ) -> Result<usize, crate::Error> {
+        let mut len = 0;
+        if self.accept_compression {
+            len += AcceptCompressionHeader::all().write(binary)?;
+        }
+        if let Some(priority) = self.priority {
+            len += PriorityHeader(priority).write(binary)?;
+        }
+        Ok(len + request.write(binary)?)
+    }
+
+    /// Combines the given requests into a single [`crate::Request::Batch`], see
+    /// [`Response::split_batch`] to retrieve the individual responses.
+    pub fn new_batch(&self, requests: &[Request]) -> Result<Request, crate::Error> {
+        let count = u8::try_from(requests.len()).map_err(|_| crate::Error::BufferToSmall)?;
+        let mut payload = Vec::new();
+        for request in requests {
+            // only the batch itself carries the compression and priority headers
+            let (_, serialized) = AcceptCompressionHeader::read_optional(&request.serialized)?;
+            let (_, serialized) = PriorityHeader::read_optional(serialized)?;
+            crate::batch::write_entry(&mut payload, serialized)?;
+        }
+
+        let mut batch =
+            self.new_request_with_payload(|id| crate::Request::Batch(id, count), &payload)?;
+        batch.batched = requests.iter().map(|r| r.request).collect();
+        Ok(batch)
+    }
+
+    /// Creates a [`crate::Request::StoreProperty`] that writes the encoded value to the property
+    /// with the given path, see [`Response::echoed_value`] for the value the device applied
+    pub fn new_store_property(&self, path: &[u8], value: &[u8]) -> Result<Request, crate::Error> {
+        let len = u8::try_from(path.len()).map_err(|_| crate::Error::BufferToSmall)?;
+        let payload = [path, value].concat();
+        self.new_request_with_payload(|id| crate::Request::StoreProperty(id, len), &payload)
+    }
+
+    pub fn new_retrieve_property(&self, path: &[u8]) -> Result<Request, crate::Error> {
+        let len = u8::try_from(path.len()).map_err(|_| crate::Error::BufferToSmall)?;
+        self.new_request_with_payload(|id| crate::Request::RetrieveProperty(id, len), path)
+    }
+
+    /// Lists at most `limit` properties starting at the given offset, see
+    /// [`ConnectionOptions::list_components`] to list all of them
+    pub fn new_list_components_range(
+        &self,
+        offset: u16,
+        limit: u8,
+    ) -> Result<Request, crate::Error> {
+        self.new_request(|id| crate::Request::ListComponentsRange(id, offset, limit))
+    }
+
+    /// Lists all properties of the device in pages of the given number of properties
+    pub fn list_components<R: Runtime>(&self, page_size: u8) -> ComponentStream<'_, R> {
+        ComponentStream::new(self, page_size)
+    }
+
+    /// Reads the user assigned name of the device, see [`well_known::DEVICE_NAME`]
+    pub fn new_retrieve_device_name(&self) -> Result<Request, crate::Error> {
+        self.new_retrieve_property(&well_known::DEVICE_NAME)
+    }
+
+    /// Reads the user assigned location of the device, see [`well_known::DEVICE_LOCATION`]
+    pub fn new_retrieve_device_location(&self) -> Result<Request, crate::Error> {
+        self.new_retrieve_property(&well_known::DEVICE_LOCATION)
+    }
+
+    /// Assigns a name to the device, fails with [`crate::Error::BufferToSmall`] if the name is
+    /// longer than [`well_known::DEVICE_NAME_MAX_LEN`]
+    pub fn new_store_device_name(&self, name: &str) -> Result<Request, crate::Error> {
+        self.new_store_string(
+            &well_known::DEVICE_NAME,
+            name,
+            well_known::DEVICE_NAME_MAX_LEN,
+        )
+    }
+
+    /// Assigns a location to the device, fails with [`crate::Error::BufferToSmall`] if the
+    /// location is longer than [`well_known::DEVICE_LOCATION_MAX_LEN`]
+    pub fn new_store_device_location(&self, location: &str) -> Result<Request, crate::Error> {
+        self.new_store_string(
+            &well_known::DEVICE_LOCATION,
+            location,
+            well_known::DEVICE_LOCATION_MAX_LEN,
+        )
+    }
+
+    fn new_store_string(
+        &self,
+        path: &[u8],
+        value: &str,
+        max_len: usize,
+    ) -> Result<Request, crate::Error> {
+        if value.len() > max_len {
+            return Err(crate::Error::BufferToSmall);
+        }
+        let mut encoded = Vec::new();
+        encoded.write_dyn_string(value)?;
+        self.new_store_property(path, &encoded)
+    }
+
+    /// Reads `len` bytes of the EEPROM starting at `offset`, see [`crate::eeprom`]
+    pub fn new_read_eeprom(&self, offset: u16, len: u8) -> Result<Request, crate::Error> {
+        self.new_request(|id| crate::Request::ReadEeprom(id, offset, len))
+    }
+
+    /// Writes the data to the EEPROM starting at `offset`, see [`crate::eeprom`]
+    pub fn new_write_eeprom(&self, offset: u16, data: &[u8]) -> Result<Request, crate::Error> {
+        self.new_request_with_payload(|id| crate::Request::WriteEeprom(id, offset), data)
+    }
+
+    /// Erases all settings of the device, confirmed with the token of the given information,
+    /// which has to be retrieved right before, see [`crate::factory_reset`]
+    pub fn new_factory_reset(
+        &self,
+        information: &DeviceInformation,
+    ) -> Result<Request, crate::Error> {
+        let token = information.factory_reset_token();
+        self.new_request(|id| crate::Request::FactoryReset(id, token))
+    }
+
+    pub fn new_reset(&self, kind: crate::ResetKind) -> Result<Request, crate::Error> {
+        self.new_request(|id| crate::Request::Reset(id, kind))
+    }
+
+    pub fn new_firmware_upgrade_begin(&self, image: &[u8]) -> Result<Request, crate::Error> {
+        let size = u32::try_from(image.len()).map_err(|_| crate::Error::BufferToSmall)?;
+        let crc = crate::firmware::Crc32::checksum(image);
+        self.new_request(|id| crate::Request::BeginFirmwareUpgrade(id, size, crc))
+    }
+
+    pub fn new_firmware_chunk(&self, offset: u32, chunk: &[u8]) -> Result<Request, crate::Error> {
+        self.new_request_with_payload(|id| crate::Request::FirmwareChunk(id, offset), chunk)
+    }
+
+    pub fn new_firmware_commit(&self) -> Result<Request, crate::Error> {
+        self.new_request(crate::Request::CommitFirmware)
+    }
+
+    /// Retrieves at most `limit` entries of the error dump starting at the given offset, see
+    /// [`Response::extract_error_dump`]. Fewer entries than requested mark the end of the dump.
+    pub fn new_retrieve_error_dump_range(
+        &self,
+        offset: u16,
+        limit: u8,
+    ) -> Result<Request, crate::Error> {
+        self.new_request(|id| crate::Request::RetrieveErrorDumpRange(id, offset, limit))
+    }
+
+    /// Removes all entries of the error dump, for example after they have been collected
+    pub fn new_clear_error_dump(&self) -> Result<Request, crate::Error> {
+        self.new_request(crate::Request::ClearErrorDump)
+    }
+
+    /// Retrieves the log records starting at the given sequence number, see
+    /// [`Response::extract_log_records`] and [`LogTail`]
+    pub fn new_tail_log(&self, since: u32) -> Result<Request, crate::Error> {
+        self.new_request(|id| crate::Request::TailLog(id, since))
+    }
+
+    /// Lists the requests deferred with [`Request::deferred`] that are not yet executed
+    pub fn new_list_pending_commands(&self) -> Result<Request, crate::Error> {
+        self.new_request(crate::Request::ListPendingCommands)
+    }
+
+    /// Cancels the deferred request with the given id, see [`Request::id`]
+    pub fn new_cancel_pending_command(&self, request_id: u8) -> Result<Request, crate::Error> {
+        self.new_request(|id| crate::Request::CancelPendingCommand(id, request_id))
+    }
+
+    /// Checks whether the device answers, without triggering any bus access on the device,
+    /// see [`ConnectionOptions::is_alive`]
+    pub fn new_ping(&self) -> Result<Request, crate::Error> {
+        self.new_request(crate::Request::Ping)
+    }
+
+    /// Whether the device acknowledges a `Request::Ping` within the given timeout, including
+    /// the resends configured in the [`ConnectionOptions`]
+    pub async fn is_alive<R: Runtime>(&self, timeout: Duration) -> bool {
+        let request = match self.new_ping() {
+            Ok(request) => request,
+            Err(_) => return false,
+        };
+        matches!(
+            R::timeout_at(Instant::now() + timeout, request.dispatch_on::<R>()).await,
+            Some(Ok(response)) if response.is_acknowledgement()
+        )
+    }
+
+    /// Reads the clock of the device, see [`Response::extract_time`]
+    pub fn new_get_time(&self) -> Result<Request, crate::Error> {
+        self.new_request(crate::Request::GetTime)
+    }
+
+    /// Sets the clock of the device to the given time without compensating the transmission
+    /// delay, see [`ConnectionOptions::synchronize_clock`]
+    pub fn new_set_time(&self, time: SystemTime) -> Result<Request, crate::Error> {
+        let millis = unix_millis(time);
+        self.new_request(|id| crate::Request::SetTime(id, millis))
+    }
+
+    /// Sets the clock of the device to the clock of this host. The time sent is advanced by
+    /// half the round trip time of a preceding `Request::GetTime`, which also tells how far
+    /// the clocks were apart. Instead of resending the `Request::SetTime`, each attempt sends
+    /// the then current time.
+    pub async fn synchronize_clock<R: Runtime>(&self) -> Result<ClockSync, DispatchError> {
+        let response = self
+            .new_get_time()
+            .map_err(DispatchError::Encoding)?
+            .dispatch_on::<R>()
+            .await?;
+        let rtt = response.stats().rtt;
+        let one_way = i64::try_from(rtt.as_millis() / 2).unwrap_or(i64::MAX);
+        let offset_millis = response
+            .extract_time()
+            .map(|device| device as i64 + one_way - unix_millis(SystemTime::now()) as i64);
+
+        let attempts = self.retry_policy.attempts(self.resend_attempts);
+        for attempt in 0..attempts {
+            // a resent request would carry a stale time, so each attempt is encoded anew
+            let options = ConnectionOptions {
+                timeout: self.retry_policy.timeout(self.timeout, attempt),
+                retry_policy: RetryPolicy::None,
+                ..self.clone()
+            };
+            let response = match options
+                .new_set_time(SystemTime::now() + rtt / 2)
+                .map_err(DispatchError::Encoding)?
+                .dispatch_on::<R>()
+                .await
+            {
+                Err(DispatchError::Timeout) => continue,
+                response => response?,
+            };
+            return if response.is_acknowledgement() {
+                Ok(ClockSync { offset_millis, rtt })
+            } else {
+                Err(DispatchError::UnexpectedResponse(Box::new(response)))
+            };
+        }
+        Err(DispatchError::Timeout)
+    }
+
+    /// Asks for a challenge to pair with, see [`crate::pairing`]
+    pub fn new_request_challenge(&self) -> Result<Request, crate::Error> {
+        self.new_request(crate::Request::RequestChallenge)
+    }
+
+    /// Answers the challenge with the secret of the device to obtain a session, see
+    /// [`Response::extract_session`]
+    pub fn new_pair(
+        &self,
+        challenge: &[u8; 8],
+        secret: &[u8; 16],
+    ) -> Result<Request, crate::Error> {
+        let response = crate::pairing::challenge_response(secret, challenge);
+        self.new_request(|id| crate::Request::Pair(id, response))
+    }
+
+    /// Reads all sensors, on the given bus only if any. Asks for the count-prefixed response
+    /// format, see [`crate::counted`], which older firmware ignores.
+    pub fn new_read_all(&self, bus: Option<crate::Bus>) -> Result<Request, crate::Error> {
+        self.new_read_all_with_flags(bus, ReadFlags::counted())
+    }
+
+    /// Reads all sensors like [`ConnectionOptions::new_read_all`]. With `reset`, the counters
+    /// are reset to `0` after being read, so that each response contains the events since the
+    /// previous one. Otherwise the deltas can be computed with a [`CounterDeltas`].
+    pub fn new_read_counters(
+        &self,
+        bus: Option<crate::Bus>,
+        reset: bool,
+    ) -> Result<Request, crate::Error> {
+        self.new_read_all_with_flags(bus, ReadFlags::counted().with_reset_counters(reset))
+    }
+
+    fn new_read_all_with_flags(
+        &self,
+        bus: Option<crate::Bus>,
+        flags: ReadFlags,
+    ) -> Result<Request, crate::Error> {
+        let mut payload = Vec::new();
+        flags.write(&mut payload)?;
+        match bus {
+            Some(bus) => {
+                self.new_request_with_payload(|id| crate::Request::ReadAllOnBus(id, bus), &payload)
+            }
+            None => self.new_request_with_payload(crate::Request::ReadAll, &payload),
+        }
+    }
+
+    /// Reads the error counters of all sensors on the given bus
+    pub fn new_read_diagnostics(&self, bus: crate::Bus) -> Result<Request, crate::Error> {
+        self.new_request(|id| crate::Request::ReadDiagnostics(id, bus))
+    }
+
     pub fn new_onewire_read<'a>(
         &self,
         devices: impl Iterator<Item = &'a onewire::Device>,
@@ -56,8 +496,12 @@ impl ConnectionOptions {
 
         let serialized = {
             let mut binary = Vec::new();
-            request.write(&mut binary)?;
-            binary.extend(devices.flat_map(|d| d.address.iter().cloned()));
+            self.write_request(&request, &mut binary)?;
+            encode_address_list(
+                crate::Bus::OneWire,
+                devices.map(|d| &d.address[..]),
+                &mut binary,
+            )?;
             binary
         };
 
@@ -65,9 +509,86 @@ impl ConnectionOptions {
             connection_options: self.clone(),
             request,
             serialized,
+            batched: Vec::new(),
         })
     }
 
+    /// Reads the devices with the given 7-bit addresses on the first I2C bus
+    pub fn new_i2c_read(
+        &self,
+        addresses: impl Iterator<Item = u8>,
+    ) -> Result<Request, crate::Error> {
+        let request = crate::Request::ReadSpecified(random::default().read(), crate::Bus::I2C);
+
+        let serialized = {
+            let mut binary = Vec::new();
+            self.write_request(&request, &mut binary)?;
+            let addresses = addresses.map(|a| [a]).collect::<Vec<_>>();
+            encode_address_list(
+                crate::Bus::I2C,
+                addresses.iter().map(|a| &a[..]),
+                &mut binary,
+            )?;
+            binary
+        };
+
+        Ok(Request {
+            connection_options: self.clone(),
+            request,
+            serialized,
+            batched: Vec::new(),
+        })
+    }
+
+    /// Drives the output with the given address, see [`crate::output`]
+    pub fn new_set_output(
+        &self,
+        bus: crate::Bus,
+        address: &[u8],
+        state: OutputState,
+    ) -> Result<Request, crate::Error> {
+        let mut payload = Vec::new();
+        encode_address_list(bus, core::iter::once(address), &mut payload)?;
+        self.new_request_with_payload(|id| crate::Request::SetOutput(id, bus, state), &payload)
+    }
+
+    /// Reads the state of the output with the given address, see [`crate::output`]
+    pub fn new_get_output(&self, bus: crate::Bus, address: &[u8]) -> Result<Request, crate::Error> {
+        let mut payload = Vec::new();
+        encode_address_list(bus, core::iter::once(address), &mut payload)?;
+        self.new_request_with_payload(|id| crate::Request::GetOutput(id, bus), &payload)
+    }
+
+    /// Applies the configuration on the device, which reverts to its previous configuration
+    /// unless [`ConnectionOptions::new_commit_network_config`] arrives within the timeout
+    pub fn new_stage_network_config(
+        &self,
+        configuration: &NetworkConfiguration,
+        timeout: Duration,
+    ) -> Result<Request, crate::Error> {
+        let timeout = u16::try_from(timeout.as_secs()).unwrap_or(u16::MAX);
+        let mut payload = Vec::new();
+        configuration.write(&mut payload)?;
+        self.new_request_with_payload(
+            |id| crate::Request::StageNetworkConfig(id, timeout),
+            &payload,
+        )
+    }
+
+    /// Keeps the staged configuration, has to be sent to the new address of the device, see
+    /// [`ConnectionOptions::with_remote_ip`]
+    pub fn new_commit_network_config(&self) -> Result<Request, crate::Error> {
+        self.new_request(crate::Request::CommitNetworkConfig)
+    }
+
+    /// The same options for another device or for the same device at a new address
+    pub fn with_remote_ip(&self, remote_ip: impl Into<IpAddr>) -> Self {
+        Self {
+            remote_ip: remote_ip.into(),
+            ..self.clone()
+        }
+    }
+
     pub fn local_address(&self) -> SocketAddr {
         SocketAddr::new(
             self.local_ip.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
@@ -80,14 +601,90 @@ impl ConnectionOptions {
     }
 }
 
+/// Skips the headers that precede a header of the given kind, the device expects them in the
+/// order of [`HeaderKind`]
+fn skip_transport_headers(datagram: &[u8], kind: HeaderKind) -> Result<&[u8], crate::Error> {
+    RequestHeaderIter::new(datagram).skip_before(kind)
+}
+
 #[derive(Debug)]
 pub struct Request {
     connection_options: ConnectionOptions,
     request: crate::Request,
     serialized: Vec<u8>,
+    batched: Vec<crate::Request>,
 }
 
 impl Request {
+    /// The id of the request, to match it with its response or to cancel it once deferred
+    pub fn id(&self) -> u8 {
+        self.request.id()
+    }
+
+    /// Routes the request through a gateway to the device behind the given hops, see
+    /// [`crate::routing`]
+    pub fn via(mut self, hops: &[u8]) -> Result<Self, crate::Error> {
+        let routing = RoutingHeader::new(hops)?;
+        let mut header = Vec::with_capacity(routing.encoded_len());
+        routing.write(&mut header)?;
+        self.serialized.splice(0..0, header);
+        Ok(self)
+    }
+
+    /// Prefixes the request with the given [`SessionHeader`], which configuration requests
+    /// require, see [`crate::pairing`]
+    pub fn with_session(mut self, session: SessionHeader) -> Result<Self, crate::Error> {
+        let mut header = Vec::with_capacity(SessionHeader::ENCODED_LEN);
+        session.write(&mut header)?;
+        let remaining = skip_transport_headers(&self.serialized, HeaderKind::Session)?;
+        let offset = self.serialized.len() - remaining.len();
+        self.serialized.splice(offset..offset, header);
+        Ok(self)
+    }
+
+    /// Prefixes the request with the given [`ScheduleHeader`], so that the device executes it
+    /// later instead of right away. The response then only acknowledges the scheduling.
+    pub fn deferred(mut self, schedule: ScheduleHeader) -> Result<Self, crate::Error> {
+        let mut header = Vec::with_capacity(ScheduleHeader::ENCODED_LEN);
+        schedule.write(&mut header)?;
+        let remaining = skip_transport_headers(&self.serialized, HeaderKind::Schedule)?;
+        let offset = self.serialized.len() - remaining.len();
+        self.serialized.splice(offset..offset, header);
+        Ok(self)
+    }
+
+    /// Extends the timeout by the time the device needs to answer a query of the given
+    /// complexity: by the estimated time if known, otherwise doubles the timeout for
+    /// `QueryComplexity::High`
+    pub fn with_complexity(mut self, complexity: QueryComplexity) -> Self {
+        let extension = match complexity {
+            QueryComplexity::Low {
+                estimated_millis: Some(millis),
+            }
+            | QueryComplexity::High {
+                estimated_millis: Some(millis),
+            } => Duration::from_millis(u64::from(millis.get())),
+            QueryComplexity::High {
+                estimated_millis: None,
+            } => self.connection_options.timeout,
+            QueryComplexity::Low {
+                estimated_millis: None,
+            }
+            | QueryComplexity::Unknown => Duration::from_millis(0),
+        };
+        self.connection_options.timeout += extension;
+        self
+    }
+
+    /// Extends the timeout according to the complexity of the reported property, see
+    /// [`Self::with_complexity`]
+    pub fn with_timeout_for(self, report: &PropertyReportV1) -> Self {
+        self.with_complexity(report.complexity)
+    }
+
+    /// Dispatches the request on a new current thread tokio runtime. Needs the `runtime-tokio`
+    /// feature, `std` no longer enables tokio on its own.
+    #[cfg(feature = "tokio")]
     pub fn dispatch(self) -> Result<Response, DispatchError> {
         tokio::runtime::Builder::new_current_thread()
             .enable_io()
@@ -97,90 +694,215 @@ impl Request {
             .block_on(self.dispatch_async())
     }
 
+    #[cfg(feature = "tokio")]
     pub async fn dispatch_async(self) -> Result<Response, DispatchError> {
-        let mut buffer = vec![0u8; self.connection_options.rx_buffer_size];
-        let socket =
-            match tokio::net::UdpSocket::bind(self.connection_options.local_address()).await {
-                Ok(socket) => socket,
+        self.dispatch_on::<Tokio>().await
+    }
+
+    /// Dispatches the request over a new UDP socket of the given [`Runtime`]
+    pub async fn dispatch_on<R: Runtime>(self) -> Result<Response, DispatchError> {
+        let mut transport = match R::bind(
+            self.connection_options.local_address(),
+            self.connection_options.remote_address(),
+        )
+        .await
+        {
+            Ok(transport) => transport,
+            Err(source) => {
+                return Err(DispatchError::Io {
+                    request: Box::new(self),
+                    source,
+                })
+            }
+        };
+        #[cfg(feature = "client-encryption")]
+        if let Some(psk) = self.connection_options.psk {
+            let mut transport = match EncryptedTransport::new(transport, psk) {
+                Ok(transport) => transport,
                 Err(source) => {
                     return Err(DispatchError::Io {
-                        request: self,
+                        request: Box::new(self),
                         source,
                     })
                 }
             };
+            return self.dispatch_with::<R>(&mut transport).await;
+        }
+        self.dispatch_with::<R>(&mut transport).await
+    }
 
-        for send_counter in 0..self.connection_options.resend_attempts.get() {
-            if let Err(source) = socket
-                .send_to(
-                    &self.serialized[..],
-                    self.connection_options.remote_address(),
-                )
-                .await
-            {
-                return Err(DispatchError::Io {
-                    request: self,
-                    source,
-                });
-            }
+    /// Dispatches the request over the given [`Transport`] instead of a new UDP socket,
+    /// resending it and waiting for its response as configured in the [`ConnectionOptions`].
+    /// The timer of the given [`Runtime`] is used for the timeouts.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, transport), fields(request = ?self.request))
+    )]
+    pub async fn dispatch_with<R: Runtime>(
+        self,
+        transport: &mut impl Transport,
+    ) -> Result<Response, DispatchError> {
+        let mut buffer = vec![0u8; self.connection_options.rx_buffer_size];
+        let retry_policy = self.connection_options.retry_policy;
+        let attempts = retry_policy.attempts(self.connection_options.resend_attempts);
+        let mut duplicates_ignored = 0u32;
+        let mut stats = DispatchStats::default();
 
-            match tokio::time::timeout(
-                self.connection_options.timeout,
-                socket.recv_from(&mut buffer),
-            )
-            .await
-            {
-                Ok(Ok((len, from))) => {
-                    if from == self.connection_options.remote_address() {
-                        let (response, payload_size) = {
-                            let mut reader = &buffer[..len];
-                            match crate::Response::read(&mut reader) {
-                                Ok(response) => (response, reader.available()),
-                                Err(source) => {
-                                    return Err(DispatchError::ProtocolError {
-                                        request: self,
-                                        source,
-                                    })
-                                }
-                            }
-                        };
-
-                        return Ok(Response {
-                            request: self.request,
-                            response,
-                            payload: buffer
-                                .into_iter()
-                                .skip(len - payload_size)
-                                .take(payload_size)
-                                .collect::<Vec<u8>>(),
-                            requests_sent: send_counter.saturating_add(1),
-                        });
-                    } else {
-                        eprintln!(
-                            "Received response of len={} from unexpected source: {:?}",
-                            len, from
-                        )
-                    }
+        for send_counter in 0..attempts {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(attempt = send_counter, "sending request");
+
+            match transport.send(&self.serialized[..]).await {
+                Ok(len) => {
+                    stats.attempts = send_counter.saturating_add(1);
+                    stats.bytes_sent += len;
                 }
-                Ok(Err(source)) => {
+                Err(source) => {
                     return Err(DispatchError::Io {
-                        request: self,
+                        request: Box::new(self),
                         source,
                     });
                 }
-                // timeout, retry
-                Err(_) => {}
+            }
+
+            let sent_at = Instant::now();
+            let deadline =
+                sent_at + retry_policy.timeout(self.connection_options.timeout, send_counter);
+
+            loop {
+                let (len, from) = match R::timeout_at(deadline, transport.recv(&mut buffer)).await {
+                    Some(Ok(received)) => received,
+                    Some(Err(source)) => {
+                        return Err(DispatchError::Io {
+                            request: Box::new(self),
+                            source,
+                        });
+                    }
+                    // timeout, retry
+                    None => {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(attempt = send_counter, "request timed out");
+                        break;
+                    }
+                };
+
+                stats.bytes_received += len;
+
+                if from != self.connection_options.remote_address() {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(len, %from, "ignoring response from unexpected source");
+                    continue;
+                }
+
+                let (response, payload_size, decompressed) = {
+                    // the hops of a routed response are the ones of the request
+                    let remaining = match RoutingHeader::read_optional(&buffer[..len]) {
+                        Ok((_, remaining)) => remaining,
+                        Err(source) => {
+                            return Err(DispatchError::ProtocolError {
+                                request: Box::new(self),
+                                source,
+                            })
+                        }
+                    };
+                    let decompressed = if remaining.first() == Some(&COMPRESSED_RESPONSE_MARKER) {
+                        let mut decompressed = Vec::new();
+                        if let Err(source) = decompress_response(remaining, &mut decompressed) {
+                            return Err(DispatchError::ProtocolError {
+                                request: Box::new(self),
+                                source,
+                            });
+                        }
+                        Some(decompressed)
+                    } else {
+                        None
+                    };
+                    let mut reader = decompressed.as_deref().unwrap_or(remaining);
+                    match crate::Response::read(&mut reader) {
+                        Ok(response) => (response, reader.available(), decompressed),
+                        Err(source) => {
+                            #[cfg(feature = "tracing")]
+                            tracing::warn!(len, error = ?source, "failed to decode response");
+                            return Err(DispatchError::ProtocolError {
+                                request: Box::new(self),
+                                source,
+                            });
+                        }
+                    }
+                };
+
+                if response.id() != self.request.id() {
+                    // a late answer to a previous request
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(id = response.id(), "ignoring duplicate response");
+                    duplicates_ignored = duplicates_ignored.saturating_add(1);
+                    continue;
+                }
+
+                stats.rtt = sent_at.elapsed();
+
+                #[cfg(feature = "tracing")]
+                tracing::debug!(?stats, ?response, "received response");
+
+                let datagram = match decompressed {
+                    Some(decompressed) => decompressed,
+                    None => {
+                        buffer.truncate(len);
+                        buffer
+                    }
+                };
+                return Ok(Response {
+                    request: self.request,
+                    response,
+                    payload_start: datagram.len() - payload_size,
+                    payload_end: datagram.len(),
+                    datagram: Arc::new(datagram),
+                    stats,
+                    duplicates_ignored,
+                    batched: self.batched,
+                });
             }
         }
+
+        #[cfg(feature = "tracing")]
+        tracing::warn!(?stats, "all requests remained unanswered");
         Err(DispatchError::Timeout)
     }
 }
 
+/// The result of [`ConnectionOptions::synchronize_clock`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ClockSync {
+    /// How many milliseconds the clock of the device was ahead before it has been set, `None`
+    /// if it had not been set
+    pub offset_millis: Option<i64>,
+    /// The round trip time the transmission delay has been estimated from
+    pub rtt: Duration,
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|since| since.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Measurements of a single [`Request::dispatch_async`]
+#[derive(Debug, Copy, Clone, PartialEq, core::default::Default)]
+pub struct DispatchStats {
+    /// The time between the last transmission of the request and the arrival of its response
+    pub rtt: Duration,
+    /// The number of times the request has been sent
+    pub attempts: u8,
+    pub bytes_sent: usize,
+    /// All bytes received while waiting for the response, including ignored datagrams
+    pub bytes_received: usize,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum DispatchError {
     #[error("Input/Output Error {source}")]
     Io {
-        request: Request,
+        request: Box<Request>,
         #[source]
         source: std::io::Error,
     },
@@ -188,32 +910,127 @@ pub enum DispatchError {
     Timeout,
     #[error("An error occurred on the underlying protocol {source}")]
     ProtocolError {
-        request: Request,
+        request: Box<Request>,
         #[source]
         source: crate::Error,
     },
+    #[error("Failed to encode the request {0}")]
+    Encoding(#[source] crate::Error),
+    #[error("The device answered with an unexpected response {:?}", .0.response)]
+    UnexpectedResponse(Box<Response>),
+}
+
+/// A device found by a [`crate::Request::DiscoverAll`] or [`crate::Request::DiscoverAllOnBus`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiscoveredDevice {
+    OneWire(OneWireAddress),
+    I2c(u16),
+    ModbusRtu(Register),
+    Can(CanId),
+    Analog(u8),
+    /// An address of an unknown bus or of a [`crate::Bus::Custom`] bus
+    Other(Vec<u8>),
+}
+
+/// The events counted between polls of [`Type::CounterU64`] values, by the address of the
+/// counter
+#[derive(Debug, Clone, core::default::Default)]
+pub struct CounterDeltas {
+    previous: HashMap<Vec<u8>, u64>,
+}
+
+impl CounterDeltas {
+    /// Remembers the counters of the response and returns the number of events since the
+    /// previous response for each counter. Counters seen for the first time are omitted.
+    pub fn update<'a>(&mut self, response: &'a Response) -> Option<Vec<(&'a [u8], u64)>> {
+        Some(
+            response
+                .extract_counters()?
+                .into_iter()
+                .filter_map(|(address, count)| {
+                    self.previous
+                        .insert(address.to_vec(), count)
+                        .map(|previous| (address, crate::value::counter_delta(previous, count)))
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The outcome of reading a single sensor, see [`Response::extract_statuses`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SensorReading<'a> {
+    pub address: &'a [u8],
+    pub status: SensorStatus,
+    /// The value read from the sensor, `None` unless the status is [`SensorStatus::Ok`]
+    pub value: Option<Value<'a>>,
 }
 
 #[derive(Debug)]
 pub struct Response {
     request: crate::Request,
     response: crate::Response,
-    payload: Vec<u8>,
-    requests_sent: u8,
+    /// The received datagram, the payload is in `payload_start..payload_end`. The responses
+    /// split from a batch share the datagram of the batch.
+    datagram: Arc<Vec<u8>>,
+    payload_start: usize,
+    payload_end: usize,
+    stats: DispatchStats,
+    /// The number of received answers to other (previous) requests
+    duplicates_ignored: u32,
+    batched: Vec<crate::Request>,
 }
 
 impl Response {
+    /// The payload following the response header
+    pub fn payload(&self) -> &[u8] {
+        &self.datagram[self.payload_start..self.payload_end]
+    }
+
+    /// The response borrowing the received datagram, see [`ResponseFrame`]
+    pub fn frame(&self) -> ResponseFrame<'_> {
+        ResponseFrame::new(self.response, self.payload())
+    }
+
+    pub fn stats(&self) -> &DispatchStats {
+        &self.stats
+    }
+
+    /// The number of answers to previous requests that arrived while waiting for this response
+    /// and have been ignored
+    pub fn duplicates_ignored(&self) -> u32 {
+        self.duplicates_ignored
+    }
+
+    /// The reason the device gave for not serving the request
+    pub fn error_code(&self) -> Option<crate::ErrorCode> {
+        self.response.error_code()
+    }
+
+    /// The type and the encoded value the device read back after a property write
+    pub fn echoed_value(&self) -> Option<(Type, &[u8])> {
+        match &self.response {
+            crate::Response::Ok(_, Format::Echoed(ty)) => Some((*ty, self.payload())),
+            _ => None,
+        }
+    }
+
+    /// Whether the device acknowledged the request with `Response::Ok(id, Format::Empty)`
+    pub fn is_acknowledgement(&self) -> bool {
+        matches!(self.response, crate::Response::Ok(_, Format::Empty))
+    }
+
     pub fn extract_values_f32(&self) -> Option<Vec<f32>> {
         match &self.response {
             crate::Response::Ok(_, Format::ValueOnly(Type::F32)) => Some(
-                self.payload
+                self.payload()
                     .chunks_exact(0_f32.to_be_bytes().len())
                     .map(|chunk| f32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
                     .collect(),
             ),
             crate::Response::Ok(_, Format::AddressValuePairs(Type::Bytes(addr_len), Type::F32)) => {
                 Some(
-                    self.payload
+                    self.payload()
                         .chunks_exact(usize::from(*addr_len) + 0_f32.to_be_bytes().len())
                         .map(|chunk| {
                             f32::from_be_bytes([
@@ -226,12 +1043,445 @@ impl Response {
                         .collect(),
                 )
             }
+            crate::Response::Ok(_, Format::CountedAddressValuePairs(Type::Bytes(_), Type::F32)) => {
+                self.frame()
+                    .address_values()?
+                    .map(|pair| match pair {
+                        Ok((_, Value::F32(value))) => Some(value),
+                        _ => None,
+                    })
+                    .collect()
+            }
+            _ => None,
+        }
+    }
+
+    /// Decodes `Type::F64` values as well as `Type::ScaledI32` values, which are scaled
+    /// accordingly
+    pub fn extract_values_f64(&self) -> Option<Vec<f64>> {
+        if let crate::Response::Ok(
+            _,
+            Format::CountedAddressValuePairs(Type::Bytes(_), Type::F64 | Type::ScaledI32 { .. }),
+        ) = &self.response
+        {
+            return self
+                .frame()
+                .address_values()?
+                .map(|pair| pair.ok()?.1.to_f64())
+                .collect();
+        }
+        let (addr_len, ty) = match &self.response {
+            crate::Response::Ok(_, Format::ValueOnly(ty)) => (0, *ty),
+            crate::Response::Ok(_, Format::AddressValuePairs(Type::Bytes(addr_len), ty)) => {
+                (usize::from(*addr_len), *ty)
+            }
+            _ => return None,
+        };
+        let size = match ty {
+            Type::F64 | Type::ScaledI32 { .. } => ty.value_size()?,
+            _ => return None,
+        };
+        self.payload()
+            .chunks_exact(addr_len + size)
+            .map(|chunk| match Value::read(ty, &mut &chunk[addr_len..]) {
+                Ok(Value::F64(value)) => Ok(value),
+                Ok(Value::ScaledI32 { raw, exponent }) => Ok(crate::value::scaled(raw, exponent)),
+                _ => Err(crate::Error::UnknownTypeIdentifier),
+            })
+            .collect::<Result<_, _>>()
+            .ok()
+    }
+
+    /// Decodes the buffered measurements of a `Format::AddressValueTimestampTriples` response
+    /// into addresses, values and the milliseconds since the unix epoch they have been sampled at
+    pub fn extract_timestamped_values(&self) -> Option<Vec<(&[u8], Value<'_>, u64)>> {
+        self.frame()
+            .address_value_timestamps()?
+            .map(|triple| {
+                let (address, value, timestamp) = triple.ok()?;
+                Some((address, value, timestamp.timestamp_millis()?))
+            })
+            .collect()
+    }
+
+    /// Decodes the addresses, statuses and values of a `Format::AddressStatusValue` response,
+    /// the value is only present if the sensor has been read successfully
+    pub fn extract_statuses(&self) -> Option<Vec<SensorReading<'_>>> {
+        self.frame()
+            .address_status_values()?
+            .map(|element| {
+                let (address, status, value) = element.ok()?;
+                Some(SensorReading {
+                    address,
+                    status,
+                    value: Some(value).filter(|_| status.is_ok()),
+                })
+            })
+            .collect()
+    }
+
+    /// Decodes the `Type::CounterU64` values of an address-value pairs response
+    pub fn extract_counters(&self) -> Option<Vec<(&[u8], u64)>> {
+        self.frame()
+            .address_values()?
+            .map(|pair| match pair.ok()? {
+                (address, Value::Counter(count)) => Some((address, count)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Decodes the state of an output answered to a [`ConnectionOptions::new_set_output`] or
+    /// [`ConnectionOptions::new_get_output`]
+    pub fn extract_output_state(&self) -> Option<OutputState> {
+        match self.extract_value()? {
+            Value::OutputState(state) => Some(state),
+            _ => None,
+        }
+    }
+
+    /// Decodes the ids of the pending requests and the seconds until they are executed of the
+    /// response to a [`ConnectionOptions::new_list_pending_commands`]
+    pub fn extract_pending_commands(&self) -> Option<Vec<(u8, u32)>> {
+        self.frame()
+            .address_values()?
+            .map(|pair| match pair.ok()? {
+                ([request_id], Value::U32(seconds)) => Some((*request_id, seconds)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Decodes the error counters of the response to a
+    /// [`ConnectionOptions::new_read_diagnostics`]
+    pub fn extract_diagnostics(&self) -> Option<Vec<(&[u8], SensorDiagnostics)>> {
+        self.frame()
+            .address_values()?
+            .map(|pair| match pair.ok()? {
+                (address, Value::SensorDiagnostics(diagnostics)) => Some((address, diagnostics)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Decodes the address-value pairs of the response to a [`ConnectionOptions::new_i2c_read`]
+    pub fn extract_i2c_values(&self) -> Option<Vec<(u8, Value<'_>)>> {
+        match &self.response {
+            crate::Response::Ok(
+                _,
+                Format::AddressValuePairs(Type::U8 | Type::Bytes(1), value_type),
+            ) => {
+                let mut payload = self.payload();
+                let mut values = Vec::new();
+                while !payload.is_empty() {
+                    let address = payload.read_u8().ok()?;
+                    values.push((address, Value::read(*value_type, &mut payload).ok()?));
+                }
+                Some(values)
+            }
+            _ => None,
+        }
+    }
+
+    /// Decodes the addresses of a discovery response according to the requested bus. Without a
+    /// requested bus, 8 byte addresses are assumed to be OneWire addresses. The CRC of OneWire
+    /// addresses is verified.
+    pub fn extract_discovered_devices(
+        &self,
+    ) -> Option<Result<Vec<DiscoveredDevice>, crate::Error>> {
+        let address_len = match &self.response {
+            crate::Response::Ok(_, Format::AddressOnly(Type::Bytes(len))) => usize::from(*len),
+            crate::Response::Ok(_, Format::AddressOnly(Type::U8)) => 1,
+            crate::Response::Ok(_, Format::AddressOnly(Type::U16)) => 2,
+            _ => return None,
+        };
+        let bus = match self.request {
+            crate::Request::DiscoverAllOnBus(_, bus) => Some(bus),
+            crate::Request::DiscoverAll(_) if address_len == 8 => Some(crate::Bus::OneWire),
+            crate::Request::DiscoverAll(_) => None,
+            _ => return None,
+        };
+
+        if address_len == 0 {
+            return Some(Err(crate::Error::UnknownTypeIdentifier));
+        }
+
+        Some(
+            self.payload()
+                .chunks(address_len)
+                .map(|address| {
+                    if address.len() != address_len {
+                        return Err(crate::Error::UnexpectedEOF);
+                    }
+                    Ok(match (bus, address) {
+                        (Some(crate::Bus::OneWire), address) => {
+                            let address = OneWireAddress(
+                                <[u8; 8]>::try_from(address)
+                                    .map_err(|_| crate::Error::UnknownTypeIdentifier)?,
+                            );
+                            if !address.is_crc_valid() {
+                                return Err(crate::Error::InvalidChecksum);
+                            }
+                            DiscoveredDevice::OneWire(address)
+                        }
+                        (Some(crate::Bus::I2C | crate::Bus::I2cN { .. }), [address]) => {
+                            DiscoveredDevice::I2c(u16::from(*address))
+                        }
+                        (Some(crate::Bus::I2C | crate::Bus::I2cN { .. }), [high, low]) => {
+                            DiscoveredDevice::I2c(u16::from_be_bytes([*high, *low]))
+                        }
+                        (Some(crate::Bus::ModbusRtu { .. }), address) => {
+                            DiscoveredDevice::ModbusRtu(Register::from_address(address)?)
+                        }
+                        (Some(crate::Bus::Can { .. }), address) => {
+                            DiscoveredDevice::Can(CanId::from_address(address)?)
+                        }
+                        (Some(crate::Bus::Analog), [channel]) => DiscoveredDevice::Analog(*channel),
+                        (_, address) => DiscoveredDevice::Other(address.to_vec()),
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// Splits the response to a [`ConnectionOptions::new_batch`] request into the responses to
+    /// the batched requests, in the order of the requests. Fails with
+    /// [`crate::Error::PayloadLengthMismatch`] if the device did not answer each batched request
+    /// exactly once.
+    pub fn split_batch(&self) -> Option<Result<Vec<Response>, crate::Error>> {
+        match &self.response {
+            crate::Response::Ok(_, Format::ValueOnly(Type::Batch)) => Some(self.split_entries()),
+            _ => None,
+        }
+    }
+
+    fn split_entries(&self) -> Result<Vec<Response>, crate::Error> {
+        let mut entries = crate::batch::BatchIter::new(self.payload());
+        let responses = self
+            .batched
+            .iter()
+            .map(|request| {
+                let entry = entries
+                    .next()
+                    .ok_or(crate::Error::PayloadLengthMismatch)??;
+                let reader = &mut &*entry;
+                let response = crate::Response::read(reader)?;
+                // the entry borrows from the shared datagram
+                let entry_end =
+                    entry.as_ptr() as usize - self.datagram.as_ptr() as usize + entry.len();
+                Ok(Response {
+                    request: *request,
+                    response,
+                    payload_start: entry_end - reader.len(),
+                    payload_end: entry_end,
+                    datagram: Arc::clone(&self.datagram),
+                    stats: self.stats,
+                    duplicates_ignored: 0,
+                    batched: Vec::new(),
+                })
+            })
+            .collect::<Result<Vec<_>, crate::Error>>()?;
+        if entries.next().is_some() {
+            return Err(crate::Error::PayloadLengthMismatch);
+        }
+        Ok(responses)
+    }
+
+    /// The offset the device expects the next firmware chunk at, as answer to
+    /// [`crate::Request::BeginFirmwareUpgrade`] and [`crate::Request::FirmwareChunk`]
+    pub fn extract_firmware_offset(&self) -> Option<u32> {
+        match (&self.request, &self.response) {
+            (
+                crate::Request::BeginFirmwareUpgrade(..) | crate::Request::FirmwareChunk(..),
+                crate::Response::Ok(_, Format::ValueOnly(Type::U32)),
+            ) => self.payload().read_u32_be().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn extract_device_information(&self) -> Option<DeviceInformation> {
+        match &self.response {
+            crate::Response::Ok(_, Format::ValueOnly(Type::DeviceInformation)) => {
+                DeviceInformation::read(&mut self.payload()).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// The uptime of the device, as answer to [`crate::Request::RetrieveDeviceInformation`]
+    pub fn extract_uptime(&self) -> Option<Duration> {
+        self.extract_device_information()
+            .map(|information| Duration::from_millis(information.uptime_millis))
+    }
+
+    /// The cause of the most recent reset of the device, as answer to
+    /// [`crate::Request::RetrieveDeviceInformation`]
+    pub fn extract_reset_reason(&self) -> Option<ResetReason> {
+        self.extract_device_information()
+            .map(|information| information.reset_reason)
+    }
+
+    /// The number of times the device has booted, as answer to
+    /// [`crate::Request::RetrieveDeviceInformation`]. A count that keeps growing together with
+    /// [`ResetReason::is_fault`] points at a device stuck in a reset loop, for example because
+    /// of brownouts.
+    pub fn extract_boot_count(&self) -> Option<u32> {
+        self.extract_device_information()?.boot_count
+    }
+
+    /// Decodes the value of a `Format::ValueOnly` or `Format::Echoed` response
+    pub fn extract_value(&self) -> Option<Value<'_>> {
+        match &self.response {
+            crate::Response::Ok(_, Format::ValueOnly(ty) | Format::Echoed(ty)) => {
+                Value::read(*ty, &mut self.payload()).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Decodes the milliseconds since the unix epoch of the response to a
+    /// [`ConnectionOptions::new_get_time`]
+    pub fn extract_time(&self) -> Option<u64> {
+        match (&self.request, self.extract_value()?) {
+            (crate::Request::GetTime(_), Value::TimestampMillis(millis)) => Some(millis),
+            _ => None,
+        }
+    }
+
+    /// Decodes the challenge of the response to a [`ConnectionOptions::new_request_challenge`]
+    pub fn extract_challenge(&self) -> Option<[u8; 8]> {
+        match (&self.request, self.extract_value()?) {
+            (crate::Request::RequestChallenge(_), Value::Bytes(challenge)) => {
+                <[u8; 8]>::try_from(challenge).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Decodes the session of the response to a [`ConnectionOptions::new_pair`]
+    pub fn extract_session(&self) -> Option<SessionHeader> {
+        match (&self.request, self.extract_value()?) {
+            (crate::Request::Pair(..), Value::Bytes(token)) => {
+                <[u8; 8]>::try_from(token).ok().map(SessionHeader)
+            }
+            _ => None,
+        }
+    }
+
+    /// Decodes the bytes read by a [`ConnectionOptions::new_read_eeprom`]
+    pub fn extract_eeprom(&self) -> Option<&[u8]> {
+        match (&self.request, self.extract_value()?) {
+            (crate::Request::ReadEeprom(..), Value::DynBytes(bytes)) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Formats the bytes read by a [`ConnectionOptions::new_read_eeprom`] as hexdump, see
+    /// [`crate::debugfmt::hexdump`]
+    pub fn eeprom_hexdump(&self) -> Option<String> {
+        match self.request {
+            crate::Request::ReadEeprom(_, offset, _) => Some(crate::debugfmt::hexdump(
+                usize::from(offset),
+                self.extract_eeprom()?,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Decodes the string of a `Format::ValueOnly` or `Format::Echoed` response, for example of
+    /// [`ConnectionOptions::new_retrieve_device_name`]
+    pub fn extract_string(&self) -> Option<&str> {
+        match self.extract_value()? {
+            Value::String(string) | Value::DynString(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Decodes the elements of a `Type::ListOf` value
+    pub fn extract_list(&self) -> Option<Vec<Value<'_>>> {
+        self.extract_value()?
+            .elements()?
+            .collect::<Result<_, _>>()
+            .ok()
+    }
+
+    pub fn extract_property_reports(&self) -> Option<Vec<PropertyReportV1>> {
+        match &self.response {
+            crate::Response::Ok(_, Format::ValueOnly(Type::DynListPropertyReportV1)) => {
+                PropertyReportIter::new(self.payload())
+                    .map(|report| report.map(PropertyReportV1::from))
+                    .collect::<Result<_, _>>()
+                    .ok()
+            }
+            _ => None,
+        }
+    }
+
+    pub fn extract_buses(&self) -> Option<Vec<BusInformation>> {
+        match &self.response {
+            crate::Response::Ok(_, Format::ValueOnly(Type::DynListBusInformation)) => {
+                let mut payload = self.payload();
+                let mut buses = Vec::new();
+                while !payload.is_empty() {
+                    buses.push(BusInformation::read(&mut payload).ok()?);
+                }
+                Some(buses)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn extract_error_dump(&self) -> Option<Vec<ErrorDumpEntry<'_>>> {
+        match &self.response {
+            crate::Response::Ok(_, Format::ValueOnly(Type::DynListErrorDumpEntry)) => {
+                ErrorDumpIter::new(self.payload())
+                    .collect::<Result<_, _>>()
+                    .ok()
+            }
+            _ => None,
+        }
+    }
+
+    pub fn extract_log_records(&self) -> Option<Vec<LogRecord<'_>>> {
+        match &self.response {
+            crate::Response::Ok(_, Format::ValueOnly(Type::DynListLogRecord)) => {
+                LogRecordIter::new(self.payload())
+                    .collect::<Result<_, _>>()
+                    .ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Decodes the [`VersionInformation`], use [`VersionInformation::is_compatible_with`] with
+    /// [`crate::info::PROTOCOL_VERSION`] to refuse devices running incompatible firmware
+    pub fn extract_version_information(&self) -> Option<VersionInformation> {
+        match &self.response {
+            crate::Response::Ok(_, Format::ValueOnly(Type::VersionInformation)) => {
+                VersionInformation::read(&mut self.payload()).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// The [`Capabilities`] of the [`VersionInformation`], to only send requests the device
+    /// supports. Devices that do not report their capabilities answer with an empty set.
+    pub fn extract_capabilities(&self) -> Option<Capabilities> {
+        self.extract_version_information()
+            .map(|information| information.capabilities.unwrap_or_else(Capabilities::empty))
+    }
+
+    pub fn extract_network_configuration(&self) -> Option<NetworkConfiguration> {
+        match &self.response {
+            crate::Response::Ok(_, Format::ValueOnly(Type::NetworkConfiguration)) => {
+                NetworkConfiguration::read(&mut self.payload()).ok()
+            }
             _ => None,
         }
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "tokio"))]
 #[cfg_attr(test, test)]
 pub fn sample_usage() {
     let options = ConnectionOptionsBuilder::default()
@@ -241,10 +1491,13 @@ pub fn sample_usage() {
         .unwrap();
 
     let request = options
-        .new_onewire_read(&[
-            onewire::Device::from_str("28:ff:f3:54:c1:17:05:33").unwrap(),
-            onewire::Device::from_str("28:ff:fe:35:c1:17:05:c0").unwrap(),
-        ])
+        .new_onewire_read(
+            [
+                onewire::Device::from_str("28:ff:f3:54:c1:17:05:33").unwrap(),
+                onewire::Device::from_str("28:ff:fe:35:c1:17:05:c0").unwrap(),
+            ]
+            .iter(),
+        )
         .unwrap();
 
     let response = request.dispatch().unwrap();
@@ -254,3 +1507,184 @@ pub fn sample_usage() {
 
     assert!(matches!(response.response, crate::Response::Ok(_, _)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_splits_into_responses_sharing_the_datagram() {
+        let mut datagram = Vec::new();
+        crate::Response::Ok(1, Format::ValueOnly(Type::Batch))
+            .write(&mut datagram)
+            .unwrap();
+        let mut entry = Vec::new();
+        crate::Response::Ok(2, Format::ValueOnly(Type::U32))
+            .write(&mut entry)
+            .unwrap();
+        entry.extend_from_slice(&42u32.to_be_bytes());
+        crate::batch::write_entry(&mut datagram, &entry).unwrap();
+        entry.clear();
+        crate::Response::NotAvailable(3).write(&mut entry).unwrap();
+        crate::batch::write_entry(&mut datagram, &entry).unwrap();
+
+        let batch = Response {
+            request: crate::Request::Batch(1, 2),
+            response: crate::Response::Ok(1, Format::ValueOnly(Type::Batch)),
+            payload_start: 4,
+            payload_end: datagram.len(),
+            datagram: Arc::new(datagram),
+            stats: DispatchStats::default(),
+            duplicates_ignored: 0,
+            batched: vec![crate::Request::GetTime(2), crate::Request::Ping(3)],
+        };
+        let responses = batch.split_batch().unwrap().unwrap();
+        assert_eq!(2, responses.len());
+        assert_eq!(&42u32.to_be_bytes()[..], responses[0].payload());
+        assert_eq!(crate::Response::NotAvailable(3), responses[1].response);
+        assert!(responses[1].payload().is_empty());
+        assert!(Arc::ptr_eq(&batch.datagram, &responses[0].datagram));
+
+        let missing = Response {
+            batched: vec![
+                crate::Request::GetTime(2),
+                crate::Request::Ping(3),
+                crate::Request::Ping(4),
+            ],
+            datagram: Arc::clone(&batch.datagram),
+            ..batch
+        };
+        assert_eq!(
+            Some(crate::Error::PayloadLengthMismatch),
+            missing.split_batch().unwrap().err()
+        );
+        let extra = Response {
+            batched: vec![crate::Request::GetTime(2)],
+            ..missing
+        };
+        assert_eq!(
+            Some(crate::Error::PayloadLengthMismatch),
+            extra.split_batch().unwrap().err()
+        );
+    }
+
+    #[test]
+    fn headers_are_in_canonical_order() {
+        let options = ConnectionOptionsBuilder::default()
+            .remote_ip(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .priority(Priority::HIGHEST)
+            .build()
+            .unwrap();
+        let request = options
+            .new_request(crate::Request::CommitNetworkConfig)
+            .unwrap()
+            .deferred(ScheduleHeader::Delay(5))
+            .unwrap()
+            .via(&[2])
+            .unwrap()
+            .with_session(SessionHeader([7; 8]))
+            .unwrap();
+
+        let markers = [
+            crate::routing::ROUTING_HEADER_MARKER,
+            crate::pairing::SESSION_HEADER_MARKER,
+            crate::schedule::SCHEDULE_HEADER_MARKER,
+            crate::priority::PRIORITY_HEADER_MARKER,
+        ];
+        let (_, datagram) = RoutingHeader::read_optional(&request.serialized).unwrap();
+        let (session, datagram) = SessionHeader::read_optional(datagram).unwrap();
+        let (schedule, datagram) = ScheduleHeader::read_optional(datagram).unwrap();
+        let (priority, datagram) = PriorityHeader::read_optional(datagram).unwrap();
+        assert_eq!(Some(SessionHeader([7; 8])), session);
+        assert_eq!(Some(ScheduleHeader::Delay(5)), schedule);
+        assert_eq!(Some(PriorityHeader(Priority::HIGHEST)), priority);
+        assert_eq!(
+            u8::from(crate::RequestOpcode::CommitNetworkConfig),
+            datagram[0]
+        );
+        assert_eq!(markers[0], request.serialized[0]);
+        assert!(!markers.contains(&datagram[0]));
+    }
+
+    #[test]
+    fn device_name_and_location_are_limited() {
+        let options = ConnectionOptionsBuilder::default()
+            .remote_ip(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .build()
+            .unwrap();
+
+        let request = options.new_store_device_name("kitchen").unwrap();
+        let mut serialized = &request.serialized[..];
+        assert_eq!(
+            Ok(crate::Request::StoreProperty(request.id(), 2)),
+            crate::Request::read(&mut serialized)
+        );
+        let (path, value) = serialized.split_at(2);
+        assert_eq!(&well_known::DEVICE_NAME[..], path);
+        assert_eq!(b"\x07kitchen", value);
+
+        let name = "n".repeat(well_known::DEVICE_NAME_MAX_LEN);
+        assert!(options.new_store_device_name(&name).is_ok());
+        assert_eq!(
+            Some(crate::Error::BufferToSmall),
+            options.new_store_device_name(&(name + "n")).err()
+        );
+        let location = "l".repeat(well_known::DEVICE_LOCATION_MAX_LEN);
+        assert!(options.new_store_device_location(&location).is_ok());
+        assert_eq!(
+            Some(crate::Error::BufferToSmall),
+            options.new_store_device_location(&(location + "l")).err()
+        );
+
+        let request = options.new_retrieve_device_location().unwrap();
+        let mut serialized = &request.serialized[..];
+        assert_eq!(
+            Ok(crate::Request::RetrieveProperty(request.id(), 2)),
+            crate::Request::read(&mut serialized)
+        );
+        assert_eq!(&well_known::DEVICE_LOCATION[..], serialized);
+    }
+
+    #[test]
+    fn extract_string_of_string_values_only() {
+        let response = |format: Format, payload: &[u8]| {
+            let mut datagram = Vec::new();
+            crate::Response::Ok(1, format).write(&mut datagram).unwrap();
+            let payload_start = datagram.len();
+            datagram.extend_from_slice(payload);
+            Response {
+                request: crate::Request::RetrieveProperty(1, 2),
+                response: crate::Response::Ok(1, format),
+                payload_start,
+                payload_end: datagram.len(),
+                datagram: Arc::new(datagram),
+                stats: DispatchStats::default(),
+                duplicates_ignored: 0,
+                batched: Vec::new(),
+            }
+        };
+        let name = response(Format::ValueOnly(Type::DynString), b"\x07kitchen");
+        assert_eq!(Some("kitchen"), name.extract_string());
+        let echoed = response(Format::Echoed(Type::DynString), b"\x00");
+        assert_eq!(Some(""), echoed.extract_string());
+        let invalid = response(Format::ValueOnly(Type::DynString), b"\x01\xFF");
+        assert_eq!(None, invalid.extract_string());
+        let truncated = response(Format::ValueOnly(Type::DynString), b"\x08kitchen");
+        assert_eq!(None, truncated.extract_string());
+        let number = response(Format::ValueOnly(Type::U16), &[0x00, 0x07]);
+        assert_eq!(None, number.extract_string());
+    }
+
+    #[test]
+    #[cfg(feature = "client-encryption")]
+    fn debug_redacts_the_psk() {
+        let options = ConnectionOptionsBuilder::default()
+            .remote_ip(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .psk([0xAB; 32])
+            .build()
+            .unwrap();
+        let debug = format!("{:?}", options);
+        assert!(debug.contains("psk: Some(\"<redacted>\")"));
+        assert!(!debug.contains("171"));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::client::{ConnectionOptions, DispatchError, Request, Response, Runtime, Tokio};
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+/// Dispatches a request to each of the given devices, with at most `concurrency` requests in
+/// flight at the same time. Requests still unanswered when the `deadline` passes are cancelled
+/// and reported as [`DispatchError::Timeout`]. The results are in the order of the given options.
+pub async fn poll_many(
+    options: &[ConnectionOptions],
+    concurrency: NonZeroUsize,
+    deadline: Duration,
+    request_builder: impl Fn(&ConnectionOptions) -> Result<Request, crate::Error>,
+) -> Vec<Result<Response, DispatchError>> {
+    poll_many_on::<Tokio>(options, concurrency, deadline, request_builder).await
+}
+
+/// Like [`poll_many`], but dispatches the requests over new sockets of the given [`Runtime`].
+/// The requests are polled concurrently by the calling task instead of spawned tasks.
+pub async fn poll_many_on<R: Runtime>(
+    options: &[ConnectionOptions],
+    concurrency: NonZeroUsize,
+    deadline: Duration,
+    request_builder: impl Fn(&ConnectionOptions) -> Result<Request, crate::Error>,
+) -> Vec<Result<Response, DispatchError>> {
+    let deadline = Instant::now() + deadline;
+    let semaphore = tokio::sync::Semaphore::new(concurrency.get());
+
+    let mut dispatches = options
+        .iter()
+        .map(|options| {
+            let request = request_builder(options);
+            let semaphore = &semaphore;
+            Box::pin(async move {
+                let _permit = semaphore.acquire().await;
+                request
+                    .map_err(DispatchError::Encoding)?
+                    .dispatch_on::<R>()
+                    .await
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut results = options.iter().map(|_| None).collect::<Vec<_>>();
+    let all_completed = std::future::poll_fn(|cx| {
+        let mut pending = false;
+        for (dispatch, result) in dispatches.iter_mut().zip(results.iter_mut()) {
+            if result.is_none() {
+                match dispatch.as_mut().poll(cx) {
+                    Poll::Ready(completed) => *result = Some(completed),
+                    Poll::Pending => pending = true,
+                }
+            }
+        }
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    });
+    // the dispatches still pending at the deadline are cancelled by dropping them
+    let _ = R::timeout_at(deadline, all_completed).await;
+    drop(dispatches);
+
+    results
+        .into_iter()
+        .map(|result| result.unwrap_or(Err(DispatchError::Timeout)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ConnectionOptionsBuilder, Transport};
+    use crate::headers::skip_request_headers;
+    use crate::{Format, Type, Write};
+    use std::io;
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// The port of the device whose transport fails to send
+    const FAILING_PORT: u16 = 3;
+
+    static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+    static MAX_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+    /// Answers each request with its remote port as [`Type::U16`] value
+    struct MockRuntime;
+
+    struct MockTransport {
+        remote: SocketAddr,
+        request: Vec<u8>,
+    }
+
+    impl Runtime for MockRuntime {
+        type Transport = MockTransport;
+
+        async fn bind(_local: SocketAddr, remote: SocketAddr) -> io::Result<MockTransport> {
+            Ok(MockTransport {
+                remote,
+                request: Vec::new(),
+            })
+        }
+
+        async fn timeout_at<F: Future>(deadline: Instant, future: F) -> Option<F::Output> {
+            tokio::time::timeout_at(deadline.into(), future).await.ok()
+        }
+    }
+
+    impl Transport for MockTransport {
+        async fn send(&mut self, datagram: &[u8]) -> io::Result<usize> {
+            if self.remote.port() == FAILING_PORT {
+                return Err(io::ErrorKind::ConnectionRefused.into());
+            }
+            self.request = datagram.to_vec();
+            Ok(datagram.len())
+        }
+
+        async fn recv(&mut self, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+            let in_flight = IN_FLIGHT.fetch_add(1, Ordering::SeqCst) + 1;
+            MAX_IN_FLIGHT.fetch_max(in_flight, Ordering::SeqCst);
+            // lets the other requests proceed before this one is answered
+            tokio::task::yield_now().await;
+            IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+
+            let request = skip_request_headers(&self.request)
+                .and_then(|mut request| crate::Request::read(&mut request))
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e)))?;
+            let mut response = Vec::new();
+            crate::Response::Ok(request.id(), Format::ValueOnly(Type::U16))
+                .write(&mut response)
+                .and_then(|_| response.write_u16_be(self.remote.port()))
+                .unwrap();
+            buffer[..response.len()].copy_from_slice(&response);
+            Ok((response.len(), self.remote))
+        }
+    }
+
+    #[test]
+    fn responses_are_matched_to_their_devices() {
+        let devices = (1..=5u16)
+            .map(|port| {
+                ConnectionOptionsBuilder::default()
+                    .remote_ip(IpAddr::V4(Ipv4Addr::LOCALHOST))
+                    .remote_port(port)
+                    .build()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let results = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(poll_many_on::<MockRuntime>(
+                &devices,
+                NonZeroUsize::new(2).unwrap(),
+                Duration::from_secs(5),
+                |device| device.new_request(crate::Request::ReadAll),
+            ));
+
+        assert_eq!(devices.len(), results.len());
+        for (port, result) in (1..=5u16).zip(results) {
+            if port == FAILING_PORT {
+                assert!(matches!(result, Err(DispatchError::Io { .. })));
+            } else {
+                assert_eq!(&port.to_be_bytes(), result.unwrap().payload());
+            }
+        }
+        assert_eq!(2, MAX_IN_FLIGHT.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::client::{ConnectionOptions, DispatchError, Request, Response};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Identifies a registration of a [`Poller`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PollId(usize);
+
+/// The outcome of a single scheduled request of a [`Poller`]
+#[derive(Debug)]
+pub struct PollResult {
+    pub id: PollId,
+    pub result: Result<Response, DispatchError>,
+}
+
+/// Periodically dispatches registered requests and sends the results to the channel returned
+/// by [`Poller::new`]. After a failure, the interval of the device is doubled for each
+/// consecutive failure, up to the maximum backoff. All requests stop once the poller or the
+/// receiver is dropped.
+#[derive(Debug)]
+pub struct Poller {
+    sender: mpsc::Sender<PollResult>,
+    tasks: Vec<JoinHandle<()>>,
+    max_backoff: Duration,
+}
+
+impl Poller {
+    /// Creates a new poller and the receiver for its results, which buffers up to `buffer`
+    /// results before the requests are delayed
+    pub fn new(buffer: usize) -> (Self, mpsc::Receiver<PollResult>) {
+        let (sender, receiver) = mpsc::channel(buffer);
+        (
+            Self {
+                sender,
+                tasks: Vec::new(),
+                max_backoff: Duration::from_secs(300),
+            },
+            receiver,
+        )
+    }
+
+    /// The maximum interval after consecutive failures, applies to future registrations only
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Starts dispatching a request built by `request_builder` every `interval` to the device.
+    /// Must be called from within a tokio runtime.
+    pub fn register(
+        &mut self,
+        device: ConnectionOptions,
+        interval: Duration,
+        request_builder: impl Fn(&ConnectionOptions) -> Result<Request, crate::Error> + Send + 'static,
+    ) -> PollId {
+        let id = PollId(self.tasks.len());
+        let sender = self.sender.clone();
+        let max_backoff = self.max_backoff;
+
+        self.tasks.push(tokio::spawn(async move {
+            let mut failures = 0u32;
+            loop {
+                let started = tokio::time::Instant::now();
+                let result = match request_builder(&device) {
+                    Ok(request) => request.dispatch_async().await,
+                    Err(e) => Err(DispatchError::Encoding(e)),
+                };
+
+                if result.is_ok() {
+                    failures = 0;
+                } else {
+                    failures = failures.saturating_add(1);
+                }
+
+                if sender.send(PollResult { id, result }).await.is_err() {
+                    // the receiver has been dropped
+                    break;
+                }
+
+                tokio::time::sleep_until(started + backoff(interval, failures, max_backoff)).await;
+            }
+        }));
+
+        id
+    }
+}
+
+impl Drop for Poller {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+fn backoff(interval: Duration, failures: u32, max_backoff: Duration) -> Duration {
+    interval
+        .checked_mul(1u32.checked_shl(failures).unwrap_or(u32::MAX))
+        .map_or(max_backoff, |delay| delay.min(max_backoff))
+        .max(interval)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(10);
+    const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+    #[test]
+    fn polls_at_the_interval_after_a_success() {
+        assert_eq!(INTERVAL, backoff(INTERVAL, 0, MAX_BACKOFF));
+    }
+
+    #[test]
+    fn doubles_the_interval_for_each_failure() {
+        assert_eq!(Duration::from_secs(20), backoff(INTERVAL, 1, MAX_BACKOFF));
+        assert_eq!(Duration::from_secs(40), backoff(INTERVAL, 2, MAX_BACKOFF));
+        assert_eq!(Duration::from_secs(160), backoff(INTERVAL, 4, MAX_BACKOFF));
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        assert_eq!(MAX_BACKOFF, backoff(INTERVAL, 5, MAX_BACKOFF));
+        assert_eq!(MAX_BACKOFF, backoff(INTERVAL, 31, MAX_BACKOFF));
+        assert_eq!(MAX_BACKOFF, backoff(INTERVAL, u32::MAX, MAX_BACKOFF));
+        // an interval above the maximum backoff is never shortened
+        let interval = Duration::from_secs(600);
+        assert_eq!(interval, backoff(interval, 3, MAX_BACKOFF));
+    }
+}
@@ -0,0 +1,267 @@
+//! Snapshots of the configuration of a device, to clone it to other devices of a fleet.
+//!
+//! [`DeviceProfile::capture`] reads the version, device and network information, the property
+//! reports, listed in pages, and the values of all readable properties. The profile can be stored with
+//! [`DeviceProfile::write`] and loaded with [`DeviceProfile::read`]. [`DeviceProfile::apply`]
+//! writes the writable properties whose values differ to another device. Properties that tell
+//! devices apart, such as [`crate::props::well_known::DEVICE_NAME`], should be removed from
+//! [`DeviceProfile::properties`] before applying a profile to more than one device.
+
+use crate::client::{ConnectionOptions, DispatchError, Response, Runtime};
+use crate::info::{DeviceInformation, NetworkConfiguration, VersionInformation};
+use crate::props::PropertyReportV1;
+use crate::value::Value;
+use crate::{Error, Format, Read, Write};
+use std::convert::TryFrom;
+
+/// The first byte of an encoded [`DeviceProfile`], to detect future changes of the encoding
+const PROFILE_FORMAT_VERSION: u8 = 0x01;
+
+#[derive(Debug)]
+pub struct DeviceProfile {
+    pub version: Option<VersionInformation>,
+    pub device: Option<DeviceInformation>,
+    pub network: Option<NetworkConfiguration>,
+    pub properties: Vec<ProfileProperty>,
+}
+
+/// A property of a [`DeviceProfile`] and its value at the time of the snapshot
+#[derive(Debug)]
+pub struct ProfileProperty {
+    pub report: PropertyReportV1,
+    /// The encoded value, `None` if the property is not readable or the device did not answer
+    /// with a value
+    pub value: Option<Vec<u8>>,
+}
+
+impl ProfileProperty {
+    /// Decodes the value according to the type hint of the report
+    pub fn decoded_value(&self) -> Option<Value<'_>> {
+        Value::read(self.report.type_hint?, &mut self.value.as_deref()?).ok()
+    }
+}
+
+impl DeviceProfile {
+    /// Takes a snapshot of the device, listing its properties in pages of the given size, see
+    /// [`ConnectionOptions::list_components`]. Information the device does not provide is left
+    /// out, failing to dispatch any of the requests or to list the properties fails the snapshot.
+    pub async fn capture<R: Runtime>(
+        device: &ConnectionOptions,
+        page_size: u8,
+    ) -> Result<Self, DispatchError> {
+        let version = dispatch::<R>(device, crate::Request::RetrieveVersionInformation)
+            .await?
+            .extract_version_information();
+        let information = dispatch::<R>(device, crate::Request::RetrieveDeviceInformation)
+            .await?
+            .extract_device_information();
+        let network = dispatch::<R>(device, crate::Request::RetrieveNetworkConfiguration)
+            .await?
+            .extract_network_configuration();
+        let reports = device.list_components::<R>(page_size).collect().await?;
+
+        let mut properties = Vec::with_capacity(reports.len());
+        for report in reports {
+            let value = if report.read {
+                let response = device
+                    .new_retrieve_property(&report.id)
+                    .map_err(DispatchError::Encoding)?
+                    .with_timeout_for(&report)
+                    .dispatch_on::<R>()
+                    .await?;
+                match response.response {
+                    crate::Response::Ok(_, Format::ValueOnly(_)) => {
+                        Some(response.payload().to_vec())
+                    }
+                    _ => None,
+                }
+            } else {
+                None
+            };
+            properties.push(ProfileProperty { report, value });
+        }
+
+        Ok(Self {
+            version,
+            device: information,
+            network,
+            properties,
+        })
+    }
+
+    pub fn property(&self, id: &[u8]) -> Option<&ProfileProperty> {
+        self.properties
+            .iter()
+            .find(|property| property.report.id == id)
+    }
+
+    /// The properties with a value that are writable on both devices and whose value differs
+    /// from the one of the given profile
+    pub fn diff<'a>(&'a self, other: &DeviceProfile) -> Vec<&'a ProfileProperty> {
+        self.properties
+            .iter()
+            .filter(|property| property.report.write && property.value.is_some())
+            .filter(|property| match other.property(&property.report.id) {
+                Some(theirs) => theirs.report.write && theirs.value != property.value,
+                None => false,
+            })
+            .collect()
+    }
+
+    /// Writes the properties that differ, see [`DeviceProfile::diff`], to the device and
+    /// returns the responses to the writes, which the device may have refused. The current
+    /// configuration of the device is captured with the given page size.
+    pub async fn apply<R: Runtime>(
+        &self,
+        device: &ConnectionOptions,
+        page_size: u8,
+    ) -> Result<Vec<Response>, DispatchError> {
+        let current = Self::capture::<R>(device, page_size).await?;
+        let mut responses = Vec::new();
+        for property in self.diff(&current) {
+            let value = property.value.as_deref().unwrap_or_default();
+            responses.push(
+                device
+                    .new_store_property(&property.report.id, value)
+                    .map_err(DispatchError::Encoding)?
+                    .with_timeout_for(&property.report)
+                    .dispatch_on::<R>()
+                    .await?,
+            );
+        }
+        Ok(responses)
+    }
+
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        let mut len = writer.write_u8(PROFILE_FORMAT_VERSION)?;
+        len += match &self.version {
+            Some(version) => writer.write_u8(0x01)? + version.write(writer)?,
+            None => writer.write_u8(0x00)?,
+        };
+        len += match &self.device {
+            Some(device) => writer.write_u8(0x01)? + device.write(writer)?,
+            None => writer.write_u8(0x00)?,
+        };
+        len += match &self.network {
+            Some(network) => writer.write_u8(0x01)? + network.write(writer)?,
+            None => writer.write_u8(0x00)?,
+        };
+        let count = u16::try_from(self.properties.len()).map_err(|_| Error::BufferToSmall)?;
+        len += writer.write_u16_be(count)?;
+        for property in &self.properties {
+            len += property.report.write(writer)?;
+            len += match &property.value {
+                Some(value) => {
+                    let value_len = u16::try_from(value.len()).map_err(|_| Error::BufferToSmall)?;
+                    writer.write_u8(0x01)?
+                        + writer.write_u16_be(value_len)?
+                        + writer.write_all(value)?
+                }
+                None => writer.write_u8(0x00)?,
+            };
+        }
+        Ok(len)
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        if reader.read_u8()? != PROFILE_FORMAT_VERSION {
+            return Err(Error::UnknownTypeIdentifier);
+        }
+        let version = match reader.read_u8()? {
+            0x00 => None,
+            _ => Some(VersionInformation::read(reader)?),
+        };
+        let device = match reader.read_u8()? {
+            0x00 => None,
+            _ => Some(DeviceInformation::read(reader)?),
+        };
+        let network = match reader.read_u8()? {
+            0x00 => None,
+            _ => Some(NetworkConfiguration::read(reader)?),
+        };
+        let count = reader.read_u16_be()?;
+        let mut properties = Vec::with_capacity(usize::from(count));
+        for _ in 0..count {
+            let report = PropertyReportV1::read(reader)?;
+            let value = match reader.read_u8()? {
+                0x00 => None,
+                _ => {
+                    let mut value = vec![0u8; usize::from(reader.read_u16_be()?)];
+                    reader.read_all(&mut value)?;
+                    Some(value)
+                }
+            };
+            properties.push(ProfileProperty { report, value });
+        }
+        Ok(Self {
+            version,
+            device,
+            network,
+            properties,
+        })
+    }
+}
+
+async fn dispatch<R: Runtime>(
+    device: &ConnectionOptions,
+    request: impl FnOnce(u8) -> crate::Request,
+) -> Result<Response, DispatchError> {
+    device
+        .new_request(request)
+        .map_err(DispatchError::Encoding)?
+        .dispatch_on::<R>()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::props::QueryComplexity;
+    use crate::Type;
+
+    fn property(id: &[u8], write: bool, value: &[u8]) -> ProfileProperty {
+        ProfileProperty {
+            report: PropertyReportV1 {
+                id: id.to_vec(),
+                type_hint: Some(Type::U16),
+                description: None,
+                unit: None,
+                range: None,
+                complexity: QueryComplexity::Unknown,
+                read: true,
+                write,
+            },
+            value: Some(value.to_vec()),
+        }
+    }
+
+    #[test]
+    fn profile_roundtrip_and_diff() {
+        let profile = DeviceProfile {
+            version: None,
+            device: None,
+            network: None,
+            properties: vec![
+                property(&[0x30, 0x01], true, &[0x00, 0x10]),
+                property(&[0x30, 0x02], false, &[0x00, 0x20]),
+                property(&[0x30, 0x03], true, &[0x00, 0x30]),
+            ],
+        };
+        let mut encoded = Vec::new();
+        let len = profile.write(&mut encoded).unwrap();
+        assert_eq!(encoded.len(), len);
+        let other = DeviceProfile::read(&mut &encoded[..]).unwrap();
+        assert_eq!(3, other.properties.len());
+        assert_eq!(
+            Some(Value::U16(0x20)),
+            other.property(&[0x30, 0x02]).unwrap().decoded_value()
+        );
+
+        let mut other = other;
+        other.properties[0].value = Some(vec![0x00, 0x11]);
+        other.properties[1].value = Some(vec![0x00, 0x21]);
+        let diff = profile.diff(&other);
+        assert_eq!(1, diff.len());
+        assert_eq!(&[0x30, 0x01][..], &diff[0].report.id[..]);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::client::{ConnectionOptions, Response};
+use crate::hex::Hex;
+use crate::props::path::PropertyPath;
+use crate::value::Value;
+use prometheus::{GaugeVec, Opts, Registry};
+
+/// Gauges for the readings of sensors and the values of properties, labeled by the `device`
+/// (its remote address), the `bus` and `sensor` address or the property `path`.
+#[derive(Debug, Clone)]
+pub struct SensorGauges {
+    readings: GaugeVec,
+    properties: GaugeVec,
+}
+
+impl SensorGauges {
+    /// Creates the gauges `<namespace>_sensor_reading` and `<namespace>_property_value`
+    pub fn new(namespace: &str) -> prometheus::Result<Self> {
+        Ok(Self {
+            readings: GaugeVec::new(
+                Opts::new("sensor_reading", "The last value read from a sensor")
+                    .namespace(namespace),
+                &["device", "bus", "sensor"],
+            )?,
+            properties: GaugeVec::new(
+                Opts::new("property_value", "The last numeric value of a property")
+                    .namespace(namespace),
+                &["device", "path"],
+            )?,
+        })
+    }
+
+    /// Creates and registers the gauges, see [`SensorGauges::new`]
+    pub fn register(namespace: &str, registry: &Registry) -> prometheus::Result<Self> {
+        let gauges = Self::new(namespace)?;
+        registry.register(Box::new(gauges.readings.clone()))?;
+        registry.register(Box::new(gauges.properties.clone()))?;
+        Ok(gauges)
+    }
+
+    /// Sets the gauges for all numeric address-value pairs of the response to a
+    /// `ReadSpecified`, `ReadAll` or `ReadAllOnBus` request, returns the number of gauges set
+    pub fn observe_readings(&self, device: &ConnectionOptions, response: &Response) -> usize {
+        let bus = match response.request {
+            crate::Request::ReadSpecified(_, bus) | crate::Request::ReadAllOnBus(_, bus) => {
+                format!("{:?}", bus)
+            }
+            crate::Request::ReadAll(_) => String::new(),
+            _ => return 0,
+        };
+        let device = device.remote_address().to_string();
+
+        address_value_pairs(response)
+            .filter_map(|(address, value)| Some((address, value.to_f64()?)))
+            .map(|(address, value)| {
+                self.readings
+                    .with_label_values(&[&device, &bus, &Hex::colons(address).to_string()])
+                    .set(value)
+            })
+            .count()
+    }
+
+    /// Sets the gauge of the property if the response to its `RetrieveProperty` request
+    /// carries a numeric value, returns whether the gauge has been set
+    pub fn observe_property(
+        &self,
+        device: &ConnectionOptions,
+        path: &PropertyPath,
+        response: &Response,
+    ) -> bool {
+        match response.extract_value().and_then(|value| value.to_f64()) {
+            Some(value) => {
+                self.properties
+                    .with_label_values(&[
+                        &device.remote_address().to_string(),
+                        &format!("{:#}", path),
+                    ])
+                    .set(value);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn address_value_pairs(response: &Response) -> impl Iterator<Item = (&[u8], Value<'_>)> {
+    response
+        .frame()
+        .address_values()
+        .into_iter()
+        .flatten()
+        .map_while(Result::ok)
+}
@@ -0,0 +1,211 @@
+#[cfg(feature = "tokio")]
+use crate::client::Tokio;
+use crate::client::{ConnectionOptions, DispatchError, Runtime};
+use crate::props::{PropertyReportV1, QueryComplexity};
+use crate::value::Value;
+use crate::{ErrorCode, Format, Type};
+
+/// A Rust type with a fixed [`Type`] to read and write properties as
+pub trait PropertyType: Sized {
+    const TYPE: Type;
+
+    fn from_value(value: Value<'_>) -> Option<Self>;
+
+    fn into_value(self) -> Value<'static>;
+}
+
+macro_rules! property_type {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl PropertyType for $ty {
+                const TYPE: Type = Type::$variant;
+
+                fn from_value(value: Value<'_>) -> Option<Self> {
+                    match value {
+                        Value::$variant(value) => Some(value),
+                        _ => None,
+                    }
+                }
+
+                fn into_value(self) -> Value<'static> {
+                    Value::$variant(self)
+                }
+            }
+        )*
+    };
+}
+
+property_type!(
+    f32 => F32,
+    f64 => F64,
+    bool => Bool,
+    u128 => U128,
+    i128 => I128,
+    u64 => U64,
+    i64 => I64,
+    u32 => U32,
+    i32 => I32,
+    u16 => U16,
+    i16 => I16,
+    u8 => U8,
+    i8 => I8,
+);
+
+#[derive(Debug, thiserror::Error)]
+pub enum PropertyError {
+    #[error("The property has the type {actual:?}, not {requested:?}")]
+    TypeMismatch { requested: Type, actual: Type },
+    #[error("The property is not readable")]
+    NotReadable,
+    #[error("The property is not writable")]
+    NotWritable,
+    #[error("The device refused to access the property ({0:?})")]
+    Refused(Option<ErrorCode>),
+    #[error("Failed to dispatch the request {0}")]
+    Dispatch(#[from] DispatchError),
+}
+
+/// A property of a device as reported by [`crate::Request::ListComponentsWithReportV1`], to
+/// read and write its value as Rust type instead of raw bytes. The type is checked against
+/// the type hint of the report before anything is sent, and against the type of the response.
+#[derive(Debug)]
+pub struct TypedPropertyHandle {
+    device: ConnectionOptions,
+    report: PropertyReportV1,
+}
+
+impl TypedPropertyHandle {
+    pub fn new(device: ConnectionOptions, report: PropertyReportV1) -> Self {
+        Self { device, report }
+    }
+
+    /// A handle for each of the reports of the device, see
+    /// [`crate::client::Response::extract_property_reports`]
+    pub fn from_reports(device: &ConnectionOptions, reports: Vec<PropertyReportV1>) -> Vec<Self> {
+        reports
+            .into_iter()
+            .map(|report| Self::new(device.clone(), report))
+            .collect()
+    }
+
+    /// Lists the properties of the device in pages of the given size, see
+    /// [`ConnectionOptions::list_components`], and creates a handle for each of them
+    pub async fn list_on<R: Runtime>(
+        device: &ConnectionOptions,
+        page_size: u8,
+    ) -> Result<Vec<Self>, DispatchError> {
+        let reports = device.list_components::<R>(page_size).collect().await?;
+        Ok(Self::from_reports(device, reports))
+    }
+
+    pub fn report(&self) -> &PropertyReportV1 {
+        &self.report
+    }
+
+    pub fn id(&self) -> &[u8] {
+        &self.report.id
+    }
+
+    pub fn type_hint(&self) -> Option<Type> {
+        self.report.type_hint
+    }
+
+    pub fn complexity(&self) -> QueryComplexity {
+        self.report.complexity
+    }
+
+    #[cfg(feature = "tokio")]
+    pub async fn get<T: PropertyType>(&self) -> Result<T, PropertyError> {
+        self.get_on::<T, Tokio>().await
+    }
+
+    /// Reads the value of the property, the timeout is extended according to its complexity
+    pub async fn get_on<T: PropertyType, R: Runtime>(&self) -> Result<T, PropertyError> {
+        if !self.report.read {
+            return Err(PropertyError::NotReadable);
+        }
+        check_type::<T>(self.report.type_hint)?;
+
+        let response = self
+            .device
+            .new_retrieve_property(&self.report.id)
+            .map_err(DispatchError::Encoding)?
+            .with_timeout_for(&self.report)
+            .dispatch_on::<R>()
+            .await?;
+        match response.response {
+            crate::Response::Ok(_, Format::ValueOnly(actual)) => {
+                check_type::<T>(Some(actual))?;
+                Value::read(actual, &mut response.payload())
+                    .ok()
+                    .and_then(T::from_value)
+                    .ok_or(PropertyError::TypeMismatch {
+                        requested: T::TYPE,
+                        actual,
+                    })
+            }
+            _ => Err(PropertyError::Refused(response.error_code())),
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    pub async fn set<T: PropertyType>(&self, value: T) -> Result<(), PropertyError> {
+        self.set_on::<T, Tokio>(value).await
+    }
+
+    /// Writes the value of the property, the timeout is extended according to its complexity
+    pub async fn set_on<T: PropertyType, R: Runtime>(&self, value: T) -> Result<(), PropertyError> {
+        if !self.report.write {
+            return Err(PropertyError::NotWritable);
+        }
+        check_type::<T>(self.report.type_hint)?;
+
+        let mut encoded = Vec::new();
+        value
+            .into_value()
+            .write(&mut encoded)
+            .map_err(DispatchError::Encoding)?;
+        let response = self
+            .device
+            .new_store_property(&self.report.id, &encoded)
+            .map_err(DispatchError::Encoding)?
+            .with_timeout_for(&self.report)
+            .dispatch_on::<R>()
+            .await?;
+        match response.response {
+            crate::Response::Ok(..) => Ok(()),
+            _ => Err(PropertyError::Refused(response.error_code())),
+        }
+    }
+}
+
+/// Whether the type of the property, if known, is the one of `T`
+fn check_type<T: PropertyType>(actual: Option<Type>) -> Result<(), PropertyError> {
+    match actual {
+        Some(actual) if actual != T::TYPE => Err(PropertyError::TypeMismatch {
+            requested: T::TYPE,
+            actual,
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn types_are_checked_against_the_hint() {
+        assert!(check_type::<f32>(Some(Type::F32)).is_ok());
+        assert!(check_type::<f32>(None).is_ok());
+        assert!(matches!(
+            check_type::<f32>(Some(Type::U16)),
+            Err(PropertyError::TypeMismatch {
+                requested: Type::F32,
+                actual: Type::U16
+            })
+        ));
+        assert_eq!(Some(1.5f32), f32::from_value(1.5f32.into_value()));
+        assert_eq!(None, u16::from_value(Value::U8(1)));
+    }
+}
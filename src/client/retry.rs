@@ -0,0 +1,127 @@
+use random::Source;
+use std::num::NonZeroU8;
+use std::time::Duration;
+
+/// How requests that remain unanswered are resent
+#[derive(Debug, Copy, Clone, PartialEq, core::default::Default)]
+pub enum RetryPolicy {
+    /// Sends the request only once
+    None,
+    /// Resends the request up to `resend_attempts` times, each time waiting for the same
+    /// timeout
+    #[default]
+    Fixed,
+    /// Resends the request up to `resend_attempts` times, multiplying the timeout by `factor`
+    /// for each attempt (but not beyond `max_timeout`) and adding a random jitter of up to
+    /// `jitter` times the timeout, so that clients on a congested link do not resend in lockstep.
+    /// A `factor` below `1.0` is treated as `1.0`, a `jitter` is limited to `0.0..=1.0`.
+    Exponential {
+        factor: f32,
+        max_timeout: Duration,
+        jitter: f32,
+    },
+}
+
+impl RetryPolicy {
+    /// An exponential backoff doubling the timeout for each attempt, up to ten times the given
+    /// timeout, with a jitter of up to 10%
+    pub fn exponential(timeout: Duration) -> Self {
+        RetryPolicy::Exponential {
+            factor: 2.0,
+            max_timeout: timeout * 10,
+            jitter: 0.1,
+        }
+    }
+
+    /// The number of times a request is sent
+    pub fn attempts(&self, resend_attempts: NonZeroU8) -> u8 {
+        match self {
+            RetryPolicy::None => 1,
+            RetryPolicy::Fixed | RetryPolicy::Exponential { .. } => resend_attempts.get(),
+        }
+    }
+
+    /// The time to wait for a response to the given attempt, starting at zero
+    pub fn timeout(&self, timeout: Duration, attempt: u8) -> Duration {
+        match *self {
+            RetryPolicy::None | RetryPolicy::Fixed => timeout,
+            RetryPolicy::Exponential {
+                factor,
+                max_timeout,
+                jitter,
+            } => {
+                let scale = f64::from(factor.max(1.0)).powi(i32::from(attempt));
+                // compared before scaling, which panics if the result overflows
+                let scaled = if timeout.as_secs_f64() * scale < max_timeout.as_secs_f64() {
+                    timeout.mul_f64(scale)
+                } else {
+                    max_timeout
+                };
+                // also excludes NaN, which clamp would keep
+                let jitter = if jitter > 0.0 { jitter.min(1.0) } else { 0.0 };
+                let jitter = f64::from(jitter) * random::default().read::<f64>();
+                scaled.saturating_add(scaled.mul_f64(jitter))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn attempts_and_fixed_timeouts() {
+        let resend_attempts = NonZeroU8::new(3).unwrap();
+        assert_eq!(1, RetryPolicy::None.attempts(resend_attempts));
+        assert_eq!(3, RetryPolicy::Fixed.attempts(resend_attempts));
+        assert_eq!(
+            3,
+            RetryPolicy::exponential(TIMEOUT).attempts(resend_attempts)
+        );
+        assert_eq!(TIMEOUT, RetryPolicy::Fixed.timeout(TIMEOUT, 200));
+    }
+
+    #[test]
+    fn exponential_timeouts_are_capped() {
+        let policy = RetryPolicy::Exponential {
+            factor: 2.0,
+            max_timeout: Duration::from_secs(10),
+            jitter: 0.0,
+        };
+        let timeouts = [0, 1, 3, 4, u8::MAX].map(|attempt| policy.timeout(TIMEOUT, attempt));
+        assert_eq!([1, 2, 8, 10, 10].map(Duration::from_secs), timeouts,);
+
+        let policy = RetryPolicy::Exponential {
+            factor: f32::NAN,
+            max_timeout: Duration::MAX,
+            jitter: 0.0,
+        };
+        assert_eq!(TIMEOUT, policy.timeout(TIMEOUT, u8::MAX));
+        let policy = RetryPolicy::Exponential {
+            factor: 0.5,
+            max_timeout: Duration::MAX,
+            jitter: f32::NAN,
+        };
+        assert_eq!(TIMEOUT, policy.timeout(TIMEOUT, 1));
+    }
+
+    #[test]
+    fn jitter_is_bounded() {
+        let policy = RetryPolicy::exponential(TIMEOUT);
+        for _ in 0..100 {
+            let timeout = policy.timeout(TIMEOUT, 1);
+            assert!(timeout >= Duration::from_secs(2));
+            assert!(timeout <= Duration::from_millis(2_200));
+        }
+
+        let policy = RetryPolicy::Exponential {
+            factor: f32::INFINITY,
+            max_timeout: Duration::MAX,
+            jitter: f32::INFINITY,
+        };
+        assert_eq!(Duration::MAX, policy.timeout(TIMEOUT, 2));
+    }
+}
@@ -0,0 +1,98 @@
+use crate::client::Transport;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Instant;
+
+/// The async runtime providing the socket and the timer to [`super::Request::dispatch_on`]
+pub trait Runtime {
+    type Transport: Transport;
+
+    /// Binds a new [`Transport`] to the local address, sending datagrams to the remote address
+    fn bind(
+        local: SocketAddr,
+        remote: SocketAddr,
+    ) -> impl Future<Output = io::Result<Self::Transport>>;
+
+    /// Resolves to `None` if the deadline passes before the future completes
+    fn timeout_at<F: Future>(
+        deadline: Instant,
+        future: F,
+    ) -> impl Future<Output = Option<F::Output>>;
+}
+
+/// The [`Runtime`] for tokio, dispatching over a [`super::UdpTransport`]
+#[cfg(feature = "tokio")]
+#[derive(Debug, Copy, Clone)]
+pub struct Tokio;
+
+#[cfg(feature = "tokio")]
+impl Runtime for Tokio {
+    type Transport = super::UdpTransport;
+
+    fn bind(
+        local: SocketAddr,
+        remote: SocketAddr,
+    ) -> impl Future<Output = io::Result<Self::Transport>> {
+        super::UdpTransport::bind(local, remote)
+    }
+
+    async fn timeout_at<F: Future>(deadline: Instant, future: F) -> Option<F::Output> {
+        tokio::time::timeout_at(deadline.into(), future).await.ok()
+    }
+}
+
+/// The [`Runtime`] for `async-io` based runtimes such as `smol` and `async-std`, dispatching
+/// over an [`AsyncIoUdpTransport`]
+#[cfg(feature = "async-io")]
+#[derive(Debug, Copy, Clone)]
+pub struct AsyncIo;
+
+#[cfg(feature = "async-io")]
+impl Runtime for AsyncIo {
+    type Transport = AsyncIoUdpTransport;
+
+    async fn bind(local: SocketAddr, remote: SocketAddr) -> io::Result<Self::Transport> {
+        Ok(AsyncIoUdpTransport {
+            socket: async_io::Async::<std::net::UdpSocket>::bind(local)?,
+            remote,
+        })
+    }
+
+    async fn timeout_at<F: Future>(deadline: Instant, future: F) -> Option<F::Output> {
+        futures_lite::future::or(async { Some(future.await) }, async {
+            async_io::Timer::at(deadline).await;
+            None
+        })
+        .await
+    }
+}
+
+/// A [`Transport`] sending plain UDP datagrams to a single remote address through `async-io`
+#[cfg(feature = "async-io")]
+#[derive(Debug)]
+pub struct AsyncIoUdpTransport {
+    socket: async_io::Async<std::net::UdpSocket>,
+    remote: SocketAddr,
+}
+
+#[cfg(feature = "async-io")]
+impl AsyncIoUdpTransport {
+    pub fn socket(&self) -> &async_io::Async<std::net::UdpSocket> {
+        &self.socket
+    }
+}
+
+#[cfg(feature = "async-io")]
+impl Transport for AsyncIoUdpTransport {
+    fn send<'a>(&'a mut self, datagram: &'a [u8]) -> impl Future<Output = io::Result<usize>> + 'a {
+        self.socket.send_to(datagram, self.remote)
+    }
+
+    fn recv<'a>(
+        &'a mut self,
+        buffer: &'a mut [u8],
+    ) -> impl Future<Output = io::Result<(usize, SocketAddr)>> + 'a {
+        self.socket.recv_from(buffer)
+    }
+}
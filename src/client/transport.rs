@@ -0,0 +1,200 @@
+#[cfg(feature = "tokio")]
+use crate::framing::length_prefixed::{self, Decoder};
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+
+/// The datagram channel a [`super::Request`] is dispatched over, see
+/// [`super::Request::dispatch_with`]. Implement this to dispatch requests through mocks,
+/// (D)TLS wrappers or tunnels.
+pub trait Transport {
+    /// Sends the whole datagram, returns the number of bytes sent
+    fn send<'a>(&'a mut self, datagram: &'a [u8]) -> impl Future<Output = io::Result<usize>> + 'a;
+
+    /// Receives the next datagram into the buffer, returns its length and its source
+    fn recv<'a>(
+        &'a mut self,
+        buffer: &'a mut [u8],
+    ) -> impl Future<Output = io::Result<(usize, SocketAddr)>> + 'a;
+}
+
+/// The default [`Transport`] sending plain UDP datagrams to a single remote address
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct UdpTransport {
+    socket: tokio::net::UdpSocket,
+    remote: SocketAddr,
+}
+
+#[cfg(feature = "tokio")]
+impl UdpTransport {
+    pub async fn bind(local: SocketAddr, remote: SocketAddr) -> io::Result<Self> {
+        Ok(Self {
+            socket: tokio::net::UdpSocket::bind(local).await?,
+            remote,
+        })
+    }
+
+    pub fn socket(&self) -> &tokio::net::UdpSocket {
+        &self.socket
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Transport for UdpTransport {
+    fn send<'a>(&'a mut self, datagram: &'a [u8]) -> impl Future<Output = io::Result<usize>> + 'a {
+        self.socket.send_to(datagram, self.remote)
+    }
+
+    fn recv<'a>(
+        &'a mut self,
+        buffer: &'a mut [u8],
+    ) -> impl Future<Output = io::Result<(usize, SocketAddr)>> + 'a {
+        self.socket.recv_from(buffer)
+    }
+}
+
+/// A [`Transport`] over a TCP connection, for devices behind links that only forward streams.
+/// The datagrams are framed with [`crate::framing::length_prefixed`].
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct TcpTransport {
+    stream: tokio::net::TcpStream,
+    remote: SocketAddr,
+    decoder: Box<Decoder<{ length_prefixed::MAX_DATAGRAM_LEN }>>,
+    /// The received bytes not yet consumed by the decoder
+    received: Vec<u8>,
+}
+
+#[cfg(feature = "tokio")]
+impl TcpTransport {
+    pub async fn connect(remote: SocketAddr) -> io::Result<Self> {
+        Ok(Self {
+            stream: tokio::net::TcpStream::connect(remote).await?,
+            remote,
+            decoder: Box::default(),
+            received: Vec::new(),
+        })
+    }
+
+    pub fn stream(&self) -> &tokio::net::TcpStream {
+        &self.stream
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Transport for TcpTransport {
+    async fn send(&mut self, datagram: &[u8]) -> io::Result<usize> {
+        use tokio::io::AsyncWriteExt;
+        let mut frame = Vec::with_capacity(length_prefixed::HEADER_LEN + datagram.len());
+        length_prefixed::encode(datagram, &mut frame)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        self.stream.write_all(&frame).await?;
+        Ok(datagram.len())
+    }
+
+    async fn recv(&mut self, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        use tokio::io::AsyncReadExt;
+        loop {
+            let mut pending = &self.received[..];
+            let datagram = self
+                .decoder
+                .push_bytes(&mut pending)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let len = datagram.map(|datagram| {
+                let len = datagram.len().min(buffer.len());
+                buffer[..len].copy_from_slice(&datagram[..len]);
+                len
+            });
+            let consumed = self.received.len() - pending.len();
+            self.received.drain(..consumed);
+            if let Some(len) = len {
+                return Ok((len, self.remote));
+            }
+
+            let mut chunk = [0u8; 1024];
+            match self.stream.read(&mut chunk).await? {
+                0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+                read => self.received.extend_from_slice(&chunk[..read]),
+            }
+        }
+    }
+}
+
+/// A [`Transport`] sealing each datagram with the pre-shared key before passing it to the
+/// wrapped [`Transport`], see [`crate::encryption`]. Received datagrams that fail to
+/// authenticate are dropped.
+#[cfg(feature = "client-encryption")]
+pub struct EncryptedTransport<T> {
+    inner: T,
+    key: [u8; 32],
+    nonces: crate::encryption::NonceSequence,
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "client-encryption")]
+impl<T: Transport> EncryptedTransport<T> {
+    /// Fails if the operating system cannot provide the random nonce prefix and counter
+    pub fn new(inner: T, key: [u8; 32]) -> io::Result<Self> {
+        let mut seed = [0u8; 12];
+        getrandom::getrandom(&mut seed).map_err(io::Error::from)?;
+        let mut prefix = [0u8; 4];
+        let mut counter = [0u8; 8];
+        prefix.copy_from_slice(&seed[..4]);
+        counter.copy_from_slice(&seed[4..]);
+        Ok(Self {
+            inner,
+            key,
+            nonces: crate::encryption::NonceSequence::new(prefix, u64::from_be_bytes(counter)),
+            buffer: Vec::new(),
+        })
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+#[cfg(feature = "client-encryption")]
+impl<T: core::fmt::Debug> core::fmt::Debug for EncryptedTransport<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EncryptedTransport")
+            .field("inner", &self.inner)
+            .field("key", &"<redacted>")
+            .field("nonces", &self.nonces)
+            .finish()
+    }
+}
+
+#[cfg(feature = "client-encryption")]
+impl<T: Transport> Transport for EncryptedTransport<T> {
+    async fn send(&mut self, datagram: &[u8]) -> io::Result<usize> {
+        self.buffer.clear();
+        self.buffer.extend_from_slice(datagram);
+        self.buffer
+            .resize(datagram.len() + crate::encryption::OVERHEAD, 0);
+        let len = crate::encryption::seal(
+            &self.key,
+            self.nonces.next_nonce(),
+            &mut self.buffer,
+            datagram.len(),
+        )
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        self.inner.send(&self.buffer[..len]).await?;
+        Ok(datagram.len())
+    }
+
+    async fn recv(&mut self, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.buffer
+            .resize(buffer.len() + crate::encryption::OVERHEAD, 0);
+        loop {
+            let (len, from) = self.inner.recv(&mut self.buffer).await?;
+            if let Ok(datagram) = crate::encryption::open(&self.key, &mut self.buffer[..len]) {
+                buffer[..datagram.len()].copy_from_slice(datagram);
+                return Ok((datagram.len(), from));
+            }
+            #[cfg(feature = "tracing")]
+            tracing::debug!(%from, "dropping datagram that failed to authenticate");
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Wall clock time for devices that cannot reach an SNTP server.
+//!
+//! The time is given in milliseconds since the unix epoch, 1970-01-01T00:00:00Z, without leap
+//! seconds. `Request::GetTime(id)` is answered with
+//! `Response::Ok(id, Format::ValueOnly(Type::TimestampMillisU64))` followed by the time, or
+//! with `Response::NotAvailable(id)` as long as the clock has not been set.
+//! `Request::SetTime(id, millis)` sets the clock and is answered with
+//! `Response::Ok(id, Format::Empty)`. Clients compensate the transmission delay themselves.
+
+use crate::value::Value;
+use crate::{Error, Format, Request, Response, Type, Write};
+
+/// The wall clock of the device, derived from its uptime
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Clock {
+    /// The unix time at an uptime of zero, wrapping
+    epoch_millis: Option<u64>,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock {
+    pub const fn new() -> Self {
+        Self { epoch_millis: None }
+    }
+
+    pub fn set(&mut self, uptime_millis: u64, unix_millis: u64) {
+        self.epoch_millis = Some(unix_millis.wrapping_sub(uptime_millis));
+    }
+
+    /// The unix time in milliseconds, `None` if the clock has not been set
+    pub fn unix_millis(&self, uptime_millis: u64) -> Option<u64> {
+        Some(self.epoch_millis?.wrapping_add(uptime_millis))
+    }
+
+    /// The unix time in seconds, as needed for [`crate::schedule::ScheduleHeader::due_millis`]
+    pub fn unix_seconds(&self, uptime_millis: u64) -> Option<u32> {
+        Some((self.unix_millis(uptime_millis)? / 1000) as u32)
+    }
+
+    /// Answers a `Request::GetTime` or `Request::SetTime`, returns `None` for any other request
+    pub fn respond(
+        &mut self,
+        request: &Request,
+        uptime_millis: u64,
+        response_writer: &mut impl Write,
+    ) -> Option<Result<usize, Error>> {
+        Some(match *request {
+            Request::GetTime(id) => match self.unix_millis(uptime_millis) {
+                Some(millis) => Response::Ok(id, Format::ValueOnly(Type::TimestampMillisU64))
+                    .write(response_writer)
+                    .and_then(|len| {
+                        Ok(len + Value::TimestampMillis(millis).write(response_writer)?)
+                    }),
+                None => Response::NotAvailable(id).write(response_writer),
+            },
+            Request::SetTime(id, millis) => {
+                self.set(uptime_millis, millis);
+                Response::Ok(id, Format::Empty).write(response_writer)
+            }
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+
+    #[test]
+    fn clock_advances_with_the_uptime() {
+        let mut clock = Clock::new();
+        let mut buffer = [0u8; 16];
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        clock
+            .respond(&Request::GetTime(1), 500, &mut cursor)
+            .unwrap()
+            .unwrap();
+        assert_eq!(&[0xF1, 1], cursor.written());
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        clock
+            .respond(&Request::SetTime(2, 1_700_000_000_000), 1_000, &mut cursor)
+            .unwrap()
+            .unwrap();
+        assert_eq!(Some(1_700_000_001_500), clock.unix_millis(2_500));
+        assert_eq!(Some(1_700_000_001), clock.unix_seconds(2_500));
+    }
+}
@@ -0,0 +1,298 @@
+//! Compression of large responses, such as error dumps and property lists, to fit them through
+//! small MTUs.
+//!
+//! A client that can decompress responses prefixes its requests with an
+//! [`AcceptCompressionHeader`] listing the supported [`Algorithm`]s, after an optional
+//! [`crate::schedule::ScheduleHeader`] and before an optional [`crate::priority::PriorityHeader`].
+//! The device may then answer with [`COMPRESSED_RESPONSE_MARKER`], the algorithm, the length of
+//! the uncompressed response as `u16` and the compressed response, see [`compress_response`].
+//! Devices that do not know the header ignore the request like any other unknown opcode.
+//!
+//! The only algorithm is [`Algorithm::Heatshrink`], the LZSS bit stream of heatshrink with a
+//! window of 2^8 and a lookahead of 2^4 bytes, which needs neither an allocator nor more than
+//! the window as state to decode.
+
+use crate::cursor::WriteCursor;
+use crate::{Error, Read, Write};
+use core::convert::TryFrom;
+
+/// The first byte of an encoded [`AcceptCompressionHeader`], which is not used by any request
+/// opcode
+pub const ACCEPT_COMPRESSION_MARKER: u8 = 0x55;
+
+/// The first byte of a compressed response, which is not used by any response opcode
+pub const COMPRESSED_RESPONSE_MARKER: u8 = 0x55;
+
+const WINDOW_BITS: u8 = 8;
+const LOOKAHEAD_BITS: u8 = 4;
+const WINDOW_LEN: usize = 1 << WINDOW_BITS;
+const MAX_MATCH_LEN: usize = 1 << LOOKAHEAD_BITS;
+/// A back reference of a single byte is longer than the literal
+const MIN_MATCH_LEN: usize = 2;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Algorithm {
+    Heatshrink,
+}
+
+impl Algorithm {
+    /// The identifier of the algorithm in a compressed response
+    pub const fn id(self) -> u8 {
+        match self {
+            Algorithm::Heatshrink => 0x01,
+        }
+    }
+
+    /// The bit of the algorithm in an [`AcceptCompressionHeader`]
+    pub const fn bit(self) -> u8 {
+        match self {
+            Algorithm::Heatshrink => 1 << 0,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Result<Self, Error> {
+        match id {
+            0x01 => Ok(Algorithm::Heatshrink),
+            _ => Err(Error::UnknownTypeIdentifier),
+        }
+    }
+}
+
+/// The algorithms the client is able to decompress, as bits of [`Algorithm::bit`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AcceptCompressionHeader(pub u8);
+
+impl AcceptCompressionHeader {
+    pub const ENCODED_LEN: usize = 1 + 1;
+
+    /// Accepts all algorithms known to this version of the protocol
+    pub const fn all() -> Self {
+        AcceptCompressionHeader(Algorithm::Heatshrink.bit())
+    }
+
+    pub const fn accepts(&self, algorithm: Algorithm) -> bool {
+        self.0 & algorithm.bit() != 0
+    }
+
+    pub fn write(&self, writer: &mut impl Write) -> Result<usize, Error> {
+        Ok(writer.write_u8(ACCEPT_COMPRESSION_MARKER)? + writer.write_u8(self.0)?)
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        if reader.read_u8()? != ACCEPT_COMPRESSION_MARKER {
+            return Err(Error::UnknownTypeIdentifier);
+        }
+        Ok(AcceptCompressionHeader(reader.read_u8()?))
+    }
+
+    /// Reads the [`AcceptCompressionHeader`] if the given datagram starts with one. Returns the
+    /// header and the remaining datagram.
+    pub fn read_optional(datagram: &[u8]) -> Result<(Option<Self>, &[u8]), Error> {
+        if datagram.first() == Some(&ACCEPT_COMPRESSION_MARKER) {
+            let reader = &mut &*datagram;
+            let header = Self::read(reader)?;
+            Ok((Some(header), reader))
+        } else {
+            Ok((None, datagram))
+        }
+    }
+}
+
+/// Compresses the encoded response into the buffer. Returns `None` if the compressed response
+/// is not shorter than the response, which is to be sent as it is then.
+pub fn compress_response(response: &[u8], buffer: &mut [u8]) -> Option<usize> {
+    let len = u16::try_from(response.len()).ok()?;
+    let limit = buffer.len().min(response.len().saturating_sub(1));
+    let mut cursor = WriteCursor::new(&mut buffer[..limit]);
+    let written = cursor.write_u8(COMPRESSED_RESPONSE_MARKER).ok()?
+        + cursor.write_u8(Algorithm::Heatshrink.id()).ok()?
+        + cursor.write_u16_be(len).ok()?
+        + compress(response, &mut cursor).ok()?;
+    Some(written)
+}
+
+/// Writes the response of a datagram starting with [`COMPRESSED_RESPONSE_MARKER`]
+pub fn decompress_response(datagram: &[u8], writer: &mut impl Write) -> Result<usize, Error> {
+    let reader = &mut &*datagram;
+    if reader.read_u8()? != COMPRESSED_RESPONSE_MARKER {
+        return Err(Error::UnknownTypeIdentifier);
+    }
+    let algorithm = Algorithm::from_id(reader.read_u8()?)?;
+    let len = reader.read_u16_be()?;
+    let written = match algorithm {
+        Algorithm::Heatshrink => decompress(reader, writer)?,
+    };
+    if written != usize::from(len) {
+        return Err(Error::PayloadLengthMismatch);
+    }
+    Ok(written)
+}
+
+/// Compresses the input with [`Algorithm::Heatshrink`]
+pub fn compress(input: &[u8], writer: &mut impl Write) -> Result<usize, Error> {
+    let mut bits = BitWriter::new(writer);
+    let mut position = 0;
+    while position < input.len() {
+        let (offset, len) = longest_match(input, position);
+        if len >= MIN_MATCH_LEN {
+            bits.push(0, 1)?;
+            bits.push((offset - 1) as u16, WINDOW_BITS)?;
+            bits.push((len - 1) as u16, LOOKAHEAD_BITS)?;
+            position += len;
+        } else {
+            bits.push(1, 1)?;
+            bits.push(u16::from(input[position]), 8)?;
+            position += 1;
+        }
+    }
+    bits.finish()
+}
+
+/// Decompresses the [`Algorithm::Heatshrink`] stream, the zero bits padding the last byte are
+/// ignored
+pub fn decompress(input: &[u8], writer: &mut impl Write) -> Result<usize, Error> {
+    let mut bits = BitReader { input, position: 0 };
+    let mut window = [0u8; WINDOW_LEN];
+    let mut written = 0usize;
+
+    while let Some(tag) = bits.pop(1) {
+        if tag == 1 {
+            let byte = bits.pop(8).ok_or(Error::UnexpectedEOF)?;
+            written += output(writer, &mut window, written, byte as u8)?;
+        } else {
+            let (index, count) = match (bits.pop(WINDOW_BITS), bits.pop(LOOKAHEAD_BITS)) {
+                (Some(index), Some(count)) => (usize::from(index), usize::from(count)),
+                _ => break,
+            };
+            let offset = index + 1;
+            if offset > written {
+                return Err(Error::UnexpectedEOF);
+            }
+            for _ in 0..=count {
+                let byte = window[(written - offset) % WINDOW_LEN];
+                written += output(writer, &mut window, written, byte)?;
+            }
+        }
+    }
+    Ok(written)
+}
+
+/// Writes the decompressed byte and keeps it in the window
+fn output(
+    writer: &mut impl Write,
+    window: &mut [u8; WINDOW_LEN],
+    written: usize,
+    byte: u8,
+) -> Result<usize, Error> {
+    window[written % WINDOW_LEN] = byte;
+    writer.write_u8(byte)
+}
+
+/// The offset and length of the longest match in the window before the position
+fn longest_match(input: &[u8], position: usize) -> (usize, usize) {
+    let max_len = (input.len() - position).min(MAX_MATCH_LEN);
+    let mut best = (0, 0);
+    for offset in 1..=position.min(WINDOW_LEN) {
+        let start = position - offset;
+        let len = (0..max_len)
+            .take_while(|i| input[start + i] == input[position + i])
+            .count();
+        if len > best.1 {
+            best = (offset, len);
+            if len == max_len {
+                break;
+            }
+        }
+    }
+    best
+}
+
+struct BitWriter<'a, W: Write> {
+    writer: &'a mut W,
+    byte: u8,
+    bits: u8,
+    written: usize,
+}
+
+impl<'a, W: Write> BitWriter<'a, W> {
+    fn new(writer: &'a mut W) -> Self {
+        Self {
+            writer,
+            byte: 0,
+            bits: 0,
+            written: 0,
+        }
+    }
+
+    /// Pushes the lowest `count` bits of the value, the most significant first
+    fn push(&mut self, value: u16, count: u8) -> Result<(), Error> {
+        for shift in (0..count).rev() {
+            self.byte = (self.byte << 1) | ((value >> shift) & 1) as u8;
+            self.bits += 1;
+            if self.bits == 8 {
+                self.written += self.writer.write_u8(self.byte)?;
+                self.byte = 0;
+                self.bits = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Pads the last byte with zero bits
+    fn finish(mut self) -> Result<usize, Error> {
+        if self.bits > 0 {
+            self.written += self.writer.write_u8(self.byte << (8 - self.bits))?;
+        }
+        Ok(self.written)
+    }
+}
+
+struct BitReader<'a> {
+    input: &'a [u8],
+    /// The position in bits
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn pop(&mut self, count: u8) -> Option<u16> {
+        if self.position + usize::from(count) > self.input.len() * 8 {
+            return None;
+        }
+        let mut value = 0u16;
+        for _ in 0..count {
+            let bit = (self.input[self.position / 8] >> (7 - self.position % 8)) & 1;
+            value = (value << 1) | u16::from(bit);
+            self.position += 1;
+        }
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compressed_response_roundtrip() {
+        let mut response = [0u8; 200];
+        response[..4].copy_from_slice(&[0x00, 7, 0x00, 0xC3]);
+        for (index, byte) in response[4..].iter_mut().enumerate() {
+            *byte = (index % 12) as u8;
+        }
+
+        let mut compressed = [0u8; 200];
+        let len = compress_response(&response, &mut compressed).unwrap();
+        assert!(len < 64);
+        assert_eq!(COMPRESSED_RESPONSE_MARKER, compressed[0]);
+
+        let mut decompressed = [0u8; 200];
+        let mut cursor = WriteCursor::new(&mut decompressed);
+        assert_eq!(
+            Ok(200),
+            decompress_response(&compressed[..len], &mut cursor)
+        );
+        assert_eq!(&response[..], cursor.written());
+
+        assert_eq!(None, compress_response(&[0x01, 2, 3, 4], &mut compressed));
+    }
+}
@@ -0,0 +1,73 @@
+//! Count-prefixed read responses.
+//!
+//! The payload of a `Format::AddressValuePairs` response does not tell how many pairs it
+//! contains, so a datagram truncated at a pair boundary cannot be told apart from a response
+//! with fewer readings. The payload of a `Format::CountedAddressValuePairs` response starts
+//! with the number of pairs as big endian `u16`.
+//!
+//! A client asks for the counted format by appending [`ReadFlags`] to a `Request::ReadAll` or
+//! `Request::ReadAllOnBus`. Older firmware ignores the flags and answers with the uncounted
+//! format, so clients have to accept both.
+//!
+//! The flags also allow to atomically read and reset `Type::CounterU64` values. Firmware that
+//! reports counters understands the flags, so the counters read with
+//! [`ReadFlags::reset_counters`] set are the events since the previous read.
+
+use crate::{Error, Format, Read, Write};
+
+/// The options a client appends to a `Request::ReadAll` or `Request::ReadAllOnBus`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, core::default::Default)]
+pub struct ReadFlags {
+    /// Whether the client understands `Format::CountedAddressValuePairs`
+    pub counted: bool,
+    /// Whether counters are reset to `0` after being read
+    pub reset_counters: bool,
+}
+
+impl ReadFlags {
+    pub const fn counted() -> Self {
+        Self {
+            counted: true,
+            reset_counters: false,
+        }
+    }
+
+    pub const fn with_reset_counters(mut self, reset_counters: bool) -> Self {
+        self.reset_counters = reset_counters;
+        self
+    }
+
+    pub fn write(&self, writer: &mut impl Write) -> Result<usize, Error> {
+        let mut flags = 0u8;
+        if self.counted {
+            flags |= 1u8 << 0;
+        }
+        if self.reset_counters {
+            flags |= 1u8 << 1;
+        }
+        writer.write_u8(flags)
+    }
+
+    /// Reads the flags following a read request, requests of older clients have none
+    pub fn read_optional(reader: &mut impl Read) -> Result<Self, Error> {
+        if reader.available() == 0 {
+            Ok(Self::default())
+        } else {
+            let flags = reader.read_u8()?;
+            Ok(Self {
+                counted: flags & (1u8 << 0) != 0,
+                reset_counters: flags & (1u8 << 1) != 0,
+            })
+        }
+    }
+
+    /// The format to answer the request with, the counted variant of the given address-value
+    /// pairs if the client understands it
+    pub const fn response_format(&self, address: crate::Type, value: crate::Type) -> Format {
+        if self.counted {
+            Format::CountedAddressValuePairs(address, value)
+        } else {
+            Format::AddressValuePairs(address, value)
+        }
+    }
+}
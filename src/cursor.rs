@@ -0,0 +1,595 @@
+use crate::{Error, Read, Write};
+
+/// A [`Read`] over a byte slice that, unlike `&[u8]`, keeps the whole buffer and tracks the
+/// current position within it.
+#[derive(Debug, Clone)]
+pub struct ReadCursor<'a> {
+    buffer: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ReadCursor<'a> {
+    pub const fn new(buffer: &'a [u8]) -> Self {
+        Self {
+            buffer,
+            position: 0,
+        }
+    }
+
+    /// The number of bytes that have been read so far
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The bytes that have not been read yet
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.buffer[self.position..]
+    }
+
+    /// Moves the cursor back to the given position, a position ahead of the current one is
+    /// ignored
+    pub fn rewind_to(&mut self, position: usize) {
+        self.position = self.position.min(position);
+    }
+
+    pub fn rewind(&mut self) {
+        self.position = 0;
+    }
+
+    pub const fn into_inner(self) -> &'a [u8] {
+        self.buffer
+    }
+}
+
+impl Read for ReadCursor<'_> {
+    fn read_u8(&mut self) -> Result<u8, Error> {
+        let value = *self.buffer.get(self.position).ok_or(Error::UnexpectedEOF)?;
+        self.position += 1;
+        Ok(value)
+    }
+
+    fn peek_u8(&self) -> Result<u8, Error> {
+        self.buffer
+            .get(self.position)
+            .copied()
+            .ok_or(Error::UnexpectedEOF)
+    }
+
+    fn skip(&mut self, n: usize) -> Result<usize, Error> {
+        if self.available() < n {
+            Err(Error::UnexpectedEOF)
+        } else {
+            self.position += n;
+            Ok(n)
+        }
+    }
+
+    fn available(&self) -> usize {
+        self.buffer.len() - self.position
+    }
+}
+
+/// A [`Write`] over a mutable byte slice that, unlike `&mut [u8]`, keeps the whole buffer and
+/// tracks the current position within it, which allows to roll back partially written data.
+#[derive(Debug)]
+pub struct WriteCursor<'a> {
+    buffer: &'a mut [u8],
+    position: usize,
+}
+
+impl<'a> WriteCursor<'a> {
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        Self {
+            buffer,
+            position: 0,
+        }
+    }
+
+    /// The number of bytes that have been written so far
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The number of bytes that can still be written
+    pub fn remaining(&self) -> usize {
+        self.buffer.len() - self.position
+    }
+
+    /// The bytes that have been written so far
+    pub fn written(&self) -> &[u8] {
+        &self.buffer[..self.position]
+    }
+
+    /// Moves the cursor back to the given position, discarding everything written after it.
+    /// A position ahead of the current one is ignored.
+    pub fn rewind_to(&mut self, position: usize) {
+        self.position = self.position.min(position);
+    }
+
+    pub fn rewind(&mut self) {
+        self.position = 0;
+    }
+
+    /// Returns the part of the buffer that has been written to
+    pub fn into_written(self) -> &'a mut [u8] {
+        &mut self.buffer[..self.position]
+    }
+}
+
+impl Write for WriteCursor<'_> {
+    fn write_u8(&mut self, value: u8) -> Result<usize, Error> {
+        let slot = self
+            .buffer
+            .get_mut(self.position)
+            .ok_or(Error::BufferToSmall)?;
+        *slot = value;
+        self.position += 1;
+        Ok(1)
+    }
+
+    fn available(&self) -> usize {
+        self.remaining()
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        if self.remaining() < bytes.len() {
+            Err(Error::BufferToSmall)
+        } else {
+            self.buffer[self.position..][..bytes.len()].copy_from_slice(bytes);
+            self.position += bytes.len();
+            Ok(bytes.len())
+        }
+    }
+
+    fn write_vectored(&mut self, slices: &[&[u8]]) -> Result<usize, Error> {
+        let len = slices.iter().map(|slice| slice.len()).sum::<usize>();
+        if self.remaining() < len {
+            return Err(Error::BufferToSmall);
+        }
+        for slice in slices {
+            self.buffer[self.position..][..slice.len()].copy_from_slice(slice);
+            self.position += slice.len();
+        }
+        Ok(len)
+    }
+}
+
+/// A [`Write`] that can discard everything written after a previous position. Not implemented
+/// for `&mut [u8]`, which forgets the bytes written to it, wrap it in a [`WriteCursor`] instead.
+pub trait Rewind: Write {
+    fn position(&self) -> usize;
+
+    fn rewind_to(&mut self, position: usize);
+}
+
+impl Rewind for WriteCursor<'_> {
+    fn position(&self) -> usize {
+        WriteCursor::position(self)
+    }
+
+    fn rewind_to(&mut self, position: usize) {
+        WriteCursor::rewind_to(self, position)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Rewind for alloc::vec::Vec<u8> {
+    fn position(&self) -> usize {
+        self.len()
+    }
+
+    fn rewind_to(&mut self, position: usize) {
+        self.truncate(position)
+    }
+}
+
+/// Wraps a [`Rewind`] so that everything written through it is discarded again unless
+/// [`Transaction::commit`] is called.
+pub struct Transaction<'w, W: Rewind> {
+    writer: &'w mut W,
+    start: usize,
+    committed: bool,
+}
+
+impl<'w, W: Rewind> Transaction<'w, W> {
+    pub fn new(writer: &'w mut W) -> Self {
+        Self {
+            start: writer.position(),
+            writer,
+            committed: false,
+        }
+    }
+
+    /// Keeps everything that has been written through this transaction
+    pub fn commit(mut self) {
+        self.committed = true;
+    }
+
+    /// Discards everything that has been written through this transaction
+    pub fn rollback(self) {}
+}
+
+impl<W: Rewind> Drop for Transaction<'_, W> {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.writer.rewind_to(self.start);
+        }
+    }
+}
+
+impl<W: Rewind> Write for Transaction<'_, W> {
+    fn write_u8(&mut self, value: u8) -> Result<usize, Error> {
+        self.writer.write_u8(value)
+    }
+
+    fn available(&self) -> usize {
+        self.writer.available()
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        self.writer.write_all(bytes)
+    }
+
+    fn write_vectored(&mut self, slices: &[&[u8]]) -> Result<usize, Error> {
+        self.writer.write_vectored(slices)
+    }
+}
+
+/// A [`Read`] that reads at most a given number of bytes from the underlying reader, see
+/// [`Read::take`].
+pub struct Take<'r, R: Read + ?Sized> {
+    reader: &'r mut R,
+    remaining: usize,
+}
+
+impl<'r, R: Read + ?Sized> Take<'r, R> {
+    pub fn new(reader: &'r mut R, limit: usize) -> Self {
+        Self {
+            reader,
+            remaining: limit,
+        }
+    }
+
+    /// The number of bytes that can still be read before the limit is reached
+    pub const fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Skips whatever has not been read up to the limit, for example the unknown tail of a
+    /// length-prefixed section
+    pub fn skip_remaining(&mut self) -> Result<usize, Error> {
+        let remaining = self.remaining;
+        self.skip(remaining)
+    }
+}
+
+impl<R: Read + ?Sized> Read for Take<'_, R> {
+    fn read_u8(&mut self) -> Result<u8, Error> {
+        if self.remaining == 0 {
+            return Err(Error::UnexpectedEOF);
+        }
+        let value = self.reader.read_u8()?;
+        self.remaining -= 1;
+        Ok(value)
+    }
+
+    fn peek_u8(&self) -> Result<u8, Error> {
+        if self.remaining == 0 {
+            Err(Error::UnexpectedEOF)
+        } else {
+            self.reader.peek_u8()
+        }
+    }
+
+    fn skip(&mut self, n: usize) -> Result<usize, Error> {
+        if self.remaining < n {
+            return Err(Error::UnexpectedEOF);
+        }
+        let skipped = self.reader.skip(n)?;
+        self.remaining -= skipped;
+        Ok(skipped)
+    }
+
+    fn available(&self) -> usize {
+        self.reader.available().min(self.remaining)
+    }
+}
+
+/// A [`Write`] that writes at most a given number of bytes to the underlying writer, see
+/// [`Write::limit`].
+pub struct Limit<'w, W: Write + ?Sized> {
+    writer: &'w mut W,
+    remaining: usize,
+}
+
+impl<'w, W: Write + ?Sized> Limit<'w, W> {
+    pub fn new(writer: &'w mut W, limit: usize) -> Self {
+        Self {
+            writer,
+            remaining: limit,
+        }
+    }
+
+    /// The number of bytes that can still be written before the limit is reached
+    pub const fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<W: Write + ?Sized> Write for Limit<'_, W> {
+    fn write_u8(&mut self, value: u8) -> Result<usize, Error> {
+        if self.remaining == 0 {
+            return Err(Error::BufferToSmall);
+        }
+        let written = self.writer.write_u8(value)?;
+        self.remaining -= written;
+        Ok(written)
+    }
+
+    fn available(&self) -> usize {
+        self.writer.available().min(self.remaining)
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        if self.remaining < bytes.len() {
+            return Err(Error::BufferToSmall);
+        }
+        let written = self.writer.write_all(bytes)?;
+        self.remaining -= written;
+        Ok(written)
+    }
+
+    fn write_vectored(&mut self, slices: &[&[u8]]) -> Result<usize, Error> {
+        if self.remaining < slices.iter().map(|slice| slice.len()).sum::<usize>() {
+            return Err(Error::BufferToSmall);
+        }
+        let written = self.writer.write_vectored(slices)?;
+        self.remaining -= written;
+        Ok(written)
+    }
+}
+
+/// A [`Write`] that collects single bytes in a buffer and passes them on with a single
+/// [`Write::write_all`] once the buffer is full or [`BufferedWriter::flush`] is called, for
+/// writers with a high cost per call such as network drivers. Bytes that are still buffered
+/// when the [`BufferedWriter`] is dropped are lost.
+pub struct BufferedWriter<'a, W: Write> {
+    writer: W,
+    buffer: &'a mut [u8],
+    position: usize,
+}
+
+impl<'a, W: Write> BufferedWriter<'a, W> {
+    pub fn new(writer: W, buffer: &'a mut [u8]) -> Self {
+        Self {
+            writer,
+            buffer,
+            position: 0,
+        }
+    }
+
+    /// The bytes that have not been passed on yet
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer[..self.position]
+    }
+
+    /// Passes the buffered bytes on to the underlying writer, returns their number
+    pub fn flush(&mut self) -> Result<usize, Error> {
+        if self.position == 0 {
+            return Ok(0);
+        }
+        let written = self.writer.write_all(&self.buffer[..self.position])?;
+        self.position = 0;
+        Ok(written)
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// The underlying writer, writing to it directly while bytes are buffered reorders them
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Flushes the buffered bytes and returns the underlying writer
+    pub fn into_inner(mut self) -> Result<W, Error> {
+        self.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> Write for BufferedWriter<'_, W> {
+    fn write_u8(&mut self, value: u8) -> Result<usize, Error> {
+        if self.buffer.is_empty() {
+            return self.writer.write_u8(value);
+        }
+        if self.available() == 0 {
+            return Err(Error::BufferToSmall);
+        }
+        if self.position == self.buffer.len() {
+            self.flush()?;
+        }
+        self.buffer[self.position] = value;
+        self.position += 1;
+        Ok(1)
+    }
+
+    fn available(&self) -> usize {
+        self.writer.available().saturating_sub(self.position)
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        if self.available() < bytes.len() {
+            return Err(Error::BufferToSmall);
+        }
+        if self.buffer.len() - self.position < bytes.len() {
+            self.flush()?;
+            if self.buffer.len() < bytes.len() {
+                // too large to be buffered at all
+                return self.writer.write_all(bytes);
+            }
+        }
+        self.buffer[self.position..][..bytes.len()].copy_from_slice(bytes);
+        self.position += bytes.len();
+        Ok(bytes.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counts the calls of the underlying writer
+    struct Driver<'a> {
+        cursor: WriteCursor<'a>,
+        calls: usize,
+    }
+
+    impl Write for Driver<'_> {
+        fn write_u8(&mut self, value: u8) -> Result<usize, Error> {
+            self.calls += 1;
+            self.cursor.write_u8(value)
+        }
+
+        fn available(&self) -> usize {
+            self.cursor.available()
+        }
+
+        fn write_all(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+            self.calls += 1;
+            self.cursor.write_all(bytes)
+        }
+    }
+
+    #[test]
+    fn buffered_writer_passes_on_bytes_in_bulk() {
+        let mut target = [0u8; 16];
+        let driver = Driver {
+            cursor: WriteCursor::new(&mut target),
+            calls: 0,
+        };
+        let mut buffer = [0u8; 4];
+        let mut writer = BufferedWriter::new(driver, &mut buffer);
+
+        for byte in 1..=6 {
+            writer.write_u8(byte).unwrap();
+        }
+        assert_eq!(&[5, 6], writer.buffered());
+        writer.write_all(&[7, 8, 9, 10, 11]).unwrap();
+        writer.write_u8(12).unwrap();
+        assert_eq!(4, writer.available());
+        assert_eq!(Err(Error::BufferToSmall), writer.write_all(&[0; 5]));
+
+        let driver = writer.into_inner().unwrap();
+        assert_eq!(4, driver.calls);
+        assert_eq!(
+            &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12],
+            driver.cursor.written()
+        );
+    }
+
+    #[test]
+    fn vectored_write_is_all_or_nothing() {
+        let mut buffer = [0u8; 6];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        assert_eq!(Ok(4), cursor.write_vectored(&[&[1, 2], &[], &[3, 4]]));
+        assert_eq!(
+            Err(Error::BufferToSmall),
+            cursor.limit(8).write_vectored(&[&[5], &[6, 7]])
+        );
+        assert_eq!(&[1, 2, 3, 4], cursor.written());
+    }
+
+    #[test]
+    fn requests_with_a_bus_are_written_all_or_nothing() {
+        let request = crate::Request::SetOutput(
+            1,
+            crate::Bus::I2cN {
+                bus: 2,
+                ten_bit: false,
+            },
+            crate::output::OutputState::On,
+        );
+        let mut buffer = [0u8; 6];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        assert_eq!(Err(Error::BufferToSmall), request.write(&mut cursor));
+        assert_eq!(0, cursor.position());
+
+        let (encoded, len) = request.encode();
+        let mut buffer = [0u8; 16];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        assert_eq!(Ok(len), request.write(&mut cursor));
+        assert_eq!(&encoded[..len], cursor.written());
+    }
+
+    #[test]
+    fn transaction_is_rolled_back_unless_committed() {
+        let mut buffer = [0u8; 4];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        cursor.write_u8(1).unwrap();
+
+        let mut transaction = Transaction::new(&mut cursor);
+        transaction.write_all(&[2, 3]).unwrap();
+        drop(transaction);
+        assert_eq!(&[1], cursor.written());
+
+        let mut transaction = Transaction::new(&mut cursor);
+        transaction.write_u8(2).unwrap();
+        transaction.rollback();
+        assert_eq!(&[1], cursor.written());
+
+        let mut transaction = Transaction::new(&mut cursor);
+        transaction.write_u8(2).unwrap();
+        assert_eq!(2, transaction.available());
+        assert_eq!(Err(Error::BufferToSmall), transaction.write_all(&[3, 4, 5]));
+        transaction.commit();
+        assert_eq!(&[1, 2], cursor.written());
+    }
+
+    #[test]
+    fn take_stops_at_the_limit() {
+        let mut reader = &[1u8, 2, 3, 4, 5, 6][..];
+        let mut take = reader.take(4);
+        assert_eq!(Ok(1), take.read_u8());
+        assert_eq!(Err(Error::UnexpectedEOF), take.skip(4));
+        assert_eq!(3, take.available());
+        assert_eq!(Ok(2), take.peek_u8());
+        assert_eq!(Ok(1), take.skip(1));
+        assert_eq!(Ok(3), take.read_u8());
+        assert_eq!(Ok(1), take.skip_remaining());
+        assert_eq!(Err(Error::UnexpectedEOF), take.peek_u8());
+        assert_eq!(Err(Error::UnexpectedEOF), take.read_u8());
+        assert_eq!(0, take.remaining());
+        assert_eq!(&[5, 6], reader);
+
+        // the underlying reader ends before the limit
+        let mut take = reader.take(8);
+        assert_eq!(2, take.available());
+        assert_eq!(
+            Err(Error::UnexpectedEOF),
+            take.read_u16_be().and(take.read_u8())
+        );
+        assert_eq!(6, take.remaining());
+    }
+
+    #[test]
+    fn limit_stops_at_the_limit() {
+        let mut buffer = [0u8; 8];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let mut limit = cursor.limit(3);
+        assert_eq!(3, limit.available());
+        assert_eq!(Ok(2), limit.write_all(&[1, 2]));
+        assert_eq!(Err(Error::BufferToSmall), limit.write_all(&[3, 4]));
+        assert_eq!(Ok(1), limit.write_u8(3));
+        assert_eq!(Err(Error::BufferToSmall), limit.write_u8(4));
+        assert_eq!(0, limit.remaining());
+        assert_eq!(&[1, 2, 3], cursor.written());
+
+        // the underlying writer ends before the limit
+        let mut limit = cursor.limit(8);
+        assert_eq!(5, limit.available());
+        assert_eq!(Err(Error::BufferToSmall), limit.write_all(&[0; 6]));
+        assert_eq!(8, limit.remaining());
+    }
+}
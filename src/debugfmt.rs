@@ -0,0 +1,374 @@
+//! Annotated, human-readable dumps of raw datagrams, for example as captured with tcpdump.
+//!
+//! Decoding stops at the first malformed field, which is reported along with the remaining
+//! undecoded bytes.
+
+use crate::address::AddressListIter;
+use crate::batch::BatchIter;
+use crate::compression::{
+    decompress_response, AcceptCompressionHeader, COMPRESSED_RESPONSE_MARKER,
+};
+use crate::frame::{Element, ElementIter};
+use crate::headers::{HeaderKind, RequestHeader, RequestHeaderIter};
+use crate::hex::Hex;
+use crate::info::NetworkConfiguration;
+use crate::pairing::SessionHeader;
+use crate::priority::PriorityHeader;
+use crate::replay::SequenceHeader;
+use crate::routing::RoutingHeader;
+use crate::{Format, Read, Request, Response};
+use std::fmt::Write;
+
+/// Explains a request datagram including its payload
+pub fn explain_request(datagram: &[u8]) -> String {
+    let mut out = String::new();
+    write_request(&mut out, 0, datagram);
+    out
+}
+
+/// Explains a response datagram including its payload
+pub fn explain_response(datagram: &[u8]) -> String {
+    let mut out = String::new();
+    write_response(&mut out, 0, datagram);
+    out
+}
+
+fn write_request(out: &mut String, indent: usize, datagram: &[u8]) {
+    let mut headers = RequestHeaderIter::new(datagram);
+    for header in &mut headers {
+        match header {
+            Ok(RequestHeader::Routing(routing)) => line(
+                out,
+                indent,
+                format_args!("routing header: hops={:02x?}", routing.hops()),
+            ),
+            Ok(RequestHeader::Sequence(SequenceHeader(sequence))) => {
+                line(out, indent, format_args!("sequence header: {}", sequence))
+            }
+            Ok(RequestHeader::Session(SessionHeader(token))) => line(
+                out,
+                indent,
+                format_args!("session header: {}", Hex::colons(&token)),
+            ),
+            Ok(RequestHeader::Schedule(schedule)) => {
+                line(out, indent, format_args!("schedule header: {:?}", schedule))
+            }
+            Ok(RequestHeader::AcceptCompression(AcceptCompressionHeader(algorithms))) => line(
+                out,
+                indent,
+                format_args!("accept compression header: 0x{:02x}", algorithms),
+            ),
+            Ok(RequestHeader::Priority(PriorityHeader(priority))) => line(
+                out,
+                indent,
+                format_args!("priority header: {}", priority.value()),
+            ),
+            Err(e) => {
+                let remaining = headers.remaining();
+                let kind = remaining.first().copied().and_then(HeaderKind::from_marker);
+                let what = kind.map_or("header", HeaderKind::name);
+                return malformed(out, indent, what, e, remaining);
+            }
+        }
+    }
+    let datagram = headers.remaining();
+
+    let mut payload = datagram;
+    let request = match Request::read(&mut payload) {
+        Ok(request) => request,
+        Err(e) => return malformed(out, indent, "request", e, datagram),
+    };
+    line(
+        out,
+        indent,
+        format_args!(
+            "request: opcode=0x{:02x} id={} {:?}",
+            datagram[0],
+            request.id(),
+            request
+        ),
+    );
+
+    match request {
+        Request::ReadSpecified(_, bus)
+        | Request::SetOutput(_, bus, _)
+        | Request::GetOutput(_, bus) => {
+            for address in AddressListIter::new(bus, payload) {
+                match address {
+                    Ok(address) => line(
+                        out,
+                        indent + 1,
+                        format_args!("address: {}", Hex::colons(address)),
+                    ),
+                    Err(e) => return malformed(out, indent + 1, "address", e, &[]),
+                }
+            }
+        }
+        Request::StoreProperty(_, len) | Request::RetrieveProperty(_, len) => {
+            let len = usize::from(len).min(payload.len());
+            let (path, value) = payload.split_at(len);
+            line(
+                out,
+                indent + 1,
+                format_args!("property path: {}", Hex::colons(path)),
+            );
+            if !value.is_empty() {
+                line(
+                    out,
+                    indent + 1,
+                    format_args!("value: {}", Hex::colons(value)),
+                );
+            }
+        }
+        Request::StageNetworkConfig(..) => match NetworkConfiguration::read(&mut payload) {
+            Ok(configuration) => line(
+                out,
+                indent + 1,
+                format_args!("configuration: {:?}", configuration),
+            ),
+            Err(e) => malformed(out, indent + 1, "configuration", e, payload),
+        },
+        Request::Batch(_, count) => {
+            for (index, entry) in BatchIter::new(payload).enumerate() {
+                match entry {
+                    Ok(entry) => {
+                        line(
+                            out,
+                            indent + 1,
+                            format_args!("entry {}/{}:", index + 1, count),
+                        );
+                        write_request(out, indent + 2, entry);
+                    }
+                    Err(e) => return malformed(out, indent + 1, "batch entry", e, &[]),
+                }
+            }
+        }
+        _ if !payload.is_empty() => {
+            line(
+                out,
+                indent + 1,
+                format_args!("payload: {}", Hex::colons(payload)),
+            );
+        }
+        _ => {}
+    }
+}
+
+/// Explains the routing header if any, returns the remaining datagram unless it is malformed
+fn write_routing_header<'a>(
+    out: &mut String,
+    indent: usize,
+    datagram: &'a [u8],
+) -> Option<&'a [u8]> {
+    match RoutingHeader::read_optional(datagram) {
+        Ok((Some(routing), remaining)) => {
+            line(
+                out,
+                indent,
+                format_args!("routing header: hops={:02x?}", routing.hops()),
+            );
+            Some(remaining)
+        }
+        Ok((None, remaining)) => Some(remaining),
+        Err(e) => {
+            malformed(out, indent, "routing header", e, datagram);
+            None
+        }
+    }
+}
+
+fn write_response(out: &mut String, indent: usize, datagram: &[u8]) {
+    let datagram = match write_routing_header(out, indent, datagram) {
+        Some(remaining) => remaining,
+        None => return,
+    };
+    if datagram.first() == Some(&COMPRESSED_RESPONSE_MARKER) {
+        let mut decompressed = Vec::new();
+        return match decompress_response(datagram, &mut decompressed) {
+            Ok(len) => {
+                line(
+                    out,
+                    indent,
+                    format_args!("compressed response: {} of {} bytes", datagram.len(), len),
+                );
+                write_response(out, indent + 1, &decompressed);
+            }
+            Err(e) => malformed(out, indent, "compressed response", e, datagram),
+        };
+    }
+    let mut payload = datagram;
+    let response = match Response::read(&mut payload) {
+        Ok(response) => response,
+        Err(e) => return malformed(out, indent, "response", e, datagram),
+    };
+    line(
+        out,
+        indent,
+        format_args!(
+            "response: opcode=0x{:02x} id={} {:?}",
+            datagram[0],
+            response.id(),
+            response
+        ),
+    );
+
+    let format = match response {
+        Response::Ok(_, format) | Response::Announcement(_, format) => format,
+        _ => {
+            if !payload.is_empty() {
+                line(
+                    out,
+                    indent + 1,
+                    format_args!("payload: {}", Hex::colons(payload)),
+                );
+            }
+            return;
+        }
+    };
+
+    match format {
+        Format::ValueOnly(crate::Type::Batch) => {
+            for (index, entry) in BatchIter::new(payload).enumerate() {
+                match entry {
+                    Ok(entry) => {
+                        line(out, indent + 1, format_args!("entry {}:", index + 1));
+                        write_response(out, indent + 2, entry);
+                    }
+                    Err(e) => return malformed(out, indent + 1, "batch entry", e, &[]),
+                }
+            }
+            return;
+        }
+        Format::CountedAddressValuePairs(..) => match (&payload[..]).read_u16_be() {
+            Ok(count) => line(out, indent + 1, format_args!("count: {}", count)),
+            Err(e) => return malformed(out, indent + 1, "count", e, payload),
+        },
+        _ => {}
+    }
+
+    let mut elements = ElementIter::new(format, payload);
+    loop {
+        let remaining = elements.remaining();
+        match elements.next() {
+            Some(Ok(element)) => line(out, indent + 1, format_args!("{}", describe(&element))),
+            Some(Err(crate::Error::TrailingBytes)) => {
+                return line(
+                    out,
+                    indent + 1,
+                    format_args!("unexpected payload: {}", Hex::colons(remaining)),
+                )
+            }
+            Some(Err(e)) => return malformed(out, indent + 1, "element", e, remaining),
+            None => break,
+        }
+    }
+    if !elements.remaining().is_empty() {
+        line(
+            out,
+            indent + 1,
+            format_args!("unexpected payload: {}", Hex::colons(elements.remaining())),
+        );
+    }
+}
+
+/// The parts of the element, in the order they are encoded in
+fn describe(element: &Element<'_>) -> String {
+    let mut description = Vec::new();
+    if let Some(address) = element.address {
+        description.push(format!("address: {}", Hex::colons(address)));
+    }
+    if let Some(status) = element.status {
+        description.push(format!("status: {:?}", status));
+    }
+    if let Some(value) = &element.value {
+        description.push(format!("value: {:?}", value));
+    }
+    if let Some(timestamp) = &element.timestamp {
+        description.push(format!("timestamp: {:?}", timestamp));
+    }
+    description.join(" ")
+}
+
+/// Formats the bytes as classic hexdump with 16 bytes per line, each line prefixed by the
+/// offset of its first byte and followed by the printable ASCII characters
+pub fn hexdump(offset: usize, bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for (index, chunk) in bytes.chunks(16).enumerate() {
+        let _ = write!(out, "{:08x}  {}", offset + index * 16, Hex::new(chunk, " "));
+        let ascii = chunk
+            .iter()
+            .map(|byte| match byte {
+                0x20..=0x7E => char::from(*byte),
+                _ => '.',
+            })
+            .collect::<String>();
+        let _ = writeln!(
+            out,
+            "{:pad$}  |{}|",
+            "",
+            ascii,
+            pad = (16 - chunk.len()) * 3
+        );
+    }
+    out
+}
+
+fn line(out: &mut String, indent: usize, args: std::fmt::Arguments<'_>) {
+    let _ = writeln!(out, "{:indent$}{}", "", args, indent = indent * 2);
+}
+
+fn malformed(out: &mut String, indent: usize, what: &str, error: crate::Error, remaining: &[u8]) {
+    line(
+        out,
+        indent,
+        format_args!(
+            "malformed {}: {:?}, undecoded: {}",
+            what,
+            error,
+            Hex::colons(remaining)
+        ),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explain_batched_requests() {
+        let mut inner = Vec::new();
+        Request::ReadAll(7).write(&mut inner).unwrap();
+
+        let mut datagram = Vec::new();
+        Request::Batch(3, 1).write(&mut datagram).unwrap();
+        crate::batch::write_entry(&mut datagram, &inner).unwrap();
+
+        let explained = explain_request(&datagram);
+        assert!(explained.contains("id=3 Batch(3, 1)"));
+        assert!(explained.contains("entry 1/1:"));
+        assert!(explained.contains("    request: opcode=0x01 id=7 ReadAll(7)"));
+    }
+
+    #[test]
+    fn hexdump_pads_the_last_line() {
+        let dump = hexdump(0x100, b"sensor_common v1\x00\xff");
+        assert_eq!(
+            "00000100  73 65 6e 73 6f 72 5f 63 6f 6d 6d 6f 6e 20 76 31  |sensor_common v1|\n\
+             00000110  00 ff                                            |..|\n",
+            dump
+        );
+    }
+
+    #[test]
+    fn explain_response_elements() {
+        let datagram = [
+            0x00, 0x07, 0x02, 0x01, 0x02, 0xFC, 0xAA, 0xBB, 0x00, 0x01, 0xCC, 0xDD, 0x00,
+        ];
+        let explained = explain_response(&datagram);
+        assert!(explained.contains("  address: aa:bb value: U16(1)\n"));
+        assert!(explained.contains("  malformed element: UnexpectedEOF, undecoded: cc:dd:00\n"));
+
+        let explained = explain_response(&[0x00, 0x07, 0xFF, 0xAA]);
+        assert!(explained.contains("  unexpected payload: aa\n"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Validation of the payload following a [`Response`] header.
+//!
+//! [`Response::read`] only decodes the header and leaves the payload uninterpreted. With
+//! [`DecodeOptions::strict`], [`Response::read_with_options`] also verifies that the payload
+//! consists of complete elements of the declared [`Format`].
+
+use crate::batch::BatchIter;
+use crate::frame::ElementIter;
+use crate::{Error, Format, Response, Type};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, core::default::Default)]
+pub struct DecodeOptions {
+    /// Whether the payload has to be consistent with the declared format
+    pub strict: bool,
+}
+
+impl DecodeOptions {
+    pub const fn strict() -> Self {
+        Self { strict: true }
+    }
+
+    pub const fn lenient() -> Self {
+        Self { strict: false }
+    }
+}
+
+impl Response {
+    /// Reads the response header from the datagram and returns the payload that follows it. In
+    /// strict mode, the payload is validated with [`validate_payload`].
+    pub fn read_with_options(
+        datagram: &[u8],
+        options: DecodeOptions,
+    ) -> Result<(Response, &[u8]), Error> {
+        let mut payload = datagram;
+        let response = Response::read(&mut payload)?;
+        if options.strict {
+            validate_payload(&response, payload)?;
+        }
+        Ok((response, payload))
+    }
+}
+
+/// Verifies that the payload consists of complete elements of the format of the response, as
+/// decoded by [`ElementIter`]. Fails with [`Error::TrailingBytes`] if bytes follow the header of
+/// a response without payload or the announced number of elements and with
+/// [`Error::PayloadLengthMismatch`] if the last element is incomplete or malformed. The
+/// elements of `Type::DynList*` values are not validated.
+pub fn validate_payload(response: &Response, payload: &[u8]) -> Result<(), Error> {
+    let format = match response.format() {
+        Some(format) => format,
+        _ if payload.is_empty() => return Ok(()),
+        _ => return Err(Error::TrailingBytes),
+    };
+
+    match format {
+        Format::ValueOnly(Type::Batch) | Format::Echoed(Type::Batch) => {
+            return if BatchIter::new(payload).all(|entry| entry.is_ok()) {
+                Ok(())
+            } else {
+                Err(Error::PayloadLengthMismatch)
+            };
+        }
+        Format::ValueOnly(ty) | Format::Echoed(ty) if is_dyn_list(ty) => return Ok(()),
+        _ => {}
+    }
+
+    let mut elements = ElementIter::new(format, payload);
+    for element in &mut elements {
+        match element {
+            Ok(_) => {}
+            Err(Error::TrailingBytes) => return Err(Error::TrailingBytes),
+            Err(_) => return Err(Error::PayloadLengthMismatch),
+        }
+    }
+    if elements.remaining().is_empty() {
+        Ok(())
+    } else {
+        Err(Error::TrailingBytes)
+    }
+}
+
+/// Whether the values of the type are lists with their own decoders
+fn is_dyn_list(ty: Type) -> bool {
+    matches!(
+        ty,
+        Type::DynListPropertyReportV1
+            | Type::DynListPropertyReportV2
+            | Type::DynListBusInformation
+            | Type::DynListErrorDumpEntry
+            | Type::DynListLogRecord
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strict_decoding_validates_the_payload() {
+        let f32_pairs = [0x00, 0x01, 0x02, 0x01, 0x08, 0x00];
+        let datagram = |payload: &[u8]| {
+            let mut datagram = [0u8; 32];
+            datagram[..f32_pairs.len()].copy_from_slice(&f32_pairs);
+            datagram[f32_pairs.len()..][..payload.len()].copy_from_slice(payload);
+            (datagram, f32_pairs.len() + payload.len())
+        };
+
+        let (complete, len) = datagram(&[1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0]);
+        let (_, payload) =
+            Response::read_with_options(&complete[..len], DecodeOptions::strict()).unwrap();
+        assert_eq!(12, payload.len());
+
+        let (incomplete, len) = datagram(&[1, 2, 3, 4, 5, 6, 7, 8, 0, 0]);
+        assert_eq!(
+            Err(Error::PayloadLengthMismatch),
+            Response::read_with_options(&incomplete[..len], DecodeOptions::strict())
+        );
+        assert!(Response::read_with_options(&incomplete[..len], DecodeOptions::lenient()).is_ok());
+
+        assert_eq!(
+            Err(Error::TrailingBytes),
+            Response::read_with_options(&[0xF1, 0x01, 0x00], DecodeOptions::strict())
+        );
+        assert_eq!(
+            Err(Error::PayloadLengthMismatch),
+            validate_payload(
+                &Response::Ok(1, Format::ValueOnly(Type::DynString)),
+                &[3, b'a']
+            )
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! Generates a Wireshark dissector (Lua) for the protocol.
+//!
+//! The opcodes are not listed here but taken from [`RequestOpcode::ALL`] and probed from the
+//! decoders of this crate, so that a dissector generated by
+//! `cargo run --features dissector -- [port]` always matches the protocol implemented by this
+//! version of the crate.
+
+use crate::{ErrorCode, Format, RequestOpcode, Response, Type};
+use std::fmt::Write;
+
+/// The names of the opcodes known to the decoders of this crate
+#[derive(Debug, Clone, PartialEq)]
+pub struct Opcodes {
+    pub requests: Vec<(u8, String)>,
+    pub responses: Vec<(u8, String)>,
+    /// The format opcodes and the number of types following them
+    pub formats: Vec<(u8, String, usize)>,
+    /// The type opcodes and the number of parameter bytes following them, `None` for
+    /// [`Type::ListOf`], which is followed by the element type
+    pub types: Vec<(u8, String, Option<usize>)>,
+    pub error_codes: Vec<(u8, String)>,
+}
+
+impl Opcodes {
+    pub fn probe() -> Self {
+        Self {
+            requests: RequestOpcode::ALL
+                .iter()
+                .map(|opcode| (u8::from(*opcode), opcode.name().to_string()))
+                .collect(),
+            responses: probe(|reader| Response::read(reader).map(|r| variant(&r))),
+            formats: (0..=u8::MAX)
+                .filter_map(|opcode| {
+                    // a type without parameters, so that each type is a single byte
+                    let ty = Type::U8.encode().0[0];
+                    let probe = [opcode, ty, ty, ty];
+                    let mut reader = &probe[..];
+                    let format = Format::read(&mut reader).ok()?;
+                    Some((opcode, variant(&format), probe.len() - 1 - reader.len()))
+                })
+                .collect(),
+            types: (0..=u8::MAX)
+                .filter_map(|opcode| {
+                    let probe = [opcode, 0, 0, 0, 0, 0, 0, 0];
+                    let ty = Type::read(&mut &probe[..]).ok()?;
+                    let parameters = match ty {
+                        Type::ListOf(_) => None,
+                        ty => Some(ty.encode().1 - 1),
+                    };
+                    Some((opcode, variant(&ty), parameters))
+                })
+                .collect(),
+            error_codes: probe(|reader| match ErrorCode::read(reader)? {
+                ErrorCode::Other(_) => Err(crate::Error::UnknownTypeIdentifier),
+                code => Ok(variant(&code)),
+            }),
+        }
+    }
+}
+
+/// Tries to decode each opcode followed by zeros
+fn probe(decode: impl Fn(&mut &[u8]) -> Result<String, crate::Error>) -> Vec<(u8, String)> {
+    (0..=u8::MAX)
+        .filter_map(|opcode| {
+            let mut probe = [0u8; 32];
+            probe[0] = opcode;
+            decode(&mut &probe[..]).ok().map(|name| (opcode, name))
+        })
+        .collect()
+}
+
+/// The name of the enum variant
+fn variant(value: &impl core::fmt::Debug) -> String {
+    let debug = format!("{:?}", value);
+    debug
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// The Lua source of a Wireshark dissector for datagrams on the given UDP port
+pub fn lua_dissector(port: u16) -> String {
+    let opcodes = Opcodes::probe();
+    let mut lua = String::new();
+
+    let _ = writeln!(
+        lua,
+        "-- generated by sensor_common {}, do not edit",
+        env!("CARGO_PKG_VERSION")
+    );
+    lua.push_str("local proto = Proto(\"sensor_common\", \"sensor_common\")\n\n");
+
+    table(&mut lua, "request_opcodes", &opcodes.requests);
+    table(&mut lua, "response_opcodes", &opcodes.responses);
+    table(
+        &mut lua,
+        "formats",
+        &opcodes
+            .formats
+            .iter()
+            .map(|(opcode, name, _)| (*opcode, name.clone()))
+            .collect::<Vec<_>>(),
+    );
+    table(
+        &mut lua,
+        "types",
+        &opcodes
+            .types
+            .iter()
+            .map(|(opcode, name, _)| (*opcode, name.clone()))
+            .collect::<Vec<_>>(),
+    );
+    table(&mut lua, "error_codes", &opcodes.error_codes);
+
+    lua.push_str("local format_types = {\n");
+    for (opcode, _, types) in &opcodes.formats {
+        let _ = writeln!(lua, "    [0x{:02X}] = {},", opcode, types);
+    }
+    lua.push_str("}\n\n");
+
+    lua.push_str("local type_parameters = {\n");
+    for (opcode, _, parameters) in &opcodes.types {
+        match parameters {
+            Some(parameters) => {
+                let _ = writeln!(lua, "    [0x{:02X}] = {},", opcode, parameters);
+            }
+            None => {
+                let _ = writeln!(lua, "    [0x{:02X}] = -1,", opcode);
+            }
+        }
+    }
+    lua.push_str("}\n\n");
+
+    let _ = writeln!(lua, "local proto_port = {}", port);
+    let _ = writeln!(
+        lua,
+        "local ROUTING_HEADER_MARKER = 0x{:02X}",
+        crate::routing::ROUTING_HEADER_MARKER
+    );
+    let _ = writeln!(
+        lua,
+        "local SEQUENCE_HEADER_MARKER = 0x{:02X}",
+        crate::replay::SEQUENCE_HEADER_MARKER
+    );
+    let _ = writeln!(
+        lua,
+        "local SESSION_HEADER_MARKER = 0x{:02X}",
+        crate::pairing::SESSION_HEADER_MARKER
+    );
+    let _ = writeln!(
+        lua,
+        "local SCHEDULE_HEADER_MARKER = 0x{:02X}",
+        crate::schedule::SCHEDULE_HEADER_MARKER
+    );
+    let _ = writeln!(
+        lua,
+        "local ACCEPT_COMPRESSION_MARKER = 0x{:02X}",
+        crate::compression::ACCEPT_COMPRESSION_MARKER
+    );
+    let _ = writeln!(
+        lua,
+        "local PRIORITY_HEADER_MARKER = 0x{:02X}",
+        crate::priority::PRIORITY_HEADER_MARKER
+    );
+    let _ = writeln!(
+        lua,
+        "local COMPRESSED_RESPONSE_MARKER = 0x{:02X}",
+        crate::compression::COMPRESSED_RESPONSE_MARKER
+    );
+    lua.push_str(LUA_DISSECTOR);
+    lua.push_str("DissectorTable.get(\"udp.port\"):add(proto_port, proto)\n");
+    let _ = writeln!(
+        lua,
+        "DissectorTable.get(\"udp.port\"):add({}, proto)",
+        crate::announcement::PORT
+    );
+    lua
+}
+
+fn table(lua: &mut String, name: &str, entries: &[(u8, String)]) {
+    let _ = writeln!(lua, "local {} = {{", name);
+    for (opcode, entry) in entries {
+        let _ = writeln!(lua, "    [0x{:02X}] = \"{}\",", opcode, entry);
+    }
+    lua.push_str("}\n\n");
+}
+
+const LUA_DISSECTOR: &str = r#"
+local f_sequence = ProtoField.uint32("sensor_common.sequence", "Sequence")
+local f_routing = ProtoField.bytes("sensor_common.routing", "Routing")
+local f_session = ProtoField.bytes("sensor_common.session", "Session")
+local f_schedule = ProtoField.bytes("sensor_common.schedule", "Schedule")
+local f_accept_compression = ProtoField.uint8("sensor_common.accept_compression", "Accept Compression", base.HEX)
+local f_compressed = ProtoField.uint8("sensor_common.compressed", "Compression Algorithm", base.HEX)
+local f_uncompressed_len = ProtoField.uint16("sensor_common.uncompressed_len", "Uncompressed Length")
+local f_priority = ProtoField.uint8("sensor_common.priority", "Priority", base.DEC, nil, 0x0F)
+local f_request = ProtoField.uint8("sensor_common.request", "Request", base.HEX, request_opcodes)
+local f_response = ProtoField.uint8("sensor_common.response", "Response", base.HEX, response_opcodes)
+local f_id = ProtoField.uint8("sensor_common.id", "Id")
+local f_format = ProtoField.uint8("sensor_common.format", "Format", base.HEX, formats)
+local f_type = ProtoField.uint8("sensor_common.type", "Type", base.HEX, types)
+local f_error = ProtoField.uint8("sensor_common.error", "Error", base.HEX, error_codes)
+local f_payload = ProtoField.bytes("sensor_common.payload", "Payload")
+
+proto.fields = {
+    f_routing, f_sequence, f_session, f_schedule, f_accept_compression, f_compressed,
+    f_uncompressed_len, f_priority, f_request, f_response, f_id, f_format, f_type, f_error,
+    f_payload
+}
+
+local function dissect_type(buffer, offset, tree)
+    local opcode = buffer(offset, 1):uint()
+    tree:add(f_type, buffer(offset, 1))
+    local parameters = type_parameters[opcode] or 0
+    if parameters < 0 then
+        return dissect_type(buffer, offset + 1, tree)
+    end
+    return offset + 1 + parameters
+end
+
+function proto.dissector(buffer, pinfo, tree)
+    if buffer:len() < 2 then
+        return 0
+    end
+    pinfo.cols.protocol = "SENSOR"
+    local subtree = tree:add(proto, buffer())
+    local offset = 0
+    local opcode
+
+    if buffer(0, 1):uint() == ROUTING_HEADER_MARKER then
+        local hops = buffer(1, 1):uint()
+        if hops > 0 then
+            subtree:add(f_routing, buffer(2, hops))
+        end
+        offset = 2 + hops
+    end
+    if pinfo.dst_port == proto_port then
+        if buffer(offset, 1):uint() == SEQUENCE_HEADER_MARKER then
+            subtree:add(f_sequence, buffer(offset + 1, 4))
+            offset = offset + 5
+        end
+        if buffer(offset, 1):uint() == SESSION_HEADER_MARKER then
+            subtree:add(f_session, buffer(offset + 1, 8))
+            offset = offset + 9
+        end
+        if buffer(offset, 1):uint() == SCHEDULE_HEADER_MARKER then
+            subtree:add(f_schedule, buffer(offset + 1, 5))
+            offset = offset + 6
+        end
+        if buffer(offset, 1):uint() == ACCEPT_COMPRESSION_MARKER then
+            subtree:add(f_accept_compression, buffer(offset + 1, 1))
+            offset = offset + 2
+        end
+        if buffer(offset, 1):uint() == PRIORITY_HEADER_MARKER then
+            subtree:add(f_priority, buffer(offset + 1, 1))
+            offset = offset + 2
+        end
+        opcode = buffer(offset, 1):uint()
+        subtree:add(f_request, buffer(offset, 1))
+        subtree:add(f_id, buffer(offset + 1, 1))
+        pinfo.cols.info = "Request " .. (request_opcodes[opcode] or "Unknown")
+        offset = offset + 2
+    elseif buffer(offset, 1):uint() == COMPRESSED_RESPONSE_MARKER then
+        subtree:add(f_compressed, buffer(offset + 1, 1))
+        subtree:add(f_uncompressed_len, buffer(offset + 2, 2))
+        pinfo.cols.info = "Compressed Response"
+        offset = offset + 4
+    else
+        opcode = buffer(offset, 1):uint()
+        subtree:add(f_response, buffer(offset, 1))
+        subtree:add(f_id, buffer(offset + 1, 1))
+        pinfo.cols.info = "Response " .. (response_opcodes[opcode] or "Unknown")
+        offset = offset + 2
+        if response_opcodes[opcode] == "Error" then
+            subtree:add(f_error, buffer(offset, 1))
+            offset = offset + 1
+        elseif response_opcodes[opcode] == "Ok" or response_opcodes[opcode] == "Announcement" then
+            local format = buffer(offset, 1):uint()
+            subtree:add(f_format, buffer(offset, 1))
+            offset = offset + 1
+            for _ = 1, (format_types[format] or 0) do
+                offset = dissect_type(buffer, offset, subtree)
+            end
+        end
+    end
+
+    if offset < buffer:len() then
+        subtree:add(f_payload, buffer(offset))
+    end
+    return buffer:len()
+end
+
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_every_request_by_the_name_of_its_variant() {
+        let opcodes = Opcodes::probe();
+        assert_eq!(RequestOpcode::ALL.len(), opcodes.requests.len());
+        for (request, encoded) in crate::test_vectors::REQUESTS {
+            assert!(opcodes.requests.contains(&(encoded[0], variant(request))));
+        }
+    }
+
+    #[test]
+    fn counts_the_types_of_each_format() {
+        let opcodes = Opcodes::probe();
+        for (format, encoded) in crate::test_vectors::FORMATS {
+            let types = opcodes
+                .formats
+                .iter()
+                .find(|(opcode, _, _)| *opcode == encoded[0])
+                .map(|(_, _, types)| *types);
+            let expected = match format {
+                Format::Empty => 0,
+                Format::AddressValueTimestampTriples(..) => 3,
+                Format::AddressValuePairs(..)
+                | Format::CountedAddressValuePairs(..)
+                | Format::AddressStatusValue(..) => 2,
+                _ => 1,
+            };
+            assert_eq!(Some(expected), types, "{:?}", format);
+        }
+    }
+}
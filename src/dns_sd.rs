@@ -0,0 +1,20 @@
+//! The DNS-SD service devices advertise themselves with over mDNS.
+//!
+//! A device registers an instance of [`SERVICE_TYPE`] on the port it serves requests on, with
+//! a TXT record carrying its firmware version, name and location under [`TXT_VERSION`],
+//! [`TXT_NAME`] and [`TXT_LOCATION`].
+//! Clients browse for the service on networks that filter the broadcasts of
+//! `Request::DiscoverAll`.
+
+/// The service type devices register
+pub const SERVICE_TYPE: &str = "_sensor._udp.local";
+
+/// The TXT record key of the firmware version, for example `1.4.2`
+pub const TXT_VERSION: &str = "version";
+
+/// The TXT record key of the device name, see [`crate::props::well_known::DEVICE_NAME`]
+pub const TXT_NAME: &str = "name";
+
+/// The TXT record key of the device location, see
+/// [`crate::props::well_known::DEVICE_LOCATION`]
+pub const TXT_LOCATION: &str = "location";
@@ -0,0 +1,110 @@
+//! Raw access to the EEPROM of a device, for provisioning and recovery.
+//!
+//! `Request::ReadEeprom(id, offset, len)` is answered with
+//! `Response::Ok(id, Format::ValueOnly(Type::DynBytes))` followed by the bytes read.
+//! `Request::WriteEeprom(id, offset)` writes the payload and is answered with
+//! `Response::Ok(id, Format::Empty)`. Regions outside of what the device exposes are rejected
+//! with `ErrorCode::ValueOutOfRange`, failing accesses with `Response::NotAvailable`.
+
+use crate::{Error, ErrorCode, Format, Request, Response, Type, Write};
+use core::ops::Range;
+
+/// The EEPROM of the device, accessed through the [`EepromResponder`]
+pub trait Eeprom {
+    fn read(&mut self, offset: u16, buffer: &mut [u8]) -> Result<(), Error>;
+
+    fn write(&mut self, offset: u16, data: &[u8]) -> Result<(), Error>;
+}
+
+/// Whether `len` bytes starting at `offset` lie within the given region
+pub fn validate_bounds(offset: u16, len: usize, region: &Range<u16>) -> Result<(), ErrorCode> {
+    let start = usize::from(offset);
+    if start >= usize::from(region.start) && start + len <= usize::from(region.end) {
+        Ok(())
+    } else {
+        Err(ErrorCode::ValueOutOfRange)
+    }
+}
+
+pub struct EepromResponder<'a> {
+    pub request_id: u8,
+    pub offset: u16,
+    /// The number of bytes to read, `None` for a `Request::WriteEeprom`
+    pub len: Option<u8>,
+    pub payload: &'a [u8],
+}
+
+impl<'a> EepromResponder<'a> {
+    pub fn opt_from(request: &Request, payload: &'a [u8]) -> Option<Self> {
+        match *request {
+            Request::ReadEeprom(id, offset, len) => Some(Self {
+                request_id: id,
+                offset,
+                len: Some(len),
+                payload,
+            }),
+            Request::WriteEeprom(id, offset) => Some(Self {
+                request_id: id,
+                offset,
+                len: None,
+                payload,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Performs the access if it lies within the readable or writable region and writes the
+    /// response
+    pub fn write(
+        self,
+        response_writer: &mut impl Write,
+        eeprom: &mut impl Eeprom,
+        readable: Range<u16>,
+        writable: Range<u16>,
+    ) -> Result<usize, Error> {
+        let id = self.request_id;
+        match self.len {
+            Some(len) => {
+                let mut buffer = [0u8; u8::MAX as usize];
+                let buffer = &mut buffer[..usize::from(len)];
+                if let Err(code) = validate_bounds(self.offset, buffer.len(), &readable) {
+                    Response::Error(id, code).write(response_writer)
+                } else if eeprom.read(self.offset, buffer).is_err() {
+                    Response::NotAvailable(id).write(response_writer)
+                } else {
+                    Ok(Response::Ok(id, Format::ValueOnly(Type::DynBytes))
+                        .write(response_writer)?
+                        + response_writer.write_dyn_bytes(buffer)?)
+                }
+            }
+            None => {
+                if let Err(code) = validate_bounds(self.offset, self.payload.len(), &writable) {
+                    Response::Error(id, code).write(response_writer)
+                } else if eeprom.write(self.offset, self.payload).is_err() {
+                    Response::NotAvailable(id).write(response_writer)
+                } else {
+                    Response::Ok(id, Format::Empty).write(response_writer)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_are_validated() {
+        assert_eq!(Ok(()), validate_bounds(0x10, 0x10, &(0x10..0x20)));
+        assert_eq!(Ok(()), validate_bounds(0x20, 0, &(0x10..0x20)));
+        assert_eq!(
+            Err(ErrorCode::ValueOutOfRange),
+            validate_bounds(0x0F, 1, &(0x10..0x20))
+        );
+        assert_eq!(
+            Err(ErrorCode::ValueOutOfRange),
+            validate_bounds(0xFFFF, 2, &(0..u16::MAX))
+        );
+    }
+}
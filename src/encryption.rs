@@ -0,0 +1,124 @@
+//! Authenticated encryption of datagrams with a pre-shared key.
+//!
+//! For devices reachable across routed networks, every datagram (request or response) can be
+//! sealed with ChaCha20-Poly1305 and the 32 byte key both sides have been provisioned with. An
+//! encrypted datagram consists of the 12 byte nonce, the encrypted datagram and the 16 byte
+//! authentication tag. Each nonce must only be used once per key, see [`NonceSequence`].
+//!
+//! This is not DTLS: there is no handshake and no forward secrecy, a leaked key exposes all
+//! recorded traffic. Replayed datagrams are authenticated as well, combine this with
+//! [`crate::replay::SequenceHeader`] if that matters.
+//!
+//! The `encryption` feature only provides [`seal`] and [`open`] and is `no_std`. The
+//! `client-encryption` feature adds the pre-shared key to the client, which seeds its nonces
+//! from the random number generator of the operating system.
+
+use crate::Error;
+use chacha20poly1305::aead::AeadInPlace;
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce, Tag};
+
+pub const NONCE_LEN: usize = 12;
+pub const TAG_LEN: usize = 16;
+
+/// The number of bytes an encrypted datagram is longer than the plaintext
+pub const OVERHEAD: usize = NONCE_LEN + TAG_LEN;
+
+/// Unique nonces made of a fixed prefix and a counter. The prefix and the initial counter must
+/// not repeat across senders or restarts, so choose them at random or derive them from a unique
+/// id and a persisted boot counter.
+#[derive(Debug, Clone)]
+pub struct NonceSequence {
+    prefix: [u8; 4],
+    counter: u64,
+}
+
+impl NonceSequence {
+    pub const fn new(prefix: [u8; 4], counter: u64) -> Self {
+        Self { prefix, counter }
+    }
+
+    pub fn next_nonce(&mut self) -> [u8; NONCE_LEN] {
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce[..4].copy_from_slice(&self.prefix);
+        nonce[4..].copy_from_slice(&self.counter.to_be_bytes());
+        self.counter = self.counter.wrapping_add(1);
+        nonce
+    }
+}
+
+/// Encrypts the first `len` bytes of the buffer in place and frames them with the nonce and
+/// the tag, returns the length of the encrypted datagram
+pub fn seal(
+    key: &[u8; 32],
+    nonce: [u8; NONCE_LEN],
+    buffer: &mut [u8],
+    len: usize,
+) -> Result<usize, Error> {
+    let sealed_len = len + OVERHEAD;
+    if buffer.len() < sealed_len {
+        return Err(Error::BufferToSmall);
+    }
+    buffer.copy_within(..len, NONCE_LEN);
+    buffer[..NONCE_LEN].copy_from_slice(&nonce);
+
+    let tag = ChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt_in_place_detached(
+            Nonce::from_slice(&nonce),
+            &[],
+            &mut buffer[NONCE_LEN..][..len],
+        )
+        .map_err(|_| Error::BufferToSmall)?;
+    buffer[NONCE_LEN + len..sealed_len].copy_from_slice(&tag);
+    Ok(sealed_len)
+}
+
+/// Decrypts the encrypted datagram in place, returns the plaintext datagram. Datagrams that
+/// have not been sealed with the key or that have been tampered with are rejected with
+/// `Error::InvalidChecksum`.
+pub fn open<'a>(key: &[u8; 32], datagram: &'a mut [u8]) -> Result<&'a mut [u8], Error> {
+    if datagram.len() < OVERHEAD {
+        return Err(Error::UnexpectedEOF);
+    }
+    let (nonce, rest) = datagram.split_at_mut(NONCE_LEN);
+    let (plaintext, tag) = rest.split_at_mut(rest.len() - TAG_LEN);
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt_in_place_detached(
+            Nonce::from_slice(nonce),
+            &[],
+            plaintext,
+            Tag::from_slice(tag),
+        )
+        .map_err(|_| Error::InvalidChecksum)?;
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_datagram_is_authenticated() {
+        let key = [0x42; 32];
+        let mut nonces = NonceSequence::new([1, 2, 3, 4], 5);
+        let mut buffer = [0u8; 64];
+        buffer[..3].copy_from_slice(&[0xE0, 7, 0x00]);
+
+        let len = seal(&key, nonces.next_nonce(), &mut buffer, 3).unwrap();
+        assert_eq!(3 + OVERHEAD, len);
+        assert_eq!(&[1, 2, 3, 4, 0, 0, 0, 0, 0, 0, 0, 5], &buffer[..NONCE_LEN]);
+        assert_ne!(&[0xE0, 7, 0x00], &buffer[NONCE_LEN..][..3]);
+
+        let mut tampered = buffer;
+        tampered[NONCE_LEN] ^= 0x01;
+        assert_eq!(
+            Err(Error::InvalidChecksum),
+            open(&key, &mut tampered[..len]).map(|_| ())
+        );
+        let mut wrong_key = buffer;
+        assert_eq!(
+            Err(Error::InvalidChecksum),
+            open(&[0x43; 32], &mut wrong_key[..len]).map(|_| ())
+        );
+        assert_eq!(&[0xE0, 7, 0x00], open(&key, &mut buffer[..len]).unwrap());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{Error, Format, Read, Response, Type, Write};
+
+/// A single entry of the answer to a [`crate::Request::RetrieveErrorDump`], which is sent as
+/// `Response::Ok(id, Format::ValueOnly(Type::DynListErrorDumpEntry))` followed by any number
+/// of entries.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ErrorDumpEntry<'a> {
+    /// The uptime in milliseconds at which the error occurred
+    pub timestamp: u64,
+    pub code: u16,
+    /// Additional, error specific information, at most 255 bytes
+    pub context: &'a [u8],
+}
+
+impl<'a> ErrorDumpEntry<'a> {
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        Ok(writer.write_u64_be(self.timestamp)?
+            + writer.write_u16_be(self.code)?
+            + writer.write_dyn_bytes(self.context)?)
+    }
+
+    /// Reads the next entry from the slice, which is advanced accordingly
+    pub fn read(reader: &mut &'a [u8]) -> Result<Self, Error> {
+        let timestamp = reader.read_u64_be()?;
+        let code = reader.read_u16_be()?;
+
+        let context_len = usize::from(reader.read_u8()?);
+        if reader.len() < context_len {
+            return Err(Error::UnexpectedEOF);
+        }
+        let (context, remaining) = reader.split_at(context_len);
+        *reader = remaining;
+
+        Ok(ErrorDumpEntry {
+            timestamp,
+            code,
+            context,
+        })
+    }
+}
+
+/// Writes the response to a [`crate::Request::RetrieveErrorDumpRange`] with at most `limit`
+/// of the entries, oldest first, starting at the given offset
+pub fn write_range<'e>(
+    response_writer: &mut impl Write,
+    request_id: u8,
+    entries: impl IntoIterator<Item = ErrorDumpEntry<'e>>,
+    offset: u16,
+    limit: u8,
+) -> Result<usize, Error> {
+    let mut len = Response::Ok(request_id, Format::ValueOnly(Type::DynListErrorDumpEntry))
+        .write(response_writer)?;
+    for entry in entries
+        .into_iter()
+        .skip(usize::from(offset))
+        .take(usize::from(limit))
+    {
+        len += entry.write(response_writer)?;
+    }
+    Ok(len)
+}
+
+/// Decodes [`ErrorDumpEntry`]s from the payload of an error dump response until the payload is
+/// exhausted. Stops after the first malformed entry.
+pub struct ErrorDumpIter<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> ErrorDumpIter<'a> {
+    pub const fn new(payload: &'a [u8]) -> Self {
+        Self { remaining: payload }
+    }
+}
+
+impl<'a> Iterator for ErrorDumpIter<'a> {
+    type Item = Result<ErrorDumpEntry<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            None
+        } else {
+            let result = ErrorDumpEntry::read(&mut self.remaining);
+            if result.is_err() {
+                self.remaining = &[];
+            }
+            Some(result)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+
+    #[test]
+    fn range_of_entries_is_written() {
+        let entries = (0..5).map(|code| ErrorDumpEntry {
+            timestamp: u64::from(code) * 1_000,
+            code,
+            context: &[],
+        });
+        let mut buffer = [0u8; 64];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        write_range(&mut cursor, 7, entries, 3, 10).unwrap();
+
+        let (header, payload) = cursor.written().split_at(4);
+        assert_eq!(&[0x00, 7, 0x00, 0xC3], header);
+        let mut entries = ErrorDumpIter::new(payload);
+        assert_eq!(Some(3), entries.next().map(|entry| entry.unwrap().code));
+        assert_eq!(Some(4), entries.next().map(|entry| entry.unwrap().code));
+        assert!(entries.next().is_none());
+    }
+}
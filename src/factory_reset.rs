@@ -0,0 +1,92 @@
+//! Confirmation of factory resets.
+//!
+//! A `Request::FactoryReset(id, token)` erases all settings of the device, so a single stray or
+//! replayed datagram must not trigger it. The token is derived from the CPU id and the uptime
+//! of the device, the client computes it from the [`DeviceInformation`] of a preceding
+//! `Request::RetrieveDeviceInformation`. It stays valid until the end of the window after the
+//! one it has been derived in, see [`TOKEN_WINDOW_MILLIS`]. The device acknowledges the request
+//! with `Response::Ok(id, Format::Empty)` before resetting, a wrong token is answered with
+//! `Response::Error(id, ErrorCode::PermissionDenied)`.
+
+use crate::firmware::Crc32;
+use crate::info::DeviceInformation;
+use crate::{Error, ErrorCode, Format, Request, Response, Write};
+
+/// The duration in milliseconds of the windows in which a token stays the same
+pub const TOKEN_WINDOW_MILLIS: u64 = 60_000;
+
+/// The token the device expects at the given uptime
+pub fn token(cpu_id: u32, uptime_millis: u64) -> [u8; 4] {
+    window_token(cpu_id, uptime_millis / TOKEN_WINDOW_MILLIS)
+}
+
+fn window_token(cpu_id: u32, window: u64) -> [u8; 4] {
+    let mut crc = Crc32::new();
+    crc.update(&cpu_id.to_be_bytes());
+    crc.update(&window.to_be_bytes());
+    crc.finish().to_be_bytes()
+}
+
+impl DeviceInformation {
+    /// The token to confirm a `Request::FactoryReset` with, see [`crate::factory_reset`]
+    pub fn factory_reset_token(&self) -> [u8; 4] {
+        token(self.cpu_id, self.uptime_millis)
+    }
+}
+
+pub struct FactoryResetResponder {
+    pub request_id: u8,
+    pub token: [u8; 4],
+}
+
+impl FactoryResetResponder {
+    pub fn opt_from(request: &Request) -> Option<Self> {
+        match *request {
+            Request::FactoryReset(id, token) => Some(Self {
+                request_id: id,
+                token,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether the token is the one of the current or of the previous window
+    pub fn is_confirmed(&self, cpu_id: u32, uptime_millis: u64) -> bool {
+        let window = uptime_millis / TOKEN_WINDOW_MILLIS;
+        self.token == window_token(cpu_id, window)
+            || (window > 0 && self.token == window_token(cpu_id, window - 1))
+    }
+
+    /// Writes the acknowledgement if the token is confirmed, the firmware has to perform the
+    /// reset after sending it
+    pub fn write(
+        &self,
+        response_writer: &mut impl Write,
+        cpu_id: u32,
+        uptime_millis: u64,
+    ) -> Result<usize, Error> {
+        if self.is_confirmed(cpu_id, uptime_millis) {
+            Response::Ok(self.request_id, Format::Empty).write(response_writer)
+        } else {
+            Response::Error(self.request_id, ErrorCode::PermissionDenied).write(response_writer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_expires_after_the_next_window() {
+        let cpu_id = 0x410F_C241;
+        let responder = FactoryResetResponder {
+            request_id: 1,
+            token: token(cpu_id, 59_000),
+        };
+        assert!(responder.is_confirmed(cpu_id, 59_000));
+        assert!(responder.is_confirmed(cpu_id, 119_999));
+        assert!(!responder.is_confirmed(cpu_id, 120_000));
+        assert!(!responder.is_confirmed(cpu_id + 1, 59_000));
+    }
+}
@@ -0,0 +1,426 @@
+//! Chunked firmware transfer.
+//!
+//! 1. The client sends `Request::BeginFirmwareUpgrade(id, size, crc)`. The device answers with
+//!    `Response::Ok(id, Format::ValueOnly(Type::U32))` followed by the offset it expects the next
+//!    chunk at. Repeating the request for the same image does not restart the transfer but
+//!    reports the current offset, which allows to resume an interrupted upgrade.
+//! 2. The client sends `Request::FirmwareChunk(id, offset)` followed by the chunk data, the
+//!    device answers with the next expected offset the same way. A chunk resent because its
+//!    response got lost is acknowledged with the next expected offset again, without storing it
+//!    a second time.
+//! 3. Once all bytes are transferred, the client sends `Request::CommitFirmware(id)`. The device
+//!    verifies the CRC-32 of the image and answers with `Response::Ok(id, Format::Empty)`.
+//!
+//! Any request that cannot be applied is answered with `Response::NotAvailable(id)`.
+
+use crate::{Error, Format, Request, Response, Type, Write};
+use core::convert::TryFrom;
+
+/// The device side storage of a firmware image, for example the inactive flash bank.
+pub trait FirmwareStorage {
+    /// Prepares the storage to receive an image of the given size, for example by erasing it
+    fn prepare(&mut self, size: u32) -> Result<(), Error>;
+
+    fn write(&mut self, offset: u32, data: &[u8]) -> Result<(), Error>;
+
+    /// Called once the complete image has been received and verified
+    fn commit(&mut self, size: u32) -> Result<(), Error>;
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FirmwareError {
+    NotStarted,
+    UnexpectedOffset { expected: u32 },
+    SizeExceeded,
+    Incomplete,
+    CrcMismatch,
+    Storage(Error),
+}
+
+impl From<Error> for FirmwareError {
+    fn from(e: Error) -> Self {
+        FirmwareError::Storage(e)
+    }
+}
+
+/// The resumable state machine of the device side of a firmware upgrade. It does not
+/// allocate and can therefore be kept in a `static`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FirmwareUpgrade {
+    Idle,
+    Receiving {
+        size: u32,
+        crc: u32,
+        offset: u32,
+        checksum: Crc32,
+    },
+    Committed {
+        size: u32,
+        crc: u32,
+    },
+}
+
+impl Default for FirmwareUpgrade {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FirmwareUpgrade {
+    pub const fn new() -> Self {
+        FirmwareUpgrade::Idle
+    }
+
+    /// Starts a new upgrade or resumes the current one if it is for the same image.
+    /// Returns the offset of the next expected chunk.
+    pub fn begin(
+        &mut self,
+        size: u32,
+        crc: u32,
+        storage: &mut impl FirmwareStorage,
+    ) -> Result<u32, FirmwareError> {
+        match *self {
+            FirmwareUpgrade::Receiving {
+                size: current_size,
+                crc: current_crc,
+                offset,
+                ..
+            } if current_size == size && current_crc == crc => Ok(offset),
+            _ => {
+                *self = FirmwareUpgrade::Idle;
+                storage.prepare(size)?;
+                *self = FirmwareUpgrade::Receiving {
+                    size,
+                    crc,
+                    offset: 0,
+                    checksum: Crc32::new(),
+                };
+                Ok(0)
+            }
+        }
+    }
+
+    /// Stores the given chunk and returns the offset of the next expected chunk. A chunk before
+    /// the expected offset has been stored already and is only acknowledged.
+    pub fn chunk(
+        &mut self,
+        offset: u32,
+        data: &[u8],
+        storage: &mut impl FirmwareStorage,
+    ) -> Result<u32, FirmwareError> {
+        match self {
+            FirmwareUpgrade::Receiving {
+                size,
+                offset: expected,
+                checksum,
+                ..
+            } => {
+                if offset < *expected {
+                    return Ok(*expected);
+                } else if offset != *expected {
+                    return Err(FirmwareError::UnexpectedOffset {
+                        expected: *expected,
+                    });
+                }
+                let end = u32::try_from(data.len())
+                    .ok()
+                    .and_then(|len| offset.checked_add(len))
+                    .filter(|end| *end <= *size)
+                    .ok_or(FirmwareError::SizeExceeded)?;
+
+                storage.write(offset, data)?;
+                checksum.update(data);
+                *expected = end;
+                Ok(end)
+            }
+            _ => Err(FirmwareError::NotStarted),
+        }
+    }
+
+    /// Verifies the received image and commits it to the storage.
+    pub fn commit(&mut self, storage: &mut impl FirmwareStorage) -> Result<(), FirmwareError> {
+        match *self {
+            FirmwareUpgrade::Receiving {
+                size,
+                crc,
+                offset,
+                checksum,
+            } => {
+                if offset != size {
+                    Err(FirmwareError::Incomplete)
+                } else if checksum.finish() != crc {
+                    *self = FirmwareUpgrade::Idle;
+                    Err(FirmwareError::CrcMismatch)
+                } else {
+                    storage.commit(size)?;
+                    *self = FirmwareUpgrade::Committed { size, crc };
+                    Ok(())
+                }
+            }
+            FirmwareUpgrade::Committed { .. } => Ok(()),
+            FirmwareUpgrade::Idle => Err(FirmwareError::NotStarted),
+        }
+    }
+
+    /// Handles the firmware related requests and writes the appropriate response. Returns
+    /// `None` without writing anything if the request is not firmware related.
+    pub fn handle(
+        &mut self,
+        request: &Request,
+        payload: &[u8],
+        storage: &mut impl FirmwareStorage,
+        response_writer: &mut impl Write,
+    ) -> Option<Result<usize, Error>> {
+        let (id, result) = match *request {
+            Request::BeginFirmwareUpgrade(id, size, crc) => {
+                (id, self.begin(size, crc, storage).map(Some))
+            }
+            Request::FirmwareChunk(id, offset) => {
+                (id, self.chunk(offset, payload, storage).map(Some))
+            }
+            Request::CommitFirmware(id) => (id, self.commit(storage).map(|_| None)),
+            _ => return None,
+        };
+
+        Some(match result {
+            Ok(Some(offset)) => Response::Ok(id, Format::ValueOnly(Type::U32))
+                .write(response_writer)
+                .and_then(|len| Ok(len + response_writer.write_all(&offset.to_be_bytes())?)),
+            Ok(None) => Response::Ok(id, Format::Empty).write(response_writer),
+            Err(_) => Response::NotAvailable(id).write(response_writer),
+        })
+    }
+}
+
+/// The CRC-32 (IEEE 802.3) checksum of a firmware image.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Crc32(u32);
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc32 {
+    pub const fn new() -> Self {
+        Crc32(0xFFFF_FFFF)
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.0 ^= u32::from(*byte);
+            for _ in 0..8 {
+                self.0 = if self.0 & 1 != 0 {
+                    (self.0 >> 1) ^ 0xEDB8_8320
+                } else {
+                    self.0 >> 1
+                };
+            }
+        }
+    }
+
+    pub const fn finish(&self) -> u32 {
+        !self.0
+    }
+
+    pub fn checksum(data: &[u8]) -> u32 {
+        let mut crc = Self::new();
+        crc.update(data);
+        crc.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+
+    struct Flash {
+        image: [u8; 8],
+        prepared: Option<u32>,
+        committed: Option<u32>,
+        fail_prepare: bool,
+    }
+
+    impl Flash {
+        fn new() -> Self {
+            Self {
+                image: [0u8; 8],
+                prepared: None,
+                committed: None,
+                fail_prepare: false,
+            }
+        }
+    }
+
+    impl FirmwareStorage for Flash {
+        fn prepare(&mut self, size: u32) -> Result<(), Error> {
+            if self.fail_prepare {
+                return Err(Error::BufferToSmall);
+            }
+            self.prepared = Some(size);
+            Ok(())
+        }
+
+        fn write(&mut self, offset: u32, data: &[u8]) -> Result<(), Error> {
+            let offset = offset as usize;
+            self.image[offset..offset + data.len()].copy_from_slice(data);
+            Ok(())
+        }
+
+        fn commit(&mut self, size: u32) -> Result<(), Error> {
+            self.committed = Some(size);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(0xCBF4_3926, Crc32::checksum(b"123456789"));
+        assert_eq!(0, Crc32::checksum(&[]));
+
+        let mut crc = Crc32::new();
+        crc.update(b"1234");
+        crc.update(b"");
+        crc.update(b"56789");
+        assert_eq!(0xCBF4_3926, crc.finish());
+    }
+
+    #[test]
+    fn interrupted_upgrade_is_resumed_and_committed() {
+        let image = *b"firmware";
+        let crc = Crc32::checksum(&image);
+        let mut flash = Flash::new();
+        let mut upgrade = FirmwareUpgrade::new();
+
+        assert_eq!(Ok(0), upgrade.begin(8, crc, &mut flash));
+        assert_eq!(Ok(5), upgrade.chunk(0, &image[..5], &mut flash));
+        assert_eq!(Err(FirmwareError::Incomplete), upgrade.commit(&mut flash));
+
+        // asking again for the same image resumes at the expected offset
+        assert_eq!(Ok(5), upgrade.begin(8, crc, &mut flash));
+        assert_eq!(
+            Err(FirmwareError::UnexpectedOffset { expected: 5 }),
+            upgrade.chunk(6, &image[6..], &mut flash)
+        );
+        // a resent chunk is acknowledged without storing it again
+        flash.image[..5].copy_from_slice(b"xxxxx");
+        assert_eq!(Ok(5), upgrade.chunk(0, &image[..5], &mut flash));
+        assert_eq!(b"xxxxx", &flash.image[..5]);
+        flash.image[..5].copy_from_slice(&image[..5]);
+        assert_eq!(
+            Err(FirmwareError::SizeExceeded),
+            upgrade.chunk(5, b"too long", &mut flash)
+        );
+        assert_eq!(Ok(8), upgrade.chunk(5, &image[5..], &mut flash));
+        assert_eq!(Ok(()), upgrade.commit(&mut flash));
+
+        assert_eq!(FirmwareUpgrade::Committed { size: 8, crc }, upgrade);
+        assert_eq!(image, flash.image);
+        assert_eq!(Some(8), flash.committed);
+        // committing again does not commit to the storage again
+        flash.committed = None;
+        assert_eq!(Ok(()), upgrade.commit(&mut flash));
+        assert_eq!(None, flash.committed);
+    }
+
+    #[test]
+    fn crc_mismatch_discards_the_upgrade() {
+        let mut flash = Flash::new();
+        let mut upgrade = FirmwareUpgrade::new();
+        assert_eq!(Err(FirmwareError::NotStarted), upgrade.commit(&mut flash));
+        assert_eq!(
+            Err(FirmwareError::NotStarted),
+            upgrade.chunk(0, b"data", &mut flash)
+        );
+
+        let crc = Crc32::checksum(b"data");
+        upgrade.begin(4, crc, &mut flash).unwrap();
+        upgrade.chunk(0, b"date", &mut flash).unwrap();
+        assert_eq!(Err(FirmwareError::CrcMismatch), upgrade.commit(&mut flash));
+        assert_eq!(FirmwareUpgrade::Idle, upgrade);
+        assert_eq!(None, flash.committed);
+
+        // a different image restarts the transfer
+        upgrade.begin(4, crc, &mut flash).unwrap();
+        upgrade.chunk(0, b"da", &mut flash).unwrap();
+        assert_eq!(Ok(0), upgrade.begin(3, crc, &mut flash));
+        assert_eq!(Some(3), flash.prepared);
+    }
+
+    #[test]
+    fn failing_storage_leaves_the_upgrade_idle() {
+        let mut flash = Flash::new();
+        flash.fail_prepare = true;
+        let mut upgrade = FirmwareUpgrade::new();
+        assert_eq!(
+            Err(FirmwareError::Storage(Error::BufferToSmall)),
+            upgrade.begin(4, 0, &mut flash)
+        );
+        assert_eq!(FirmwareUpgrade::Idle, upgrade);
+    }
+
+    fn assert_response(expected: Response, offset: Option<u32>, written: &[u8]) {
+        let mut buffer = [0u8; 16];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        expected.write(&mut cursor).unwrap();
+        if let Some(offset) = offset {
+            cursor.write_all(&offset.to_be_bytes()).unwrap();
+        }
+        assert_eq!(cursor.written(), written);
+    }
+
+    #[test]
+    fn handle_answers_with_the_next_offset() {
+        let mut flash = Flash::new();
+        let mut upgrade = FirmwareUpgrade::new();
+        let mut buffer = [0u8; 16];
+        let offset = Format::ValueOnly(Type::U32);
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let request = Request::BeginFirmwareUpgrade(3, 4, Crc32::checksum(b"data"));
+        let result = upgrade.handle(&request, &[], &mut flash, &mut cursor);
+        assert_eq!(Some(Ok(cursor.written().len())), result);
+        assert_response(Response::Ok(3, offset), Some(0), cursor.written());
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        upgrade.handle(
+            &Request::FirmwareChunk(4, 0),
+            b"data",
+            &mut flash,
+            &mut cursor,
+        );
+        assert_response(Response::Ok(4, offset), Some(4), cursor.written());
+
+        // the response got lost and the chunk is resent
+        let mut cursor = WriteCursor::new(&mut buffer);
+        upgrade.handle(
+            &Request::FirmwareChunk(5, 0),
+            b"data",
+            &mut flash,
+            &mut cursor,
+        );
+        assert_response(Response::Ok(5, offset), Some(4), cursor.written());
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        upgrade.handle(
+            &Request::FirmwareChunk(5, 6),
+            b"data",
+            &mut flash,
+            &mut cursor,
+        );
+        assert_response(Response::NotAvailable(5), None, cursor.written());
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        upgrade.handle(&Request::CommitFirmware(6), &[], &mut flash, &mut cursor);
+        assert_response(Response::Ok(6, Format::Empty), None, cursor.written());
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let request = Request::RetrieveDeviceInformation(7);
+        assert!(upgrade
+            .handle(&request, &[], &mut flash, &mut cursor)
+            .is_none());
+        assert!(cursor.written().is_empty());
+    }
+}
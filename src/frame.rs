@@ -0,0 +1,631 @@
+//! Response datagrams with their payload.
+//!
+//! A [`ResponseFrame`] borrows the receive buffer. Its values are only decoded when they are
+//! iterated, so that a collector polling many devices at a high rate does not need to
+//! allocate per response. On the device side, a [`ResponseWriter`] only accepts payload
+//! elements that match the format declared in the response header.
+
+use crate::cursor::{Rewind, Transaction};
+use crate::status::SensorStatus;
+use crate::value::Value;
+use crate::{Error, Format, Read, Response, Type};
+
+/// A decoded [`Response`] header and the payload following it
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ResponseFrame<'a> {
+    response: Response,
+    payload: &'a [u8],
+}
+
+impl<'a> ResponseFrame<'a> {
+    pub const fn new(response: Response, payload: &'a [u8]) -> Self {
+        Self { response, payload }
+    }
+
+    /// Decodes the header of the response datagram, the payload is not looked at
+    pub fn decode(datagram: &'a [u8]) -> Result<Self, Error> {
+        let mut payload = datagram;
+        let response = Response::read(&mut payload)?;
+        Ok(Self { response, payload })
+    }
+
+    pub const fn response(&self) -> &Response {
+        &self.response
+    }
+
+    pub const fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// The elements of the payload of a response with a [`Format`], whatever the format is
+    pub fn elements(&self) -> Option<ElementIter<'a>> {
+        Some(ElementIter::new(self.response.format()?, self.payload))
+    }
+
+    /// The values of a `Format::ValueOnly` or `Format::Echoed` response
+    pub fn values(&self) -> Option<ValueIter<'a>> {
+        match self.response.format() {
+            Some(Format::ValueOnly(_) | Format::Echoed(_)) => Some(ValueIter(self.elements()?)),
+            _ => None,
+        }
+    }
+
+    /// The addresses and values of a `Format::AddressValuePairs` or
+    /// `Format::CountedAddressValuePairs` response with addresses of a fixed size. For the
+    /// latter, fewer pairs than announced are reported as [`Error::UnexpectedEOF`].
+    pub fn address_values(&self) -> Option<AddressValueIter<'a>> {
+        match self.response.format() {
+            Some(Format::CountedAddressValuePairs(..)) if self.payload.len() < 2 => None,
+            Some(
+                Format::AddressValuePairs(address, _)
+                | Format::CountedAddressValuePairs(address, _),
+            ) if has_fixed_size(address) => Some(AddressValueIter(self.elements()?)),
+            _ => None,
+        }
+    }
+
+    /// The addresses, statuses and values of a `Format::AddressStatusValue` response with
+    /// addresses of a fixed size
+    pub fn address_status_values(&self) -> Option<AddressStatusValueIter<'a>> {
+        match self.response.format() {
+            Some(Format::AddressStatusValue(address, _)) if has_fixed_size(address) => {
+                Some(AddressStatusValueIter(self.elements()?))
+            }
+            _ => None,
+        }
+    }
+
+    /// The addresses, values and timestamps of a `Format::AddressValueTimestampTriples`
+    /// response with addresses of a fixed size
+    pub fn address_value_timestamps(&self) -> Option<AddressValueTimestampIter<'a>> {
+        match self.response.format() {
+            Some(Format::AddressValueTimestampTriples(address, ..)) if has_fixed_size(address) => {
+                Some(AddressValueTimestampIter(self.elements()?))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn has_fixed_size(ty: Type) -> bool {
+    matches!(ty.value_size(), Some(len) if len > 0)
+}
+
+/// A single element of a payload, the parts its [`Format`] does not have are `None`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element<'a> {
+    /// The address as encoded
+    pub address: Option<&'a [u8]>,
+    pub status: Option<SensorStatus>,
+    pub value: Option<Value<'a>>,
+    pub timestamp: Option<Value<'a>>,
+}
+
+/// Decodes the elements of a payload of any [`Format`] one after another, this is the decoder
+/// behind the other iterators of this module, [`crate::decode::validate_payload`] and the
+/// debug output. Stops after the first malformed element. Bytes following the elements of a
+/// `Format::Empty` payload are reported as [`Error::TrailingBytes`], those following the
+/// announced number of elements of a `Format::CountedAddressValuePairs` payload are left in
+/// [`ElementIter::remaining`]. Lists (`Type::DynList*` and `Type::Batch`) cannot be decoded
+/// as elements, see [`Value::read`].
+#[derive(Debug, Clone)]
+pub struct ElementIter<'a> {
+    format: Format,
+    remaining: &'a [u8],
+    /// The number of elements still announced by the count prefix
+    expected: Option<Result<u16, Error>>,
+}
+
+impl<'a> ElementIter<'a> {
+    pub fn new(format: Format, payload: &'a [u8]) -> Self {
+        let mut remaining = payload;
+        let expected = match format {
+            Format::CountedAddressValuePairs(..) => Some(remaining.read_u16_be()),
+            _ => None,
+        };
+        Self {
+            format,
+            remaining,
+            expected,
+        }
+    }
+
+    /// The bytes not decoded yet
+    pub const fn remaining(&self) -> &'a [u8] {
+        self.remaining
+    }
+
+    fn decode(&mut self) -> Result<Element<'a>, Error> {
+        let remaining = &mut self.remaining;
+        let mut element = Element {
+            address: None,
+            status: None,
+            value: None,
+            timestamp: None,
+        };
+        match self.format {
+            Format::Empty => return Err(Error::TrailingBytes),
+            Format::ValueOnly(ty) | Format::Echoed(ty) => {
+                element.value = Some(Value::read(ty, remaining)?);
+            }
+            Format::AddressOnly(address) => {
+                element.address = Some(read_address(address, remaining)?);
+            }
+            Format::AddressValuePairs(address, ty)
+            | Format::CountedAddressValuePairs(address, ty) => {
+                element.address = Some(read_address(address, remaining)?);
+                element.value = Some(Value::read(ty, remaining)?);
+            }
+            Format::AddressStatusValue(address, ty) => {
+                element.address = Some(read_address(address, remaining)?);
+                element.status = Some(SensorStatus::read(remaining)?);
+                element.value = Some(Value::read(ty, remaining)?);
+            }
+            Format::AddressValueTimestampTriples(address, ty, timestamp) => {
+                element.address = Some(read_address(address, remaining)?);
+                element.value = Some(Value::read(ty, remaining)?);
+                element.timestamp = Some(Value::read(timestamp, remaining)?);
+            }
+        }
+        Ok(element)
+    }
+}
+
+impl<'a> Iterator for ElementIter<'a> {
+    type Item = Result<Element<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.expected {
+            None if self.remaining.is_empty() => return None,
+            None => {}
+            Some(Ok(0)) => return None,
+            Some(Ok(ref mut expected)) => *expected -= 1,
+            Some(Err(e)) => {
+                self.expected = Some(Ok(0));
+                return Some(Err(e));
+            }
+        }
+        let element = self.decode();
+        if element.is_err() {
+            self.remaining = &[];
+            self.expected = self.expected.map(|_| Ok(0));
+        }
+        Some(element)
+    }
+}
+
+/// Reads an address of the given type and returns it as encoded
+fn read_address<'a>(ty: Type, reader: &mut &'a [u8]) -> Result<&'a [u8], Error> {
+    let encoded = *reader;
+    Value::read(ty, reader)?;
+    Ok(&encoded[..encoded.len() - reader.len()])
+}
+
+/// Decodes the values of a payload one after another. Stops after the first malformed value.
+#[derive(Debug, Clone)]
+pub struct ValueIter<'a>(ElementIter<'a>);
+
+impl<'a> Iterator for ValueIter<'a> {
+    type Item = Result<Value<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(
+            self.0
+                .next()?
+                .and_then(|element| element.value.ok_or(Error::UnknownTypeIdentifier)),
+        )
+    }
+}
+
+/// Decodes the address-value pairs of a payload one after another. Stops after the first
+/// malformed pair.
+#[derive(Debug, Clone)]
+pub struct AddressValueIter<'a>(ElementIter<'a>);
+
+impl<'a> Iterator for AddressValueIter<'a> {
+    type Item = Result<(&'a [u8], Value<'a>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.0.next()?.and_then(|element| match element {
+            Element {
+                address: Some(address),
+                value: Some(value),
+                ..
+            } => Ok((address, value)),
+            _ => Err(Error::UnknownTypeIdentifier),
+        }))
+    }
+}
+
+/// Decodes the addresses, statuses and values of a payload one after another. Stops after the
+/// first malformed element.
+#[derive(Debug, Clone)]
+pub struct AddressStatusValueIter<'a>(ElementIter<'a>);
+
+impl<'a> Iterator for AddressStatusValueIter<'a> {
+    type Item = Result<(&'a [u8], SensorStatus, Value<'a>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.0.next()?.and_then(|element| match element {
+            Element {
+                address: Some(address),
+                status: Some(status),
+                value: Some(value),
+                ..
+            } => Ok((address, status, value)),
+            _ => Err(Error::UnknownTypeIdentifier),
+        }))
+    }
+}
+
+/// Decodes the address-value-timestamp triples of a payload one after another. Stops after the
+/// first malformed triple.
+#[derive(Debug, Clone)]
+pub struct AddressValueTimestampIter<'a>(ElementIter<'a>);
+
+impl<'a> Iterator for AddressValueTimestampIter<'a> {
+    type Item = Result<(&'a [u8], Value<'a>, Value<'a>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.0.next()?.and_then(|element| match element {
+            Element {
+                address: Some(address),
+                value: Some(value),
+                timestamp: Some(timestamp),
+                ..
+            } => Ok((address, value, timestamp)),
+            _ => Err(Error::UnknownTypeIdentifier),
+        }))
+    }
+}
+
+/// Writes a `Response::Ok` header and the elements of its payload. Elements that do not match
+/// the declared [`Format`] are rejected with [`Error::UnknownTypeIdentifier`] without writing
+/// anything, elements that do not fit are rolled back.
+pub struct ResponseWriter<'w, W: Rewind> {
+    writer: &'w mut W,
+    format: Format,
+    written: usize,
+    /// The number of pairs of a `Format::CountedAddressValuePairs` response still to push
+    remaining_count: u16,
+}
+
+impl<'w, W: Rewind> ResponseWriter<'w, W> {
+    /// Writes the header of the response with the given id and format. Responses of the
+    /// `Format::CountedAddressValuePairs` are started with [`ResponseWriter::with_count`].
+    pub fn new(writer: &'w mut W, id: u8, format: Format) -> Result<Self, Error> {
+        Self::with_header(writer, Response::Ok(id, format))
+    }
+
+    /// Writes the header of a `Response::Announcement` with the given sequence number and
+    /// format, see [`crate::announcement`]
+    pub fn announcement(writer: &'w mut W, sequence: u8, format: Format) -> Result<Self, Error> {
+        Self::with_header(writer, Response::Announcement(sequence, format))
+    }
+
+    fn with_header(writer: &'w mut W, response: Response) -> Result<Self, Error> {
+        let format = response.format().ok_or(Error::UnknownTypeIdentifier)?;
+        if let Format::CountedAddressValuePairs(..) = format {
+            return Err(Error::UnknownTypeIdentifier);
+        }
+        let written = response.write(writer)?;
+        Ok(Self {
+            writer,
+            format,
+            written,
+            remaining_count: 0,
+        })
+    }
+
+    /// Writes the header of a `Format::CountedAddressValuePairs` response announcing the given
+    /// number of pairs, exactly that many pairs have to be pushed
+    pub fn with_count(
+        writer: &'w mut W,
+        id: u8,
+        address: Type,
+        value: Type,
+        count: u16,
+    ) -> Result<Self, Error> {
+        let format = Format::CountedAddressValuePairs(address, value);
+        let written = Response::Ok(id, format).write(writer)? + writer.write_u16_be(count)?;
+        Ok(Self {
+            writer,
+            format,
+            written,
+            remaining_count: count,
+        })
+    }
+
+    pub const fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Appends a value to a `Format::ValueOnly` or `Format::Echoed` response
+    pub fn push_value<'v>(&mut self, value: impl Into<Value<'v>>) -> Result<usize, Error> {
+        let value = value.into();
+        match self.format {
+            Format::ValueOnly(ty) | Format::Echoed(ty) if ty == value.ty() => self.push(&[value]),
+            _ => Err(Error::UnknownTypeIdentifier),
+        }
+    }
+
+    /// Appends an address to a `Format::AddressOnly` response
+    pub fn push_address<'v>(&mut self, address: impl Into<Value<'v>>) -> Result<usize, Error> {
+        let address = address.into();
+        match self.format {
+            Format::AddressOnly(ty) if ty == address.ty() => self.push(&[address]),
+            _ => Err(Error::UnknownTypeIdentifier),
+        }
+    }
+
+    /// Appends an address and its value to a `Format::AddressValuePairs` or
+    /// `Format::CountedAddressValuePairs` response
+    pub fn push_pair<'v>(
+        &mut self,
+        address: impl Into<Value<'v>>,
+        value: impl Into<Value<'v>>,
+    ) -> Result<usize, Error> {
+        let (address, value) = (address.into(), value.into());
+        match self.format {
+            Format::AddressValuePairs(address_ty, value_ty)
+                if address_ty == address.ty() && value_ty == value.ty() =>
+            {
+                self.push(&[address, value])
+            }
+            Format::CountedAddressValuePairs(address_ty, value_ty)
+                if address_ty == address.ty() && value_ty == value.ty() =>
+            {
+                if self.remaining_count == 0 {
+                    return Err(Error::BufferToSmall);
+                }
+                let written = self.push(&[address, value])?;
+                self.remaining_count -= 1;
+                Ok(written)
+            }
+            _ => Err(Error::UnknownTypeIdentifier),
+        }
+    }
+
+    /// Appends an address, the status of the sensor and its value to a
+    /// `Format::AddressStatusValue` response. The value is ignored by clients unless the status
+    /// is [`SensorStatus::Ok`].
+    pub fn push_status<'v>(
+        &mut self,
+        address: impl Into<Value<'v>>,
+        status: SensorStatus,
+        value: impl Into<Value<'v>>,
+    ) -> Result<usize, Error> {
+        let (address, value) = (address.into(), value.into());
+        match self.format {
+            Format::AddressStatusValue(address_ty, value_ty)
+                if address_ty == address.ty() && value_ty == value.ty() =>
+            {
+                let mut encoded = [0u8; 1];
+                status.write(&mut &mut encoded[..])?;
+                self.push(&[address, Value::U8(encoded[0]), value])
+            }
+            _ => Err(Error::UnknownTypeIdentifier),
+        }
+    }
+
+    /// Appends an address, its value and the time it has been sampled at to a
+    /// `Format::AddressValueTimestampTriples` response
+    pub fn push_triple<'v>(
+        &mut self,
+        address: impl Into<Value<'v>>,
+        value: impl Into<Value<'v>>,
+        timestamp: Value<'v>,
+    ) -> Result<usize, Error> {
+        let (address, value) = (address.into(), value.into());
+        match self.format {
+            Format::AddressValueTimestampTriples(address_ty, value_ty, timestamp_ty)
+                if address_ty == address.ty()
+                    && value_ty == value.ty()
+                    && timestamp_ty == timestamp.ty() =>
+            {
+                self.push(&[address, value, timestamp])
+            }
+            _ => Err(Error::UnknownTypeIdentifier),
+        }
+    }
+
+    /// The number of bytes written, including the header. Fails with
+    /// [`Error::PayloadLengthMismatch`] if fewer pairs have been pushed than announced by
+    /// [`ResponseWriter::with_count`].
+    pub fn finish(self) -> Result<usize, Error> {
+        if self.remaining_count != 0 {
+            Err(Error::PayloadLengthMismatch)
+        } else {
+            Ok(self.written)
+        }
+    }
+
+    fn push(&mut self, values: &[Value<'_>]) -> Result<usize, Error> {
+        let mut transaction = Transaction::new(&mut *self.writer);
+        let mut written = 0;
+        for value in values {
+            written += value.write(&mut transaction)?;
+        }
+        transaction.commit();
+        self.written += written;
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+
+    #[test]
+    fn iterates_address_value_pairs() {
+        let datagram = [
+            0x00, 0x07, 0x02, 0x01, 0x02, 0xFC, 0xAA, 0xBB, 0x00, 0x01, 0xCC, 0xDD, 0x00,
+        ];
+        let frame = ResponseFrame::decode(&datagram).unwrap();
+        assert_eq!(&datagram[6..], frame.payload());
+        assert!(frame.values().is_none());
+
+        let mut pairs = frame.address_values().unwrap();
+        assert_eq!(Some(Ok((&[0xAA, 0xBB][..], Value::U16(1)))), pairs.next());
+        assert_eq!(Some(Err(Error::UnexpectedEOF)), pairs.next());
+        assert_eq!(None, pairs.next());
+    }
+
+    #[test]
+    fn writer_enforces_the_format() {
+        let mut buffer = [0u8; 14];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let mut writer = ResponseWriter::new(
+            &mut cursor,
+            7,
+            Format::AddressValuePairs(Type::U8, Type::F32),
+        )
+        .unwrap();
+        assert_eq!(5, writer.push_pair(3u8, 21.5f32).unwrap());
+        assert_eq!(
+            Err(Error::UnknownTypeIdentifier),
+            writer.push_pair(3u8, 21.5f64)
+        );
+        assert_eq!(
+            Err(Error::UnknownTypeIdentifier),
+            writer.push_value(21.5f32)
+        );
+        assert_eq!(Err(Error::BufferToSmall), writer.push_pair(4u8, 1.0f32));
+        let len = writer.finish().unwrap();
+
+        let frame = ResponseFrame::decode(&buffer[..len]).unwrap();
+        let mut pairs = frame.address_values().unwrap();
+        assert_eq!(Some(Ok((&[3][..], Value::F32(21.5)))), pairs.next());
+        assert_eq!(None, pairs.next());
+    }
+
+    #[test]
+    fn counted_pairs_reveal_truncation() {
+        let mut buffer = [0u8; 32];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let mut writer =
+            ResponseWriter::with_count(&mut cursor, 7, Type::U8, Type::U16, 2).unwrap();
+        writer.push_pair(1u8, 10u16).unwrap();
+        writer.push_pair(2u8, 20u16).unwrap();
+        assert_eq!(Err(Error::BufferToSmall), writer.push_pair(3u8, 30u16));
+        let len = writer.finish().unwrap();
+
+        let complete = ResponseFrame::decode(&buffer[..len]).unwrap();
+        assert_eq!(2, complete.address_values().unwrap().count());
+
+        let truncated = ResponseFrame::decode(&buffer[..len - 3]).unwrap();
+        let mut pairs = truncated.address_values().unwrap();
+        assert_eq!(Some(Ok((&[1][..], Value::U16(10)))), pairs.next());
+        assert_eq!(Some(Err(Error::UnexpectedEOF)), pairs.next());
+        assert_eq!(None, pairs.next());
+    }
+
+    #[test]
+    fn element_that_does_not_fit_is_rolled_back() {
+        let mut buffer = [0u8; 12];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let format = Format::AddressValuePairs(Type::U8, Type::DynString);
+        let mut writer = ResponseWriter::new(&mut cursor, 7, format).unwrap();
+        assert_eq!(4, writer.push_pair(1u8, Value::DynString("ab")).unwrap());
+        assert_eq!(
+            Err(Error::BufferToSmall),
+            writer.push_pair(2u8, Value::DynString("abcdef"))
+        );
+        let len = writer.finish().unwrap();
+        assert_eq!(5 + 4, len);
+        assert_eq!(len, cursor.position());
+
+        let frame = ResponseFrame::decode(&buffer[..len]).unwrap();
+        let mut pairs = frame.address_values().unwrap();
+        assert_eq!(Some(Ok((&[1][..], Value::DynString("ab")))), pairs.next());
+        assert_eq!(None, pairs.next());
+    }
+
+    #[test]
+    fn counted_pairs_have_to_be_complete() {
+        let mut buffer = [0u8; 32];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let mut writer =
+            ResponseWriter::with_count(&mut cursor, 7, Type::U8, Type::U16, 2).unwrap();
+        writer.push_pair(1u8, 10u16).unwrap();
+        assert_eq!(Err(Error::PayloadLengthMismatch), writer.finish());
+    }
+
+    #[test]
+    fn reports_the_status_per_sensor() {
+        let mut buffer = [0u8; 32];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let format = Format::AddressStatusValue(Type::U8, Type::F32);
+        let mut writer = ResponseWriter::new(&mut cursor, 7, format).unwrap();
+        writer.push_status(1u8, SensorStatus::Ok, 21.5f32).unwrap();
+        writer
+            .push_status(2u8, SensorStatus::CrcError, 0f32)
+            .unwrap();
+        let len = writer.finish().unwrap();
+
+        let frame = ResponseFrame::decode(&buffer[..len]).unwrap();
+        let mut statuses = frame
+            .address_status_values()
+            .unwrap()
+            .map(|element| element.map(|(_, status, _)| status));
+        assert_eq!(Some(Ok(SensorStatus::Ok)), statuses.next());
+        assert_eq!(Some(Ok(SensorStatus::CrcError)), statuses.next());
+        assert_eq!(None, statuses.next());
+    }
+
+    #[test]
+    fn status_values_stop_after_a_truncated_element() {
+        let mut buffer = [0u8; 32];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let format = Format::AddressStatusValue(Type::U8, Type::F32);
+        let mut writer = ResponseWriter::new(&mut cursor, 7, format).unwrap();
+        writer.push_status(1u8, SensorStatus::Ok, 21.5f32).unwrap();
+        let len = writer.finish().unwrap();
+        buffer[len..len + 4].copy_from_slice(&[2, 0x00, 0x41, 0xAC]);
+
+        let frame = ResponseFrame::decode(&buffer[..len + 4]).unwrap();
+        let mut elements = frame.address_status_values().unwrap();
+        assert!(matches!(
+            elements.next(),
+            Some(Ok((&[1], SensorStatus::Ok, _)))
+        ));
+        assert_eq!(
+            Some(Err(Error::UnexpectedEOF)),
+            elements.next().map(|e| e.map(|_| ()))
+        );
+        assert!(elements.next().is_none());
+    }
+
+    #[test]
+    fn elements_of_any_format() {
+        let format = Format::AddressValueTimestampTriples(Type::U8, Type::U16, Type::U8);
+        let mut elements = ElementIter::new(format, &[1, 0x00, 0x0A, 9, 2]);
+        assert_eq!(
+            Some(Ok(Element {
+                address: Some(&[1]),
+                status: None,
+                value: Some(Value::U16(10)),
+                timestamp: Some(Value::U8(9)),
+            })),
+            elements.next()
+        );
+        assert_eq!(Some(Err(Error::UnexpectedEOF)), elements.next());
+        assert_eq!(None, elements.next());
+
+        let mut elements = ElementIter::new(Format::Empty, &[0xAA]);
+        assert_eq!(Some(Err(Error::TrailingBytes)), elements.next());
+        assert_eq!(None, elements.next());
+        assert_eq!(None, ElementIter::new(Format::Empty, &[]).next());
+
+        let counted = Format::CountedAddressValuePairs(Type::U8, Type::U8);
+        let mut elements = ElementIter::new(counted, &[0x00, 0x01, 5, 50, 0xAA]);
+        assert_eq!(Some(Value::U8(50)), elements.next().unwrap().unwrap().value);
+        assert_eq!(None, elements.next());
+        assert_eq!(&[0xAA], elements.remaining());
+
+        let mut elements = ElementIter::new(counted, &[0x00]);
+        assert_eq!(Some(Err(Error::UnexpectedEOF)), elements.next());
+        assert_eq!(None, elements.next());
+    }
+}
@@ -0,0 +1,148 @@
+//! Consistent Overhead Byte Stuffing.
+//!
+//! The datagram is encoded so that it contains no `0x00`, which then terminates the frame. A
+//! receiver that lost track of the frame boundaries resynchronizes at the next `0x00`. The
+//! overhead is one byte per started block of 254 bytes plus the delimiter, see
+//! [`max_encoded_len`].
+
+use crate::{Error, Read, Write};
+
+pub const DELIMITER: u8 = 0x00;
+
+/// The number of non-zero bytes a single block can hold
+const MAX_BLOCK_LEN: usize = 0xFE;
+
+/// The maximum length of the encoded frame of a datagram of the given length, including the
+/// delimiter
+pub const fn max_encoded_len(len: usize) -> usize {
+    1 + len + len / MAX_BLOCK_LEN + 1
+}
+
+/// Encodes the datagram and writes it followed by the delimiter, returns the number of bytes
+/// written
+pub fn encode(datagram: &[u8], writer: &mut impl Write) -> Result<usize, Error> {
+    let mut len = 0;
+    let mut remaining = datagram;
+    loop {
+        let block = &remaining[..remaining.len().min(MAX_BLOCK_LEN)];
+        let run = block
+            .iter()
+            .position(|b| *b == DELIMITER)
+            .unwrap_or(block.len());
+        len += writer.write_u8(run as u8 + 1)?;
+        len += writer.write_all(&remaining[..run])?;
+
+        if run == remaining.len() {
+            break;
+        } else if run == MAX_BLOCK_LEN {
+            remaining = &remaining[run..];
+        } else {
+            // the zero is implied by the length of the block
+            remaining = &remaining[run + 1..];
+        }
+    }
+    Ok(len + writer.write_u8(DELIMITER)?)
+}
+
+/// Reads and decodes the next frame up to and including its delimiter, returns the length of
+/// the datagram written to the destination. A frame that ends before its last block is
+/// rejected with `Error::UnexpectedEOF`, a datagram that does not fit into the destination
+/// with `Error::BufferToSmall`.
+pub fn decode(reader: &mut impl Read, destination: &mut [u8]) -> Result<usize, Error> {
+    let mut len = 0;
+    let mut push = |byte: u8| -> Result<(), Error> {
+        *destination.get_mut(len).ok_or(Error::BufferToSmall)? = byte;
+        len += 1;
+        Ok(())
+    };
+
+    // the zero implied by a short block is only pushed once another block follows
+    let mut implied_zero = false;
+    loop {
+        let code = reader.read_u8()?;
+        if code == DELIMITER {
+            break;
+        }
+        if implied_zero {
+            push(0x00)?;
+        }
+        for _ in 1..code {
+            match reader.read_u8()? {
+                DELIMITER => return Err(Error::UnexpectedEOF),
+                byte => push(byte)?,
+            }
+        }
+        implied_zero = usize::from(code) <= MAX_BLOCK_LEN;
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+
+    fn roundtrip(datagram: &[u8], encoded: &[u8]) {
+        let mut buffer = [0u8; 600];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        assert_eq!(encoded.len(), encode(datagram, &mut cursor).unwrap());
+        assert_eq!(encoded, cursor.written());
+        assert!(encoded.len() <= max_encoded_len(datagram.len()));
+
+        let mut decoded = [0u8; 600];
+        let len = decode(&mut &*encoded, &mut decoded).unwrap();
+        assert_eq!(datagram, &decoded[..len]);
+    }
+
+    #[test]
+    fn frames_roundtrip() {
+        roundtrip(&[], &[0x01, 0x00]);
+        roundtrip(&[0x00], &[0x01, 0x01, 0x00]);
+        roundtrip(&[0x11, 0x00, 0x22], &[0x02, 0x11, 0x02, 0x22, 0x00]);
+        roundtrip(&[0xE0, 7, 0x00, 0x00], &[0x03, 0xE0, 7, 0x01, 0x01, 0x00]);
+
+        let long = [0x42u8; 300];
+        let mut encoded = [0x42u8; 303];
+        encoded[0] = 0xFF;
+        encoded[255] = 47;
+        encoded[302] = 0x00;
+        roundtrip(&long, &encoded);
+
+        encoded[255] = 0x00;
+        roundtrip(&long[..254], &encoded[..256]);
+
+        assert_eq!(
+            Err(Error::UnexpectedEOF),
+            decode(&mut &[0x03, 0x11, 0x00][..], &mut [0u8; 8])
+        );
+        assert_eq!(
+            Err(Error::BufferToSmall),
+            decode(&mut &[0x03, 0x11, 0x22, 0x00][..], &mut [0u8; 1])
+        );
+    }
+
+    /// A reader that cannot look ahead, like one reading from a serial port
+    struct Serial<'a>(&'a [u8]);
+
+    impl Read for Serial<'_> {
+        fn read_u8(&mut self) -> Result<u8, Error> {
+            self.0.read_u8()
+        }
+
+        fn available(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    #[test]
+    fn decodes_without_peeking() {
+        let mut serial = Serial(&[0x02, 0x11, 0x02, 0x22, 0x00, 0x01, 0x00]);
+        assert_eq!(Err(Error::Unsupported), serial.peek_u8());
+
+        let mut decoded = [0u8; 8];
+        let len = decode(&mut serial, &mut decoded).unwrap();
+        assert_eq!(&[0x11, 0x00, 0x22], &decoded[..len]);
+        assert_eq!(0, decode(&mut serial, &mut decoded).unwrap());
+        assert_eq!(0, serial.available());
+    }
+}
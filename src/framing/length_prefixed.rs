@@ -0,0 +1,123 @@
+//! Frames of the length of the datagram as `u16` big endian followed by the datagram, for
+//! transports that are already reliable streams, such as TCP.
+//!
+//! Unlike [`super::cobs`], a receiver cannot resynchronize within a stream, so the connection
+//! has to be closed on any error. [`Decoder`] reassembles the frames from the chunks as they
+//! are received.
+
+use crate::{Error, Write};
+use core::convert::TryFrom;
+
+pub const HEADER_LEN: usize = 2;
+
+/// The maximum length of a datagram that can be framed
+pub const MAX_DATAGRAM_LEN: usize = u16::MAX as usize;
+
+/// Writes the length of the datagram followed by the datagram, returns the number of bytes
+/// written. Datagrams longer than [`MAX_DATAGRAM_LEN`] are rejected with
+/// `Error::BufferToSmall`.
+pub fn encode(datagram: &[u8], writer: &mut impl Write) -> Result<usize, Error> {
+    let len = u16::try_from(datagram.len()).map_err(|_| Error::BufferToSmall)?;
+    Ok(writer.write_u16_be(len)? + writer.write_all(datagram)?)
+}
+
+/// Reassembles frames of datagrams of up to `N` bytes from the received chunks
+#[derive(Debug, Clone)]
+pub struct Decoder<const N: usize> {
+    header: [u8; HEADER_LEN],
+    header_len: usize,
+    buffer: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Default for Decoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Decoder<N> {
+    pub const fn new() -> Self {
+        Self {
+            header: [0u8; HEADER_LEN],
+            header_len: 0,
+            buffer: [0u8; N],
+            len: 0,
+        }
+    }
+
+    /// Consumes the received bytes up to the end of the next frame and returns its datagram,
+    /// or consumes all bytes and returns `None` if the frame is not complete yet. Call it again
+    /// while bytes remain, they belong to the next frame. Datagrams longer than `N` bytes are
+    /// discarded and reported with `Error::BufferToSmall` once they have been received.
+    pub fn push_bytes(&mut self, bytes: &mut &[u8]) -> Result<Option<&[u8]>, Error> {
+        while self.header_len < HEADER_LEN {
+            let (&byte, remaining) = match bytes.split_first() {
+                Some(split) => split,
+                None => return Ok(None),
+            };
+            self.header[self.header_len] = byte;
+            self.header_len += 1;
+            *bytes = remaining;
+        }
+
+        let expected = usize::from(u16::from_be_bytes(self.header));
+        let (chunk, remaining) = bytes.split_at((expected - self.len).min(bytes.len()));
+        if let Some(destination) = self.buffer.get_mut(self.len..self.len + chunk.len()) {
+            destination.copy_from_slice(chunk);
+        }
+        self.len += chunk.len();
+        *bytes = remaining;
+
+        if self.len < expected {
+            return Ok(None);
+        }
+        self.header_len = 0;
+        self.len = 0;
+        if expected <= N {
+            Ok(Some(&self.buffer[..expected]))
+        } else {
+            Err(Error::BufferToSmall)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+
+    #[test]
+    fn frames_are_reassembled_from_chunks() {
+        let mut stream = [0u8; 18];
+        let mut cursor = WriteCursor::new(&mut stream);
+        encode(&[0xE0, 7, 0x00], &mut cursor).unwrap();
+        encode(&[], &mut cursor).unwrap();
+        encode(&[1, 2, 3, 4, 5], &mut cursor).unwrap();
+        encode(&[0x01, 8], &mut cursor).unwrap();
+        assert_eq!(
+            &[0, 3, 0xE0, 7, 0x00, 0, 0, 0, 5, 1, 2, 3, 4, 5, 0, 2, 0x01, 8],
+            cursor.written()
+        );
+
+        let mut decoder = Decoder::<4>::new();
+        let mut chunk = &stream[..1];
+        assert_eq!(Ok(None), decoder.push_bytes(&mut chunk));
+        let mut chunk = &stream[1..10];
+        assert_eq!(
+            Ok(Some(&[0xE0, 7, 0x00][..])),
+            decoder.push_bytes(&mut chunk)
+        );
+        assert_eq!(Ok(Some(&[][..])), decoder.push_bytes(&mut chunk));
+        assert_eq!(Ok(None), decoder.push_bytes(&mut chunk));
+        assert!(chunk.is_empty());
+
+        let mut chunk = &stream[10..16];
+        assert_eq!(Err(Error::BufferToSmall), decoder.push_bytes(&mut chunk));
+        assert_eq!(Ok(None), decoder.push_bytes(&mut chunk));
+        assert_eq!(
+            Ok(Some(&[0x01, 8][..])),
+            decoder.push_bytes(&mut &stream[16..])
+        );
+    }
+}
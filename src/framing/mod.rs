@@ -0,0 +1,8 @@
+//! Framing of datagrams on byte streams without datagram boundaries, such as a raw UART.
+//!
+//! Both the firmware and the host encode each request and response datagram into a frame and
+//! decode the frames they receive back into datagrams, which are then handled as if they had
+//! been received over UDP.
+
+pub mod cobs;
+pub mod length_prefixed;
@@ -0,0 +1,250 @@
+//! The optional headers in front of a request.
+//!
+//! A request datagram may start with, in this order, a [`RoutingHeader`], a [`SequenceHeader`],
+//! a [`SessionHeader`], a [`ScheduleHeader`], an [`AcceptCompressionHeader`] and a
+//! [`PriorityHeader`]. Each header starts with a marker byte that is not used by any request
+//! opcode, so the headers that are present can be told apart from the request following them.
+
+use crate::compression::{AcceptCompressionHeader, ACCEPT_COMPRESSION_MARKER};
+use crate::pairing::{SessionHeader, SESSION_HEADER_MARKER};
+use crate::priority::{PriorityHeader, PRIORITY_HEADER_MARKER};
+use crate::replay::{SequenceHeader, SEQUENCE_HEADER_MARKER};
+use crate::routing::{RoutingHeader, ROUTING_HEADER_MARKER};
+use crate::schedule::{ScheduleHeader, SCHEDULE_HEADER_MARKER};
+use crate::Error;
+
+/// The kinds of headers in the order they precede the request
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HeaderKind {
+    Routing,
+    Sequence,
+    Session,
+    Schedule,
+    AcceptCompression,
+    Priority,
+}
+
+impl HeaderKind {
+    const ALL: [HeaderKind; 6] = [
+        HeaderKind::Routing,
+        HeaderKind::Sequence,
+        HeaderKind::Session,
+        HeaderKind::Schedule,
+        HeaderKind::AcceptCompression,
+        HeaderKind::Priority,
+    ];
+
+    /// The kind of header starting with the given marker byte
+    pub fn from_marker(marker: u8) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.marker() == marker)
+    }
+
+    pub const fn marker(self) -> u8 {
+        match self {
+            HeaderKind::Routing => ROUTING_HEADER_MARKER,
+            HeaderKind::Sequence => SEQUENCE_HEADER_MARKER,
+            HeaderKind::Session => SESSION_HEADER_MARKER,
+            HeaderKind::Schedule => SCHEDULE_HEADER_MARKER,
+            HeaderKind::AcceptCompression => ACCEPT_COMPRESSION_MARKER,
+            HeaderKind::Priority => PRIORITY_HEADER_MARKER,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            HeaderKind::Routing => "routing header",
+            HeaderKind::Sequence => "sequence header",
+            HeaderKind::Session => "session header",
+            HeaderKind::Schedule => "schedule header",
+            HeaderKind::AcceptCompression => "accept compression header",
+            HeaderKind::Priority => "priority header",
+        }
+    }
+
+    /// Reads the header of this kind if the datagram starts with one. Returns the header and
+    /// the remaining datagram.
+    fn read_optional(self, datagram: &[u8]) -> Result<(Option<RequestHeader>, &[u8]), Error> {
+        Ok(match self {
+            HeaderKind::Routing => {
+                let (header, remaining) = RoutingHeader::read_optional(datagram)?;
+                (header.map(RequestHeader::Routing), remaining)
+            }
+            HeaderKind::Sequence => {
+                let (header, remaining) = SequenceHeader::read_optional(datagram)?;
+                (header.map(RequestHeader::Sequence), remaining)
+            }
+            HeaderKind::Session => {
+                let (header, remaining) = SessionHeader::read_optional(datagram)?;
+                (header.map(RequestHeader::Session), remaining)
+            }
+            HeaderKind::Schedule => {
+                let (header, remaining) = ScheduleHeader::read_optional(datagram)?;
+                (header.map(RequestHeader::Schedule), remaining)
+            }
+            HeaderKind::AcceptCompression => {
+                let (header, remaining) = AcceptCompressionHeader::read_optional(datagram)?;
+                (header.map(RequestHeader::AcceptCompression), remaining)
+            }
+            HeaderKind::Priority => {
+                let (header, remaining) = PriorityHeader::read_optional(datagram)?;
+                (header.map(RequestHeader::Priority), remaining)
+            }
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RequestHeader {
+    Routing(RoutingHeader),
+    Sequence(SequenceHeader),
+    Session(SessionHeader),
+    Schedule(ScheduleHeader),
+    AcceptCompression(AcceptCompressionHeader),
+    Priority(PriorityHeader),
+}
+
+impl RequestHeader {
+    pub const fn kind(&self) -> HeaderKind {
+        match self {
+            RequestHeader::Routing(_) => HeaderKind::Routing,
+            RequestHeader::Sequence(_) => HeaderKind::Sequence,
+            RequestHeader::Session(_) => HeaderKind::Session,
+            RequestHeader::Schedule(_) => HeaderKind::Schedule,
+            RequestHeader::AcceptCompression(_) => HeaderKind::AcceptCompression,
+            RequestHeader::Priority(_) => HeaderKind::Priority,
+        }
+    }
+}
+
+/// Iterates over the headers in front of a request. Stops after the first malformed header,
+/// [`RequestHeaderIter::remaining`] then starts with it.
+pub struct RequestHeaderIter<'a> {
+    remaining: &'a [u8],
+    /// The index of the next kind of header in [`HeaderKind::ALL`]
+    next: usize,
+}
+
+impl<'a> RequestHeaderIter<'a> {
+    pub const fn new(datagram: &'a [u8]) -> Self {
+        Self {
+            remaining: datagram,
+            next: 0,
+        }
+    }
+
+    /// The datagram after the headers iterated so far
+    pub const fn remaining(&self) -> &'a [u8] {
+        self.remaining
+    }
+
+    /// Skips the headers that precede a header of the given kind and returns the remaining
+    /// datagram, which is where a header of that kind belongs
+    pub fn skip_before(mut self, kind: HeaderKind) -> Result<&'a [u8], Error> {
+        while let Some(next) = HeaderKind::ALL.get(self.next).filter(|next| **next < kind) {
+            let (_, remaining) = next.read_optional(self.remaining)?;
+            self.remaining = remaining;
+            self.next += 1;
+        }
+        Ok(self.remaining)
+    }
+}
+
+impl Iterator for RequestHeaderIter<'_> {
+    type Item = Result<RequestHeader, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(kind) = HeaderKind::ALL.get(self.next) {
+            self.next += 1;
+            match kind.read_optional(self.remaining) {
+                Ok((Some(header), remaining)) => {
+                    self.remaining = remaining;
+                    return Some(Ok(header));
+                }
+                Ok((None, _)) => {}
+                Err(e) => {
+                    self.next = HeaderKind::ALL.len();
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Skips all headers in front of the request and returns the request datagram
+pub fn skip_request_headers(datagram: &[u8]) -> Result<&[u8], Error> {
+    let mut headers = RequestHeaderIter::new(datagram);
+    for header in &mut headers {
+        header?;
+    }
+    Ok(headers.remaining())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::priority::Priority;
+    use crate::{Request, Write};
+
+    #[test]
+    fn headers_are_read_in_order() {
+        let mut datagram = [0u8; 64];
+        let mut writer = &mut datagram[..];
+        RoutingHeader::new(&[3])
+            .unwrap()
+            .write(&mut writer)
+            .unwrap();
+        SessionHeader([7; 8]).write(&mut writer).unwrap();
+        PriorityHeader(Priority::HIGHEST)
+            .write(&mut writer)
+            .unwrap();
+        Request::Ping(9).write(&mut writer).unwrap();
+        let len = 64 - writer.available();
+        let datagram = &datagram[..len];
+
+        let mut headers = RequestHeaderIter::new(datagram);
+        assert_eq!(
+            Some(Ok(HeaderKind::Routing)),
+            headers.next().map(|h| h.map(|h| h.kind()))
+        );
+        assert_eq!(
+            Some(Ok(RequestHeader::Session(SessionHeader([7; 8])))),
+            headers.next()
+        );
+        assert_eq!(
+            Some(Ok(RequestHeader::Priority(PriorityHeader(
+                Priority::HIGHEST
+            )))),
+            headers.next()
+        );
+        assert_eq!(None, headers.next());
+        assert_eq!(
+            Ok(Request::Ping(9)),
+            Request::read(&mut headers.remaining())
+        );
+
+        let request = skip_request_headers(datagram).unwrap();
+        assert_eq!(headers.remaining(), request);
+        let schedule = RequestHeaderIter::new(datagram)
+            .skip_before(HeaderKind::Schedule)
+            .unwrap();
+        assert_eq!(PriorityHeader::ENCODED_LEN, schedule.len() - request.len());
+    }
+
+    #[test]
+    fn iteration_stops_at_a_malformed_header() {
+        let datagram = [0x51, 0x05, 1];
+        let mut headers = RequestHeaderIter::new(&datagram);
+        assert!(matches!(headers.next(), Some(Err(_))));
+        assert_eq!(None, headers.next());
+        assert_eq!(&datagram[..], headers.remaining());
+        assert_eq!(
+            Some(HeaderKind::Routing),
+            HeaderKind::from_marker(datagram[0])
+        );
+        assert!(skip_request_headers(&datagram).is_err());
+    }
+}
@@ -0,0 +1,46 @@
+//! Hexadecimal representation of raw bytes, such as addresses and property paths.
+
+use core::fmt;
+
+/// Displays the bytes as lower case hex digits, two per byte, with the separator in between
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Hex<'a> {
+    bytes: &'a [u8],
+    separator: &'a str,
+}
+
+impl<'a> Hex<'a> {
+    pub const fn new(bytes: &'a [u8], separator: &'a str) -> Self {
+        Self { bytes, separator }
+    }
+
+    /// The bytes separated by colons, as addresses and property paths are written, for
+    /// example `28:ff:4c`
+    pub const fn colons(bytes: &'a [u8]) -> Self {
+        Self::new(bytes, ":")
+    }
+}
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.bytes.iter().enumerate() {
+            if i > 0 {
+                f.write_str(self.separator)?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separates_the_bytes() {
+        assert_eq!("28:ff:04", Hex::colons(&[0x28, 0xFF, 0x04]).to_string());
+        assert_eq!("28ff", Hex::new(&[0x28, 0xFF], "").to_string());
+        assert_eq!("", Hex::colons(&[]).to_string());
+    }
+}
@@ -0,0 +1,587 @@
+use crate::cursor::WriteCursor;
+use crate::props::persistence::Persistence;
+use crate::props::well_known::DEVICE_BOOT_COUNT;
+use crate::props::ModuleId;
+use crate::{Bus, Error, Read, Write};
+
+/// The structured answer to a [`crate::Request::RetrieveDeviceInformation`], sent as
+/// `Response::Ok(id, Format::ValueOnly(Type::DeviceInformation))`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DeviceInformation {
+    /// The core clock frequency in Hz
+    pub frequency: u32,
+    pub uptime_millis: u64,
+    /// The raw value of the CPUID register
+    pub cpu_id: u32,
+    pub module_id: Option<ModuleId>,
+    pub reset_reason: ResetReason,
+    /// The number of times the device has booted, `None` if the device does not count them
+    pub boot_count: Option<u32>,
+}
+
+impl DeviceInformation {
+    pub const fn cpu_implementer(&self) -> u8 {
+        (self.cpu_id >> 24) as u8
+    }
+
+    pub const fn cpu_variant(&self) -> u8 {
+        ((self.cpu_id >> 20) & 0x0F) as u8
+    }
+
+    pub const fn cpu_part_number(&self) -> u16 {
+        ((self.cpu_id >> 4) & 0x0FFF) as u16
+    }
+
+    pub const fn cpu_revision(&self) -> u8 {
+        (self.cpu_id & 0x0F) as u8
+    }
+
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        Ok(writer.write_u32_be(self.frequency)?
+            + writer.write_u64_be(self.uptime_millis)?
+            + writer.write_u32_be(self.cpu_id)?
+            + self.reset_reason.write(writer)?
+            + if let Some(module) = self.module_id {
+                writer.write_u8(0x01)? + writer.write_all(&[module.group, module.id, module.ext])?
+            } else {
+                writer.write_u8(0x00)?
+            }
+            + if let Some(boot_count) = self.boot_count {
+                writer.write_u8(0x01)? + writer.write_u32_be(boot_count)?
+            } else {
+                writer.write_u8(0x00)?
+            })
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        let frequency = reader.read_u32_be()?;
+        let uptime_millis = reader.read_u64_be()?;
+        let cpu_id = reader.read_u32_be()?;
+
+        let reset_reason = ResetReason::read(reader)?;
+
+        let module_id = match reader.read_u8()? {
+            0x00 => None,
+            0x01 => Some(ModuleId {
+                group: reader.read_u8()?,
+                id: reader.read_u8()?,
+                ext: reader.read_u8()?,
+            }),
+            _ => return Err(Error::UnknownTypeIdentifier),
+        };
+
+        let boot_count = match reader.read_u8()? {
+            0x00 => None,
+            0x01 => Some(reader.read_u32_be()?),
+            _ => return Err(Error::UnknownTypeIdentifier),
+        };
+
+        Ok(DeviceInformation {
+            frequency,
+            uptime_millis,
+            cpu_id,
+            module_id,
+            reset_reason,
+            boot_count,
+        })
+    }
+}
+
+/// Increments the boot counter stored as [`DEVICE_BOOT_COUNT`] and returns the new value, to
+/// be called once early during the boot of the device. A missing counter starts at one.
+pub fn increment_boot_count(persistence: &mut dyn Persistence) -> Result<u32, Error> {
+    let mut buffer = [0u8; 4];
+    let mut cursor = WriteCursor::new(&mut buffer);
+    let boot_count = match persistence.load(&DEVICE_BOOT_COUNT, &mut cursor)? {
+        0 => 1,
+        _ => cursor.written().read_u32_be()?.wrapping_add(1),
+    };
+    persistence.store(&DEVICE_BOOT_COUNT, &mut &boot_count.to_be_bytes()[..])?;
+    Ok(boot_count)
+}
+
+/// The structured answer to a [`crate::Request::RetrieveNetworkConfiguration`], sent as
+/// `Response::Ok(id, Format::ValueOnly(Type::NetworkConfiguration))`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NetworkConfiguration {
+    pub mac: [u8; 6],
+    pub ip: [u8; 4],
+    pub subnet: [u8; 4],
+    pub gateway: [u8; 4],
+    /// Whether `ip`, `subnet` and `gateway` have been assigned through DHCP
+    pub dhcp: bool,
+    pub primary_dns: Option<[u8; 4]>,
+    /// Requires a `primary_dns`, the servers are encoded as a list
+    pub secondary_dns: Option<[u8; 4]>,
+}
+
+impl NetworkConfiguration {
+    /// Fails with [`Error::NonCanonical`] for a `secondary_dns` without a `primary_dns`, which
+    /// would be read back as the primary one
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        if self.primary_dns.is_none() && self.secondary_dns.is_some() {
+            return Err(Error::NonCanonical);
+        }
+        Ok(writer.write_all(&self.mac)?
+            + writer.write_all(&self.ip)?
+            + writer.write_all(&self.subnet)?
+            + writer.write_all(&self.gateway)?
+            + writer.write_u8(if self.dhcp { 0x01 } else { 0x00 })?
+            + {
+                let mut len = writer.write_u8(
+                    self.primary_dns
+                        .iter()
+                        .chain(self.secondary_dns.iter())
+                        .count() as u8,
+                )?;
+                for dns in self.primary_dns.iter().chain(self.secondary_dns.iter()) {
+                    len += writer.write_all(dns)?;
+                }
+                len
+            })
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        let mut configuration = NetworkConfiguration {
+            mac: [0u8; 6],
+            ip: [0u8; 4],
+            subnet: [0u8; 4],
+            gateway: [0u8; 4],
+            dhcp: false,
+            primary_dns: None,
+            secondary_dns: None,
+        };
+
+        reader.read_all(&mut configuration.mac)?;
+        reader.read_all(&mut configuration.ip)?;
+        reader.read_all(&mut configuration.subnet)?;
+        reader.read_all(&mut configuration.gateway)?;
+        configuration.dhcp = reader.read_u8()? != 0x00;
+
+        let dns_count = reader.read_u8()?;
+        if dns_count > 2 {
+            return Err(Error::UnknownTypeIdentifier);
+        }
+        if dns_count > 0 {
+            let mut primary = [0u8; 4];
+            reader.read_all(&mut primary)?;
+            configuration.primary_dns = Some(primary);
+        }
+        if dns_count > 1 {
+            let mut secondary = [0u8; 4];
+            reader.read_all(&mut secondary)?;
+            configuration.secondary_dns = Some(secondary);
+        }
+
+        Ok(configuration)
+    }
+}
+
+/// A single entry of the answer to a [`crate::Request::ListBuses`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BusInformation {
+    pub bus: Bus,
+    /// Whether the bus answers `DiscoverAllOnBus`
+    pub discoverable: bool,
+    /// Whether values can be written to the devices on the bus
+    pub writable: bool,
+    /// The maximum number of devices the bus supports, `None` if unknown or unlimited
+    pub max_devices: Option<u16>,
+}
+
+impl BusInformation {
+    pub fn write(&self, writer: &mut impl Write) -> Result<usize, Error> {
+        let flags = if self.discoverable { 1u8 << 0 } else { 0u8 }
+            | if self.writable { 1u8 << 1 } else { 0u8 };
+        Ok(self.bus.write(writer)?
+            + writer.write_u8(flags)?
+            + writer.write_u16_be(self.max_devices.unwrap_or_default())?)
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        let bus = Bus::read(reader)?;
+        let flags = reader.read_u8()?;
+        let max_devices = reader.read_u16_be()?;
+        Ok(BusInformation {
+            bus,
+            discoverable: flags & (1u8 << 0) != 0,
+            writable: flags & (1u8 << 1) != 0,
+            max_devices: Some(max_devices).filter(|max| *max > 0),
+        })
+    }
+}
+
+/// The version of the protocol implemented by this crate
+pub const PROTOCOL_VERSION: Version = Version::new(0, 1, 0);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+}
+
+impl Version {
+    pub const fn new(major: u16, minor: u16, patch: u16) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Whether this version can be used where at least `required` is needed, following the
+    /// semver rules: the major versions (or the minor versions for `0.x`) must be equal and this
+    /// version must not be older than `required`.
+    pub fn is_compatible_with(&self, required: &Version) -> bool {
+        self.major == required.major
+            && (self.major != 0 || self.minor == required.minor)
+            && self >= required
+    }
+
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        Ok(writer.write_all(&self.major.to_be_bytes())?
+            + writer.write_all(&self.minor.to_be_bytes())?
+            + writer.write_all(&self.patch.to_be_bytes())?)
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        let mut version = [0u8; 6];
+        reader.read_all(&mut version)?;
+        Ok(Version {
+            major: u16::from_be_bytes([version[0], version[1]]),
+            minor: u16::from_be_bytes([version[2], version[3]]),
+            patch: u16::from_be_bytes([version[4], version[5]]),
+        })
+    }
+}
+
+/// The structured answer to a [`crate::Request::RetrieveVersionInformation`], sent as
+/// `Response::Ok(id, Format::ValueOnly(Type::VersionInformation))`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VersionInformation {
+    pub protocol_version: Version,
+    pub firmware_version: Version,
+    /// The hash of the commit the firmware was built from
+    pub build_hash: [u8; 20],
+    /// The optional features the firmware supports, `None` if the device does not report them.
+    /// Only encoded for a `protocol_version` of at least [`Capabilities::SINCE`].
+    pub capabilities: Option<Capabilities>,
+}
+
+impl VersionInformation {
+    /// Whether the protocol version of the device is compatible with the minimal protocol
+    /// version required by the client, see [`Version::is_compatible_with`].
+    pub fn is_compatible_with(&self, client_min: &Version) -> bool {
+        self.protocol_version.is_compatible_with(client_min)
+    }
+
+    /// Fails with [`Error::NonCanonical`] for `capabilities` with a `protocol_version` that
+    /// predates [`Capabilities::SINCE`]
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        let reports_capabilities = self.protocol_version >= Capabilities::SINCE;
+        if !reports_capabilities && self.capabilities.is_some() {
+            return Err(Error::NonCanonical);
+        }
+        Ok(self.protocol_version.write(writer)?
+            + self.firmware_version.write(writer)?
+            + writer.write_all(&self.build_hash)?
+            + match self.capabilities {
+                _ if !reports_capabilities => 0,
+                Some(capabilities) => {
+                    writer.write_u8(0x01)? + writer.write_u32_be(capabilities.bits())?
+                }
+                None => writer.write_u8(0x00)?,
+            })
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        let protocol_version = Version::read(reader)?;
+        let firmware_version = Version::read(reader)?;
+        let mut build_hash = [0u8; 20];
+        reader.read_all(&mut build_hash)?;
+
+        let capabilities = if protocol_version < Capabilities::SINCE {
+            None
+        } else {
+            match reader.read_u8()? {
+                0x00 => None,
+                0x01 => Some(Capabilities::from_bits(reader.read_u32_be()?)),
+                _ => return Err(Error::UnknownTypeIdentifier),
+            }
+        };
+
+        Ok(VersionInformation {
+            protocol_version,
+            firmware_version,
+            build_hash,
+            capabilities,
+        })
+    }
+}
+
+/// An optional feature of the protocol a device may support
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Capability {
+    /// Properties can be written with [`crate::Request::StoreProperty`]
+    PropertyWrite,
+    /// Responses exceeding the MTU are split across several datagrams
+    Fragmentation,
+    /// Requests can be combined with [`crate::Request::Batch`]
+    Batch,
+    /// Properties can be listed with [`crate::Request::ListComponentsWithReportV2`]
+    PropertyReportV2,
+    /// Properties can be listed page by page with [`crate::Request::ListComponentsRange`]
+    ListComponentsRange,
+    /// Responses are compressed if requested, see [`crate::compression`]
+    Compression,
+    /// Requests can be deferred, see [`crate::schedule`]
+    Schedule,
+    /// The firmware can be upgraded, see [`crate::firmware`]
+    FirmwareUpgrade,
+    /// Clients can pair to obtain a session, see [`crate::pairing`]
+    Pairing,
+    /// The log can be followed with [`crate::Request::TailLog`]
+    TailLog,
+}
+
+impl Capability {
+    pub const ALL: &'static [Capability] = &[
+        Capability::PropertyWrite,
+        Capability::Fragmentation,
+        Capability::Batch,
+        Capability::PropertyReportV2,
+        Capability::ListComponentsRange,
+        Capability::Compression,
+        Capability::Schedule,
+        Capability::FirmwareUpgrade,
+        Capability::Pairing,
+        Capability::TailLog,
+    ];
+
+    /// The bit of the capability in [`Capabilities::bits`]
+    pub const fn bit(self) -> u32 {
+        1 << match self {
+            Capability::PropertyWrite => 0,
+            Capability::Fragmentation => 1,
+            Capability::Batch => 2,
+            Capability::PropertyReportV2 => 3,
+            Capability::ListComponentsRange => 4,
+            Capability::Compression => 5,
+            Capability::Schedule => 6,
+            Capability::FirmwareUpgrade => 7,
+            Capability::Pairing => 8,
+            Capability::TailLog => 9,
+        }
+    }
+}
+
+/// The set of [`Capability`]s of a device, as reported in its [`VersionInformation`]. Bits of
+/// capabilities unknown to this version of the crate are kept.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Capabilities(u32);
+
+impl Capabilities {
+    /// The protocol version from which on the [`VersionInformation`] reports the capabilities
+    pub const SINCE: Version = Version::new(0, 1, 0);
+
+    pub const fn empty() -> Self {
+        Capabilities(0)
+    }
+
+    pub const fn from_bits(bits: u32) -> Self {
+        Capabilities(bits)
+    }
+
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub const fn with(self, capability: Capability) -> Self {
+        Capabilities(self.0 | capability.bit())
+    }
+
+    pub fn insert(&mut self, capability: Capability) {
+        self.0 |= capability.bit();
+    }
+
+    pub const fn has(&self, capability: Capability) -> bool {
+        self.0 & capability.bit() != 0
+    }
+
+    /// The known capabilities in the set
+    pub fn iter(&self) -> impl Iterator<Item = Capability> + '_ {
+        Capability::ALL
+            .iter()
+            .copied()
+            .filter(move |capability| self.has(*capability))
+    }
+}
+
+/// The cause of the most recent device reset as reported by the reset controller.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ResetReason {
+    Unknown,
+    PowerOn,
+    Pin,
+    Brownout,
+    Watchdog,
+    Software,
+    Lockup,
+}
+
+impl ResetReason {
+    /// Whether the reset has not been requested, a device that keeps resetting for these
+    /// reasons is likely to suffer from an unstable supply or a crashing firmware
+    pub const fn is_fault(&self) -> bool {
+        matches!(
+            self,
+            ResetReason::Brownout | ResetReason::Watchdog | ResetReason::Lockup
+        )
+    }
+
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        writer.write_u8(match self {
+            ResetReason::Unknown => 0x00,
+            ResetReason::PowerOn => 0x01,
+            ResetReason::Pin => 0x02,
+            ResetReason::Brownout => 0x03,
+            ResetReason::Watchdog => 0x04,
+            ResetReason::Software => 0x05,
+            ResetReason::Lockup => 0x06,
+        })
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        Ok(match reader.read_u8()? {
+            0x00 => ResetReason::Unknown,
+            0x01 => ResetReason::PowerOn,
+            0x02 => ResetReason::Pin,
+            0x03 => ResetReason::Brownout,
+            0x04 => ResetReason::Watchdog,
+            0x05 => ResetReason::Software,
+            0x06 => ResetReason::Lockup,
+            _ => return Err(Error::UnknownTypeIdentifier),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_compatibility() {
+        let required = Version::new(1, 2, 0);
+        assert!(Version::new(1, 2, 0).is_compatible_with(&required));
+        assert!(Version::new(1, 3, 1).is_compatible_with(&required));
+        assert!(!Version::new(1, 1, 9).is_compatible_with(&required));
+        assert!(!Version::new(2, 0, 0).is_compatible_with(&required));
+
+        let required = Version::new(0, 1, 0);
+        assert!(Version::new(0, 1, 4).is_compatible_with(&required));
+        assert!(!Version::new(0, 2, 0).is_compatible_with(&required));
+    }
+
+    #[test]
+    fn capabilities_are_gated_by_the_protocol_version() {
+        let mut information = VersionInformation {
+            protocol_version: Capabilities::SINCE,
+            firmware_version: Version::new(0, 4, 1),
+            build_hash: [0xAB; 20],
+            capabilities: Some(
+                Capabilities::empty()
+                    .with(Capability::Batch)
+                    .with(Capability::TailLog),
+            ),
+        };
+        let mut buffer = [0u8; 48];
+        let len = information.write(&mut &mut buffer[..]).unwrap();
+        assert_eq!(2 * 6 + 20 + 5, len);
+        assert_eq!(&[0x01, 0x00, 0x00, 0x02, 0x04], &buffer[len - 5..len]);
+        // bytes following the structure are not mistaken for capabilities
+        let mut reader = &buffer[..len + 1];
+        assert_eq!(information, VersionInformation::read(&mut reader).unwrap());
+        assert_eq!(1, reader.len());
+        let capabilities = information.capabilities.unwrap();
+        assert!(capabilities.has(Capability::Batch));
+        assert!(!capabilities.has(Capability::PropertyWrite));
+        assert_eq!(2, capabilities.iter().count());
+
+        information.capabilities = None;
+        let len = information.write(&mut &mut buffer[..]).unwrap();
+        assert_eq!(2 * 6 + 20 + 1, len);
+        assert_eq!(
+            information,
+            VersionInformation::read(&mut &buffer[..len]).unwrap()
+        );
+
+        information.protocol_version = Version::new(0, 0, 9);
+        let len = information.write(&mut &mut buffer[..]).unwrap();
+        assert_eq!(2 * 6 + 20, len);
+        let mut reader = &buffer[..len + 5];
+        assert_eq!(information, VersionInformation::read(&mut reader).unwrap());
+        assert_eq!(5, reader.len());
+
+        information.capabilities = Some(Capabilities::empty());
+        assert_eq!(
+            Err(Error::NonCanonical),
+            information.write(&mut &mut buffer[..])
+        );
+    }
+
+    #[test]
+    fn boot_count_is_optional() {
+        let mut information = DeviceInformation {
+            frequency: 72_000_000,
+            uptime_millis: 1_234,
+            cpu_id: 0x410F_C241,
+            module_id: None,
+            reset_reason: ResetReason::Brownout,
+            boot_count: Some(17),
+        };
+        let mut buffer = [0u8; 32];
+        let len = information.write(&mut &mut buffer[..]).unwrap();
+        assert_eq!(23, len);
+        assert_eq!(
+            information,
+            DeviceInformation::read(&mut &buffer[..len]).unwrap()
+        );
+
+        information.boot_count = None;
+        let len = information.write(&mut &mut buffer[..]).unwrap();
+        assert_eq!(19, len);
+        let mut reader = &buffer[..len + 4];
+        assert_eq!(information, DeviceInformation::read(&mut reader).unwrap());
+        assert_eq!(4, reader.len());
+        assert_eq!(
+            Err(Error::UnexpectedEOF),
+            DeviceInformation::read(&mut &buffer[..len - 1])
+        );
+    }
+
+    #[test]
+    fn secondary_dns_requires_a_primary_one() {
+        let mut configuration = NetworkConfiguration {
+            mac: [0x02, 0, 0, 0, 0, 1],
+            ip: [192, 168, 1, 10],
+            subnet: [255, 255, 255, 0],
+            gateway: [192, 168, 1, 1],
+            dhcp: true,
+            primary_dns: None,
+            secondary_dns: Some([9, 9, 9, 9]),
+        };
+        let mut buffer = [0u8; 32];
+        assert_eq!(
+            Err(Error::NonCanonical),
+            configuration.write(&mut &mut buffer[..])
+        );
+
+        configuration.primary_dns = Some([1, 1, 1, 1]);
+        let len = configuration.write(&mut &mut buffer[..]).unwrap();
+        assert_eq!(
+            configuration,
+            NetworkConfiguration::read(&mut &buffer[..len]).unwrap()
+        );
+    }
+}
@@ -3,9 +3,56 @@
 #[macro_use]
 extern crate num_enum;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod address;
+#[cfg(feature = "pcap")]
+pub mod analysis;
+pub mod announcement;
+pub mod batch;
+pub mod can;
 #[cfg(feature = "std")]
 pub mod client;
+pub mod clock;
+pub mod compression;
+pub mod counted;
+pub mod cursor;
+#[cfg(feature = "std")]
+pub mod debugfmt;
+pub mod decode;
+#[cfg(feature = "dissector")]
+pub mod dissector;
+pub mod dns_sd;
+pub mod eeprom;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+pub mod error_dump;
+pub mod factory_reset;
+pub mod firmware;
+pub mod frame;
+pub mod framing;
+pub mod headers;
+pub mod hex;
+pub mod info;
+pub mod log;
+pub mod modbus;
+pub mod network;
+pub mod output;
+pub mod pairing;
+pub mod priority;
 pub mod props;
+pub mod replay;
+pub mod routing;
+pub mod schedule;
+pub mod server;
+pub mod status;
+#[cfg(any(test, feature = "test-vectors"))]
+pub mod test_vectors;
+pub mod tlv;
+pub mod value;
+
+use core::convert::TryFrom;
 
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Error {
@@ -13,6 +60,24 @@ pub enum Error {
     UnexpectedEOF,
     UnknownTypeIdentifier,
     InvalidUtf8,
+    InvalidChecksum,
+    /// Bytes follow a message that has no payload, see [`decode::DecodeOptions::strict`]
+    TrailingBytes,
+    /// The payload does not consist of complete elements of the declared format
+    PayloadLengthMismatch,
+    /// A property path is longer than [`props::tree::PROPERTY_PATH_MAX_LEN`]
+    PropertyPathTooLong,
+    /// The reader does not support the operation, see [`Read::peek_u8`]
+    Unsupported,
+    /// A value that would not be read back as written, such as an `Other` code that collides
+    /// with a named variant
+    NonCanonical,
+    /// A string is not in the expected notation, see [`address::OneWireAddress`]
+    Unparsable,
+    /// A [`Type::ListOf`] is nested deeper than [`ListElement::MAX_ENCODED_LEN`] allows
+    NestingTooDeep,
+    /// A value or list is longer than the 255 bytes or elements its length prefix can express
+    ValueTooLong,
 }
 
 #[cfg(feature = "std")]
@@ -26,90 +91,481 @@ impl std::fmt::Display for Error {
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Request {
     ReadSpecified(u8, Bus),
     ReadAll(u8),
     ReadAllOnBus(u8, Bus),
+    /// Answered with `Response::Ok(id, Format::AddressValuePairs(_, Type::SensorDiagnostics))`
+    /// containing the [`status::SensorDiagnostics`] of each sensor on the bus
+    ReadDiagnostics(u8, Bus),
     DiscoverAll(u8),
     DiscoverAllOnBus(u8, Bus),
+    /// Answered with `Response::Ok(id, Format::ValueOnly(Type::DynListBusInformation))`
+    /// followed by a [`info::BusInformation`] for each bus of the device
+    ListBuses(u8),
+
+    /// Drives the output whose address follows as payload, see [`output`]
+    SetOutput(u8, Bus, output::OutputState),
+    /// Reads the state of the output whose address follows as payload, see [`output`]
+    GetOutput(u8, Bus),
+
+    /// Lists the requests deferred with a [`schedule::ScheduleHeader`]
+    ListPendingCommands(u8),
+    /// Cancels the deferred request with the given id, see [`schedule`]
+    CancelPendingCommand(u8, u8),
+
+    /// Answered with `Response::Ok(id, Format::Empty)` right away, without accessing any bus,
+    /// to check whether the device is alive, see [`server::respond_to_ping`]
+    Ping(u8),
+
+    /// Answered with the milliseconds since the unix epoch, see [`clock`]
+    GetTime(u8),
+    /// Sets the clock to the given milliseconds since the unix epoch, see [`clock`]
+    SetTime(u8, u64),
+
+    /// Answered with a challenge for a [`Request::Pair`], see [`pairing`]
+    RequestChallenge(u8),
+    /// Answers the challenge to obtain a session token, see [`pairing`]
+    Pair(u8, [u8; 8]),
 
     SetNetworkMac(u8, [u8; 6]),
     SetNetworkIpSubnetGateway(u8, [u8; 4], [u8; 4], [u8; 4]),
+    /// Sets the primary and the optional secondary DNS server
+    SetNetworkDns(u8, [u8; 4], Option<[u8; 4]>),
+    /// Applies the [`info::NetworkConfiguration`] that follows as payload until it is committed
+    /// within the given number of seconds, see [`network`]
+    StageNetworkConfig(u8, u16),
+    CommitNetworkConfig(u8),
 
     ListComponents(u8),
     ListComponentsWithReportV1(u8),
+    ListComponentsWithReportV2(u8),
+    /// Lists at most the given number of properties starting at the given offset, answered
+    /// like [`Request::ListComponentsWithReportV1`]. Fewer properties than requested mark the
+    /// end of the list.
+    ListComponentsRange(u8, u16, u8),
 
+    /// Contains the given number of requests as payload, see [`batch`]
+    Batch(u8, u8),
+
+    /// Restarts the device. The device acknowledges the request with
+    /// `Response::Ok(id, Format::Empty)` before performing the reset.
+    Reset(u8, ResetKind),
+    /// Erases all settings of the device, if confirmed with the token of
+    /// [`factory_reset`]. The device acknowledges the request before performing the reset.
+    FactoryReset(u8, [u8; 4]),
+
+    /// Announces a firmware image of the given size in bytes and with the given CRC-32.
+    /// See [`firmware::FirmwareUpgrade`] for the sequence of requests and responses.
+    BeginFirmwareUpgrade(u8, u32, u32),
+    /// Transfers the payload as chunk of the firmware image, starting at the given offset
+    FirmwareChunk(u8, u32),
+    CommitFirmware(u8),
+
+    /// Reads the given number of bytes of the EEPROM at the given offset, see [`eeprom`]
+    ReadEeprom(u8, u16, u8),
+    /// Writes the payload to the EEPROM at the given offset, see [`eeprom`]
+    WriteEeprom(u8, u16),
+
+    /// Writes a property, the payload contains the property path of the given length followed
+    /// by the value. The device answers with `Format::Echoed` if the property is readable.
+    StoreProperty(u8, u8),
     RetrieveProperty(u8, u8),
     RetrieveErrorDump(u8),
+    /// Retrieves at most the given number of error dump entries starting at the given offset,
+    /// the oldest entry first. Answered like [`Request::RetrieveErrorDump`], fewer entries than
+    /// requested mark the end of the dump, see [`error_dump::write_range`].
+    RetrieveErrorDumpRange(u8, u16, u8),
+    /// Removes all entries of the error dump, answered with `Response::Ok(id, Format::Empty)`
+    ClearErrorDump(u8),
+    /// Retrieves the log records starting at the given sequence number, see [`log`]
+    TailLog(u8, u32),
     RetrieveDeviceInformation(u8),
     RetrieveNetworkConfiguration(u8),
     RetrieveVersionInformation(u8),
 }
 
+/// Generates [`RequestOpcode`] and its table of all opcodes from a single list
+macro_rules! request_opcodes {
+    ($($name:ident = $value:literal,)*) => {
+        /// The first byte of an encoded [`Request`], one for each variant of the same name
+        #[repr(u8)]
+        #[derive(Copy, Clone, PartialEq, Eq, Debug, IntoPrimitive, TryFromPrimitive)]
+        pub enum RequestOpcode {
+            $($name = $value,)*
+        }
+
+        impl RequestOpcode {
+            /// All opcodes in ascending order, for example to list the requests a device
+            /// supports
+            pub const ALL: &'static [RequestOpcode] = &[$(RequestOpcode::$name,)*];
+
+            /// The name of the [`Request`] variant
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(RequestOpcode::$name => stringify!($name),)*
+                }
+            }
+        }
+    };
+}
+
+request_opcodes! {
+    ReadSpecified = 0x00,
+    ReadAll = 0x01,
+    ReadAllOnBus = 0x02,
+    ReadDiagnostics = 0x03,
+    DiscoverAll = 0x10,
+    DiscoverAllOnBus = 0x11,
+    ListBuses = 0x12,
+    SetOutput = 0x20,
+    GetOutput = 0x21,
+    ListPendingCommands = 0x30,
+    CancelPendingCommand = 0x31,
+    Ping = 0x40,
+    RequestChallenge = 0x60,
+    Pair = 0x61,
+    GetTime = 0x70,
+    SetTime = 0x71,
+    SetNetworkMac = 0xA0,
+    SetNetworkIpSubnetGateway = 0xA1,
+    SetNetworkDns = 0xA2,
+    StageNetworkConfig = 0xA3,
+    CommitNetworkConfig = 0xA4,
+    Batch = 0xB0,
+    ListComponents = 0xD0,
+    ListComponentsWithReportV1 = 0xD1,
+    ListComponentsWithReportV2 = 0xD2,
+    ListComponentsRange = 0xD3,
+    Reset = 0xE0,
+    FactoryReset = 0xE1,
+    BeginFirmwareUpgrade = 0xE8,
+    FirmwareChunk = 0xE9,
+    CommitFirmware = 0xEA,
+    ReadEeprom = 0xEC,
+    WriteEeprom = 0xED,
+    TailLog = 0xF7,
+    RetrieveErrorDumpRange = 0xF8,
+    ClearErrorDump = 0xF9,
+    StoreProperty = 0xFA,
+    RetrieveProperty = 0xFB,
+    RetrieveErrorDump = 0xFC,
+    RetrieveDeviceInformation = 0xFD,
+    RetrieveNetworkConfiguration = 0xFE,
+    RetrieveVersionInformation = 0xFF,
+}
+
+/// The bytes of a [`Request`] encoded by a `const fn`, which cannot call [`Write`]
+#[derive(Copy, Clone)]
+struct EncodeBuffer {
+    bytes: [u8; Request::max_encoded_len()],
+    len: usize,
+}
+
+impl EncodeBuffer {
+    const fn new() -> Self {
+        Self {
+            bytes: [0u8; Request::max_encoded_len()],
+            len: 0,
+        }
+    }
+
+    const fn push(mut self, byte: u8) -> Self {
+        self.bytes[self.len] = byte;
+        self.len += 1;
+        self
+    }
+
+    const fn extend(mut self, bytes: &[u8]) -> Self {
+        let mut i = 0;
+        while i < bytes.len() {
+            self = self.push(bytes[i]);
+            i += 1;
+        }
+        self
+    }
+
+    const fn push_bus(mut self, bus: Bus) -> Self {
+        let (encoded, len) = bus.encode();
+        let mut i = 0;
+        while i < len {
+            self = self.push(encoded[i]);
+            i += 1;
+        }
+        self
+    }
+}
+
+/// Encodes a [`Request`] at compile time into an array of exactly its length, for example to
+/// keep the requests a firmware sends to other devices in flash.
+///
+/// ```
+/// use sensor_common::{encode_request, Request};
+///
+/// static RETRIEVE_VERSION: [u8; 2] = encode_request!(Request::RetrieveVersionInformation(0x01));
+/// assert_eq!([0xFF, 0x01], RETRIEVE_VERSION);
+/// ```
+#[macro_export]
+macro_rules! encode_request {
+    ($request:expr) => {{
+        const ENCODED: ([u8; $crate::Request::max_encoded_len()], usize) =
+            $crate::Request::encode(&$request);
+        const BYTES: [u8; ENCODED.1] = {
+            let mut bytes = [0u8; ENCODED.1];
+            let mut i = 0;
+            while i < ENCODED.1 {
+                bytes[i] = ENCODED.0[i];
+                i += 1;
+            }
+            bytes
+        };
+        BYTES
+    }};
+}
+
 impl Request {
-    pub fn id(&self) -> u8 {
+    /// The maximum number of bytes [`Request::write`] writes for any request, not including
+    /// the payload that follows some requests
+    pub const fn max_encoded_len() -> usize {
+        // opcode, id, ip, subnet and gateway of SetNetworkIpSubnetGateway, checked against the
+        // largest instance of every request in the test vectors
+        1 + 1 + 3 * 4
+    }
+
+    /// The first byte of the encoded request
+    pub const fn opcode(&self) -> RequestOpcode {
+        match self {
+            Request::ReadSpecified(..) => RequestOpcode::ReadSpecified,
+            Request::ReadAll(..) => RequestOpcode::ReadAll,
+            Request::ReadAllOnBus(..) => RequestOpcode::ReadAllOnBus,
+            Request::ReadDiagnostics(..) => RequestOpcode::ReadDiagnostics,
+            Request::DiscoverAll(..) => RequestOpcode::DiscoverAll,
+            Request::DiscoverAllOnBus(..) => RequestOpcode::DiscoverAllOnBus,
+            Request::ListBuses(..) => RequestOpcode::ListBuses,
+            Request::SetOutput(..) => RequestOpcode::SetOutput,
+            Request::GetOutput(..) => RequestOpcode::GetOutput,
+            Request::ListPendingCommands(..) => RequestOpcode::ListPendingCommands,
+            Request::CancelPendingCommand(..) => RequestOpcode::CancelPendingCommand,
+            Request::Ping(..) => RequestOpcode::Ping,
+            Request::RequestChallenge(..) => RequestOpcode::RequestChallenge,
+            Request::Pair(..) => RequestOpcode::Pair,
+            Request::GetTime(..) => RequestOpcode::GetTime,
+            Request::SetTime(..) => RequestOpcode::SetTime,
+            Request::SetNetworkMac(..) => RequestOpcode::SetNetworkMac,
+            Request::SetNetworkIpSubnetGateway(..) => RequestOpcode::SetNetworkIpSubnetGateway,
+            Request::SetNetworkDns(..) => RequestOpcode::SetNetworkDns,
+            Request::StageNetworkConfig(..) => RequestOpcode::StageNetworkConfig,
+            Request::CommitNetworkConfig(..) => RequestOpcode::CommitNetworkConfig,
+            Request::Batch(..) => RequestOpcode::Batch,
+            Request::ListComponents(..) => RequestOpcode::ListComponents,
+            Request::ListComponentsWithReportV1(..) => RequestOpcode::ListComponentsWithReportV1,
+            Request::ListComponentsWithReportV2(..) => RequestOpcode::ListComponentsWithReportV2,
+            Request::ListComponentsRange(..) => RequestOpcode::ListComponentsRange,
+            Request::Reset(..) => RequestOpcode::Reset,
+            Request::FactoryReset(..) => RequestOpcode::FactoryReset,
+            Request::BeginFirmwareUpgrade(..) => RequestOpcode::BeginFirmwareUpgrade,
+            Request::FirmwareChunk(..) => RequestOpcode::FirmwareChunk,
+            Request::CommitFirmware(..) => RequestOpcode::CommitFirmware,
+            Request::ReadEeprom(..) => RequestOpcode::ReadEeprom,
+            Request::WriteEeprom(..) => RequestOpcode::WriteEeprom,
+            Request::TailLog(..) => RequestOpcode::TailLog,
+            Request::RetrieveErrorDumpRange(..) => RequestOpcode::RetrieveErrorDumpRange,
+            Request::ClearErrorDump(..) => RequestOpcode::ClearErrorDump,
+            Request::StoreProperty(..) => RequestOpcode::StoreProperty,
+            Request::RetrieveProperty(..) => RequestOpcode::RetrieveProperty,
+            Request::RetrieveErrorDump(..) => RequestOpcode::RetrieveErrorDump,
+            Request::RetrieveDeviceInformation(..) => RequestOpcode::RetrieveDeviceInformation,
+            Request::RetrieveNetworkConfiguration(..) => {
+                RequestOpcode::RetrieveNetworkConfiguration
+            }
+            Request::RetrieveVersionInformation(..) => RequestOpcode::RetrieveVersionInformation,
+        }
+    }
+
+    pub const fn id(&self) -> u8 {
         match self {
             Request::ReadSpecified(id, _) => *id,
             Request::ReadAll(id) => *id,
             Request::ReadAllOnBus(id, _) => *id,
+            Request::ReadDiagnostics(id, _) => *id,
             Request::DiscoverAll(id) => *id,
             Request::DiscoverAllOnBus(id, _) => *id,
+            Request::ListBuses(id) => *id,
+            Request::SetOutput(id, _, _) => *id,
+            Request::GetOutput(id, _) => *id,
+            Request::ListPendingCommands(id) => *id,
+            Request::CancelPendingCommand(id, _) => *id,
+            Request::Ping(id) => *id,
+            Request::GetTime(id) => *id,
+            Request::SetTime(id, _) => *id,
+            Request::RequestChallenge(id) => *id,
+            Request::Pair(id, _) => *id,
             Request::SetNetworkMac(id, _) => *id,
             Request::SetNetworkIpSubnetGateway(id, _, _, _) => *id,
+            Request::SetNetworkDns(id, _, _) => *id,
+            Request::StageNetworkConfig(id, _) => *id,
+            Request::CommitNetworkConfig(id) => *id,
             Request::ListComponents(id) => *id,
             Request::ListComponentsWithReportV1(id) => *id,
+            Request::ListComponentsWithReportV2(id) => *id,
+            Request::ListComponentsRange(id, _, _) => *id,
+            Request::Batch(id, _) => *id,
+            Request::Reset(id, _) => *id,
+            Request::FactoryReset(id, _) => *id,
+            Request::BeginFirmwareUpgrade(id, _, _) => *id,
+            Request::FirmwareChunk(id, _) => *id,
+            Request::CommitFirmware(id) => *id,
+            Request::ReadEeprom(id, _, _) => *id,
+            Request::WriteEeprom(id, _) => *id,
+            Request::StoreProperty(id, _) => *id,
             Request::RetrieveProperty(id, _) => *id,
             Request::RetrieveErrorDump(id) => *id,
+            Request::RetrieveErrorDumpRange(id, _, _) => *id,
+            Request::ClearErrorDump(id) => *id,
+            Request::TailLog(id, _) => *id,
             Request::RetrieveDeviceInformation(id) => *id,
             Request::RetrieveNetworkConfiguration(id) => *id,
             Request::RetrieveVersionInformation(id) => *id,
         }
     }
 
+    /// The encoded request and the number of used bytes, see [`encode_request!`] to encode
+    /// requests at compile time
+    pub const fn encode(&self) -> ([u8; Request::max_encoded_len()], usize) {
+        let encoded = EncodeBuffer::new()
+            .push(self.opcode() as u8)
+            .push(self.id());
+        let encoded = match *self {
+            Request::ReadSpecified(_, bus)
+            | Request::ReadAllOnBus(_, bus)
+            | Request::ReadDiagnostics(_, bus)
+            | Request::DiscoverAllOnBus(_, bus)
+            | Request::GetOutput(_, bus) => encoded.push_bus(bus),
+            Request::SetOutput(_, bus, state) => encoded.push_bus(bus).extend(&state.encode()),
+            Request::CancelPendingCommand(_, pending_id) => encoded.push(pending_id),
+            Request::SetTime(_, millis) => encoded.extend(&millis.to_be_bytes()),
+            Request::Pair(_, response) => encoded.extend(&response),
+            Request::SetNetworkMac(_, mac) => encoded.extend(&mac),
+            Request::SetNetworkIpSubnetGateway(_, ip, subnet, gateway) => {
+                encoded.extend(&ip).extend(&subnet).extend(&gateway)
+            }
+            Request::SetNetworkDns(_, primary, secondary) => match secondary {
+                Some(secondary) => encoded.extend(&primary).push(0x01).extend(&secondary),
+                None => encoded.extend(&primary).push(0x00),
+            },
+            Request::StageNetworkConfig(_, timeout) => encoded.extend(&timeout.to_be_bytes()),
+            Request::ListComponentsRange(_, offset, limit)
+            | Request::ReadEeprom(_, offset, limit)
+            | Request::RetrieveErrorDumpRange(_, offset, limit) => {
+                encoded.extend(&offset.to_be_bytes()).push(limit)
+            }
+            Request::Batch(_, count) => encoded.push(count),
+            Request::Reset(_, kind) => encoded.push(kind.encode()),
+            Request::FactoryReset(_, token) => encoded.extend(&token),
+            Request::BeginFirmwareUpgrade(_, size, crc) => encoded
+                .extend(&size.to_be_bytes())
+                .extend(&crc.to_be_bytes()),
+            Request::FirmwareChunk(_, offset) => encoded.extend(&offset.to_be_bytes()),
+            Request::WriteEeprom(_, offset) => encoded.extend(&offset.to_be_bytes()),
+            Request::StoreProperty(_, len) | Request::RetrieveProperty(_, len) => encoded.push(len),
+            Request::TailLog(_, since) => encoded.extend(&since.to_be_bytes()),
+            Request::ReadAll(_)
+            | Request::DiscoverAll(_)
+            | Request::ListBuses(_)
+            | Request::ListPendingCommands(_)
+            | Request::Ping(_)
+            | Request::GetTime(_)
+            | Request::RequestChallenge(_)
+            | Request::CommitNetworkConfig(_)
+            | Request::ListComponents(_)
+            | Request::ListComponentsWithReportV1(_)
+            | Request::ListComponentsWithReportV2(_)
+            | Request::CommitFirmware(_)
+            | Request::RetrieveErrorDump(_)
+            | Request::ClearErrorDump(_)
+            | Request::RetrieveDeviceInformation(_)
+            | Request::RetrieveNetworkConfiguration(_)
+            | Request::RetrieveVersionInformation(_) => encoded,
+        };
+        (encoded.bytes, encoded.len)
+    }
+
+    /// Writes the request with as few calls of the writer as possible, the header and all
+    /// fields as a single [`Write::write_vectored`]
     pub fn write(&self, writer: &mut impl Write) -> Result<usize, Error> {
+        let header = &[u8::from(self.opcode()), self.id()][..];
         Ok(match *self {
-            Request::ReadSpecified(id, bus) => {
-                writer.write_u8(0x00)? + writer.write_u8(id)? + bus.write(writer)?
+            Request::ReadSpecified(_, bus)
+            | Request::ReadAllOnBus(_, bus)
+            | Request::ReadDiagnostics(_, bus)
+            | Request::DiscoverAllOnBus(_, bus)
+            | Request::GetOutput(_, bus) => {
+                let (bus, len) = bus.encode();
+                writer.write_vectored(&[header, &bus[..len]])?
             }
-            Request::ReadAll(id) => writer.write_u8(0x01)? + writer.write_u8(id)?,
-            Request::ReadAllOnBus(id, bus) => {
-                writer.write_u8(0x02)? + writer.write_u8(id)? + bus.write(writer)?
+            Request::SetOutput(_, bus, state) => {
+                let (bus, len) = bus.encode();
+                writer.write_vectored(&[header, &bus[..len], &state.encode()])?
             }
-            Request::DiscoverAll(id) => writer.write_u8(0x10)? + writer.write_u8(id)?,
-            Request::DiscoverAllOnBus(id, bus) => {
-                writer.write_u8(0x11)? + writer.write_u8(id)? + bus.write(writer)?
+            Request::CancelPendingCommand(_, pending_id) => {
+                writer.write_vectored(&[header, &[pending_id]])?
             }
-
-            Request::SetNetworkMac(id, mac) => {
-                writer.write_u8(0xA0)? + writer.write_u8(id)? + writer.write_all(&mac)?
+            Request::SetTime(_, millis) => {
+                writer.write_vectored(&[header, &millis.to_be_bytes()])?
             }
-            Request::SetNetworkIpSubnetGateway(id, ip, subnet, gateway) => {
-                writer.write_u8(0xA1)?
-                    + writer.write_u8(id)?
-                    + writer.write_all(&ip)?
-                    + writer.write_all(&subnet)?
-                    + writer.write_all(&gateway)?
+            Request::Pair(_, response) => writer.write_vectored(&[header, &response])?,
+            Request::SetNetworkMac(_, mac) => writer.write_vectored(&[header, &mac])?,
+            Request::SetNetworkIpSubnetGateway(_, ip, subnet, gateway) => {
+                writer.write_vectored(&[header, &ip, &subnet, &gateway])?
             }
-
-            Request::ListComponents(id) => writer.write_u8(0xD0)? + writer.write_u8(id)?,
-            Request::ListComponentsWithReportV1(id) => {
-                writer.write_u8(0xD1)? + writer.write_u8(id)?
+            Request::SetNetworkDns(_, primary, secondary) => match secondary {
+                Some(secondary) => {
+                    writer.write_vectored(&[header, &primary, &[0x01], &secondary])?
+                }
+                None => writer.write_vectored(&[header, &primary, &[0x00]])?,
+            },
+            Request::StageNetworkConfig(_, timeout) => {
+                writer.write_vectored(&[header, &timeout.to_be_bytes()])?
             }
-
-            Request::RetrieveProperty(id, len) => {
-                writer.write_u8(0xFB)? + writer.write_u8(id)? + writer.write_u8(len)?
+            Request::ListComponentsRange(_, offset, limit)
+            | Request::ReadEeprom(_, offset, limit)
+            | Request::RetrieveErrorDumpRange(_, offset, limit) => {
+                writer.write_vectored(&[header, &offset.to_be_bytes(), &[limit]])?
             }
-
-            Request::RetrieveErrorDump(id) => writer.write_u8(0xFC)? + writer.write_u8(id)?,
-            Request::RetrieveDeviceInformation(id) => {
-                writer.write_u8(0xFD)? + writer.write_u8(id)?
+            Request::Batch(_, count) => writer.write_vectored(&[header, &[count]])?,
+            Request::Reset(_, kind) => writer.write_vectored(&[header, &[kind.encode()]])?,
+            Request::FactoryReset(_, token) => writer.write_vectored(&[header, &token])?,
+            Request::BeginFirmwareUpgrade(_, size, crc) => {
+                writer.write_vectored(&[header, &size.to_be_bytes(), &crc.to_be_bytes()])?
             }
-            Request::RetrieveNetworkConfiguration(id) => {
-                writer.write_u8(0xFE)? + writer.write_u8(id)?
+            Request::FirmwareChunk(_, offset) => {
+                writer.write_vectored(&[header, &offset.to_be_bytes()])?
             }
-            Request::RetrieveVersionInformation(id) => {
-                writer.write_u8(0xFF)? + writer.write_u8(id)?
+            Request::WriteEeprom(_, offset) => {
+                writer.write_vectored(&[header, &offset.to_be_bytes()])?
             }
+            Request::StoreProperty(_, len) | Request::RetrieveProperty(_, len) => {
+                writer.write_vectored(&[header, &[len]])?
+            }
+            Request::TailLog(_, since) => writer.write_vectored(&[header, &since.to_be_bytes()])?,
+            Request::ReadAll(_)
+            | Request::DiscoverAll(_)
+            | Request::ListBuses(_)
+            | Request::ListPendingCommands(_)
+            | Request::Ping(_)
+            | Request::GetTime(_)
+            | Request::RequestChallenge(_)
+            | Request::CommitNetworkConfig(_)
+            | Request::ListComponents(_)
+            | Request::ListComponentsWithReportV1(_)
+            | Request::ListComponentsWithReportV2(_)
+            | Request::CommitFirmware(_)
+            | Request::RetrieveErrorDump(_)
+            | Request::ClearErrorDump(_)
+            | Request::RetrieveDeviceInformation(_)
+            | Request::RetrieveNetworkConfiguration(_)
+            | Request::RetrieveVersionInformation(_) => writer.write_all(header)?,
         })
     }
 
@@ -125,15 +581,42 @@ impl Request {
     }
 
     pub fn read(reader: &mut impl Read) -> Result<Request, Error> {
-        Ok(match reader.read_u8()? {
-            0x00 => Request::ReadSpecified(reader.read_u8()?, Bus::read(reader)?),
-            0x01 => Request::ReadAll(reader.read_u8()?),
-            0x02 => Request::ReadAllOnBus(reader.read_u8()?, Bus::read(reader)?),
-            0x10 => Request::DiscoverAll(reader.read_u8()?),
-            0x11 => Request::DiscoverAllOnBus(reader.read_u8()?, Bus::read(reader)?),
-
-            0xA0 => Request::SetNetworkMac(
-                reader.read_u8()?,
+        let opcode =
+            RequestOpcode::try_from(reader.read_u8()?).map_err(|_| Error::UnknownTypeIdentifier)?;
+        let id = reader.read_u8()?;
+        Ok(match opcode {
+            RequestOpcode::ReadSpecified => Request::ReadSpecified(id, Bus::read(reader)?),
+            RequestOpcode::ReadAll => Request::ReadAll(id),
+            RequestOpcode::ReadAllOnBus => Request::ReadAllOnBus(id, Bus::read(reader)?),
+            RequestOpcode::ReadDiagnostics => Request::ReadDiagnostics(id, Bus::read(reader)?),
+            RequestOpcode::DiscoverAll => Request::DiscoverAll(id),
+            RequestOpcode::DiscoverAllOnBus => Request::DiscoverAllOnBus(id, Bus::read(reader)?),
+            RequestOpcode::ListBuses => Request::ListBuses(id),
+
+            RequestOpcode::SetOutput => {
+                Request::SetOutput(id, Bus::read(reader)?, output::OutputState::read(reader)?)
+            }
+            RequestOpcode::GetOutput => Request::GetOutput(id, Bus::read(reader)?),
+
+            RequestOpcode::ListPendingCommands => Request::ListPendingCommands(id),
+            RequestOpcode::CancelPendingCommand => {
+                Request::CancelPendingCommand(id, reader.read_u8()?)
+            }
+
+            RequestOpcode::Ping => Request::Ping(id),
+
+            RequestOpcode::GetTime => Request::GetTime(id),
+            RequestOpcode::SetTime => Request::SetTime(id, reader.read_u64_be()?),
+
+            RequestOpcode::RequestChallenge => Request::RequestChallenge(id),
+            RequestOpcode::Pair => {
+                let mut response = [0u8; 8];
+                reader.read_all(&mut response)?;
+                Request::Pair(id, response)
+            }
+
+            RequestOpcode::SetNetworkMac => Request::SetNetworkMac(
+                id,
                 [
                     reader.read_u8()?,
                     reader.read_u8()?,
@@ -143,8 +626,14 @@ impl Request {
                     reader.read_u8()?,
                 ],
             ),
-            0xA1 => Request::SetNetworkIpSubnetGateway(
-                reader.read_u8()?,
+            RequestOpcode::SetNetworkIpSubnetGateway => Request::SetNetworkIpSubnetGateway(
+                id,
+                [
+                    reader.read_u8()?,
+                    reader.read_u8()?,
+                    reader.read_u8()?,
+                    reader.read_u8()?,
+                ],
                 [
                     reader.read_u8()?,
                     reader.read_u8()?,
@@ -157,47 +646,148 @@ impl Request {
                     reader.read_u8()?,
                     reader.read_u8()?,
                 ],
+            ),
+            RequestOpcode::SetNetworkDns => Request::SetNetworkDns(
+                id,
                 [
                     reader.read_u8()?,
                     reader.read_u8()?,
                     reader.read_u8()?,
                     reader.read_u8()?,
                 ],
+                match reader.read_u8()? {
+                    0x00 => None,
+                    0x01 => Some([
+                        reader.read_u8()?,
+                        reader.read_u8()?,
+                        reader.read_u8()?,
+                        reader.read_u8()?,
+                    ]),
+                    _ => return Err(Error::UnknownTypeIdentifier),
+                },
             ),
+            RequestOpcode::StageNetworkConfig => {
+                Request::StageNetworkConfig(id, reader.read_u16_be()?)
+            }
+            RequestOpcode::CommitNetworkConfig => Request::CommitNetworkConfig(id),
+
+            RequestOpcode::ListComponents => Request::ListComponents(id),
+            RequestOpcode::ListComponentsWithReportV1 => Request::ListComponentsWithReportV1(id),
+            RequestOpcode::ListComponentsWithReportV2 => Request::ListComponentsWithReportV2(id),
+            RequestOpcode::ListComponentsRange => {
+                Request::ListComponentsRange(id, reader.read_u16_be()?, reader.read_u8()?)
+            }
 
-            0xD0 => Request::ListComponents(reader.read_u8()?),
-            0xD1 => Request::ListComponentsWithReportV1(reader.read_u8()?),
+            RequestOpcode::Batch => Request::Batch(id, reader.read_u8()?),
 
-            0xFB => Request::RetrieveProperty(reader.read_u8()?, reader.read_u8()?),
-            0xFC => Request::RetrieveErrorDump(reader.read_u8()?),
-            0xFD => Request::RetrieveDeviceInformation(reader.read_u8()?),
-            0xFE => Request::RetrieveNetworkConfiguration(reader.read_u8()?),
-            0xFF => Request::RetrieveVersionInformation(reader.read_u8()?),
-            _ => return Err(Error::UnknownTypeIdentifier),
+            RequestOpcode::Reset => Request::Reset(id, ResetKind::read(reader)?),
+            RequestOpcode::FactoryReset => Request::FactoryReset(
+                id,
+                [
+                    reader.read_u8()?,
+                    reader.read_u8()?,
+                    reader.read_u8()?,
+                    reader.read_u8()?,
+                ],
+            ),
+
+            RequestOpcode::BeginFirmwareUpgrade => {
+                Request::BeginFirmwareUpgrade(id, reader.read_u32_be()?, reader.read_u32_be()?)
+            }
+            RequestOpcode::FirmwareChunk => Request::FirmwareChunk(id, reader.read_u32_be()?),
+            RequestOpcode::CommitFirmware => Request::CommitFirmware(id),
+
+            RequestOpcode::ReadEeprom => {
+                Request::ReadEeprom(id, reader.read_u16_be()?, reader.read_u8()?)
+            }
+            RequestOpcode::WriteEeprom => Request::WriteEeprom(id, reader.read_u16_be()?),
+
+            RequestOpcode::StoreProperty => Request::StoreProperty(id, reader.read_u8()?),
+            RequestOpcode::RetrieveProperty => Request::RetrieveProperty(id, reader.read_u8()?),
+            RequestOpcode::RetrieveErrorDump => Request::RetrieveErrorDump(id),
+            RequestOpcode::RetrieveErrorDumpRange => {
+                Request::RetrieveErrorDumpRange(id, reader.read_u16_be()?, reader.read_u8()?)
+            }
+            RequestOpcode::ClearErrorDump => Request::ClearErrorDump(id),
+            RequestOpcode::TailLog => Request::TailLog(id, reader.read_u32_be()?),
+            RequestOpcode::RetrieveDeviceInformation => Request::RetrieveDeviceInformation(id),
+            RequestOpcode::RetrieveNetworkConfiguration => {
+                Request::RetrieveNetworkConfiguration(id)
+            }
+            RequestOpcode::RetrieveVersionInformation => Request::RetrieveVersionInformation(id),
         })
     }
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Bus {
     OneWire,
+    /// The only or first I2C bus of the device with 7-bit addresses, same as
+    /// `Bus::I2cN { bus: 0, ten_bit: false }`
     I2C,
+    /// The I2C bus with the given index, for boards with several I2C peripherals. 10-bit
+    /// addresses are encoded as `u16` big endian, 7-bit addresses as `u8`.
+    I2cN {
+        bus: u8,
+        ten_bit: bool,
+    },
+    /// The Modbus-RTU device with the given unit id behind a gateway, see [`modbus`] for the
+    /// addressing of its registers
+    ModbusRtu {
+        unit: u8,
+    },
+    /// A CAN bus with standard 11-bit or extended 29-bit identifiers, see [`can`] for the
+    /// addressing of its nodes
+    Can {
+        extended: bool,
+    },
+    /// The directly wired analog inputs of the device. The address of a channel is its index
+    /// as `Type::U8`, so `ReadAllOnBus` is answered with
+    /// `Format::AddressValuePairs(Type::U8, _)` and `DiscoverAllOnBus` with
+    /// `Format::AddressOnly(Type::U8)`.
+    Analog,
     Custom(u8),
 }
 
 impl Bus {
+    pub const fn max_encoded_len() -> usize {
+        3
+    }
+
+    /// The encoded bus and the number of used bytes
+    pub const fn encode(&self) -> ([u8; Bus::max_encoded_len()], usize) {
+        match *self {
+            Bus::OneWire => ([0x00, 0, 0], 1),
+            Bus::I2C => ([0x01, 0, 0], 1),
+            Bus::I2cN { bus, ten_bit } => ([0x02, bus, ten_bit as u8], 3),
+            Bus::ModbusRtu { unit } => ([0x03, unit, 0], 2),
+            Bus::Can { extended } => ([0x04, extended as u8, 0], 2),
+            Bus::Analog => ([0x05, 0, 0], 1),
+            Bus::Custom(id) => ([0xFF, id, 0], 2),
+        }
+    }
+
     pub fn write(&self, writer: &mut impl Write) -> Result<usize, Error> {
-        Ok(match self {
-            Bus::OneWire => writer.write_u8(0x00)?,
-            Bus::I2C => writer.write_u8(0x01)?,
-            Bus::Custom(id) => writer.write_u8(0xFF)? + writer.write_u8(*id)?,
-        })
+        let (encoded, len) = self.encode();
+        writer.write_all(&encoded[..len])
     }
 
     pub fn read(reader: &mut impl Read) -> Result<Bus, Error> {
         Ok(match reader.read_u8()? {
             0x00 => Bus::OneWire,
             0x01 => Bus::I2C,
+            0x02 => Bus::I2cN {
+                bus: reader.read_u8()?,
+                ten_bit: reader.read_u8()? != 0x00,
+            },
+            0x03 => Bus::ModbusRtu {
+                unit: reader.read_u8()?,
+            },
+            0x04 => Bus::Can {
+                extended: reader.read_u8()? != 0x00,
+            },
+            0x05 => Bus::Analog,
             0xFF => Bus::Custom(reader.read_u8()?),
             _ => return Err(Error::UnknownTypeIdentifier),
         })
@@ -205,18 +795,81 @@ impl Bus {
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ResetKind {
+    /// Restarts the firmware without resetting the peripherals
+    Soft,
+    /// Resets the whole system, as if the reset pin was pulled
+    Hard,
+    /// Restarts into the bootloader, for example to flash a new firmware
+    ToBootloader,
+}
+
+impl ResetKind {
+    pub const fn encode(&self) -> u8 {
+        match self {
+            ResetKind::Soft => 0x00,
+            ResetKind::Hard => 0x01,
+            ResetKind::ToBootloader => 0x02,
+        }
+    }
+
+    pub fn write(&self, writer: &mut impl Write) -> Result<usize, Error> {
+        writer.write_u8(self.encode())
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<ResetKind, Error> {
+        Ok(match reader.read_u8()? {
+            0x00 => ResetKind::Soft,
+            0x01 => ResetKind::Hard,
+            0x02 => ResetKind::ToBootloader,
+            _ => return Err(Error::UnknownTypeIdentifier),
+        })
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Response {
     NotImplemented(u8),
     NotAvailable(u8),
+    /// The request could not be served for the given reason
+    Error(u8, ErrorCode),
     Ok(u8, Format),
+    /// Sent unsolicited to the multicast group of [`announcement`] with a sequence number
+    /// instead of a request id, followed by the latest readings in the given format
+    Announcement(u8, Format),
 }
 
 impl Response {
+    /// The maximum number of bytes [`Response::write`] writes for any response header, not
+    /// including the payload
+    pub const fn max_encoded_len() -> usize {
+        1 + 1 + Format::max_encoded_len()
+    }
+
     pub fn id(&self) -> u8 {
         match self {
             Response::NotImplemented(id) => *id,
             Response::NotAvailable(id) => *id,
+            Response::Error(id, _) => *id,
             Response::Ok(id, _) => *id,
+            Response::Announcement(sequence, _) => *sequence,
+        }
+    }
+
+    /// The format of the payload of a `Response::Ok` or `Response::Announcement`
+    pub fn format(&self) -> Option<Format> {
+        match self {
+            Response::Ok(_, format) | Response::Announcement(_, format) => Some(*format),
+            _ => None,
+        }
+    }
+
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        match self {
+            Response::Error(_, code) => Some(*code),
+            _ => None,
         }
     }
 
@@ -224,8 +877,12 @@ impl Response {
         Ok(match self {
             Response::NotImplemented(id) => writer.write_u8(0xF0)? + writer.write_u8(*id)?,
             Response::NotAvailable(id) => writer.write_u8(0xF1)? + writer.write_u8(*id)?,
-            Response::Ok(id, format) => {
-                writer.write_u8(0x00)? + writer.write_u8(*id)? + format.write(writer)?
+            Response::Error(id, code) => {
+                writer.write_u8(0xF2)? + writer.write_u8(*id)? + code.write(writer)?
+            }
+            Response::Ok(id, format) => format.write_with_header(&[0x00, *id], writer)?,
+            Response::Announcement(sequence, format) => {
+                format.write_with_header(&[0x01, *sequence], writer)?
             }
         })
     }
@@ -234,30 +891,151 @@ impl Response {
         Ok(match reader.read_u8()? {
             0xF0 => Response::NotImplemented(reader.read_u8()?),
             0xF1 => Response::NotAvailable(reader.read_u8()?),
+            0xF2 => Response::Error(reader.read_u8()?, ErrorCode::read(reader)?),
             0x00 => Response::Ok(reader.read_u8()?, Format::read(reader)?),
+            0x01 => Response::Announcement(reader.read_u8()?, Format::read(reader)?),
             _ => return Err(Error::UnknownTypeIdentifier),
         })
     }
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ErrorCode {
+    /// The device is busy, the request should be sent again later
+    BusyTryAgain,
+    /// The requested property path is longer than [`props::tree::PROPERTY_PATH_MAX_LEN`] or
+    /// shorter than announced by the request. Unknown paths are answered with
+    /// `Response::NotAvailable`.
+    InvalidPropertyPath,
+    PermissionDenied,
+    /// The request payload or the response exceeds the buffers of the device
+    PayloadTooLarge,
+    /// The value to store is outside the range of valid values of the property
+    ValueOutOfRange,
+    /// An error code unknown to this version of the protocol
+    Other(u8),
+}
+
+impl ErrorCode {
+    /// The error code of the encoded byte, [`ErrorCode::Other`] only for bytes that are not the
+    /// code of a named error
+    pub const fn from_code(code: u8) -> Self {
+        match code {
+            0x01 => ErrorCode::BusyTryAgain,
+            0x02 => ErrorCode::InvalidPropertyPath,
+            0x03 => ErrorCode::PermissionDenied,
+            0x04 => ErrorCode::PayloadTooLarge,
+            0x05 => ErrorCode::ValueOutOfRange,
+            code => ErrorCode::Other(code),
+        }
+    }
+
+    pub const fn code(&self) -> u8 {
+        match self {
+            ErrorCode::BusyTryAgain => 0x01,
+            ErrorCode::InvalidPropertyPath => 0x02,
+            ErrorCode::PermissionDenied => 0x03,
+            ErrorCode::PayloadTooLarge => 0x04,
+            ErrorCode::ValueOutOfRange => 0x05,
+            ErrorCode::Other(code) => *code,
+        }
+    }
+
+    /// Fails with [`Error::NonCanonical`] for an [`ErrorCode::Other`] with the code of a named
+    /// error, which would be read back as the named error
+    pub fn write(&self, writer: &mut impl Write) -> Result<usize, Error> {
+        if ErrorCode::from_code(self.code()) != *self {
+            return Err(Error::NonCanonical);
+        }
+        writer.write_u8(self.code())
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<ErrorCode, Error> {
+        Ok(ErrorCode::from_code(reader.read_u8()?))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Format {
     Empty,
     ValueOnly(Type),
     AddressOnly(Type),
     AddressValuePairs(Type, Type),
+    /// The answer to a successful property write, followed by the value as read back from the
+    /// property after it has been written
+    Echoed(Type),
+    /// Buffered measurements, each consisting of the address, the value and the time the value
+    /// has been sampled at
+    AddressValueTimestampTriples(Type, Type, Type),
+    /// Address-value pairs preceded by their number as big endian `u16`, see [`counted`]
+    CountedAddressValuePairs(Type, Type),
+    /// Address-value pairs with the [`status::SensorStatus`] of each sensor in between
+    AddressStatusValue(Type, Type),
 }
 
 impl Format {
+    pub const fn max_encoded_len() -> usize {
+        1 + 3 * Type::max_encoded_len()
+    }
+
+    /// The size in bytes of a single element of the payload (a value, an address or an
+    /// address-value pair), if all elements have the same, fixed size
+    pub const fn payload_element_size(&self) -> Option<usize> {
+        match self {
+            Format::Empty => Some(0),
+            Format::ValueOnly(t) | Format::AddressOnly(t) | Format::Echoed(t) => t.value_size(),
+            Format::AddressValuePairs(t1, t2) => match (t1.value_size(), t2.value_size()) {
+                (Some(s1), Some(s2)) => Some(s1 + s2),
+                _ => None,
+            },
+            Format::AddressValueTimestampTriples(t1, t2, t3) => {
+                match (t1.value_size(), t2.value_size(), t3.value_size()) {
+                    (Some(s1), Some(s2), Some(s3)) => Some(s1 + s2 + s3),
+                    _ => None,
+                }
+            }
+            // the elements are preceded by their count
+            Format::CountedAddressValuePairs(..) => None,
+            Format::AddressStatusValue(t1, t2) => match (t1.value_size(), t2.value_size()) {
+                (Some(s1), Some(s2)) => Some(s1 + 1 + s2),
+                _ => None,
+            },
+        }
+    }
+
     pub fn write(&self, writer: &mut impl Write) -> Result<usize, Error> {
-        Ok(match self {
-            Format::ValueOnly(t) => writer.write_u8(0x00)? + t.write(writer)?,
-            Format::AddressOnly(t) => writer.write_u8(0x01)? + t.write(writer)?,
-            Format::AddressValuePairs(t1, t2) => {
-                writer.write_u8(0x02)? + t1.write(writer)? + t2.write(writer)?
+        self.write_with_header(&[], writer)
+    }
+
+    /// Writes the header followed by the format as a single [`Write::write_vectored`]
+    fn write_with_header(&self, header: &[u8], writer: &mut impl Write) -> Result<usize, Error> {
+        let (id, types) = match *self {
+            Format::ValueOnly(t) => (0x00, [Some(t), None, None]),
+            Format::AddressOnly(t) => (0x01, [Some(t), None, None]),
+            Format::AddressValuePairs(t1, t2) => (0x02, [Some(t1), Some(t2), None]),
+            Format::Echoed(t) => (0x03, [Some(t), None, None]),
+            Format::AddressValueTimestampTriples(t1, t2, t3) => {
+                (0x04, [Some(t1), Some(t2), Some(t3)])
             }
-            Format::Empty => writer.write_u8(0xFF)?,
-        })
+            Format::CountedAddressValuePairs(t1, t2) => (0x05, [Some(t1), Some(t2), None]),
+            Format::AddressStatusValue(t1, t2) => (0x06, [Some(t1), Some(t2), None]),
+            Format::Empty => (0xFF, [None, None, None]),
+        };
+        let mut encoded = [([0u8; Type::max_encoded_len()], 0); 3];
+        for (encoded, ty) in encoded.iter_mut().zip(types.iter()) {
+            if let Some(ty) = ty {
+                *encoded = ty.encode();
+            }
+        }
+        writer.write_vectored(&[
+            header,
+            &[id],
+            &encoded[0].0[..encoded[0].1],
+            &encoded[1].0[..encoded[1].1],
+            &encoded[2].0[..encoded[2].1],
+        ])
     }
 
     pub fn read(reader: &mut impl Read) -> Result<Format, Error> {
@@ -265,6 +1043,14 @@ impl Format {
             0x00 => Format::ValueOnly(Type::read(reader)?),
             0x01 => Format::AddressOnly(Type::read(reader)?),
             0x02 => Format::AddressValuePairs(Type::read(reader)?, Type::read(reader)?),
+            0x03 => Format::Echoed(Type::read(reader)?),
+            0x04 => Format::AddressValueTimestampTriples(
+                Type::read(reader)?,
+                Type::read(reader)?,
+                Type::read(reader)?,
+            ),
+            0x05 => Format::CountedAddressValuePairs(Type::read(reader)?, Type::read(reader)?),
+            0x06 => Format::AddressStatusValue(Type::read(reader)?, Type::read(reader)?),
             0xFF => Format::Empty,
             _ => return Err(Error::UnknownTypeIdentifier),
         })
@@ -272,8 +1058,30 @@ impl Format {
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Type {
     F32,
+    F64,
+    /// A single byte, `0x00` for `false` and any other value for `true`
+    Bool,
+    /// A fixed-point value, encoded as `i32` that has to be multiplied by `10^exponent`
+    ScaledI32 {
+        exponent: i8,
+    },
+    /// Milliseconds since the unix epoch, as `u64`
+    TimestampMillisU64,
+    /// Seconds since the unix epoch, as `u32`
+    TimestampSecondsU32,
+    /// A list of values of the same type, prefixed by the number of values as `u8`
+    ListOf(ListElement),
+    /// A length prefixed area of type-length-value encoded entries, see [`tlv`]
+    Tlv,
+    /// A 128-bit unique identifier, for example the serial number of a device
+    Uuid,
+    /// A monotonically increasing count of events as `u64`, like the pulses of a S0 energy
+    /// meter, that wraps around to `0` after `u64::MAX`. The difference of two readings is
+    /// therefore `current.wrapping_sub(previous)`, see [`value::counter_delta`].
+    CounterU64,
     Bytes(u8),
     String(u8),
     PropertyId,
@@ -281,6 +1089,16 @@ pub enum Type {
     DynBytes,
 
     DynListPropertyReportV1,
+    DeviceInformation,
+    NetworkConfiguration,
+    DynListErrorDumpEntry,
+    VersionInformation,
+    Batch,
+    DynListPropertyReportV2,
+    DynListBusInformation,
+    SensorDiagnostics,
+    OutputState,
+    DynListLogRecord,
 
     U128,
     I128,
@@ -295,40 +1113,160 @@ pub enum Type {
 }
 
 impl Type {
+    pub const fn max_encoded_len() -> usize {
+        1 + ListElement::MAX_ENCODED_LEN
+    }
+
+    /// The size in bytes of a single value of this type, if it is fixed
+    pub const fn value_size(&self) -> Option<usize> {
+        match self {
+            Type::F32 => Some(4),
+            Type::F64 => Some(8),
+            Type::Bool => Some(1),
+            Type::Uuid => Some(16),
+            Type::CounterU64 => Some(8),
+            Type::ScaledI32 { .. } => Some(4),
+            Type::TimestampMillisU64 => Some(8),
+            Type::TimestampSecondsU32 => Some(4),
+            Type::ListOf(_) | Type::Tlv => None,
+            Type::Bytes(size) | Type::String(size) => Some(*size as usize),
+            Type::PropertyId
+            | Type::DynString
+            | Type::DynBytes
+            | Type::DynListPropertyReportV1
+            | Type::DeviceInformation
+            | Type::NetworkConfiguration
+            | Type::DynListErrorDumpEntry
+            | Type::Batch
+            | Type::DynListPropertyReportV2
+            | Type::DynListBusInformation
+            | Type::DynListLogRecord
+            | Type::VersionInformation => None,
+            Type::SensorDiagnostics => Some(status::SensorDiagnostics::ENCODED_LEN),
+            Type::OutputState => Some(output::OutputState::ENCODED_LEN),
+            Type::U128 | Type::I128 => Some(16),
+            Type::U64 | Type::I64 => Some(8),
+            Type::U32 | Type::I32 => Some(4),
+            Type::U16 | Type::I16 => Some(2),
+            Type::U8 | Type::I8 => Some(1),
+        }
+    }
+
+    /// The encoded type and the number of used bytes
+    pub const fn encode(&self) -> ([u8; Type::max_encoded_len()], usize) {
+        let mut encoded = [0u8; Type::max_encoded_len()];
+        let (opcode, parameter) = match self {
+            Type::F32 => (0x00, None),
+            Type::Bytes(size) => (0x01, Some(*size)),
+            Type::String(size) => (0x02, Some(*size)),
+            Type::PropertyId => (0x03, None),
+            Type::DynString => (0x04, None),
+            Type::DynBytes => (0x05, None),
+            Type::F64 => (0x06, None),
+            Type::Bool => (0x07, None),
+            Type::ScaledI32 { exponent } => (0x08, Some(*exponent as u8)),
+            Type::TimestampMillisU64 => (0x09, None),
+            Type::TimestampSecondsU32 => (0x0A, None),
+            Type::ListOf(element) => {
+                encoded[0] = 0x0B;
+                let mut i = 0;
+                while i < element.len as usize {
+                    encoded[1 + i] = element.encoded[i];
+                    i += 1;
+                }
+                return (encoded, 1 + element.len as usize);
+            }
+            Type::Tlv => (0x0C, None),
+            Type::Uuid => (0x0D, None),
+            Type::CounterU64 => (0x0E, None),
+
+            Type::DynListPropertyReportV1 => (0xC0, None),
+            Type::DeviceInformation => (0xC1, None),
+            Type::NetworkConfiguration => (0xC2, None),
+            Type::DynListErrorDumpEntry => (0xC3, None),
+            Type::VersionInformation => (0xC4, None),
+            Type::Batch => (0xC5, None),
+            Type::DynListPropertyReportV2 => (0xC6, None),
+            Type::DynListBusInformation => (0xC7, None),
+            Type::SensorDiagnostics => (0xC8, None),
+            Type::OutputState => (0xC9, None),
+            Type::DynListLogRecord => (0xCA, None),
+
+            Type::U128 => (0xF6, None),
+            Type::I128 => (0xF7, None),
+            Type::U64 => (0xF8, None),
+            Type::I64 => (0xF9, None),
+            Type::U32 => (0xFA, None),
+            Type::I32 => (0xFB, None),
+            Type::U16 => (0xFC, None),
+            Type::I16 => (0xFD, None),
+            Type::U8 => (0xFE, None),
+            Type::I8 => (0xFF, None),
+        };
+        encoded[0] = opcode;
+        if let Some(parameter) = parameter {
+            encoded[1] = parameter;
+            (encoded, 2)
+        } else {
+            (encoded, 1)
+        }
+    }
+
     pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
-        Ok(match self {
-            Type::F32 => writer.write_u8(0x00)?,
-            Type::Bytes(size) => writer.write_u8(0x01)? + writer.write_u8(*size)?,
-            Type::String(size) => writer.write_u8(0x02)? + writer.write_u8(*size)?,
-            Type::PropertyId => writer.write_u8(0x03)?,
-            Type::DynString => writer.write_u8(0x04)?,
-            Type::DynBytes => writer.write_u8(0x05)?,
-
-            Type::DynListPropertyReportV1 => writer.write_u8(0xC0)?,
-
-            Type::U128 => writer.write_u8(0xF6)?,
-            Type::I128 => writer.write_u8(0xF7)?,
-            Type::U64 => writer.write_u8(0xF8)?,
-            Type::I64 => writer.write_u8(0xF9)?,
-            Type::U32 => writer.write_u8(0xFA)?,
-            Type::I32 => writer.write_u8(0xFB)?,
-            Type::U16 => writer.write_u8(0xFC)?,
-            Type::I16 => writer.write_u8(0xFD)?,
-            Type::U8 => writer.write_u8(0xFE)?,
-            Type::I8 => writer.write_u8(0xFF)?,
-        })
+        let (encoded, len) = self.encode();
+        writer.write_all(&encoded[..len])
     }
 
     pub fn read(reader: &mut dyn Read) -> Result<Type, Error> {
-        Ok(match reader.read_u8()? {
+        Self::read_with_opcode(reader.read_u8()?, reader)
+    }
+
+    fn read_with_opcode(opcode: u8, reader: &mut dyn Read) -> Result<Type, Error> {
+        Ok(match opcode {
             0x00 => Type::F32,
             0x01 => Type::Bytes(reader.read_u8()?),
             0x02 => Type::String(reader.read_u8()?),
             0x03 => Type::PropertyId,
             0x04 => Type::DynString,
             0x05 => Type::DynBytes,
+            0x06 => Type::F64,
+            0x07 => Type::Bool,
+            0x08 => Type::ScaledI32 {
+                exponent: reader.read_u8()? as i8,
+            },
+            0x09 => Type::TimestampMillisU64,
+            0x0A => Type::TimestampSecondsU32,
+            0x0B => {
+                // read the nested lists iteratively to limit the stack usage
+                let mut depth = 1;
+                let mut element = loop {
+                    match reader.read_u8()? {
+                        0x0B if depth < ListElement::MAX_ENCODED_LEN => depth += 1,
+                        0x0B => return Err(Error::NestingTooDeep),
+                        opcode => break Self::read_with_opcode(opcode, reader)?,
+                    }
+                };
+                for _ in 0..depth {
+                    element =
+                        Type::ListOf(ListElement::try_new(element).ok_or(Error::NestingTooDeep)?);
+                }
+                element
+            }
+            0x0C => Type::Tlv,
+            0x0D => Type::Uuid,
+            0x0E => Type::CounterU64,
 
             0xC0 => Type::DynListPropertyReportV1,
+            0xC1 => Type::DeviceInformation,
+            0xC2 => Type::NetworkConfiguration,
+            0xC3 => Type::DynListErrorDumpEntry,
+            0xC4 => Type::VersionInformation,
+            0xC5 => Type::Batch,
+            0xC6 => Type::DynListPropertyReportV2,
+            0xC7 => Type::DynListBusInformation,
+            0xC8 => Type::SensorDiagnostics,
+            0xC9 => Type::OutputState,
+            0xCA => Type::DynListLogRecord,
 
             0xF6 => Type::U128,
             0xF7 => Type::I128,
@@ -346,9 +1284,77 @@ impl Type {
     }
 }
 
+/// The encoded type of the elements of a [`Type::ListOf`], which allows lists to be nested
+/// while [`Type`] stays `Copy` and can be used in `static`s.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ListElement {
+    encoded: [u8; ListElement::MAX_ENCODED_LEN],
+    len: u8,
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for ListElement {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        ListElement::try_new(u.arbitrary()?).ok_or(arbitrary::Error::IncorrectFormat)
+    }
+}
+
+impl ListElement {
+    pub const MAX_ENCODED_LEN: usize = 6;
+
+    /// Panics if the type is nested too deep to be encoded in [`Self::MAX_ENCODED_LEN`] bytes
+    pub const fn new(ty: Type) -> Self {
+        match Self::try_new(ty) {
+            Some(element) => element,
+            None => panic!("The list element type is nested too deep"),
+        }
+    }
+
+    pub const fn try_new(ty: Type) -> Option<Self> {
+        let (encoded, len) = ty.encode();
+        if len > Self::MAX_ENCODED_LEN {
+            return None;
+        }
+
+        let mut element = ListElement {
+            encoded: [0u8; Self::MAX_ENCODED_LEN],
+            len: len as u8,
+        };
+        let mut i = 0;
+        while i < len {
+            element.encoded[i] = encoded[i];
+            i += 1;
+        }
+        Some(element)
+    }
+
+    pub fn ty(&self) -> Type {
+        // always valid, since it has been encoded by `Self::try_new`
+        Type::read(&mut &self.encoded[..usize::from(self.len)]).unwrap_or(Type::DynBytes)
+    }
+}
+
 pub trait Read {
     fn read_u8(&mut self) -> Result<u8, Error>;
 
+    /// Returns the next byte without consuming it. Readers that cannot look ahead fail with
+    /// [`Error::Unsupported`], which is what the default implementation does.
+    fn peek_u8(&self) -> Result<u8, Error> {
+        Err(Error::Unsupported)
+    }
+
+    /// Consumes the next `n` bytes without looking at them
+    fn skip(&mut self, n: usize) -> Result<usize, Error> {
+        if self.available() < n {
+            Err(Error::UnexpectedEOF)
+        } else {
+            for _ in 0..n {
+                self.read_u8()?;
+            }
+            Ok(n)
+        }
+    }
+
     fn read_all(&mut self, destination: &mut [u8]) -> Result<u8, Error> {
         let len = destination.len().min(u8::MAX as usize) as u8;
         if self.available() < usize::from(len) {
@@ -361,18 +1367,58 @@ pub trait Read {
         }
     }
 
+    /// Returns a reader that reads at most `limit` bytes from this reader
+    fn take(&mut self, limit: usize) -> cursor::Take<'_, Self>
+    where
+        Self: Sized,
+    {
+        cursor::Take::new(self, limit)
+    }
+
     fn available(&self) -> usize;
 
-    #[cfg(feature = "std")]
-    fn read_dyn_string(&mut self) -> Result<String, Error> {
-        self.read_dyn_bytes()
-            .and_then(|bytes| String::from_utf8(bytes).map_err(|_| Error::InvalidUtf8))
+    #[inline]
+    fn read_u16_be(&mut self) -> Result<u16, Error> {
+        let mut bytes = [0u8; 2];
+        self.read_all(&mut bytes)?;
+        Ok(u16::from_be_bytes(bytes))
+    }
+
+    #[inline]
+    fn read_u32_be(&mut self) -> Result<u32, Error> {
+        let mut bytes = [0u8; 4];
+        self.read_all(&mut bytes)?;
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    #[inline]
+    fn read_u64_be(&mut self) -> Result<u64, Error> {
+        let mut bytes = [0u8; 8];
+        self.read_all(&mut bytes)?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    #[inline]
+    fn read_i32_be(&mut self) -> Result<i32, Error> {
+        self.read_u32_be().map(|value| value as i32)
+    }
+
+    #[inline]
+    fn read_f32_be(&mut self) -> Result<f32, Error> {
+        self.read_u32_be().map(f32::from_bits)
     }
 
-    #[cfg(feature = "std")]
-    fn read_dyn_bytes(&mut self) -> Result<Vec<u8>, Error> {
+    #[cfg(feature = "alloc")]
+    fn read_dyn_string(&mut self) -> Result<alloc::string::String, Error> {
+        self.read_dyn_bytes().and_then(|bytes| {
+            alloc::string::String::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)
+        })
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_dyn_bytes(&mut self) -> Result<alloc::vec::Vec<u8>, Error> {
         let len = self.read_u8()?;
-        let mut bytes = vec![0x00; usize::from(len)];
+        let mut bytes = alloc::vec![0x00; usize::from(len)];
         self.read_all(&mut bytes)?;
         Ok(bytes)
     }
@@ -389,6 +1435,19 @@ impl<'a> Read for &'a [u8] {
         }
     }
 
+    fn peek_u8(&self) -> Result<u8, Error> {
+        self.first().copied().ok_or(Error::UnexpectedEOF)
+    }
+
+    fn skip(&mut self, n: usize) -> Result<usize, Error> {
+        if self.len() < n {
+            Err(Error::UnexpectedEOF)
+        } else {
+            *self = &self[n..];
+            Ok(n)
+        }
+    }
+
     fn available(&self) -> usize {
         self.len()
     }
@@ -399,6 +1458,39 @@ pub trait Write {
 
     fn available(&self) -> usize;
 
+    /// Returns a writer that writes at most `limit` bytes to this writer
+    fn limit(&mut self, limit: usize) -> cursor::Limit<'_, Self>
+    where
+        Self: Sized,
+    {
+        cursor::Limit::new(self, limit)
+    }
+
+    #[inline]
+    fn write_u16_be(&mut self, value: u16) -> Result<usize, Error> {
+        self.write_all(&value.to_be_bytes())
+    }
+
+    #[inline]
+    fn write_u32_be(&mut self, value: u32) -> Result<usize, Error> {
+        self.write_all(&value.to_be_bytes())
+    }
+
+    #[inline]
+    fn write_u64_be(&mut self, value: u64) -> Result<usize, Error> {
+        self.write_all(&value.to_be_bytes())
+    }
+
+    #[inline]
+    fn write_i32_be(&mut self, value: i32) -> Result<usize, Error> {
+        self.write_all(&value.to_be_bytes())
+    }
+
+    #[inline]
+    fn write_f32_be(&mut self, value: f32) -> Result<usize, Error> {
+        self.write_all(&value.to_be_bytes())
+    }
+
     #[inline]
     fn write_dyn_string(&mut self, string: &str) -> Result<usize, Error> {
         self.write_dyn_bytes(string.as_bytes())
@@ -420,6 +1512,20 @@ pub trait Write {
             Ok(bytes.len())
         }
     }
+
+    /// Writes the slices one after another, nothing is written if they do not fit as a whole.
+    /// Writers that can pass on several slices at once, like a DMA descriptor chain, should
+    /// override this.
+    fn write_vectored(&mut self, slices: &[&[u8]]) -> Result<usize, Error> {
+        let len = slices.iter().map(|slice| slice.len()).sum::<usize>();
+        if self.available() < len {
+            return Err(Error::BufferToSmall);
+        }
+        for slice in slices {
+            self.write_all(slice)?;
+        }
+        Ok(len)
+    }
 }
 
 impl<'a> Write for &'a mut [u8] {
@@ -438,8 +1544,8 @@ impl<'a> Write for &'a mut [u8] {
     }
 }
 
-#[cfg(feature = "std")]
-impl Write for Vec<u8> {
+#[cfg(feature = "alloc")]
+impl Write for alloc::vec::Vec<u8> {
     fn write_u8(&mut self, value: u8) -> Result<usize, Error> {
         self.push(value);
         Ok(1)
@@ -453,4 +1559,13 @@ impl Write for Vec<u8> {
         self.extend_from_slice(bytes);
         Ok(bytes.len())
     }
+
+    fn write_vectored(&mut self, slices: &[&[u8]]) -> Result<usize, Error> {
+        let len = slices.iter().map(|slice| slice.len()).sum::<usize>();
+        self.reserve(len);
+        for slice in slices {
+            self.extend_from_slice(slice);
+        }
+        Ok(len)
+    }
 }
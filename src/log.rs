@@ -0,0 +1,225 @@
+//! Log records of a device for debugging in the field.
+//!
+//! A device keeps its most recent log records, each with a sequence number that increments
+//! with every record. [`crate::Request::TailLog`] asks for the records starting at a sequence
+//! number and is answered with `Response::Ok(id, Format::ValueOnly(Type::DynListLogRecord))`
+//! followed by as many [`LogRecord`]s, oldest first, as fit into the response, see
+//! [`write_tail`]. A client tails the log by asking for the sequence number following the last
+//! record it received.
+
+use crate::{Error, Format, Read, Response, Type, Write};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        writer.write_u8(match self {
+            LogLevel::Error => 0x00,
+            LogLevel::Warn => 0x01,
+            LogLevel::Info => 0x02,
+            LogLevel::Debug => 0x03,
+            LogLevel::Trace => 0x04,
+        })
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        Ok(match reader.read_u8()? {
+            0x00 => LogLevel::Error,
+            0x01 => LogLevel::Warn,
+            0x02 => LogLevel::Info,
+            0x03 => LogLevel::Debug,
+            0x04 => LogLevel::Trace,
+            _ => return Err(Error::UnknownTypeIdentifier),
+        })
+    }
+}
+
+/// A single record of the answer to a [`crate::Request::TailLog`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LogRecord<'a> {
+    /// Increments with every record, wrapping around after `u32::MAX`
+    pub sequence: u32,
+    pub level: LogLevel,
+    /// The uptime in milliseconds at which the record was logged
+    pub timestamp: u64,
+    /// The part of the firmware that logged the record, at most 255 bytes
+    pub module: &'a str,
+    /// The message, which is usually but not necessarily text, at most 255 bytes
+    pub message: &'a [u8],
+}
+
+impl<'a> LogRecord<'a> {
+    pub const fn encoded_len(&self) -> usize {
+        4 + 1 + 8 + 1 + self.module.len() + 1 + self.message.len()
+    }
+
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        Ok(writer.write_u32_be(self.sequence)?
+            + self.level.write(writer)?
+            + writer.write_u64_be(self.timestamp)?
+            + writer.write_dyn_bytes(self.module.as_bytes())?
+            + writer.write_dyn_bytes(self.message)?)
+    }
+
+    /// Reads the next record from the slice, which is advanced accordingly
+    pub fn read(reader: &mut &'a [u8]) -> Result<Self, Error> {
+        let sequence = reader.read_u32_be()?;
+        let level = LogLevel::read(reader)?;
+        let timestamp = reader.read_u64_be()?;
+        let module = core::str::from_utf8(take_dyn(reader)?).map_err(|_| Error::InvalidUtf8)?;
+        let message = take_dyn(reader)?;
+        Ok(LogRecord {
+            sequence,
+            level,
+            timestamp,
+            module,
+            message,
+        })
+    }
+}
+
+fn take_dyn<'a>(reader: &mut &'a [u8]) -> Result<&'a [u8], Error> {
+    let len = usize::from(reader.read_u8()?);
+    if reader.len() < len {
+        return Err(Error::UnexpectedEOF);
+    }
+    let (bytes, remaining) = reader.split_at(len);
+    *reader = remaining;
+    Ok(bytes)
+}
+
+/// Writes the response to a [`crate::Request::TailLog`] with the records, oldest first, whose
+/// sequence number is not before `since`, or with all records if `since` is `0`. Stops before
+/// the first record that would exceed `max_len` bytes of response, the client asks for the
+/// remaining records with the next request.
+///
+/// Because `0` asks for all records, a client that resumes at sequence number `0` after the
+/// sequence numbers wrapped around receives the whole log again. Clients should therefore drop
+/// records they already received.
+pub fn write_tail<'r>(
+    response_writer: &mut impl Write,
+    request_id: u8,
+    records: impl IntoIterator<Item = LogRecord<'r>>,
+    since: u32,
+    max_len: usize,
+) -> Result<usize, Error> {
+    let mut len = Response::Ok(request_id, Format::ValueOnly(Type::DynListLogRecord))
+        .write(response_writer)?;
+    for record in records
+        .into_iter()
+        .filter(|record| since == 0 || !is_before(record.sequence, since))
+    {
+        if len + record.encoded_len() > max_len {
+            break;
+        }
+        len += record.write(response_writer)?;
+    }
+    Ok(len)
+}
+
+/// Whether the sequence number is before the other one, considering the wrap around
+pub const fn is_before(sequence: u32, other: u32) -> bool {
+    (sequence.wrapping_sub(other) as i32) < 0
+}
+
+/// Decodes [`LogRecord`]s from the payload of a log response until the payload is exhausted.
+/// Stops after the first malformed record.
+pub struct LogRecordIter<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> LogRecordIter<'a> {
+    pub const fn new(payload: &'a [u8]) -> Self {
+        Self { remaining: payload }
+    }
+}
+
+impl<'a> Iterator for LogRecordIter<'a> {
+    type Item = Result<LogRecord<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            None
+        } else {
+            let result = LogRecord::read(&mut self.remaining);
+            if result.is_err() {
+                self.remaining = &[];
+            }
+            Some(result)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+
+    #[test]
+    fn tail_starts_at_sequence_and_fits_into_response() {
+        let records = (u32::MAX - 2..=u32::MAX)
+            .chain(0..3)
+            .map(|sequence| LogRecord {
+                sequence,
+                level: LogLevel::Info,
+                timestamp: u64::from(sequence),
+                module: "net",
+                message: b"link up",
+            });
+        let mut buffer = [0u8; 128];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let record_len = 4 + 1 + 8 + 1 + 3 + 1 + 7;
+        write_tail(&mut cursor, 7, records, u32::MAX, 4 + 3 * record_len).unwrap();
+
+        let (header, payload) = cursor.written().split_at(4);
+        assert_eq!(&[0x00, 7, 0x00, 0xCA], header);
+        let mut records = LogRecordIter::new(payload);
+        let first = records.next().unwrap().unwrap();
+        assert_eq!(u32::MAX, first.sequence);
+        assert_eq!("net", first.module);
+        assert_eq!(&b"link up"[..], first.message);
+        assert_eq!(Some(0), records.next().map(|r| r.unwrap().sequence));
+        assert_eq!(Some(1), records.next().map(|r| r.unwrap().sequence));
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn tail_since_zero_starts_at_the_oldest_record() {
+        let records = [0x8000_0000, 0x8000_0001]
+            .iter()
+            .map(|&sequence| LogRecord {
+                sequence,
+                level: LogLevel::Debug,
+                timestamp: 0,
+                module: "",
+                message: b"",
+            });
+        let mut buffer = [0u8; 64];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        write_tail(&mut cursor, 7, records.clone(), 0, 64).unwrap();
+        let mut sequences = LogRecordIter::new(&cursor.written()[4..]).map(|r| r.unwrap().sequence);
+        assert_eq!(Some(0x8000_0000), sequences.next());
+        assert_eq!(Some(0x8000_0001), sequences.next());
+        assert_eq!(None, sequences.next());
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        write_tail(&mut cursor, 7, records, 0x8000_0002, 64).unwrap();
+        assert_eq!(4, cursor.written().len());
+    }
+
+    #[test]
+    fn sequence_numbers_wrap_around() {
+        assert!(is_before(1, 2));
+        assert!(!is_before(2, 2));
+        assert!(is_before(u32::MAX, 0));
+        assert!(!is_before(0, u32::MAX));
+        assert!(is_before(0x8000_0000, 0));
+    }
+}
@@ -0,0 +1,124 @@
+//! Addressing of Modbus registers behind a [`crate::Bus::ModbusRtu`] bridge.
+//!
+//! The address of a register in the `AddressValuePairs` of a response and in the payload of a
+//! `ReadSpecified` request is the [`RegisterKind`] followed by the register number as `u16` big
+//! endian, which results in an address type of `Type::Bytes(3)`. The value type is `Type::U16`
+//! for registers and `Type::Bool` for coils and discrete inputs.
+
+use crate::{Error, Type};
+
+/// The kind of a register, encoded as the Modbus function code to read it
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RegisterKind {
+    Coil,
+    DiscreteInput,
+    HoldingRegister,
+    InputRegister,
+}
+
+impl RegisterKind {
+    pub const fn function_code(self) -> u8 {
+        match self {
+            RegisterKind::Coil => 0x01,
+            RegisterKind::DiscreteInput => 0x02,
+            RegisterKind::HoldingRegister => 0x03,
+            RegisterKind::InputRegister => 0x04,
+        }
+    }
+
+    pub const fn from_function_code(code: u8) -> Option<Self> {
+        match code {
+            0x01 => Some(RegisterKind::Coil),
+            0x02 => Some(RegisterKind::DiscreteInput),
+            0x03 => Some(RegisterKind::HoldingRegister),
+            0x04 => Some(RegisterKind::InputRegister),
+            _ => None,
+        }
+    }
+
+    /// The type of the values of registers of this kind
+    pub const fn value_type(self) -> Type {
+        match self {
+            RegisterKind::Coil | RegisterKind::DiscreteInput => Type::Bool,
+            RegisterKind::HoldingRegister | RegisterKind::InputRegister => Type::U16,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Register {
+    pub kind: RegisterKind,
+    pub number: u16,
+}
+
+impl Register {
+    pub const ADDRESS_LEN: usize = 3;
+    pub const ADDRESS_TYPE: Type = Type::Bytes(Self::ADDRESS_LEN as u8);
+
+    pub const fn new(kind: RegisterKind, number: u16) -> Self {
+        Self { kind, number }
+    }
+
+    pub const fn to_address(self) -> [u8; Self::ADDRESS_LEN] {
+        let number = self.number.to_be_bytes();
+        [self.kind.function_code(), number[0], number[1]]
+    }
+
+    pub fn from_address(address: &[u8]) -> Result<Self, Error> {
+        match address {
+            [code, high, low] => Ok(Register {
+                kind: RegisterKind::from_function_code(*code)
+                    .ok_or(Error::UnknownTypeIdentifier)?,
+                number: u16::from_be_bytes([*high, *low]),
+            }),
+            _ => Err(Error::UnexpectedEOF),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_address_roundtrip() {
+        for kind in [
+            RegisterKind::Coil,
+            RegisterKind::DiscreteInput,
+            RegisterKind::HoldingRegister,
+            RegisterKind::InputRegister,
+        ] {
+            for number in [0, 0x1234, u16::MAX] {
+                let register = Register::new(kind, number);
+                let address = register.to_address();
+                assert_eq!(Register::ADDRESS_LEN, address.len());
+                assert_eq!(Ok(register), Register::from_address(&address));
+            }
+        }
+        assert_eq!(
+            [0x03, 0x12, 0x34],
+            Register::new(RegisterKind::HoldingRegister, 0x1234).to_address()
+        );
+    }
+
+    #[test]
+    fn invalid_register_addresses_are_rejected() {
+        assert_eq!(
+            Err(Error::UnknownTypeIdentifier),
+            Register::from_address(&[0x05, 0x00, 0x01])
+        );
+        assert_eq!(
+            Err(Error::UnknownTypeIdentifier),
+            Register::from_address(&[0x00, 0x00, 0x01])
+        );
+        assert_eq!(
+            Err(Error::UnexpectedEOF),
+            Register::from_address(&[0x03, 0x00])
+        );
+        assert_eq!(
+            Err(Error::UnexpectedEOF),
+            Register::from_address(&[0x03, 0x00, 0x01, 0x00])
+        );
+        assert_eq!(Err(Error::UnexpectedEOF), Register::from_address(&[]));
+    }
+}
@@ -0,0 +1,193 @@
+//! Two-phase changes of the network configuration.
+//!
+//! A device given a wrong address through `Request::SetNetworkIpSubnetGateway` can no longer be
+//! reached to correct it. Instead:
+//!
+//! 1. The client sends `Request::StageNetworkConfig(id, timeout)` followed by the new
+//!    [`NetworkConfiguration`]. The device answers with `Response::Ok(id, Format::Empty)` and
+//!    applies the configuration afterwards.
+//! 2. The client sends `Request::CommitNetworkConfig(id)` to the new address. The device answers
+//!    with `Response::Ok(id, Format::Empty)` and keeps the configuration.
+//!
+//! If the commit does not arrive within the timeout in seconds, the device reverts to the
+//! previous configuration. [`NetworkTransaction`] implements this for the firmware.
+
+use crate::info::NetworkConfiguration;
+use crate::{Error, ErrorCode, Format, Read, Request, Response, Write};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum State {
+    Idle,
+    /// Acknowledged but not yet applied
+    Staged {
+        previous: NetworkConfiguration,
+        staged: NetworkConfiguration,
+        timeout_millis: u64,
+    },
+    /// Applied and waiting for the commit
+    Applied {
+        previous: NetworkConfiguration,
+        deadline_millis: u64,
+    },
+}
+
+/// The state machine of the device side of a network configuration change
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NetworkTransaction {
+    state: State,
+}
+
+impl Default for NetworkTransaction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NetworkTransaction {
+    pub const fn new() -> Self {
+        Self { state: State::Idle }
+    }
+
+    /// Whether a staged configuration has not been committed yet
+    pub fn is_pending(&self) -> bool {
+        !matches!(self.state, State::Idle)
+    }
+
+    /// Answers a `Request::StageNetworkConfig` or `Request::CommitNetworkConfig`, returns `None`
+    /// for any other request. A configuration is not staged while another one is pending,
+    /// which is answered with `ErrorCode::BusyTryAgain`.
+    pub fn respond(
+        &mut self,
+        request: &Request,
+        payload: &mut impl Read,
+        current: &NetworkConfiguration,
+        response_writer: &mut impl Write,
+    ) -> Option<Result<usize, Error>> {
+        let response = match *request {
+            Request::StageNetworkConfig(id, timeout_seconds) => {
+                match NetworkConfiguration::read(payload) {
+                    _ if self.is_pending() => Response::Error(id, ErrorCode::BusyTryAgain),
+                    Ok(staged) => {
+                        self.state = State::Staged {
+                            previous: *current,
+                            staged,
+                            timeout_millis: u64::from(timeout_seconds) * 1000,
+                        };
+                        Response::Ok(id, Format::Empty)
+                    }
+                    Err(_) => Response::NotAvailable(id),
+                }
+            }
+            Request::CommitNetworkConfig(id) => match self.state {
+                State::Applied { .. } => {
+                    self.state = State::Idle;
+                    Response::Ok(id, Format::Empty)
+                }
+                _ => Response::NotAvailable(id),
+            },
+            _ => return None,
+        };
+        Some(response.write(response_writer))
+    }
+
+    /// The staged configuration to apply, once the acknowledgement of the stage request has
+    /// been sent. The timeout for the commit starts now.
+    pub fn apply(&mut self, uptime_millis: u64) -> Option<NetworkConfiguration> {
+        match self.state {
+            State::Staged {
+                previous,
+                staged,
+                timeout_millis,
+            } => {
+                self.state = State::Applied {
+                    previous,
+                    deadline_millis: uptime_millis + timeout_millis,
+                };
+                Some(staged)
+            }
+            _ => None,
+        }
+    }
+
+    /// The previous configuration to revert to, if the commit has not arrived in time
+    pub fn poll(&mut self, uptime_millis: u64) -> Option<NetworkConfiguration> {
+        match self.state {
+            State::Applied {
+                previous,
+                deadline_millis,
+            } if uptime_millis >= deadline_millis => {
+                self.state = State::Idle;
+                Some(previous)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+
+    fn config(ip: [u8; 4]) -> NetworkConfiguration {
+        NetworkConfiguration {
+            mac: [0x02, 0, 0, 0, 0, 1],
+            ip,
+            subnet: [255, 255, 255, 0],
+            gateway: [192, 168, 0, 1],
+            dhcp: false,
+            primary_dns: None,
+            secondary_dns: None,
+        }
+    }
+
+    fn respond(transaction: &mut NetworkTransaction, request: Request, payload: &[u8]) -> Response {
+        let mut buffer = [0u8; 8];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        transaction
+            .respond(
+                &request,
+                &mut &*payload,
+                &config([192, 168, 0, 2]),
+                &mut cursor,
+            )
+            .unwrap()
+            .unwrap();
+        Response::read(&mut cursor.written()).unwrap()
+    }
+
+    #[test]
+    fn uncommitted_configuration_is_reverted() {
+        let mut staged = [0u8; 32];
+        let len = config([192, 168, 0, 3])
+            .write(&mut &mut staged[..])
+            .unwrap();
+
+        let mut transaction = NetworkTransaction::new();
+        let stage = Request::StageNetworkConfig(1, 10);
+        assert_eq!(
+            Response::Ok(1, Format::Empty),
+            respond(&mut transaction, stage, &staged[..len])
+        );
+        assert_eq!(
+            Response::NotAvailable(2),
+            respond(&mut transaction, Request::CommitNetworkConfig(2), &[])
+        );
+        assert_eq!(Some(config([192, 168, 0, 3])), transaction.apply(1_000));
+        assert_eq!(
+            Response::Error(1, ErrorCode::BusyTryAgain),
+            respond(&mut transaction, stage, &staged[..len])
+        );
+        assert_eq!(None, transaction.poll(10_999));
+        assert_eq!(Some(config([192, 168, 0, 2])), transaction.poll(11_000));
+        assert!(!transaction.is_pending());
+
+        respond(&mut transaction, stage, &staged[..len]);
+        transaction.apply(20_000);
+        assert_eq!(
+            Response::Ok(4, Format::Empty),
+            respond(&mut transaction, Request::CommitNetworkConfig(4), &[])
+        );
+        assert_eq!(None, transaction.poll(40_000));
+    }
+}
@@ -0,0 +1,202 @@
+//! Control of relays and PWM outputs attached to the buses of a device.
+//!
+//! The address of the output follows a `Request::SetOutput` or `Request::GetOutput` as payload,
+//! encoded as single entry of an address list (see [`crate::address`]). Both requests are
+//! answered with the state of the output, `Response::Ok(id, Format::Echoed(Type::OutputState))`
+//! for `SetOutput` and `Response::Ok(id, Format::ValueOnly(Type::OutputState))` for
+//! `GetOutput`, unknown outputs with `Response::NotAvailable`.
+
+use crate::address::AddressListIter;
+use crate::{Bus, Error, ErrorCode, Format, Read, Request, Response, Type, Write};
+
+/// The state of an output, encoded as kind `u8` followed by the duty cycle as `u16` big endian,
+/// which is `0` unless the output is driven by PWM
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum OutputState {
+    Off,
+    On,
+    /// The duty cycle of a PWM output, from `0` (off) to `u16::MAX` (fully on)
+    Pwm(u16),
+}
+
+impl OutputState {
+    pub const ENCODED_LEN: usize = 1 + 2;
+
+    pub const fn encode(&self) -> [u8; OutputState::ENCODED_LEN] {
+        let (kind, duty) = match *self {
+            OutputState::Off => (0x00, 0u16),
+            OutputState::On => (0x01, 0),
+            OutputState::Pwm(duty) => (0x02, duty),
+        };
+        let duty = duty.to_be_bytes();
+        [kind, duty[0], duty[1]]
+    }
+
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        writer.write_all(&self.encode())
+    }
+
+    pub fn read(reader: &mut dyn Read) -> Result<Self, Error> {
+        let kind = reader.read_u8()?;
+        let duty = reader.read_u16_be()?;
+        Ok(match kind {
+            0x00 => OutputState::Off,
+            0x01 => OutputState::On,
+            0x02 => OutputState::Pwm(duty),
+            _ => return Err(Error::UnknownTypeIdentifier),
+        })
+    }
+}
+
+/// The outputs of the firmware, called by the [`OutputResponder`]
+pub trait Outputs {
+    /// Drives the output and returns its new state, `None` if there is no such output
+    fn set_output(
+        &mut self,
+        bus: Bus,
+        address: &[u8],
+        state: OutputState,
+    ) -> Result<Option<OutputState>, ErrorCode>;
+
+    /// The current state of the output, `None` if there is no such output
+    fn get_output(&mut self, bus: Bus, address: &[u8]) -> Result<Option<OutputState>, ErrorCode>;
+}
+
+pub struct OutputResponder<'a> {
+    pub request_id: u8,
+    pub bus: Bus,
+    /// The state to set, `None` for a `Request::GetOutput`
+    pub state: Option<OutputState>,
+    pub payload: &'a [u8],
+}
+
+impl<'a> OutputResponder<'a> {
+    pub fn opt_from(request: &Request, payload: &'a [u8]) -> Option<Self> {
+        match *request {
+            Request::SetOutput(id, bus, state) => Some(Self {
+                request_id: id,
+                bus,
+                state: Some(state),
+                payload,
+            }),
+            Request::GetOutput(id, bus) => Some(Self {
+                request_id: id,
+                bus,
+                state: None,
+                payload,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Sets or gets the state of the addressed output and writes the response. A malformed
+    /// address is answered with `Response::NotAvailable`, errors of the outputs with
+    /// `Response::Error`.
+    pub fn write(
+        self,
+        response_writer: &mut impl Write,
+        outputs: &mut impl Outputs,
+    ) -> Result<usize, Error> {
+        let result = match AddressListIter::new(self.bus, self.payload).next() {
+            Some(Ok(address)) => match self.state {
+                Some(state) => outputs.set_output(self.bus, address, state),
+                None => outputs.get_output(self.bus, address),
+            },
+            _ => Ok(None),
+        };
+
+        match result {
+            Ok(Some(state)) => {
+                let format = if self.state.is_some() {
+                    Format::Echoed(Type::OutputState)
+                } else {
+                    Format::ValueOnly(Type::OutputState)
+                };
+                Ok(
+                    Response::Ok(self.request_id, format).write(response_writer)?
+                        + state.write(response_writer)?,
+                )
+            }
+            Ok(None) => Response::NotAvailable(self.request_id).write(response_writer),
+            Err(code) => Response::Error(self.request_id, code).write(response_writer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+
+    struct Relay(OutputState);
+
+    impl Outputs for Relay {
+        fn set_output(
+            &mut self,
+            _bus: Bus,
+            address: &[u8],
+            state: OutputState,
+        ) -> Result<Option<OutputState>, ErrorCode> {
+            match (address, state) {
+                ([0x20], OutputState::Pwm(_)) => Err(ErrorCode::ValueOutOfRange),
+                ([0x20], state) => {
+                    self.0 = state;
+                    Ok(Some(state))
+                }
+                _ => Ok(None),
+            }
+        }
+
+        fn get_output(
+            &mut self,
+            _bus: Bus,
+            address: &[u8],
+        ) -> Result<Option<OutputState>, ErrorCode> {
+            Ok(Some(self.0).filter(|_| address == [0x20]))
+        }
+    }
+
+    fn respond(relay: &mut Relay, request: Request, address: &[u8]) -> Response {
+        let mut buffer = [0u8; 16];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        OutputResponder::opt_from(&request, address)
+            .unwrap()
+            .write(&mut cursor, relay)
+            .unwrap();
+        let mut written = cursor.written();
+        let response = Response::read(&mut written).unwrap();
+        if let Response::Ok(..) = response {
+            assert_eq!(Ok(relay.0), OutputState::read(&mut written));
+        }
+        response
+    }
+
+    #[test]
+    fn outputs_are_set_and_read_back() {
+        let mut relay = Relay(OutputState::Off);
+        let set = Request::SetOutput(1, Bus::I2C, OutputState::On);
+
+        assert_eq!(
+            Response::Ok(1, Format::Echoed(Type::OutputState)),
+            respond(&mut relay, set, &[0x20])
+        );
+        assert_eq!(OutputState::On, relay.0);
+        assert_eq!(
+            Response::Ok(2, Format::ValueOnly(Type::OutputState)),
+            respond(&mut relay, Request::GetOutput(2, Bus::I2C), &[0x20])
+        );
+        assert_eq!(
+            Response::NotAvailable(3),
+            respond(&mut relay, Request::GetOutput(3, Bus::I2C), &[0x21])
+        );
+        assert_eq!(
+            Response::Error(4, ErrorCode::ValueOutOfRange),
+            respond(
+                &mut relay,
+                Request::SetOutput(4, Bus::I2C, OutputState::Pwm(1)),
+                &[0x20]
+            )
+        );
+    }
+}
@@ -0,0 +1,296 @@
+//! Challenge-response pairing to access the configuration of a device.
+//!
+//! Requests that change the network configuration (the `0xA0` opcode range) are only accepted
+//! with a valid [`SessionHeader`]. To obtain one, a client that knows the device specific
+//! secret:
+//!
+//! 1. sends `Request::RequestChallenge(id)`, which is answered with
+//!    `Response::Ok(id, Format::ValueOnly(Type::Bytes(8)))` followed by a random challenge,
+//! 2. sends `Request::Pair(id, response)` with the [`challenge_response`] of the challenge,
+//!    which is answered the same way with the session token, or with
+//!    `Response::Error(id, ErrorCode::PermissionDenied)`,
+//! 3. prefixes the configuration requests with the [`SessionHeader`] of the token, after an
+//!    optional [`crate::replay::SequenceHeader`] and before an optional
+//!    [`crate::schedule::ScheduleHeader`].
+//!
+//! Each challenge can be answered once. The session expires after
+//! [`SESSION_VALIDITY_MILLIS`]. [`Pairing`] implements the device side.
+
+use crate::{Error, ErrorCode, Format, Read, Request, Response, Type, Write};
+
+/// The first byte of an encoded [`SessionHeader`], which is not used by any request opcode
+pub const SESSION_HEADER_MARKER: u8 = 0x54;
+
+/// The duration in milliseconds a challenge can be answered
+pub const CHALLENGE_VALIDITY_MILLIS: u64 = 10_000;
+
+/// The duration in milliseconds a session token is accepted after pairing
+pub const SESSION_VALIDITY_MILLIS: u64 = 5 * 60_000;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SessionHeader(pub [u8; 8]);
+
+impl SessionHeader {
+    pub const ENCODED_LEN: usize = 1 + 8;
+
+    pub fn write(&self, writer: &mut impl Write) -> Result<usize, Error> {
+        Ok(writer.write_u8(SESSION_HEADER_MARKER)? + writer.write_all(&self.0)?)
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        if reader.read_u8()? != SESSION_HEADER_MARKER {
+            return Err(Error::UnknownTypeIdentifier);
+        }
+        let mut token = [0u8; 8];
+        reader.read_all(&mut token)?;
+        Ok(SessionHeader(token))
+    }
+
+    /// Reads the [`SessionHeader`] if the given datagram starts with one. Returns the header
+    /// and the remaining datagram.
+    pub fn read_optional(datagram: &[u8]) -> Result<(Option<Self>, &[u8]), Error> {
+        if datagram.first() == Some(&SESSION_HEADER_MARKER) {
+            let reader = &mut &*datagram;
+            let header = Self::read(reader)?;
+            Ok((Some(header), reader))
+        } else {
+            Ok((None, datagram))
+        }
+    }
+}
+
+/// Whether the request has to be prefixed with a valid [`SessionHeader`]
+pub const fn requires_session(request: &Request) -> bool {
+    matches!(
+        request,
+        Request::SetNetworkMac(..)
+            | Request::SetNetworkIpSubnetGateway(..)
+            | Request::SetNetworkDns(..)
+            | Request::StageNetworkConfig(..)
+            | Request::CommitNetworkConfig(..)
+            | Request::WriteEeprom(..)
+            | Request::FactoryReset(..)
+    )
+}
+
+/// The response to the challenge, the SipHash-2-4 of the challenge keyed with the secret
+pub fn challenge_response(secret: &[u8; 16], challenge: &[u8; 8]) -> [u8; 8] {
+    siphash24(secret, challenge).to_be_bytes()
+}
+
+/// The session token the device hands out for a correct response to the challenge
+fn session_token(secret: &[u8; 16], challenge: &[u8; 8]) -> [u8; 8] {
+    let mut message = [0xFFu8; 9];
+    message[..8].copy_from_slice(challenge);
+    siphash24(secret, &message).to_be_bytes()
+}
+
+/// The device side of the pairing, holding the outstanding challenge and the current session
+#[derive(Debug, Clone)]
+pub struct Pairing {
+    secret: [u8; 16],
+    /// The challenge and the uptime it has been issued at
+    challenge: Option<([u8; 8], u64)>,
+    /// The token and the uptime it expires at
+    session: Option<([u8; 8], u64)>,
+}
+
+impl Pairing {
+    pub const fn new(secret: [u8; 16]) -> Self {
+        Self {
+            secret,
+            challenge: None,
+            session: None,
+        }
+    }
+
+    /// Answers a `Request::RequestChallenge` with the given random challenge or a
+    /// `Request::Pair`, returns `None` for any other request
+    pub fn respond(
+        &mut self,
+        request: &Request,
+        uptime_millis: u64,
+        random_challenge: impl FnOnce() -> [u8; 8],
+        response_writer: &mut impl Write,
+    ) -> Option<Result<usize, Error>> {
+        let (id, value) = match *request {
+            Request::RequestChallenge(id) => {
+                let challenge = random_challenge();
+                self.challenge = Some((challenge, uptime_millis));
+                (id, Some(challenge))
+            }
+            Request::Pair(id, response) => {
+                let token = self
+                    .challenge
+                    .take()
+                    .filter(|(_, issued)| uptime_millis < issued + CHALLENGE_VALIDITY_MILLIS)
+                    .filter(|(challenge, _)| {
+                        constant_time_eq(&challenge_response(&self.secret, challenge), &response)
+                    })
+                    .map(|(challenge, _)| session_token(&self.secret, &challenge));
+                if let Some(token) = token {
+                    self.session = Some((token, uptime_millis + SESSION_VALIDITY_MILLIS));
+                }
+                (id, token)
+            }
+            _ => return None,
+        };
+
+        Some(match value {
+            Some(value) => Response::Ok(id, Format::ValueOnly(Type::Bytes(8)))
+                .write(response_writer)
+                .and_then(|len| Ok(len + response_writer.write_all(&value)?)),
+            None => Response::Error(id, ErrorCode::PermissionDenied).write(response_writer),
+        })
+    }
+
+    /// Whether the request may be handled, either because it does not require a session or
+    /// because the header carries the token of the current session
+    pub fn is_authorized(
+        &self,
+        request: &Request,
+        header: Option<SessionHeader>,
+        uptime_millis: u64,
+    ) -> bool {
+        !requires_session(request)
+            || matches!(
+                (self.session, header),
+                (Some((token, expires)), Some(SessionHeader(header)))
+                    if constant_time_eq(&token, &header) && uptime_millis < expires
+            )
+    }
+
+    /// Ends the current session, for example after the configuration has been committed
+    pub fn end_session(&mut self) {
+        self.session = None;
+    }
+}
+
+/// Compares the MACs without returning early at the first differing byte, which would tell an
+/// attacker how many leading bytes of a guess are correct
+fn constant_time_eq(a: &[u8; 8], b: &[u8; 8]) -> bool {
+    a.iter()
+        .zip(b)
+        .fold(0, |difference, (a, b)| difference | (a ^ b))
+        == 0
+}
+
+/// SipHash-2-4 of the message, a keyed hash function suited as MAC for short messages
+fn siphash24(key: &[u8; 16], message: &[u8]) -> u64 {
+    let k0 = u64::from_le_bytes([
+        key[0], key[1], key[2], key[3], key[4], key[5], key[6], key[7],
+    ]);
+    let k1 = u64::from_le_bytes([
+        key[8], key[9], key[10], key[11], key[12], key[13], key[14], key[15],
+    ]);
+    let mut v = [
+        k0 ^ 0x736f_6d65_7073_6575,
+        k1 ^ 0x646f_7261_6e64_6f6d,
+        k0 ^ 0x6c79_6765_6e65_7261,
+        k1 ^ 0x7465_6462_7974_6573,
+    ];
+
+    fn round(v: &mut [u64; 4]) {
+        v[0] = v[0].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(13) ^ v[0];
+        v[0] = v[0].rotate_left(32);
+        v[2] = v[2].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(16) ^ v[2];
+        v[0] = v[0].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(21) ^ v[0];
+        v[2] = v[2].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(17) ^ v[2];
+        v[2] = v[2].rotate_left(32);
+    }
+
+    let mut compress = |m: u64| {
+        v[3] ^= m;
+        round(&mut v);
+        round(&mut v);
+        v[0] ^= m;
+    };
+
+    let mut chunks = message.chunks_exact(8);
+    for chunk in &mut chunks {
+        compress(u64::from_le_bytes([
+            chunk[0], chunk[1], chunk[2], chunk[3], chunk[4], chunk[5], chunk[6], chunk[7],
+        ]));
+    }
+    let mut last = [0u8; 8];
+    last[..chunks.remainder().len()].copy_from_slice(chunks.remainder());
+    last[7] = message.len() as u8;
+    compress(u64::from_le_bytes(last));
+
+    v[2] ^= 0xFF;
+    for _ in 0..4 {
+        round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+
+    #[test]
+    fn siphash_reference_vector() {
+        let mut key = [0u8; 16];
+        key.iter_mut().enumerate().for_each(|(i, k)| *k = i as u8);
+        let mut message = [0u8; 15];
+        message
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, m)| *m = i as u8);
+        assert_eq!(0xa129_ca61_49be_45e5, siphash24(&key, &message));
+    }
+
+    #[test]
+    fn macs_differing_in_any_byte_are_unequal() {
+        let mac = [1, 2, 3, 4, 5, 6, 7, 8];
+        assert!(constant_time_eq(&mac, &mac));
+        for index in 0..mac.len() {
+            let mut other = mac;
+            other[index] ^= 0x80;
+            assert!(!constant_time_eq(&mac, &other));
+        }
+    }
+
+    #[test]
+    fn configuration_requires_a_paired_session() {
+        let secret = [0x42; 16];
+        let mut pairing = Pairing::new(secret);
+        let set_mac = Request::SetNetworkMac(3, [0x02, 0, 0, 0, 0, 1]);
+        let mut buffer = [0u8; 16];
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        pairing
+            .respond(&Request::RequestChallenge(1), 0, || [7; 8], &mut cursor)
+            .unwrap()
+            .unwrap();
+        assert_eq!(&[7; 8], &cursor.written()[5..]);
+
+        let response = challenge_response(&secret, &[7; 8]);
+        let mut cursor = WriteCursor::new(&mut buffer);
+        pairing
+            .respond(&Request::Pair(2, response), 1_000, || [0; 8], &mut cursor)
+            .unwrap()
+            .unwrap();
+        let session = SessionHeader(session_token(&secret, &[7; 8]));
+        assert_eq!(&session.0, &cursor.written()[5..]);
+
+        assert!(pairing.is_authorized(&Request::ReadAll(4), None, 1_000));
+        assert!(!pairing.is_authorized(&set_mac, None, 1_000));
+        assert!(pairing.is_authorized(&set_mac, Some(session), 1_000));
+        assert!(!pairing.is_authorized(&set_mac, Some(session), 1_000 + SESSION_VALIDITY_MILLIS));
+        assert!(!pairing.is_authorized(&Request::WriteEeprom(6, 0x10), None, 1_000));
+        assert!(!pairing.is_authorized(&Request::FactoryReset(7, [0; 4]), None, 1_000));
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        pairing
+            .respond(&Request::Pair(5, response), 2_000, || [0; 8], &mut cursor)
+            .unwrap()
+            .unwrap();
+        assert_eq!(&[0xF2, 5, 0x03], cursor.written());
+    }
+}
@@ -0,0 +1,90 @@
+//! Prioritization of requests.
+//!
+//! Firmware that can only hold a single request at a time may drop a pending low priority
+//! request, such as a periodic poll, in favor of a higher priority one, such as a
+//! configuration change. The priority is a nibble from [`Priority::LOWEST`] to
+//! [`Priority::HIGHEST`], sent in a [`PriorityHeader`] directly in front of the request, after
+//! all other headers. Requests without the header have [`Priority::DEFAULT`].
+
+use crate::headers::{HeaderKind, RequestHeaderIter};
+use crate::{Error, Read, Write};
+
+/// The first byte of an encoded [`PriorityHeader`], which is not used by any request opcode
+pub const PRIORITY_HEADER_MARKER: u8 = 0x52;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Priority(u8);
+
+impl Priority {
+    pub const LOWEST: Priority = Priority(0x0);
+    pub const DEFAULT: Priority = Priority(0x7);
+    pub const HIGHEST: Priority = Priority(0xF);
+
+    /// `None` for values that do not fit into a nibble
+    pub const fn new(value: u8) -> Option<Self> {
+        if value <= Self::HIGHEST.0 {
+            Some(Priority(value))
+        } else {
+            None
+        }
+    }
+
+    pub const fn value(self) -> u8 {
+        self.0
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PriorityHeader(pub Priority);
+
+impl PriorityHeader {
+    pub const ENCODED_LEN: usize = 1 + 1;
+
+    pub fn write(&self, writer: &mut impl Write) -> Result<usize, Error> {
+        Ok(writer.write_u8(PRIORITY_HEADER_MARKER)? + writer.write_u8(self.0.value())?)
+    }
+
+    /// The upper nibble is reserved and ignored
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        if reader.read_u8()? != PRIORITY_HEADER_MARKER {
+            return Err(Error::UnknownTypeIdentifier);
+        }
+        Ok(PriorityHeader(Priority(reader.read_u8()? & 0x0F)))
+    }
+
+    /// Reads the [`PriorityHeader`] if the given datagram starts with one. Returns the header
+    /// and the remaining datagram.
+    pub fn read_optional(datagram: &[u8]) -> Result<(Option<Self>, &[u8]), Error> {
+        if datagram.first() == Some(&PRIORITY_HEADER_MARKER) {
+            let reader = &mut &*datagram;
+            let header = Self::read(reader)?;
+            Ok((Some(header), reader))
+        } else {
+            Ok((None, datagram))
+        }
+    }
+}
+
+/// The priority of the received request datagram, skipping the headers in front of the
+/// [`PriorityHeader`]
+pub fn priority_of(datagram: &[u8]) -> Result<Priority, Error> {
+    let datagram = RequestHeaderIter::new(datagram).skip_before(HeaderKind::Priority)?;
+    let (header, _) = PriorityHeader::read_optional(datagram)?;
+    Ok(header.map_or(Priority::DEFAULT, |PriorityHeader(priority)| priority))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn priority_is_found_behind_other_headers() {
+        assert_eq!(Ok(Priority::DEFAULT), priority_of(&[0x01, 8]));
+        assert_eq!(
+            Ok(Priority::HIGHEST),
+            priority_of(&[0x50, 0, 0, 0, 1, PRIORITY_HEADER_MARKER, 0xFF, 0xA4, 8])
+        );
+        assert_eq!(None, Priority::new(0x10));
+    }
+}
@@ -1,43 +1,164 @@
-use crate::props::{ComponentRoot, ModuleId, Property, PropertyId, PropertyReportV1};
-use crate::{Error, Format, Read, Request, Response, Type, Write};
+use crate::cursor::{Rewind, Take, Transaction};
+use crate::props::persistence::{read_remaining, Persistence, PERSISTED_VALUE_MAX_LEN};
+use crate::props::range::{is_numeric, NUMERIC_VALUE_MAX_LEN};
+use crate::props::registry::PropertyRegistry;
+use crate::props::tree::{ComponentNode, PROPERTY_PATH_MAX_LEN};
+use crate::props::{
+    AccessLevel, ComponentRoot, ModuleId, Property, PropertyId, PropertyReportV1, PropertyReportV2,
+};
+use crate::{Error, ErrorCode, Format, Read, Request, Response, Type, Write};
 
 pub struct ListComponentsResponder {
     pub request_id: u8,
     pub dyn_list_report_v1: bool,
+    /// Takes precedence over `dyn_list_report_v1`
+    pub dyn_list_report_v2: bool,
+    /// The offset of the first and the maximum number of properties to list, all if `None`
+    pub range: Option<(u16, u8)>,
 }
 
 impl ListComponentsResponder {
     #[inline]
     pub fn opt_from(request: &Request) -> Option<Self> {
         match request {
-            Request::ListComponents(id) | Request::ListComponentsWithReportV1(id) => Some(Self {
+            Request::ListComponents(id)
+            | Request::ListComponentsWithReportV1(id)
+            | Request::ListComponentsWithReportV2(id) => Some(Self {
                 request_id: *id,
                 dyn_list_report_v1: matches!(request, Request::ListComponentsWithReportV1(_)),
+                dyn_list_report_v2: matches!(request, Request::ListComponentsWithReportV2(_)),
+                range: None,
+            }),
+            Request::ListComponentsRange(id, offset, limit) => Some(Self {
+                request_id: *id,
+                dyn_list_report_v1: true,
+                dyn_list_report_v2: false,
+                range: Some((*offset, *limit)),
             }),
             _ => None,
         }
     }
 
+    /// Writes the list of properties. If the list cannot be written completely, everything
+    /// written so far is discarded and `Response::NotAvailable` is written instead.
     #[inline]
     pub fn write<P, T, M>(
         &self,
-        response_writer: &mut impl Write,
+        response_writer: &mut impl Rewind,
         properties: &[Property<P, T>],
         module_properties: Option<(ModuleId, &[Property<P, M>])>,
     ) -> Result<usize, Error> {
         let available_before = response_writer.available();
+        let mut transaction = Transaction::new(response_writer);
+
+        if self
+            .write_list(&mut transaction, properties, module_properties)
+            .is_ok()
+        {
+            transaction.commit();
+        } else {
+            transaction.rollback();
+            Response::NotAvailable(self.request_id).write(response_writer)?;
+        }
+
+        Ok(available_before - response_writer.available())
+    }
+
+    /// Writes the list of all properties in the given tree of components, see [`Self::write`]
+    #[inline]
+    pub fn write_tree<P, T>(
+        &self,
+        response_writer: &mut impl Rewind,
+        root: &ComponentNode<P, T>,
+    ) -> Result<usize, Error> {
+        let available_before = response_writer.available();
+        let mut transaction = Transaction::new(response_writer);
+
+        let mut index = 0;
+        let result = self.write_header(&mut transaction).and_then(|_| {
+            root.visit(&mut |prefix, property| {
+                index += 1;
+                if self.is_in_range(index - 1) {
+                    self.write_entry(&mut transaction, prefix, property)
+                } else {
+                    Ok(())
+                }
+            })
+        });
+
+        if result.is_ok() {
+            transaction.commit();
+        } else {
+            transaction.rollback();
+            Response::NotAvailable(self.request_id).write(response_writer)?;
+        }
+
+        Ok(available_before - response_writer.available())
+    }
+
+    /// Whether the property at the given index of the list is to be written
+    fn is_in_range(&self, index: usize) -> bool {
+        match self.range {
+            Some((offset, limit)) => {
+                let offset = usize::from(offset);
+                index >= offset && index - offset < usize::from(limit)
+            }
+            None => true,
+        }
+    }
+
+    fn write_header(&self, response_writer: &mut impl Write) -> Result<usize, Error> {
         Response::Ok(
             self.request_id,
-            if self.dyn_list_report_v1 {
+            if self.dyn_list_report_v2 {
+                Format::ValueOnly(Type::DynListPropertyReportV2)
+            } else if self.dyn_list_report_v1 {
                 Format::ValueOnly(Type::DynListPropertyReportV1)
             } else {
                 Format::AddressOnly(Type::PropertyId)
             },
         )
-        .write(response_writer)?;
+        .write(response_writer)
+    }
+
+    /// Writes the id of the property, prefixed by the given path, and the report if requested
+    fn write_entry<P, T>(
+        &self,
+        response_writer: &mut impl Write,
+        prefix: &[u8],
+        property: &Property<P, T>,
+    ) -> Result<(), Error> {
+        let prefix_len = prefix.len().min(u8::MAX as usize);
+        let id_len = property.id.len().min(u8::MAX as usize - prefix_len);
+
+        response_writer.write_u8((prefix_len + id_len) as u8)?;
+        response_writer.write_all(&prefix[..prefix_len])?;
+        response_writer.write_all(&property.id[..id_len])?;
+
+        if self.dyn_list_report_v2 {
+            PropertyReportV2::from(property).write_no_id(response_writer)?;
+        } else if self.dyn_list_report_v1 {
+            PropertyReportV1::from(property).write_no_id(response_writer)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_list<P, T, M>(
+        &self,
+        response_writer: &mut impl Write,
+        properties: &[Property<P, T>],
+        module_properties: Option<(ModuleId, &[Property<P, M>])>,
+    ) -> Result<(), Error> {
+        self.write_header(response_writer)?;
 
-        for property in properties {
-            if self.dyn_list_report_v1 {
+        for (index, property) in properties.iter().enumerate() {
+            if !self.is_in_range(index) {
+                continue;
+            }
+            if self.dyn_list_report_v2 {
+                PropertyReportV2::from(property).write(response_writer)?;
+            } else if self.dyn_list_report_v1 {
                 PropertyReportV1::from(property).write(response_writer)?;
             } else {
                 PropertyId::from_slice(property.id).write(response_writer)?;
@@ -45,27 +166,14 @@ impl ListComponentsResponder {
         }
 
         if let Some((module_id, module_properties)) = module_properties {
-            for property in module_properties {
-                let prefix_len = 4;
-                let id_len = property.id.len().min((u8::MAX - prefix_len) as usize) as u8;
-                let len = prefix_len + id_len;
-
-                response_writer.write_u8(len)?;
-                response_writer.write_all(&[
-                    ComponentRoot::Module as u8,
-                    module_id.group,
-                    module_id.id,
-                    module_id.ext,
-                ])?;
-                response_writer.write_all(&property.id[..id_len as usize])?;
-
-                if self.dyn_list_report_v1 {
-                    PropertyReportV1::from(property).write_no_id(response_writer)?;
+            for (index, property) in module_properties.iter().enumerate() {
+                if self.is_in_range(properties.len() + index) {
+                    self.write_entry(response_writer, &module_id.to_path(), property)?;
                 }
             }
         }
 
-        Ok(available_before - response_writer.available())
+        Ok(())
     }
 }
 
@@ -73,6 +181,8 @@ pub struct RetrievePropertyResponder<'a> {
     pub request_id: u8,
     pub prop_id_len: u8,
     pub payload: &'a mut dyn Read,
+    /// The level the request has been authenticated with, [`AccessLevel::Public`] by default
+    pub access_level: AccessLevel,
 }
 
 impl<'a> RetrievePropertyResponder<'a> {
@@ -82,38 +192,43 @@ impl<'a> RetrievePropertyResponder<'a> {
                 request_id: *id,
                 prop_id_len: *len,
                 payload,
+                access_level: AccessLevel::Public,
             })
         } else {
             None
         }
     }
 
+    /// Sets the level the request has been authenticated with
+    pub fn with_access_level(mut self, access_level: AccessLevel) -> Self {
+        self.access_level = access_level;
+        self
+    }
+
+    /// Writes the value of the requested property. If the property is unknown, not readable or
+    /// its read fn fails, `Response::NotAvailable` is written instead. Properties that can not
+    /// be accessed with the level of the request are answered with
+    /// `Response::Error(id, ErrorCode::PermissionDenied)`, paths longer than
+    /// [`PROPERTY_PATH_MAX_LEN`] or shorter than announced with
+    /// `Response::Error(id, ErrorCode::InvalidPropertyPath)`.
     #[inline]
     pub fn write<P, T, M>(
         self,
-        response_writer: &mut impl Write,
-        properties: &[Property<P, T>],
-        module_properties: Option<(ModuleId, &[Property<P, M>])>,
+        response_writer: &mut impl Rewind,
+        properties: &PropertyRegistry<P, T>,
+        module_properties: Option<(ModuleId, &PropertyRegistry<P, M>)>,
         p: &mut P,
         t: &mut T,
         m: &mut M,
     ) -> Result<usize, Error> {
-        const PID_PATH_MAX_DEPTH: usize = 8_usize;
-
         let available_before = response_writer.available();
-        let len = PID_PATH_MAX_DEPTH.min(usize::from(self.prop_id_len));
-
-        let buffer = {
-            let mut buffer = [0u8; PID_PATH_MAX_DEPTH];
-            for i in 0..len {
-                buffer[i as usize] = self.payload.read_u8()?;
-            }
-            buffer
+        let mut buffer = [0u8; PROPERTY_PATH_MAX_LEN];
+        let pid_path = match read_pid_path(&mut *self.payload, self.prop_id_len, &mut buffer) {
+            Ok(pid_path) => pid_path,
+            Err(e) => return reject_pid_path(self.request_id, e, response_writer),
         };
-
-        let pid_path = &buffer[..len];
         let module = module_properties.as_ref().map(|(m, _)| m);
-        let module_properties = module_properties.as_ref().map(|(_, p)| *p).unwrap_or(&[]);
+        let module_properties = module_properties.as_ref().map(|(_, p)| *p);
 
         match pid_path {
             [component, module_group, module_id, module_ext, prop_id @ ..]
@@ -122,42 +237,580 @@ impl<'a> RetrievePropertyResponder<'a> {
                     && Some(*module_id) == module.map(|m| m.id)
                     && Some(*module_ext) == module.map(|m| m.ext) =>
             {
-                for property in module_properties {
-                    if property.id == prop_id {
-                        drop(buffer);
-                        if let Some(read_fn) = property.read.as_ref() {
-                            Response::Ok(
-                                self.request_id,
-                                Format::ValueOnly(property.type_hint.unwrap_or(Type::DynBytes)),
-                            )
-                            .write(response_writer)?;
-                            read_fn(p, m, response_writer)?;
-                        }
-                        break;
-                    }
+                let property = module_properties.and_then(|m| m.find(prop_id));
+                self.retrieve(response_writer, property, p, m)?;
+            }
+            _ => self.retrieve(response_writer, properties.find(pid_path), p, t)?,
+        }
+
+        if available_before == response_writer.available() {
+            Response::NotAvailable(self.request_id).write(response_writer)?;
+        }
+
+        Ok(available_before - response_writer.available())
+    }
+
+    /// Writes the value of the requested property of the given tree of components, see
+    /// [`Self::write`]
+    #[inline]
+    pub fn write_tree<P, T>(
+        self,
+        response_writer: &mut impl Rewind,
+        root: &ComponentNode<P, T>,
+        p: &mut P,
+        t: &mut T,
+    ) -> Result<usize, Error> {
+        let available_before = response_writer.available();
+        let mut buffer = [0u8; PROPERTY_PATH_MAX_LEN];
+        let pid_path = match read_pid_path(&mut *self.payload, self.prop_id_len, &mut buffer) {
+            Ok(pid_path) => pid_path,
+            Err(e) => return reject_pid_path(self.request_id, e, response_writer),
+        };
+
+        self.retrieve(response_writer, root.find(pid_path), p, t)?;
+
+        if available_before == response_writer.available() {
+            Response::NotAvailable(self.request_id).write(response_writer)?;
+        }
+
+        Ok(available_before - response_writer.available())
+    }
+
+    /// Writes the value of the property, or `Response::Error(id, ErrorCode::PermissionDenied)`
+    /// if it can not be accessed with the level of the request. Nothing is written for an
+    /// unknown property.
+    fn retrieve<P, T>(
+        &self,
+        response_writer: &mut impl Rewind,
+        property: Option<&Property<P, T>>,
+        p: &mut P,
+        t: &mut T,
+    ) -> Result<(), Error> {
+        if let Some(property) = property {
+            match check_access(self.access_level, property) {
+                Ok(()) => write_property_value(
+                    self.request_id,
+                    property,
+                    p,
+                    t,
+                    response_writer,
+                    Format::ValueOnly,
+                ),
+                Err(code) => {
+                    Response::Error(self.request_id, code).write(response_writer)?;
                 }
             }
+        }
+        Ok(())
+    }
+}
+
+/// Fails with [`ErrorCode::PermissionDenied`] if the property can not be accessed with the given
+/// level
+fn check_access<P, T>(
+    access_level: AccessLevel,
+    property: &Property<P, T>,
+) -> Result<(), ErrorCode> {
+    if property.access_level > access_level {
+        Err(ErrorCode::PermissionDenied)
+    } else {
+        Ok(())
+    }
+}
+
+/// Writes the `Response::Ok` header and the value of the given property. Nothing is written if
+/// the property is not readable or its read fn fails.
+fn write_property_value<P, T>(
+    request_id: u8,
+    property: &Property<P, T>,
+    p: &mut P,
+    t: &mut T,
+    response_writer: &mut impl Rewind,
+    format: fn(Type) -> Format,
+) {
+    if let Some(read_fn) = property.read.as_ref() {
+        let mut transaction = Transaction::new(response_writer);
+        let result = Response::Ok(
+            request_id,
+            format(property.type_hint.unwrap_or(Type::DynBytes)),
+        )
+        .write(&mut transaction)
+        .and_then(|_| read_fn(p, t, &mut transaction));
+
+        if result.is_ok() {
+            transaction.commit();
+        }
+    }
+}
+
+pub struct StorePropertyResponder<'a> {
+    pub request_id: u8,
+    pub prop_id_len: u8,
+    pub payload: &'a mut dyn Read,
+    /// The level the request has been authenticated with, [`AccessLevel::Public`] by default
+    pub access_level: AccessLevel,
+    /// Receives the values written to persistent properties
+    pub persistence: Option<&'a mut dyn Persistence>,
+}
+
+impl<'a> StorePropertyResponder<'a> {
+    pub fn opt_from(request: &Request, payload: &'a mut dyn Read) -> Option<Self> {
+        if let Request::StoreProperty(id, len) = request {
+            Some(Self {
+                request_id: *id,
+                prop_id_len: *len,
+                payload,
+                access_level: AccessLevel::Public,
+                persistence: None,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Sets the level the request has been authenticated with
+    pub fn with_access_level(mut self, access_level: AccessLevel) -> Self {
+        self.access_level = access_level;
+        self
+    }
+
+    /// Sets the storage to store the values written to persistent properties in
+    pub fn with_persistence(mut self, persistence: &'a mut dyn Persistence) -> Self {
+        self.persistence = Some(persistence);
+        self
+    }
+
+    /// Writes the remaining payload to the requested property and answers with the value read
+    /// back from the property, or `Response::Ok(id, Format::Empty)` if it is not readable.
+    /// Values of persistent properties are stored afterwards, if a persistence is set.
+    /// Unknown properties are answered with `Response::NotAvailable`, properties that are not
+    /// writable with `Response::NotImplemented` and paths longer than [`PROPERTY_PATH_MAX_LEN`]
+    /// or shorter than announced with `Response::Error(id, ErrorCode::InvalidPropertyPath)`.
+    #[inline]
+    pub fn write<P, T, M>(
+        self,
+        response_writer: &mut impl Rewind,
+        properties: &PropertyRegistry<P, T>,
+        module_properties: Option<(ModuleId, &PropertyRegistry<P, M>)>,
+        p: &mut P,
+        t: &mut T,
+        m: &mut M,
+    ) -> Result<usize, Error> {
+        let mut buffer = [0u8; PROPERTY_PATH_MAX_LEN];
+        let pid_path = match read_pid_path(&mut *self.payload, self.prop_id_len, &mut buffer) {
+            Ok(pid_path) => pid_path,
+            Err(e) => return reject_pid_path(self.request_id, e, response_writer),
+        };
+
+        match pid_path {
+            [component, module_group, module_id, module_ext, prop_id @ ..]
+                if *component == ComponentRoot::Module as u8
+                    && module_properties.as_ref().map(|(m, _)| *m)
+                        == Some(ModuleId {
+                            group: *module_group,
+                            id: *module_id,
+                            ext: *module_ext,
+                        }) =>
+            {
+                let property = module_properties.and_then(|(_, m)| m.find(prop_id));
+                self.store(response_writer, pid_path, property, p, m)
+            }
             _ => {
-                for property in properties {
-                    if property.id == pid_path {
-                        if let Some(read_fn) = property.read.as_ref() {
-                            Response::Ok(
-                                self.request_id,
-                                Format::ValueOnly(property.type_hint.unwrap_or(Type::DynBytes)),
-                            )
-                            .write(response_writer)?;
-                            read_fn(p, t, response_writer)?;
+                let property = properties.find(pid_path);
+                self.store(response_writer, pid_path, property, p, t)
+            }
+        }
+    }
+
+    /// Writes the requested property of the given tree of components, see [`Self::write`]
+    #[inline]
+    pub fn write_tree<P, T>(
+        self,
+        response_writer: &mut impl Rewind,
+        root: &ComponentNode<P, T>,
+        p: &mut P,
+        t: &mut T,
+    ) -> Result<usize, Error> {
+        let mut buffer = [0u8; PROPERTY_PATH_MAX_LEN];
+        let pid_path = match read_pid_path(&mut *self.payload, self.prop_id_len, &mut buffer) {
+            Ok(pid_path) => pid_path,
+            Err(e) => return reject_pid_path(self.request_id, e, response_writer),
+        };
+        let property = root.find(pid_path);
+        self.store(response_writer, pid_path, property, p, t)
+    }
+
+    /// Stores the value of the property with the given path, the path is also the key of the
+    /// persisted value
+    fn store<P, T>(
+        self,
+        response_writer: &mut impl Rewind,
+        path: &[u8],
+        property: Option<&Property<P, T>>,
+        p: &mut P,
+        t: &mut T,
+    ) -> Result<usize, Error> {
+        let available_before = response_writer.available();
+        let mut buffer = [0u8; NUMERIC_VALUE_MAX_LEN];
+        let mut value_buffer = [0u8; PERSISTED_VALUE_MAX_LEN];
+        let persistence = self
+            .persistence
+            .filter(|_| property.is_some_and(|p| p.persistent));
+
+        match property {
+            None => Response::NotAvailable(self.request_id).write(response_writer)?,
+            Some(property) if property.access_level > self.access_level => {
+                Response::Error(self.request_id, ErrorCode::PermissionDenied)
+                    .write(response_writer)?
+            }
+            Some(property) if self.payload.available() > value_max_len(property) => {
+                Response::Error(self.request_id, ErrorCode::PayloadTooLarge)
+                    .write(response_writer)?
+            }
+            Some(property) => match property.write.as_ref() {
+                None => Response::NotImplemented(self.request_id).write(response_writer)?,
+                Some(write_fn) => {
+                    let payload = &mut Take::new(self.payload, value_max_len(property));
+                    let result = match read_ranged_value(property, payload, &mut buffer) {
+                        Ok(Some((_, false))) => {
+                            Response::Error(self.request_id, ErrorCode::ValueOutOfRange)
+                                .write(response_writer)?;
+                            return Ok(available_before - response_writer.available());
+                        }
+                        Ok(Some((value, true))) => write_fn(p, t, &mut &*value).map(|_| value),
+                        Ok(None) if persistence.is_some() => {
+                            read_remaining(payload, &mut value_buffer)
+                                .and_then(|value| write_fn(p, t, &mut &*value).map(|_| value))
+                        }
+                        Ok(None) => write_fn(p, t, payload).map(|_| &[][..]),
+                        Err(e) => Err(e),
+                    };
+
+                    let result = match (result, persistence) {
+                        (Ok(value), Some(persistence)) => persistence.store(path, &mut &*value),
+                        (result, _) => result.map(|_| 0),
+                    };
+
+                    if result.is_err() {
+                        Response::NotAvailable(self.request_id).write(response_writer)?
+                    } else if property.read.is_some() {
+                        write_property_value(
+                            self.request_id,
+                            property,
+                            p,
+                            t,
+                            response_writer,
+                            Format::Echoed,
+                        );
+                        if available_before == response_writer.available() {
+                            Response::Ok(self.request_id, Format::Empty).write(response_writer)?
+                        } else {
+                            0
                         }
-                        break;
+                    } else {
+                        Response::Ok(self.request_id, Format::Empty).write(response_writer)?
                     }
                 }
+            },
+        };
+
+        Ok(available_before - response_writer.available())
+    }
+}
+
+/// The size of values of the type hint of the property, unlimited for types of variable size
+fn value_max_len<P, T>(property: &Property<P, T>) -> usize {
+    property
+        .type_hint
+        .and_then(|ty| ty.value_size())
+        .unwrap_or(usize::MAX)
+}
+
+/// Reads the value to store into the buffer and validates it against the range of the property.
+/// Returns `None` without reading anything if the property has no range or no numeric type hint.
+pub fn read_ranged_value<'b, P, T>(
+    property: &Property<P, T>,
+    payload: &mut dyn Read,
+    buffer: &'b mut [u8; NUMERIC_VALUE_MAX_LEN],
+) -> Result<Option<(&'b [u8], bool)>, Error> {
+    match (property.type_hint, property.range) {
+        (Some(ty), Some(range)) if is_numeric(ty) => {
+            let value = &mut buffer[..ty.value_size().unwrap_or_default()];
+            for byte in value.iter_mut() {
+                *byte = payload.read_u8()?;
             }
+            let valid = range.validate(ty, value)?;
+            Ok(Some((value, valid)))
         }
+        _ => Ok(None),
+    }
+}
 
-        if available_before == response_writer.available() {
-            Response::NotAvailable(self.request_id).write(response_writer)?;
+/// Reads the property path of the given length into the buffer. Fails with
+/// [`Error::PropertyPathTooLong`] instead of truncating a path that does not fit, see
+/// [`reject_pid_path`].
+fn read_pid_path<'b>(
+    payload: &mut dyn Read,
+    len: u8,
+    buffer: &'b mut [u8; PROPERTY_PATH_MAX_LEN],
+) -> Result<&'b [u8], Error> {
+    let len = usize::from(len);
+    if len > PROPERTY_PATH_MAX_LEN {
+        return Err(Error::PropertyPathTooLong);
+    }
+    for byte in buffer.iter_mut().take(len) {
+        *byte = payload.read_u8()?;
+    }
+    Ok(&buffer[..len])
+}
+
+/// Answers a property path that is too long or shorter than announced by the request with
+/// `Response::Error(id, ErrorCode::InvalidPropertyPath)`, so that the client does not retry the
+/// request until it times out. Other errors are returned as they are.
+fn reject_pid_path(
+    request_id: u8,
+    error: Error,
+    response_writer: &mut impl Write,
+) -> Result<usize, Error> {
+    match error {
+        Error::PropertyPathTooLong | Error::UnexpectedEOF => {
+            Response::Error(request_id, ErrorCode::InvalidPropertyPath).write(response_writer)
         }
+        e => Err(e),
+    }
+}
 
-        Ok(available_before - response_writer.available())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+    use crate::props::PropertyReportIter;
+
+    fn range(offset: u16, limit: u8) -> ListComponentsResponder {
+        ListComponentsResponder::opt_from(&Request::ListComponentsRange(1, offset, limit)).unwrap()
+    }
+
+    #[test]
+    fn range_selects_the_page() {
+        let page = range(2, 3);
+        assert!(!page.is_in_range(0));
+        assert!(!page.is_in_range(1));
+        assert!((2..5).all(|index| page.is_in_range(index)));
+        assert!(!page.is_in_range(5));
+
+        assert!(!range(0, 0).is_in_range(0));
+        assert!(range(u16::MAX, 1).is_in_range(usize::from(u16::MAX)));
+        assert!(!range(u16::MAX, 1).is_in_range(usize::from(u16::MAX) + 1));
+        let all = ListComponentsResponder::opt_from(&Request::ListComponents(1)).unwrap();
+        assert!(all.is_in_range(usize::MAX));
+    }
+
+    #[test]
+    fn lists_the_properties_page_by_page() {
+        const PROPERTIES: [Property<(), ()>; 3] = [
+            Property::new(&[0x10]),
+            Property::new(&[0x11]),
+            Property::new(&[0x12]),
+        ];
+        let ids = |offset, limit| {
+            let mut buffer = [0u8; 128];
+            let mut cursor = WriteCursor::new(&mut buffer);
+            range(offset, limit)
+                .write::<(), (), ()>(&mut cursor, &PROPERTIES, None)
+                .unwrap();
+            let len = cursor.position();
+            let mut payload = &buffer[..len];
+            Response::read(&mut payload).unwrap();
+            let mut ids = [0u8; 3];
+            let mut count = 0;
+            for report in PropertyReportIter::new(payload) {
+                ids[count] = report.unwrap().id[0];
+                count += 1;
+            }
+            (ids, count)
+        };
+        assert_eq!(([0x10, 0x11, 0], 2), ids(0, 2));
+        assert_eq!(([0x12, 0, 0], 1), ids(2, 2));
+        assert_eq!(([0; 3], 0), ids(3, 2));
+    }
+
+    #[test]
+    fn incomplete_list_is_replaced_by_not_available() {
+        const PROPERTIES: [Property<(), ()>; 3] = [
+            Property::new(&[0x10]),
+            Property::new(&[0x11]),
+            Property::new(&[0x12]),
+        ];
+        let mut buffer = [0xAAu8; 12];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let len = range(0, 3)
+            .write::<(), (), ()>(&mut cursor, &PROPERTIES, None)
+            .unwrap();
+        assert_eq!(len, cursor.position());
+        assert_eq!(
+            Ok(Response::NotAvailable(1)),
+            Response::read(&mut cursor.written())
+        );
+    }
+
+    #[test]
+    fn failing_read_fn_is_replaced_by_not_available() {
+        static PROPERTIES: PropertyRegistry<(), ()> = PropertyRegistry::new(&[Property {
+            read: Some(|_, _, writer| {
+                writer.write_u8(0x12)?;
+                Err(Error::UnexpectedEOF)
+            }),
+            ..Property::new(&[0x10]).with_type(Type::U16)
+        }]);
+        let mut buffer = [0u8; 16];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let mut payload = &[0x10][..];
+        let len =
+            RetrievePropertyResponder::opt_from(&Request::RetrieveProperty(7, 1), &mut payload)
+                .unwrap()
+                .write::<(), (), ()>(&mut cursor, &PROPERTIES, None, &mut (), &mut (), &mut ())
+                .unwrap();
+        assert_eq!(len, cursor.position());
+        assert_eq!(
+            Ok(Response::NotAvailable(7)),
+            Response::read(&mut cursor.written())
+        );
+    }
+
+    #[test]
+    fn pid_path_longer_than_the_maximum_is_rejected() {
+        let mut buffer = [0u8; PROPERTY_PATH_MAX_LEN];
+        let path = [7u8; PROPERTY_PATH_MAX_LEN + 1];
+
+        let mut payload = &path[..];
+        let len = PROPERTY_PATH_MAX_LEN as u8;
+        assert_eq!(
+            Ok(&path[..PROPERTY_PATH_MAX_LEN]),
+            read_pid_path(&mut payload, len, &mut buffer)
+        );
+
+        let mut payload = &path[..];
+        assert_eq!(
+            Err(Error::PropertyPathTooLong),
+            read_pid_path(&mut payload, len + 1, &mut buffer)
+        );
+    }
+
+    #[test]
+    fn pid_path_longer_than_the_maximum_is_answered_with_an_error() {
+        static PROPERTIES: PropertyRegistry<(), ()> =
+            PropertyRegistry::new(&[Property::new(&[0x10]).with_type(Type::U8)]);
+        let path = [0x10u8; PROPERTY_PATH_MAX_LEN + 1];
+        let len = path.len() as u8;
+
+        let mut buffer = [0u8; 16];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let mut payload = &path[..];
+        RetrievePropertyResponder::opt_from(&Request::RetrieveProperty(7, len), &mut payload)
+            .unwrap()
+            .write::<(), (), ()>(&mut cursor, &PROPERTIES, None, &mut (), &mut (), &mut ())
+            .unwrap();
+        assert_eq!(
+            Ok(Response::Error(7, ErrorCode::InvalidPropertyPath)),
+            Response::read(&mut cursor.written())
+        );
+
+        let mut buffer = [0u8; 16];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let mut payload = &path[..];
+        StorePropertyResponder::opt_from(&Request::StoreProperty(8, len), &mut payload)
+            .unwrap()
+            .write::<(), (), ()>(&mut cursor, &PROPERTIES, None, &mut (), &mut (), &mut ())
+            .unwrap();
+        assert_eq!(
+            Ok(Response::Error(8, ErrorCode::InvalidPropertyPath)),
+            Response::read(&mut cursor.written())
+        );
+    }
+
+    #[test]
+    fn truncated_pid_path_is_answered_with_an_error() {
+        static PROPERTIES: PropertyRegistry<(), ()> =
+            PropertyRegistry::new(&[Property::new(&[0x10, 0x11]).with_type(Type::U8)]);
+        let mut buffer = [0u8; 16];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let mut payload = &[0x10][..];
+        RetrievePropertyResponder::opt_from(&Request::RetrieveProperty(7, 2), &mut payload)
+            .unwrap()
+            .write::<(), (), ()>(&mut cursor, &PROPERTIES, None, &mut (), &mut (), &mut ())
+            .unwrap();
+        assert_eq!(
+            Ok(Response::Error(7, ErrorCode::InvalidPropertyPath)),
+            Response::read(&mut cursor.written())
+        );
+    }
+
+    #[test]
+    fn retrieving_without_access_is_denied() {
+        static PROPERTIES: PropertyRegistry<(), ()> =
+            PropertyRegistry::new(
+                &[Property::new(&[0x10]).with_access_level(AccessLevel::Factory)],
+            );
+        let retrieve = |response_writer: &mut WriteCursor| {
+            let mut payload = &[0x10][..];
+            RetrievePropertyResponder::opt_from(&Request::RetrieveProperty(7, 1), &mut payload)
+                .unwrap()
+                .write::<(), (), ()>(
+                    response_writer,
+                    &PROPERTIES,
+                    None,
+                    &mut (),
+                    &mut (),
+                    &mut (),
+                )
+        };
+
+        let mut buffer = [0u8; 16];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let len = retrieve(&mut cursor).unwrap();
+        assert_eq!(
+            Ok(Response::Error(7, ErrorCode::PermissionDenied)),
+            Response::read(&mut &buffer[..len])
+        );
+
+        let mut buffer = [0u8; 1];
+        assert_eq!(
+            Err(Error::BufferToSmall),
+            retrieve(&mut WriteCursor::new(&mut buffer))
+        );
+    }
+
+    #[test]
+    fn storing_more_than_the_type_hint_is_rejected() {
+        static PROPERTIES: PropertyRegistry<u16, ()> = PropertyRegistry::new(&[Property {
+            write: Some(|value, _, reader| {
+                *value = reader.read_u16_be()?;
+                Ok(2)
+            }),
+            ..Property::new(&[0x10]).with_type(Type::U16)
+        }]);
+        let store = |value: &mut u16, mut payload: &[u8]| {
+            let mut buffer = [0u8; 16];
+            let mut cursor = WriteCursor::new(&mut buffer);
+            StorePropertyResponder::opt_from(&Request::StoreProperty(7, 1), &mut payload)
+                .unwrap()
+                .write::<u16, (), ()>(&mut cursor, &PROPERTIES, None, value, &mut (), &mut ())
+                .unwrap();
+            Response::read(&mut cursor.written()).unwrap()
+        };
+
+        let mut value = 0;
+        assert_eq!(
+            Response::Error(7, ErrorCode::PayloadTooLarge),
+            store(&mut value, &[0x10, 0x12, 0x34, 0x56])
+        );
+        assert_eq!(0, value);
+        assert_eq!(
+            Response::Ok(7, Format::Empty),
+            store(&mut value, &[0x10, 0x12, 0x34])
+        );
+        assert_eq!(0x1234, value);
+        assert_eq!(Response::NotAvailable(7), store(&mut value, &[0x10, 0x56]));
+        assert_eq!(0x1234, value);
     }
 }
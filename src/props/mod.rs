@@ -1,7 +1,20 @@
+#[cfg(feature = "alloc")]
+use crate::hex::Hex;
+use crate::props::range::ValueRange;
+use crate::props::unit::Unit;
 use crate::{Error, Read, Type, Write};
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
 use core::num::NonZeroU16;
 
 pub mod handling;
+pub mod path;
+pub mod persistence;
+pub mod range;
+pub mod registry;
+pub mod tree;
+pub mod unit;
+pub mod well_known;
 
 #[macro_export]
 macro_rules! property_read_fn {
@@ -72,6 +85,12 @@ pub enum DeviceComponent {
     Cpu = 0x00,
     Frequency = 0x01,
     Uptime = 0x02,
+    /// The user assigned name of the device, see [`well_known::DEVICE_NAME`]
+    Name = 0x03,
+    /// The user assigned location of the device, see [`well_known::DEVICE_LOCATION`]
+    Location = 0x04,
+    /// The number of times the device has booted, see [`well_known::DEVICE_BOOT_COUNT`]
+    BootCount = 0x05,
 }
 
 #[repr(u8)]
@@ -128,6 +147,7 @@ pub enum SntpComponent {
     LastUpdateMillis = 0x02,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct ModuleId {
     pub group: u8,
     pub id: u8,
@@ -161,6 +181,7 @@ impl<'a> From<&'a [u8]> for PropertyId<'a> {
 }
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum QueryComplexity {
     Unknown,
     Low {
@@ -186,6 +207,7 @@ impl QueryComplexity {
 
     pub fn read(reader: &mut impl crate::Read) -> Result<Self, crate::Error> {
         Ok(match reader.read_u8()? {
+            0x00 => Self::Unknown,
             0x10 => {
                 let mut millis = 0u16.to_be_bytes();
                 reader.read_all(millis.as_mut())?;
@@ -207,26 +229,66 @@ impl QueryComplexity {
     pub fn write(&self, writer: &mut dyn crate::Write) -> Result<usize, crate::Error> {
         match self {
             QueryComplexity::Unknown => writer.write_u8(0x00),
-            QueryComplexity::Low { estimated_millis } => {
-                writer.write_u8(0x10)?;
-                writer.write_all(
+            QueryComplexity::Low { estimated_millis } => Ok(writer.write_u8(0x10)?
+                + writer.write_all(
                     &estimated_millis
                         .map(|n| n.get().to_be_bytes())
                         .unwrap_or_default(),
-                )
-            }
-            QueryComplexity::High { estimated_millis } => {
-                writer.write_u8(0x20)?;
-                writer.write_all(
+                )?),
+            QueryComplexity::High { estimated_millis } => Ok(writer.write_u8(0x20)?
+                + writer.write_all(
                     &estimated_millis
                         .map(|n| n.get().to_be_bytes())
                         .unwrap_or_default(),
-                )
-            }
+                )?),
         }
     }
 }
 
+/// The role of a property, allows clients to group properties without knowing their paths
+#[derive(Debug, Copy, Clone, PartialEq, core::default::Default)]
+pub enum Category {
+    #[default]
+    Unspecified,
+    Sensor,
+    Actuator,
+    Config,
+    Diagnostic,
+}
+
+impl Category {
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        writer.write_u8(match self {
+            Category::Unspecified => 0x00,
+            Category::Sensor => 0x01,
+            Category::Actuator => 0x02,
+            Category::Config => 0x03,
+            Category::Diagnostic => 0x04,
+        })
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        Ok(match reader.read_u8()? {
+            0x00 => Category::Unspecified,
+            0x01 => Category::Sensor,
+            0x02 => Category::Actuator,
+            0x03 => Category::Config,
+            0x04 => Category::Diagnostic,
+            _ => return Err(Error::UnknownTypeIdentifier),
+        })
+    }
+}
+
+/// The level a request has to be authenticated with to access a property. Properties above the
+/// level of a request are still listed, but cannot be read or written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, core::default::Default)]
+pub enum AccessLevel {
+    #[default]
+    Public,
+    Maintenance,
+    Factory,
+}
+
 pub type ReadFn<P, T> = fn(&mut P, &mut T, &mut dyn Write) -> Result<usize, Error>;
 pub type WriteFn<P, T> = fn(&mut P, &mut T, &mut dyn Read) -> Result<usize, Error>;
 
@@ -234,22 +296,105 @@ pub struct Property<P, T> {
     pub id: &'static [u8],
     pub type_hint: Option<Type>,
     pub description: Option<&'static str>,
+    pub unit: Option<Unit>,
+    pub range: Option<ValueRange>,
+    pub category: Category,
+    /// Whether written values survive a reboot of the device. If set, the
+    /// [`handling::StorePropertyResponder`] passes written values to its
+    /// [`persistence::Persistence`].
+    pub persistent: bool,
+    pub access_level: AccessLevel,
     pub complexity: QueryComplexity,
     pub read: Option<ReadFn<P, T>>,
     pub write: Option<WriteFn<P, T>>,
 }
 
+impl<P, T> Property<P, T> {
+    /// A property that is neither readable nor writable, see the `with_*` methods to describe it
+    pub const fn new(id: &'static [u8]) -> Self {
+        Self {
+            id,
+            type_hint: None,
+            description: None,
+            unit: None,
+            range: None,
+            category: Category::Unspecified,
+            persistent: false,
+            access_level: AccessLevel::Public,
+            complexity: QueryComplexity::Unknown,
+            read: None,
+            write: None,
+        }
+    }
+
+    pub const fn with_type(mut self, type_hint: Type) -> Self {
+        self.type_hint = Some(type_hint);
+        self
+    }
+
+    pub const fn with_description(mut self, description: &'static str) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    pub const fn with_unit(mut self, unit: Unit) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+
+    pub const fn with_range(mut self, range: ValueRange) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    pub const fn with_category(mut self, category: Category) -> Self {
+        self.category = category;
+        self
+    }
+
+    pub const fn with_access_level(mut self, access_level: AccessLevel) -> Self {
+        self.access_level = access_level;
+        self
+    }
+
+    pub const fn with_complexity(mut self, complexity: QueryComplexity) -> Self {
+        self.complexity = complexity;
+        self
+    }
+
+    pub const fn persistent(mut self) -> Self {
+        self.persistent = true;
+        self
+    }
+
+    pub const fn readable(mut self, read: ReadFn<P, T>) -> Self {
+        self.read = Some(read);
+        self
+    }
+
+    pub const fn writable(mut self, write: WriteFn<P, T>) -> Self {
+        self.write = Some(write);
+        self
+    }
+}
+
 #[derive(Debug)]
+#[cfg_attr(
+    all(feature = "arbitrary", feature = "alloc"),
+    derive(arbitrary::Arbitrary)
+)]
 pub struct PropertyReportV1 {
-    #[cfg(feature = "std")]
+    #[cfg(feature = "alloc")]
     pub id: Vec<u8>,
-    #[cfg(not(feature = "std"))]
+    #[cfg(not(feature = "alloc"))]
     pub id: &'static [u8],
     pub type_hint: Option<Type>,
-    #[cfg(feature = "std")]
+    #[cfg(feature = "alloc")]
     pub description: Option<String>,
-    #[cfg(not(feature = "std"))]
+    #[cfg(not(feature = "alloc"))]
     pub description: Option<&'static str>,
+    pub unit: Option<Unit>,
+    pub range: Option<ValueRange>,
     pub complexity: QueryComplexity,
     pub read: bool,
     pub write: bool,
@@ -272,7 +417,9 @@ impl PropertyReportV1 {
                 .map(|_| 1u8 << 6)
                 .unwrap_or_default()
             | if self.read { 1u8 << 5 } else { 0u8 }
-            | if self.write { 1u8 << 4 } else { 0u8 };
+            | if self.write { 1u8 << 4 } else { 0u8 }
+            | self.unit.map(|_| 1u8 << 3).unwrap_or_default()
+            | self.encodable_range().map(|_| 1u8 << 2).unwrap_or_default();
 
         Ok(writer.write_u8(header)?
             + if let Some(ty) = self.type_hint {
@@ -286,10 +433,20 @@ impl PropertyReportV1 {
             } else {
                 0
             }
+            + if let Some(unit) = self.unit {
+                unit.write(writer)?
+            } else {
+                0
+            }
+            + if let Some((ty, range)) = self.encodable_range() {
+                range.write(ty, writer)?
+            } else {
+                0
+            }
             + self.complexity.write(writer)?)
     }
 
-    #[cfg(feature = "std")]
+    #[cfg(feature = "alloc")]
     pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
         let id = {
             let id_len = usize::from(reader.read_u8()?);
@@ -309,7 +466,22 @@ impl PropertyReportV1 {
             let desc_len = usize::from(reader.read_u8()?);
             let mut vec = core::iter::repeat(0u8).take(desc_len).collect::<Vec<u8>>();
             reader.read_all(&mut vec[..])?;
-            Some(String::from_utf8_lossy(&vec).to_string())
+            Some(String::from_utf8_lossy(&vec).into_owned())
+        } else {
+            None
+        };
+
+        let unit = if header & (1u8 << 3) != 0 {
+            Some(Unit::read(reader)?)
+        } else {
+            None
+        };
+
+        let range = if header & (1u8 << 2) != 0 {
+            Some(ValueRange::read(
+                ty.ok_or(Error::UnknownTypeIdentifier)?,
+                reader,
+            )?)
         } else {
             None
         };
@@ -319,24 +491,131 @@ impl PropertyReportV1 {
             id,
             type_hint: ty,
             description: desc,
+            unit,
+            range,
             complexity,
             read: header & (1u8 << 5) != 0,
             write: header & (1u8 << 4) != 0,
         })
     }
 
-    #[cfg(feature = "std")]
+    /// The range can only be encoded in the format of a numeric type hint
+    fn encodable_range(&self) -> Option<(Type, ValueRange)> {
+        self.type_hint
+            .filter(|ty| range::is_numeric(*ty))
+            .zip(self.range)
+    }
+
+    #[cfg(feature = "alloc")]
     pub fn id_formatted(&self) -> String {
-        let mut string = String::with_capacity(self.id.len() * 3 - 1);
-        for (i, id) in self.id.iter().enumerate() {
-            if i > 0 {
-                string.push(':');
-            }
+        use alloc::string::ToString;
+        Hex::colons(&self.id).to_string()
+    }
+}
+
+/// A [`PropertyReportV1`] that borrows the id and the description from the received payload,
+/// so that reports can also be read without allocating.
+#[derive(Debug, Copy, Clone)]
+pub struct PropertyReportV1Ref<'a> {
+    pub id: &'a [u8],
+    pub type_hint: Option<Type>,
+    pub description: Option<&'a str>,
+    pub unit: Option<Unit>,
+    pub range: Option<ValueRange>,
+    pub complexity: QueryComplexity,
+    pub read: bool,
+    pub write: bool,
+}
+
+impl<'a> PropertyReportV1Ref<'a> {
+    /// Reads the next report from the slice, which is advanced accordingly
+    pub fn read(reader: &mut &'a [u8]) -> Result<Self, Error> {
+        let id = read_slice(reader)?;
+        let header = reader.read_u8()?;
+
+        let type_hint = if header & (1u8 << 7) != 0 {
+            Some(Type::read(reader)?)
+        } else {
+            None
+        };
+
+        let description = if header & (1u8 << 6) != 0 {
+            Some(core::str::from_utf8(read_slice(reader)?).map_err(|_| Error::InvalidUtf8)?)
+        } else {
+            None
+        };
+
+        let unit = if header & (1u8 << 3) != 0 {
+            Some(Unit::read(reader)?)
+        } else {
+            None
+        };
+
+        let range = if header & (1u8 << 2) != 0 {
+            Some(ValueRange::read(
+                type_hint.ok_or(Error::UnknownTypeIdentifier)?,
+                reader,
+            )?)
+        } else {
+            None
+        };
+
+        let complexity = QueryComplexity::read(reader)?;
+        Ok(PropertyReportV1Ref {
+            id,
+            type_hint,
+            description,
+            unit,
+            range,
+            complexity,
+            read: header & (1u8 << 5) != 0,
+            write: header & (1u8 << 4) != 0,
+        })
+    }
+}
 
-            use std::fmt::Write;
-            write!(&mut string, "{:02x}", *id).unwrap();
+#[cfg(feature = "alloc")]
+impl From<PropertyReportV1Ref<'_>> for PropertyReportV1 {
+    fn from(report: PropertyReportV1Ref<'_>) -> Self {
+        PropertyReportV1 {
+            id: report.id.to_vec(),
+            type_hint: report.type_hint,
+            description: report.description.map(Into::into),
+            unit: report.unit,
+            range: report.range,
+            complexity: report.complexity,
+            read: report.read,
+            write: report.write,
+        }
+    }
+}
+
+/// Decodes [`PropertyReportV1Ref`]s from the payload of a
+/// `Response::Ok(id, Format::ValueOnly(Type::DynListPropertyReportV1))` until the payload is
+/// exhausted. Stops after the first malformed report.
+pub struct PropertyReportIter<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> PropertyReportIter<'a> {
+    pub const fn new(payload: &'a [u8]) -> Self {
+        Self { remaining: payload }
+    }
+}
+
+impl<'a> Iterator for PropertyReportIter<'a> {
+    type Item = Result<PropertyReportV1Ref<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            None
+        } else {
+            let result = PropertyReportV1Ref::read(&mut self.remaining);
+            if result.is_err() {
+                self.remaining = &[];
+            }
+            Some(result)
         }
-        string
     }
 }
 
@@ -346,9 +625,158 @@ impl<P, T> From<&Property<P, T>> for PropertyReportV1 {
             id: property.id.into(),
             type_hint: property.type_hint,
             description: property.description.map(Into::into),
+            unit: property.unit,
+            range: property.range,
+            complexity: property.complexity,
+            read: property.read.is_some(),
+            write: property.write.is_some(),
+        }
+    }
+}
+
+/// The extended description of a property, sent as list of
+/// `Response::Ok(id, Format::ValueOnly(Type::DynListPropertyReportV2))`. In contrast to
+/// [`PropertyReportV1`] it borrows from the property or the received payload and can therefore
+/// be read and written without allocating.
+#[derive(Debug, Copy, Clone)]
+pub struct PropertyReportV2<'a> {
+    pub id: &'a [u8],
+    pub type_hint: Option<Type>,
+    pub description: Option<&'a str>,
+    pub unit: Option<Unit>,
+    pub range: Option<ValueRange>,
+    pub category: Category,
+    pub persistent: bool,
+    pub complexity: QueryComplexity,
+    pub read: bool,
+    pub write: bool,
+    /// Type-length-value encoded extensions, see [`crate::tlv::TlvIter`]. Readers skip the tags
+    /// they do not know. Currently always empty.
+    pub extensions: &'a [u8],
+}
+
+impl<'a> PropertyReportV2<'a> {
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        Ok(writer.write_dyn_bytes(self.id)? + self.write_no_id(writer)?)
+    }
+
+    pub fn write_no_id(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        let range = self
+            .type_hint
+            .filter(|ty| range::is_numeric(*ty))
+            .zip(self.range);
+
+        let header = self.type_hint.map(|_| 1u8 << 7).unwrap_or_default()
+            | self.description.map(|_| 1u8 << 6).unwrap_or_default()
+            | if self.read { 1u8 << 5 } else { 0u8 }
+            | if self.write { 1u8 << 4 } else { 0u8 }
+            | self.unit.map(|_| 1u8 << 3).unwrap_or_default()
+            | range.map(|_| 1u8 << 2).unwrap_or_default()
+            | if self.persistent { 1u8 << 1 } else { 0u8 };
+
+        Ok(writer.write_u8(header)?
+            + self.category.write(writer)?
+            + if let Some(ty) = self.type_hint {
+                ty.write(writer)?
+            } else {
+                0
+            }
+            + if let Some(desc) = self.description {
+                writer.write_dyn_bytes(desc.as_bytes())?
+            } else {
+                0
+            }
+            + if let Some(unit) = self.unit {
+                unit.write(writer)?
+            } else {
+                0
+            }
+            + if let Some((ty, range)) = range {
+                range.write(ty, writer)?
+            } else {
+                0
+            }
+            + self.complexity.write(writer)?
+            + writer.write_dyn_bytes(self.extensions)?)
+    }
+
+    /// Reads the next report from the slice, which is advanced accordingly
+    pub fn read(reader: &mut &'a [u8]) -> Result<Self, Error> {
+        let id = read_slice(reader)?;
+        let header = reader.read_u8()?;
+        let category = Category::read(reader)?;
+
+        let type_hint = if header & (1u8 << 7) != 0 {
+            Some(Type::read(reader)?)
+        } else {
+            None
+        };
+
+        let description = if header & (1u8 << 6) != 0 {
+            Some(core::str::from_utf8(read_slice(reader)?).map_err(|_| Error::InvalidUtf8)?)
+        } else {
+            None
+        };
+
+        let unit = if header & (1u8 << 3) != 0 {
+            Some(Unit::read(reader)?)
+        } else {
+            None
+        };
+
+        let range = if header & (1u8 << 2) != 0 {
+            Some(ValueRange::read(
+                type_hint.ok_or(Error::UnknownTypeIdentifier)?,
+                reader,
+            )?)
+        } else {
+            None
+        };
+
+        let complexity = QueryComplexity::read(reader)?;
+        let extensions = crate::tlv::read_area(reader)?;
+
+        Ok(PropertyReportV2 {
+            id,
+            type_hint,
+            description,
+            unit,
+            range,
+            category,
+            persistent: header & (1u8 << 1) != 0,
+            complexity,
+            read: header & (1u8 << 5) != 0,
+            write: header & (1u8 << 4) != 0,
+            extensions,
+        })
+    }
+}
+
+impl<'a, P, T> From<&'a Property<P, T>> for PropertyReportV2<'a> {
+    fn from(property: &'a Property<P, T>) -> Self {
+        PropertyReportV2 {
+            id: property.id,
+            type_hint: property.type_hint,
+            description: property.description,
+            unit: property.unit,
+            range: property.range,
+            category: property.category,
+            persistent: property.persistent,
             complexity: property.complexity,
             read: property.read.is_some(),
             write: property.write.is_some(),
+            extensions: &[],
         }
     }
 }
+
+/// Reads a slice prefixed by its length in bytes
+fn read_slice<'a>(reader: &mut &'a [u8]) -> Result<&'a [u8], Error> {
+    let len = usize::from(reader.read_u8()?);
+    if reader.len() < len {
+        return Err(Error::UnexpectedEOF);
+    }
+    let (slice, remaining) = reader.split_at(len);
+    *reader = remaining;
+    Ok(slice)
+}
@@ -0,0 +1,180 @@
+use crate::hex::Hex;
+use crate::props::tree::PROPERTY_PATH_MAX_LEN;
+use crate::props::{
+    ComponentRoot, CpuComponent, DeviceComponent, EeePromComponent, MetaInformation,
+    NetworkComponent, PlatformComponent, SntpComponent, TemperatureComponent,
+};
+use crate::Error;
+use core::fmt;
+use core::str::FromStr;
+
+/// The path of a property, parsed from and formatted to either the hex notation
+/// (`"30:11:10"`) or the symbolic notation (`"platform/network/mac"`). Segments of the symbolic
+/// notation without a name are written as hex byte (`"module/01/02/00/10"`).
+///
+/// `Display` writes the hex notation, the alternate form (`{:#}`) the symbolic notation.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct PropertyPath {
+    bytes: [u8; PROPERTY_PATH_MAX_LEN],
+    len: u8,
+}
+
+impl PropertyPath {
+    pub fn new(path: &[u8]) -> Result<Self, Error> {
+        let mut bytes = [0u8; PROPERTY_PATH_MAX_LEN];
+        bytes
+            .get_mut(..path.len())
+            .ok_or(Error::BufferToSmall)?
+            .copy_from_slice(path);
+        Ok(Self {
+            bytes,
+            len: path.len() as u8,
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..usize::from(self.len)]
+    }
+
+    fn push(&mut self, byte: u8) -> Result<(), Error> {
+        *self
+            .bytes
+            .get_mut(usize::from(self.len))
+            .ok_or(Error::BufferToSmall)? = byte;
+        self.len += 1;
+        Ok(())
+    }
+}
+
+impl AsRef<[u8]> for PropertyPath {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl FromStr for PropertyPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut path = PropertyPath::new(&[])?;
+        if s.contains('/') {
+            for segment in s.split('/').filter(|s| !s.is_empty()) {
+                let byte = match NAMES.iter().find(|(parent, _, name)| {
+                    *parent == path.as_bytes() && name.eq_ignore_ascii_case(segment)
+                }) {
+                    Some((_, byte, _)) => *byte,
+                    None => parse_hex(segment)?,
+                };
+                path.push(byte)?;
+            }
+        } else {
+            for segment in s.split(':').filter(|s| !s.is_empty()) {
+                path.push(parse_hex(segment)?)?;
+            }
+        }
+        Ok(path)
+    }
+}
+
+impl fmt::Display for PropertyPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.as_bytes().iter().enumerate() {
+            if f.alternate() {
+                if i > 0 {
+                    f.write_str("/")?;
+                }
+                match name(&self.as_bytes()[..i], *byte) {
+                    Some(name) => f.write_str(name)?,
+                    None => write!(f, "{:02x}", byte)?,
+                }
+            } else {
+                return Hex::colons(self.as_bytes()).fmt(f);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for PropertyPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PropertyPath({:#})", self)
+    }
+}
+
+fn parse_hex(segment: &str) -> Result<u8, Error> {
+    u8::from_str_radix(segment, 16).map_err(|_| Error::UnknownTypeIdentifier)
+}
+
+fn name(parent: &[u8], byte: u8) -> Option<&'static str> {
+    NAMES
+        .iter()
+        .find(|(p, b, _)| *p == parent && *b == byte)
+        .map(|(_, _, name)| *name)
+}
+
+const DEVICE: u8 = ComponentRoot::Device as u8;
+const PLATFORM: u8 = ComponentRoot::Platform as u8;
+
+/// The names of the path segments, by the path of their parent
+#[rustfmt::skip]
+const NAMES: &[(&[u8], u8, &str)] = &[
+    (&[], ComponentRoot::Device as u8, "device"),
+    (&[], ComponentRoot::System as u8, "system"),
+    (&[], ComponentRoot::Platform as u8, "platform"),
+    (&[], ComponentRoot::Module as u8, "module"),
+
+    (&[DEVICE], DeviceComponent::Cpu as u8, "cpu"),
+    (&[DEVICE], DeviceComponent::Frequency as u8, "frequency"),
+    (&[DEVICE], DeviceComponent::Uptime as u8, "uptime"),
+    (&[DEVICE], DeviceComponent::Name as u8, "name"),
+    (&[DEVICE], DeviceComponent::Location as u8, "location"),
+    (&[DEVICE], DeviceComponent::BootCount as u8, "boot_count"),
+
+    (&[DEVICE, DeviceComponent::Cpu as u8], CpuComponent::Id as u8, "id"),
+    (&[DEVICE, DeviceComponent::Cpu as u8], CpuComponent::Implementer as u8, "implementer"),
+    (&[DEVICE, DeviceComponent::Cpu as u8], CpuComponent::Variant as u8, "variant"),
+    (&[DEVICE, DeviceComponent::Cpu as u8], CpuComponent::PartNumber as u8, "part_number"),
+    (&[DEVICE, DeviceComponent::Cpu as u8], CpuComponent::Revision as u8, "revision"),
+
+    (&[PLATFORM], PlatformComponent::Meta as u8, "meta"),
+    (&[PLATFORM], PlatformComponent::EeeProm as u8, "eeprom"),
+    (&[PLATFORM], PlatformComponent::Network as u8, "network"),
+    (&[PLATFORM], PlatformComponent::Temperature as u8, "temperature"),
+    (&[PLATFORM], PlatformComponent::Sntp as u8, "sntp"),
+
+    (&[PLATFORM, PlatformComponent::Meta as u8], MetaInformation::Version as u8, "version"),
+    (&[PLATFORM, PlatformComponent::EeeProm as u8], EeePromComponent::MagicCrcStart as u8, "magic_crc_start"),
+    (&[PLATFORM, PlatformComponent::Network as u8], NetworkComponent::Mac as u8, "mac"),
+    (&[PLATFORM, PlatformComponent::Network as u8], NetworkComponent::Ip as u8, "ip"),
+    (&[PLATFORM, PlatformComponent::Network as u8], NetworkComponent::Subnet as u8, "subnet"),
+    (&[PLATFORM, PlatformComponent::Network as u8], NetworkComponent::Gateway as u8, "gateway"),
+    (&[PLATFORM, PlatformComponent::Temperature as u8], TemperatureComponent::Value as u8, "value"),
+    (&[PLATFORM, PlatformComponent::Sntp as u8], SntpComponent::CurrentTimeMillis as u8, "current_time_millis"),
+    (&[PLATFORM, PlatformComponent::Sntp as u8], SntpComponent::LastOffsetMillis as u8, "last_offset_millis"),
+    (&[PLATFORM, PlatformComponent::Sntp as u8], SntpComponent::LastUpdateMillis as u8, "last_update_millis"),
+];
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::props::well_known;
+    use std::format;
+    use std::string::ToString;
+
+    #[test]
+    fn parse_and_format() {
+        let path: PropertyPath = "platform/network/mac".parse().unwrap();
+        assert_eq!(&well_known::PLATFORM_NETWORK_MAC[..], path.as_bytes());
+        assert_eq!("30:11:10", path.to_string());
+        assert_eq!("platform/network/mac", format!("{:#}", path));
+        assert_eq!(path, "30:11:10".parse().unwrap());
+
+        let module: PropertyPath = "module/01/02/00/ff".parse().unwrap();
+        assert_eq!(&[0x40, 0x01, 0x02, 0x00, 0xFF][..], module.as_bytes());
+        assert_eq!("module/01/02/00/ff", format!("{:#}", module));
+
+        assert!("platform/network/nope".parse::<PropertyPath>().is_err());
+    }
+}
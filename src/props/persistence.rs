@@ -0,0 +1,275 @@
+use crate::cursor::WriteCursor;
+#[cfg(feature = "std")]
+use crate::hex::Hex;
+use crate::props::registry::PropertyRegistry;
+use crate::props::tree::{ComponentNode, PROPERTY_PATH_MAX_LEN};
+use crate::props::well_known::DEVICE_LOCATION_MAX_LEN;
+use crate::props::{ModuleId, Property};
+use crate::{Error, Read, Write};
+
+/// The maximum size of a value written to a persistent property, large enough for the longest
+/// well-known `Type::DynString` and its length prefix
+pub const PERSISTED_VALUE_MAX_LEN: usize = DEVICE_LOCATION_MAX_LEN + 1;
+
+/// The non-volatile storage of the values of persistent properties, for example an EEPROM.
+/// Values are stored in the format they have been written to the property and keyed by the
+/// full path of the property, so that properties of different modules with the same id do not
+/// share a value.
+pub trait Persistence {
+    /// Writes the stored value of the property with the given path, nothing if there is none
+    fn load(&mut self, path: &[u8], writer: &mut dyn Write) -> Result<usize, Error>;
+
+    /// Replaces the stored value of the property with the given path by the remaining bytes of
+    /// the reader
+    fn store(&mut self, path: &[u8], reader: &mut dyn Read) -> Result<usize, Error>;
+}
+
+/// Writes the stored values to all persistent and writable properties, for example after a
+/// reboot. Properties without a stored value are skipped.
+pub fn restore<P, T>(
+    properties: &PropertyRegistry<P, T>,
+    persistence: &mut dyn Persistence,
+    p: &mut P,
+    t: &mut T,
+) -> Result<(), Error> {
+    for property in properties.properties() {
+        restore_property(&[], property, persistence, p, t)?;
+    }
+    Ok(())
+}
+
+/// Restores the properties of the given module, see [`restore`]
+pub fn restore_module<P, M>(
+    module: ModuleId,
+    properties: &PropertyRegistry<P, M>,
+    persistence: &mut dyn Persistence,
+    p: &mut P,
+    m: &mut M,
+) -> Result<(), Error> {
+    for property in properties.properties() {
+        restore_property(&module.to_path(), property, persistence, p, m)?;
+    }
+    Ok(())
+}
+
+/// Restores the properties of all nodes of the given tree of components, see [`restore`]
+pub fn restore_tree<P, T>(
+    root: &ComponentNode<P, T>,
+    persistence: &mut dyn Persistence,
+    p: &mut P,
+    t: &mut T,
+) -> Result<(), Error> {
+    root.visit(&mut |prefix, property| restore_property(prefix, property, persistence, p, t))
+}
+
+fn restore_property<P, T>(
+    prefix: &[u8],
+    property: &Property<P, T>,
+    persistence: &mut dyn Persistence,
+    p: &mut P,
+    t: &mut T,
+) -> Result<(), Error> {
+    if let Some(write_fn) = property.write.as_ref().filter(|_| property.persistent) {
+        let mut path = [0u8; PROPERTY_PATH_MAX_LEN];
+        let len = prefix.len() + property.id.len();
+        let path = path.get_mut(..len).ok_or(Error::PropertyPathTooLong)?;
+        path[..prefix.len()].copy_from_slice(prefix);
+        path[prefix.len()..].copy_from_slice(property.id);
+
+        let mut buffer = [0u8; PERSISTED_VALUE_MAX_LEN];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        if persistence.load(path, &mut cursor)? > 0 {
+            write_fn(p, t, &mut cursor.written())?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads the remaining bytes of the reader into the buffer
+pub(crate) fn read_remaining<'b>(
+    reader: &mut dyn Read,
+    buffer: &'b mut [u8; PERSISTED_VALUE_MAX_LEN],
+) -> Result<&'b [u8], Error> {
+    let len = reader.available();
+    if len > buffer.len() {
+        return Err(Error::BufferToSmall);
+    }
+    reader.read_all(&mut buffer[..len])?;
+    Ok(&buffer[..len])
+}
+
+/// Stores each value in its own file in the given directory, named after the hex encoded path of
+/// the property. Failing to read a file is reported as [`Error::UnexpectedEOF`], failing to
+/// write one as [`Error::BufferToSmall`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct FilePersistence {
+    directory: std::path::PathBuf,
+}
+
+#[cfg(feature = "std")]
+impl FilePersistence {
+    pub fn new(directory: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    fn file(&self, path: &[u8]) -> std::path::PathBuf {
+        self.directory.join(Hex::new(path, "").to_string())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Persistence for FilePersistence {
+    fn load(&mut self, path: &[u8], writer: &mut dyn Write) -> Result<usize, Error> {
+        match std::fs::read(self.file(path)) {
+            Ok(value) => writer.write_all(&value),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(_) => Err(Error::UnexpectedEOF),
+        }
+    }
+
+    fn store(&mut self, path: &[u8], reader: &mut dyn Read) -> Result<usize, Error> {
+        let mut value = vec![0u8; reader.available()];
+        for byte in value.iter_mut() {
+            *byte = reader.read_u8()?;
+        }
+        std::fs::create_dir_all(&self.directory)
+            .and_then(|_| std::fs::write(self.file(path), &value))
+            .map_err(|_| Error::BufferToSmall)?;
+        Ok(value.len())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::props::handling::StorePropertyResponder;
+    use crate::props::ComponentRoot;
+    use crate::{Request, Response};
+    use std::collections::HashMap;
+
+    struct Memory(HashMap<Vec<u8>, Vec<u8>>);
+
+    impl Persistence for Memory {
+        fn load(&mut self, path: &[u8], writer: &mut dyn Write) -> Result<usize, Error> {
+            self.0
+                .get(path)
+                .map_or(Ok(0), |value| writer.write_all(value))
+        }
+
+        fn store(&mut self, path: &[u8], reader: &mut dyn Read) -> Result<usize, Error> {
+            let mut value = vec![0u8; reader.available()];
+            let len = usize::from(reader.read_all(&mut value)?);
+            self.0.insert(path.to_vec(), value);
+            Ok(len)
+        }
+    }
+
+    fn write_u8(_: &mut (), value: &mut u8, reader: &mut dyn Read) -> Result<usize, Error> {
+        *value = reader.read_u8()?;
+        Ok(1)
+    }
+
+    static PROPERTIES: PropertyRegistry<(), u8> = PropertyRegistry::new(&[Property::new(&[0x10])
+        .with_type(crate::Type::U8)
+        .persistent()
+        .writable(write_u8)]);
+    const MODULE: ModuleId = ModuleId {
+        group: 1,
+        id: 2,
+        ext: 0,
+    };
+
+    fn store(memory: &mut Memory, path: &[u8], value: u8, t: &mut u8, m: &mut u8) {
+        let mut payload = path.to_vec();
+        payload.push(value);
+        let reader = &mut &payload[..];
+        let request = Request::StoreProperty(1, path.len() as u8);
+        StorePropertyResponder::opt_from(&request, reader)
+            .unwrap()
+            .with_persistence(memory)
+            .write(
+                &mut Vec::new(),
+                &PROPERTIES,
+                Some((MODULE, &PROPERTIES)),
+                &mut (),
+                t,
+                m,
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn values_are_keyed_by_the_full_path() {
+        let mut memory = Memory(HashMap::new());
+        let (mut t, mut m) = (0, 0);
+        store(&mut memory, &[0x10], 1, &mut t, &mut m);
+        store(
+            &mut memory,
+            &[ComponentRoot::Module as u8, 1, 2, 0, 0x10],
+            2,
+            &mut t,
+            &mut m,
+        );
+        assert_eq!((1, 2), (t, m));
+        assert_eq!(2, memory.0.len());
+
+        let (mut t, mut m) = (0, 0);
+        restore(&PROPERTIES, &mut memory, &mut (), &mut t).unwrap();
+        restore_module(MODULE, &PROPERTIES, &mut memory, &mut (), &mut m).unwrap();
+        assert_eq!((1, 2), (t, m));
+    }
+
+    #[test]
+    fn location_of_the_maximum_length_is_stored() {
+        use crate::props::well_known::DEVICE_LOCATION;
+
+        fn write_location(
+            _: &mut (),
+            location: &mut String,
+            reader: &mut dyn Read,
+        ) -> Result<usize, Error> {
+            *location = reader.read_dyn_string()?;
+            Ok(location.len() + 1)
+        }
+
+        static LOCATIONS: PropertyRegistry<(), String> =
+            PropertyRegistry::new(&[Property::new(&DEVICE_LOCATION)
+                .with_type(crate::Type::DynString)
+                .persistent()
+                .writable(write_location)]);
+
+        let location = "l".repeat(DEVICE_LOCATION_MAX_LEN);
+        let mut payload = DEVICE_LOCATION.to_vec();
+        payload.write_dyn_string(&location).unwrap();
+        let request = Request::StoreProperty(1, DEVICE_LOCATION.len() as u8);
+
+        let mut memory = Memory(HashMap::new());
+        let mut stored = String::new();
+        let mut response = Vec::new();
+        StorePropertyResponder::opt_from(&request, &mut &payload[..])
+            .unwrap()
+            .with_persistence(&mut memory)
+            .write::<(), String, ()>(
+                &mut response,
+                &LOCATIONS,
+                None,
+                &mut (),
+                &mut stored,
+                &mut (),
+            )
+            .unwrap();
+
+        assert_eq!(
+            Ok(Response::Ok(1, crate::Format::Empty)),
+            Response::read(&mut &response[..])
+        );
+        assert_eq!(location, stored);
+        assert_eq!(
+            Some(&payload[DEVICE_LOCATION.len()..]),
+            memory.0.get(&DEVICE_LOCATION[..]).map(Vec::as_slice)
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use crate::value::scaled;
+use crate::{Error, Read, Type, Write};
+
+/// The valid values of a numeric property. The bounds are encoded in the format of the type hint
+/// of the property, so a range can only be reported for properties with a numeric type hint.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct ValueRange {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// The distance between two valid values, counted from `min` (or zero if there is no `min`)
+    pub step: Option<f64>,
+}
+
+impl ValueRange {
+    pub const fn new() -> Self {
+        Self {
+            min: None,
+            max: None,
+            step: None,
+        }
+    }
+
+    pub const fn with_min(mut self, min: f64) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    pub const fn with_max(mut self, max: f64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    pub const fn with_step(mut self, step: f64) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    /// Whether the given value lies within the bounds and on a step
+    pub fn contains(&self, value: f64) -> bool {
+        if self.min.is_some_and(|min| value < min) || self.max.is_some_and(|max| value > max) {
+            return false;
+        }
+
+        match self.step {
+            Some(step) if step > 0.0 => {
+                let steps = (value - self.min.unwrap_or_default()) / step;
+                let deviation = steps - round(steps);
+                -1e-6 < deviation && deviation < 1e-6
+            }
+            _ => true,
+        }
+    }
+
+    /// Decodes the value in the format of the given type and checks whether it is within the
+    /// range, see [`Self::contains`]. Values of types that are not numeric are always valid.
+    pub fn validate(&self, ty: Type, value: &[u8]) -> Result<bool, Error> {
+        if is_numeric(ty) {
+            Ok(self.contains(read_value(ty, &mut &*value)?))
+        } else {
+            Ok(true)
+        }
+    }
+
+    pub fn write(&self, ty: Type, writer: &mut dyn Write) -> Result<usize, Error> {
+        if !is_numeric(ty) {
+            return Err(Error::UnknownTypeIdentifier);
+        }
+
+        let flags = self.min.map(|_| 1u8 << 0).unwrap_or_default()
+            | self.max.map(|_| 1u8 << 1).unwrap_or_default()
+            | self.step.map(|_| 1u8 << 2).unwrap_or_default();
+
+        let mut len = writer.write_u8(flags)?;
+        for value in [self.min, self.max, self.step].iter().flatten() {
+            len += write_value(ty, *value, writer)?;
+        }
+        Ok(len)
+    }
+
+    pub fn read(ty: Type, reader: &mut impl Read) -> Result<Self, Error> {
+        if !is_numeric(ty) {
+            return Err(Error::UnknownTypeIdentifier);
+        }
+
+        let flags = reader.read_u8()?;
+        let mut range = ValueRange::new();
+        for (bit, value) in [&mut range.min, &mut range.max, &mut range.step]
+            .iter_mut()
+            .enumerate()
+        {
+            if flags & (1u8 << bit) != 0 {
+                **value = Some(read_value(ty, reader)?);
+            }
+        }
+        Ok(range)
+    }
+}
+
+impl Default for ValueRange {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Rounds half away from zero, `f64::round` is not available without `std`
+fn round(value: f64) -> f64 {
+    if value < 0.0 {
+        -((0.5 - value) as i64 as f64)
+    } else {
+        (value + 0.5) as i64 as f64
+    }
+}
+
+/// The size of the largest numeric type, see [`is_numeric`]
+pub const NUMERIC_VALUE_MAX_LEN: usize = 16;
+
+/// Whether a [`ValueRange`] can be encoded for values of the given type
+pub const fn is_numeric(ty: Type) -> bool {
+    matches!(
+        ty,
+        Type::F32
+            | Type::F64
+            | Type::ScaledI32 { .. }
+            | Type::U128
+            | Type::I128
+            | Type::U64
+            | Type::I64
+            | Type::U32
+            | Type::I32
+            | Type::U16
+            | Type::I16
+            | Type::U8
+            | Type::I8
+    )
+}
+
+fn write_value(ty: Type, value: f64, writer: &mut dyn Write) -> Result<usize, Error> {
+    match ty {
+        Type::F32 => writer.write_all(&(value as f32).to_be_bytes()),
+        Type::F64 => writer.write_all(&value.to_be_bytes()),
+        Type::ScaledI32 { exponent } => {
+            let raw = value / scaled(1, exponent);
+            writer.write_all(&((raw + if raw < 0.0 { -0.5 } else { 0.5 }) as i32).to_be_bytes())
+        }
+        Type::U128 => writer.write_all(&(value as u128).to_be_bytes()),
+        Type::I128 => writer.write_all(&(value as i128).to_be_bytes()),
+        Type::U64 => writer.write_all(&(value as u64).to_be_bytes()),
+        Type::I64 => writer.write_all(&(value as i64).to_be_bytes()),
+        Type::U32 => writer.write_all(&(value as u32).to_be_bytes()),
+        Type::I32 => writer.write_all(&(value as i32).to_be_bytes()),
+        Type::U16 => writer.write_all(&(value as u16).to_be_bytes()),
+        Type::I16 => writer.write_all(&(value as i16).to_be_bytes()),
+        Type::U8 => writer.write_all(&(value as u8).to_be_bytes()),
+        Type::I8 => writer.write_all(&(value as i8).to_be_bytes()),
+        _ => Err(Error::UnknownTypeIdentifier),
+    }
+}
+
+fn read_value(ty: Type, reader: &mut impl Read) -> Result<f64, Error> {
+    let mut bytes = [0u8; NUMERIC_VALUE_MAX_LEN];
+    let size = ty.value_size().ok_or(Error::UnknownTypeIdentifier)?;
+    reader.read_all(&mut bytes[..size])?;
+
+    macro_rules! decode {
+        ($ty:ty) => {{
+            let mut be = <$ty>::default().to_be_bytes();
+            be.copy_from_slice(&bytes[..size]);
+            <$ty>::from_be_bytes(be) as f64
+        }};
+    }
+
+    Ok(match ty {
+        Type::F32 => decode!(f32),
+        Type::F64 => decode!(f64),
+        Type::ScaledI32 { exponent } => decode!(i32) * scaled(1, exponent),
+        Type::U128 => decode!(u128),
+        Type::I128 => decode!(i128),
+        Type::U64 => decode!(u64),
+        Type::I64 => decode!(i64),
+        Type::U32 => decode!(u32),
+        Type::I32 => decode!(i32),
+        Type::U16 => decode!(u16),
+        Type::I16 => decode!(i16),
+        Type::U8 => decode!(u8),
+        Type::I8 => decode!(i8),
+        _ => return Err(Error::UnknownTypeIdentifier),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_validation() {
+        let range = ValueRange::new()
+            .with_min(10.0)
+            .with_max(20.0)
+            .with_step(2.5);
+        assert!(range.contains(10.0));
+        assert!(range.contains(17.5));
+        assert!(range.contains(20.0));
+        assert!(!range.contains(9.0));
+        assert!(!range.contains(21.0));
+        assert!(!range.contains(11.0));
+
+        assert!(range.validate(Type::U16, &15u16.to_be_bytes()).unwrap());
+        assert!(!range.validate(Type::I8, &(-5i8).to_be_bytes()).unwrap());
+        assert_eq!(
+            Err(Error::UnexpectedEOF),
+            range.validate(Type::U32, &[0x00])
+        );
+    }
+
+    #[test]
+    fn negative_values_on_a_step() {
+        let range = ValueRange::new().with_step(2.0);
+        assert!(range.contains(-4.0));
+        assert!(range.contains(-2.0));
+        assert!(!range.contains(-3.0));
+        assert!(!range.contains(-0.5));
+
+        let range = ValueRange::new().with_min(-10.0).with_step(2.5);
+        assert!(range.contains(-10.0));
+        assert!(range.contains(-7.5));
+        assert!(range.contains(5.0));
+        assert!(!range.contains(-8.0));
+        assert!(range.validate(Type::I8, &(-5i8).to_be_bytes()).unwrap());
+        assert!(!range.validate(Type::I8, &(-6i8).to_be_bytes()).unwrap());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::props::Property;
+
+/// Creates a [`PropertyRegistry`] from the given properties, which must be sorted by their id.
+/// When used as initializer of a `static` or `const`, duplicate or unsorted ids are rejected at
+/// compile time.
+///
+/// ```
+/// # use sensor_common::properties;
+/// # use sensor_common::props::registry::PropertyRegistry;
+/// # use sensor_common::props::Property;
+/// static PROPERTIES: PropertyRegistry<(), ()> = properties![
+///     Property::new(&[0x10, 0x00, 0x00]),
+///     Property::new(&[0x10, 0x01]),
+/// ];
+/// assert!(PROPERTIES.find(&[0x10, 0x01]).is_some());
+/// ```
+///
+/// A duplicate id does not compile:
+///
+/// ```compile_fail
+/// # use sensor_common::properties;
+/// # use sensor_common::props::registry::PropertyRegistry;
+/// # use sensor_common::props::Property;
+/// static PROPERTIES: PropertyRegistry<(), ()> = properties![
+///     Property::new(&[0x10, 0x01]),
+///     Property::new(&[0x10, 0x01]),
+/// ];
+/// ```
+#[macro_export]
+macro_rules! properties {
+    ($($property:expr),* $(,)?) => {
+        $crate::props::registry::PropertyRegistry::new(&[$($property),*])
+    };
+}
+
+/// A static table of [`Property`]s sorted by their id, which allows to look up properties by
+/// binary search.
+pub struct PropertyRegistry<P: 'static, T: 'static> {
+    properties: &'static [Property<P, T>],
+}
+
+impl<P: 'static, T: 'static> PropertyRegistry<P, T> {
+    /// # Panics
+    ///
+    /// If the ids of the given properties are not unique and in ascending order. Evaluated
+    /// in a const context, this is a compile time error instead.
+    pub const fn new(properties: &'static [Property<P, T>]) -> Self {
+        let mut i = 1;
+        while i < properties.len() {
+            assert!(
+                is_less(properties[i - 1].id, properties[i].id),
+                "Property ids must be unique and in ascending order"
+            );
+            i += 1;
+        }
+        Self { properties }
+    }
+
+    pub const fn properties(&self) -> &'static [Property<P, T>] {
+        self.properties
+    }
+
+    pub fn find(&self, id: &[u8]) -> Option<&'static Property<P, T>> {
+        self.properties
+            .binary_search_by(|property| property.id.cmp(id))
+            .ok()
+            .map(|index| &self.properties[index])
+    }
+}
+
+/// Lexicographic comparison of two property ids, as `<[u8]>::cmp` is not yet const
+const fn is_less(a: &[u8], b: &[u8]) -> bool {
+    let mut i = 0;
+    while i < a.len() && i < b.len() {
+        if a[i] != b[i] {
+            return a[i] < b[i];
+        }
+        i += 1;
+    }
+    a.len() < b.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static PROPERTIES: PropertyRegistry<(), ()> = PropertyRegistry::new(&[
+        Property::new(&[0x10]),
+        Property::new(&[0x10, 0x00, 0x00]),
+        Property::new(&[0x10, 0x01]),
+        Property::new(&[0x20]),
+    ]);
+
+    #[test]
+    fn find_looks_up_the_exact_id() {
+        assert_eq!(
+            Some(&[0x10, 0x01][..]),
+            PROPERTIES.find(&[0x10, 0x01]).map(|p| p.id)
+        );
+        assert_eq!(Some(&[0x20][..]), PROPERTIES.find(&[0x20]).map(|p| p.id));
+        assert!(PROPERTIES.find(&[0x30]).is_none());
+        assert!(PROPERTIES.find(&[]).is_none());
+    }
+
+    #[test]
+    fn find_does_not_match_prefixes() {
+        assert_eq!(Some(&[0x10][..]), PROPERTIES.find(&[0x10]).map(|p| p.id));
+        assert!(PROPERTIES.find(&[0x10, 0x00]).is_none());
+        assert!(PROPERTIES.find(&[0x10, 0x01, 0x00]).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Property ids must be unique and in ascending order")]
+    fn unsorted_ids_are_rejected() {
+        static UNSORTED: [Property<(), ()>; 2] = [Property::new(&[0x11]), Property::new(&[0x10])];
+        PropertyRegistry::new(&UNSORTED);
+    }
+}
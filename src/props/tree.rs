@@ -0,0 +1,185 @@
+use crate::props::registry::PropertyRegistry;
+use crate::props::{ComponentRoot, ModuleId, Property};
+use crate::Error;
+
+/// The maximum length of a property path, including the prefixes of all [`ComponentNode`]s
+pub const PROPERTY_PATH_MAX_LEN: usize = 16;
+
+/// A node in the tree of components of a device. The path of a property is the concatenation
+/// of the prefixes of all nodes from the root to the node of the property, followed by the id
+/// of the property.
+///
+/// This allows a device with several plug-in modules to list and address the properties of all
+/// modules through a single responder, for example:
+///
+/// ```ignore
+/// static MODULE_A: [u8; 4] = ModuleId { group: 1, id: 1, ext: 0 }.to_path();
+/// static MODULE_B: [u8; 4] = ModuleId { group: 1, id: 2, ext: 0 }.to_path();
+/// static DEVICE: ComponentNode<Platform, Modules> = ComponentNode::new(
+///     &[],
+///     &PLATFORM_PROPERTIES,
+///     &[
+///         ComponentNode::new(&MODULE_A, &MODULE_A_PROPERTIES, &[]),
+///         ComponentNode::new(&MODULE_B, &MODULE_B_PROPERTIES, &[]),
+///     ],
+/// );
+/// ```
+pub struct ComponentNode<'a, P: 'static, T: 'static> {
+    pub prefix: &'a [u8],
+    pub properties: &'a PropertyRegistry<P, T>,
+    pub children: &'a [ComponentNode<'a, P, T>],
+}
+
+impl<'a, P: 'static, T: 'static> ComponentNode<'a, P, T> {
+    pub const fn new(
+        prefix: &'a [u8],
+        properties: &'a PropertyRegistry<P, T>,
+        children: &'a [ComponentNode<'a, P, T>],
+    ) -> Self {
+        Self {
+            prefix,
+            properties,
+            children,
+        }
+    }
+
+    /// Looks up the property with the given path, relative to this node
+    pub fn find(&self, path: &[u8]) -> Option<&'static Property<P, T>> {
+        self.children
+            .iter()
+            .filter_map(|child| {
+                path.strip_prefix(child.prefix)
+                    .and_then(|remaining| child.find(remaining))
+            })
+            .next()
+            .or_else(|| self.properties.find(path))
+    }
+
+    /// Calls `f` for each property of this node and all its children with the path prefix of the
+    /// node of the property. Nodes with a path prefix longer than [`PROPERTY_PATH_MAX_LEN`] are
+    /// skipped.
+    pub fn visit(
+        &self,
+        f: &mut impl FnMut(&[u8], &'static Property<P, T>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        self.visit_with_prefix(&mut [0u8; PROPERTY_PATH_MAX_LEN], 0, f)
+    }
+
+    fn visit_with_prefix(
+        &self,
+        buffer: &mut [u8; PROPERTY_PATH_MAX_LEN],
+        prefix_len: usize,
+        f: &mut impl FnMut(&[u8], &'static Property<P, T>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        for property in self.properties.properties() {
+            f(&buffer[..prefix_len], property)?;
+        }
+
+        for child in self.children {
+            let child_prefix_len = prefix_len + child.prefix.len();
+            if child_prefix_len <= PROPERTY_PATH_MAX_LEN {
+                buffer[prefix_len..child_prefix_len].copy_from_slice(child.prefix);
+                child.visit_with_prefix(buffer, child_prefix_len, f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ModuleId {
+    /// The path prefix of the properties of this module
+    pub const fn to_path(self) -> [u8; 4] {
+        [ComponentRoot::Module as u8, self.group, self.id, self.ext]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const M: u8 = ComponentRoot::Module as u8;
+    const MODULE: [u8; 4] = ModuleId {
+        group: 1,
+        id: 2,
+        ext: 0,
+    }
+    .to_path();
+
+    static ROOT: PropertyRegistry<(), ()> =
+        PropertyRegistry::new(&[Property::new(&[0x10]), Property::new(&[0x11])]);
+    static MODULE_PROPERTIES: PropertyRegistry<(), ()> =
+        PropertyRegistry::new(&[Property::new(&[0x10]), Property::new(&[0x12])]);
+    static DEEP_PROPERTIES: PropertyRegistry<(), ()> =
+        PropertyRegistry::new(&[Property::new(&[0x13])]);
+    static TOO_DEEP_PROPERTIES: PropertyRegistry<(), ()> =
+        PropertyRegistry::new(&[Property::new(&[0x14])]);
+
+    /// The module prefix, followed by a prefix of 11 bytes and a one byte id, fills
+    /// [`PROPERTY_PATH_MAX_LEN`]. The prefix of 13 bytes does not fit.
+    static TREE: ComponentNode<(), ()> = ComponentNode::new(
+        &[],
+        &ROOT,
+        &[ComponentNode::new(
+            &MODULE,
+            &MODULE_PROPERTIES,
+            &[
+                ComponentNode::new(&[0x70; 11], &DEEP_PROPERTIES, &[]),
+                ComponentNode::new(&[0x71; 13], &TOO_DEEP_PROPERTIES, &[]),
+            ],
+        )],
+    );
+
+    const DEEP_PATH: [u8; PROPERTY_PATH_MAX_LEN] = [
+        M, 1, 2, 0, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x13,
+    ];
+
+    fn find(path: &[u8]) -> Option<&'static [u8]> {
+        TREE.find(path).map(|property| property.id)
+    }
+
+    #[test]
+    fn find_looks_up_nested_properties() {
+        assert_eq!(Some(&[0x11][..]), find(&[0x11]));
+        assert_eq!(Some(&[0x10][..]), find(&[M, 1, 2, 0, 0x10]));
+        assert_eq!(Some(&[0x12][..]), find(&[M, 1, 2, 0, 0x12]));
+        // a property of the root, not of the module
+        assert_eq!(None, find(&[M, 1, 2, 0, 0x11]));
+    }
+
+    #[test]
+    fn find_fails_for_a_missing_child() {
+        assert_eq!(None, find(&[M, 1, 3, 0, 0x10]));
+        assert_eq!(None, find(&[M, 1, 2, 0, 0x72, 0x13]));
+        assert_eq!(None, find(&MODULE));
+    }
+
+    #[test]
+    fn paths_up_to_the_maximum_length_are_visited() {
+        assert_eq!(Some(&[0x13][..]), find(&DEEP_PATH));
+
+        let mut visited = [([0u8; PROPERTY_PATH_MAX_LEN], 0); 6];
+        let mut count = 0;
+        TREE.visit(&mut |prefix, property| {
+            let (path, len) = &mut visited[count];
+            *len = prefix.len() + property.id.len();
+            path[..prefix.len()].copy_from_slice(prefix);
+            path[prefix.len()..*len].copy_from_slice(property.id);
+            count += 1;
+            Ok(())
+        })
+        .unwrap();
+
+        let expected: [&[u8]; 5] = [
+            &[0x10],
+            &[0x11],
+            &[M, 1, 2, 0, 0x10],
+            &[M, 1, 2, 0, 0x12],
+            &DEEP_PATH,
+        ];
+        assert_eq!(expected.len(), count);
+        for (expected, (path, len)) in expected.iter().zip(&visited) {
+            assert_eq!(*expected, &path[..*len]);
+        }
+    }
+}
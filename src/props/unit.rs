@@ -0,0 +1,123 @@
+use crate::{Error, Read, Write};
+
+/// The unit of the value of a property
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Unit {
+    Celsius,
+    Fahrenheit,
+    Pascal,
+    Percent,
+    Volt,
+    Ampere,
+    Lux,
+    Custom(CustomUnit),
+}
+
+impl Unit {
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        Ok(match self {
+            Unit::Celsius => writer.write_u8(0x00)?,
+            Unit::Fahrenheit => writer.write_u8(0x01)?,
+            Unit::Pascal => writer.write_u8(0x02)?,
+            Unit::Percent => writer.write_u8(0x03)?,
+            Unit::Volt => writer.write_u8(0x04)?,
+            Unit::Ampere => writer.write_u8(0x05)?,
+            Unit::Lux => writer.write_u8(0x06)?,
+            Unit::Custom(custom) => {
+                writer.write_u8(0xFF)? + writer.write_dyn_string(custom.as_str())?
+            }
+        })
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        Ok(match reader.read_u8()? {
+            0x00 => Unit::Celsius,
+            0x01 => Unit::Fahrenheit,
+            0x02 => Unit::Pascal,
+            0x03 => Unit::Percent,
+            0x04 => Unit::Volt,
+            0x05 => Unit::Ampere,
+            0x06 => Unit::Lux,
+            0xFF => {
+                let len = usize::from(reader.read_u8()?);
+                let mut bytes = [0u8; CustomUnit::MAX_LEN];
+                if len > bytes.len() {
+                    return Err(Error::BufferToSmall);
+                }
+                reader.read_all(&mut bytes[..len])?;
+                let custom = core::str::from_utf8(&bytes[..len]).map_err(|_| Error::InvalidUtf8)?;
+                Unit::Custom(CustomUnit::new(custom))
+            }
+            _ => return Err(Error::UnknownTypeIdentifier),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Unit::Celsius => "°C",
+            Unit::Fahrenheit => "°F",
+            Unit::Pascal => "Pa",
+            Unit::Percent => "%",
+            Unit::Volt => "V",
+            Unit::Ampere => "A",
+            Unit::Lux => "lx",
+            Unit::Custom(custom) => custom.as_str(),
+        }
+    }
+}
+
+/// The symbol of a unit not covered by [`Unit`], for example `"rpm"` or `"µg/m³"`. Stored inline
+/// so that it can be used in `static` property tables without allocating.
+#[derive(Copy, Clone, PartialEq)]
+pub struct CustomUnit {
+    bytes: [u8; CustomUnit::MAX_LEN],
+    len: u8,
+}
+
+impl CustomUnit {
+    pub const MAX_LEN: usize = 15;
+
+    /// Truncates the symbol to at most [`CustomUnit::MAX_LEN`] bytes
+    pub const fn new(symbol: &str) -> Self {
+        let symbol = symbol.as_bytes();
+        let mut len = if symbol.len() < Self::MAX_LEN {
+            symbol.len()
+        } else {
+            Self::MAX_LEN
+        };
+        // do not split a multi-byte character
+        while len < symbol.len() && len > 0 && symbol[len] & 0b1100_0000 == 0b1000_0000 {
+            len -= 1;
+        }
+
+        let mut bytes = [0u8; Self::MAX_LEN];
+        let mut i = 0;
+        while i < len {
+            bytes[i] = symbol[i];
+            i += 1;
+        }
+
+        Self {
+            bytes,
+            len: len as u8,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.bytes[..usize::from(self.len)]).unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for CustomUnit {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(CustomUnit::new(u.arbitrary()?))
+    }
+}
+
+impl core::fmt::Debug for CustomUnit {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("CustomUnit").field(&self.as_str()).finish()
+    }
+}
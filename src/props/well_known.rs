@@ -0,0 +1,72 @@
+//! The property paths of the components every device is expected to provide, built from the
+//! component enums of the [`crate::props`] module.
+
+use crate::props::{
+    ComponentRoot, CpuComponent, DeviceComponent, EeePromComponent, MetaInformation,
+    NetworkComponent, PlatformComponent, SntpComponent, TemperatureComponent,
+};
+
+const fn device(component: DeviceComponent) -> [u8; 2] {
+    [ComponentRoot::Device as u8, component as u8]
+}
+
+const fn platform(component: PlatformComponent, sub: u8) -> [u8; 3] {
+    [ComponentRoot::Platform as u8, component as u8, sub]
+}
+
+pub const DEVICE_FREQUENCY: [u8; 2] = device(DeviceComponent::Frequency);
+pub const DEVICE_UPTIME: [u8; 2] = device(DeviceComponent::Uptime);
+
+/// A writable and persistent `Type::DynString` to tell devices apart by more than their
+/// address, for example `"boiler-room-1"`
+pub const DEVICE_NAME: [u8; 2] = device(DeviceComponent::Name);
+/// A writable and persistent `Type::DynString` describing where the device is installed
+pub const DEVICE_LOCATION: [u8; 2] = device(DeviceComponent::Location);
+/// A persistent `Type::U32` counting the boots of the device, see
+/// [`crate::info::increment_boot_count`]
+pub const DEVICE_BOOT_COUNT: [u8; 2] = device(DeviceComponent::BootCount);
+/// The maximum length in bytes of the [`DEVICE_NAME`] devices have to accept
+pub const DEVICE_NAME_MAX_LEN: usize = 32;
+/// The maximum length in bytes of the [`DEVICE_LOCATION`] devices have to accept
+pub const DEVICE_LOCATION_MAX_LEN: usize = 64;
+
+pub const CPU_ID: [u8; 3] = CpuComponent::Id.to_cid_path();
+pub const CPU_IMPLEMENTER: [u8; 3] = CpuComponent::Implementer.to_cid_path();
+pub const CPU_VARIANT: [u8; 3] = CpuComponent::Variant.to_cid_path();
+pub const CPU_PART_NUMBER: [u8; 3] = CpuComponent::PartNumber.to_cid_path();
+pub const CPU_REVISION: [u8; 3] = CpuComponent::Revision.to_cid_path();
+
+pub const PLATFORM_META_VERSION: [u8; 3] =
+    platform(PlatformComponent::Meta, MetaInformation::Version as u8);
+
+pub const PLATFORM_EEPROM_MAGIC_CRC_START: [u8; 3] = platform(
+    PlatformComponent::EeeProm,
+    EeePromComponent::MagicCrcStart as u8,
+);
+
+pub const PLATFORM_NETWORK_MAC: [u8; 3] =
+    platform(PlatformComponent::Network, NetworkComponent::Mac as u8);
+pub const PLATFORM_NETWORK_IP: [u8; 3] =
+    platform(PlatformComponent::Network, NetworkComponent::Ip as u8);
+pub const PLATFORM_NETWORK_SUBNET: [u8; 3] =
+    platform(PlatformComponent::Network, NetworkComponent::Subnet as u8);
+pub const PLATFORM_NETWORK_GATEWAY: [u8; 3] =
+    platform(PlatformComponent::Network, NetworkComponent::Gateway as u8);
+
+pub const PLATFORM_TEMPERATURE_VALUE: [u8; 3] = platform(
+    PlatformComponent::Temperature,
+    TemperatureComponent::Value as u8,
+);
+
+pub const PLATFORM_SNTP_CURRENT_TIME: [u8; 3] = platform(
+    PlatformComponent::Sntp,
+    SntpComponent::CurrentTimeMillis as u8,
+);
+pub const PLATFORM_SNTP_LAST_OFFSET: [u8; 3] = platform(
+    PlatformComponent::Sntp,
+    SntpComponent::LastOffsetMillis as u8,
+);
+pub const PLATFORM_SNTP_LAST_UPDATE: [u8; 3] = platform(
+    PlatformComponent::Sntp,
+    SntpComponent::LastUpdateMillis as u8,
+);
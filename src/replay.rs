@@ -0,0 +1,135 @@
+//! Replay protection through monotonic sequence numbers.
+//!
+//! A request can be prefixed with a [`SequenceHeader`], which the device validates with a
+//! [`ReplayWindow`] before handling the request, so that captured datagrams cannot be replayed.
+
+use crate::{Error, Read, Write};
+
+/// The first byte of an encoded [`SequenceHeader`], which is not used by any request opcode
+pub const SEQUENCE_HEADER_MARKER: u8 = 0x50;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SequenceHeader(pub u32);
+
+impl SequenceHeader {
+    pub const ENCODED_LEN: usize = 1 + 4;
+
+    pub fn write(&self, writer: &mut impl Write) -> Result<usize, Error> {
+        Ok(writer.write_u8(SEQUENCE_HEADER_MARKER)? + writer.write_u32_be(self.0)?)
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        if reader.read_u8()? != SEQUENCE_HEADER_MARKER {
+            return Err(Error::UnknownTypeIdentifier);
+        }
+        Ok(SequenceHeader(reader.read_u32_be()?))
+    }
+
+    /// Reads the [`SequenceHeader`] if the given datagram starts with one. Returns the header
+    /// and the remaining datagram.
+    pub fn read_optional(datagram: &[u8]) -> Result<(Option<Self>, &[u8]), Error> {
+        if datagram.first() == Some(&SEQUENCE_HEADER_MARKER) {
+            let reader = &mut &*datagram;
+            let header = Self::read(reader)?;
+            Ok((Some(header), reader))
+        } else {
+            Ok((None, datagram))
+        }
+    }
+}
+
+/// Accepts each sequence number at most once. Sequence numbers may arrive out of order as long
+/// as they are not older than [`ReplayWindow::SIZE`] numbers behind the highest one seen.
+#[derive(Debug, Copy, Clone)]
+pub struct ReplayWindow {
+    highest: Option<u32>,
+    /// Bit `n` is set if `highest - n` has been seen
+    seen: u64,
+}
+
+impl Default for ReplayWindow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReplayWindow {
+    pub const SIZE: u32 = 64;
+
+    pub const fn new() -> Self {
+        Self {
+            highest: None,
+            seen: 0,
+        }
+    }
+
+    /// Returns whether the sequence number is fresh and marks it as seen if so
+    pub fn accept(&mut self, sequence: u32) -> bool {
+        match self.highest {
+            None => {
+                self.highest = Some(sequence);
+                self.seen = 1;
+                true
+            }
+            Some(highest) if sequence > highest => {
+                let shift = sequence - highest;
+                self.seen = if shift < Self::SIZE {
+                    (self.seen << shift) | 1
+                } else {
+                    1
+                };
+                self.highest = Some(sequence);
+                true
+            }
+            Some(highest) => {
+                let offset = highest - sequence;
+                if offset >= Self::SIZE || self.seen & (1 << offset) != 0 {
+                    false
+                } else {
+                    self.seen |= 1 << offset;
+                    true
+                }
+            }
+        }
+    }
+
+    /// Forgets all seen sequence numbers, for example after the shared key has been changed
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_window_rejects_duplicates_and_old_numbers() {
+        let mut window = ReplayWindow::new();
+        assert!(window.accept(100));
+        assert!(!window.accept(100));
+        assert!(window.accept(102));
+        assert!(window.accept(101));
+        assert!(!window.accept(101));
+        assert!(window.accept(200));
+        assert!(!window.accept(102));
+        assert!(window.accept(199));
+    }
+
+    #[test]
+    fn sequence_header_is_optional() {
+        let mut datagram = [0u8; 7];
+        SequenceHeader(0x01020304)
+            .write(&mut &mut datagram[..])
+            .unwrap();
+        datagram[5..].copy_from_slice(&[0x01, 0x42]);
+
+        let (header, request) = SequenceHeader::read_optional(&datagram).unwrap();
+        assert_eq!(Some(SequenceHeader(0x01020304)), header);
+        assert_eq!(&[0x01, 0x42], request);
+
+        let (header, request) = SequenceHeader::read_optional(request).unwrap();
+        assert_eq!(None, header);
+        assert_eq!(&[0x01, 0x42], request);
+    }
+}
@@ -0,0 +1,195 @@
+//! Routing of requests to devices behind a gateway.
+//!
+//! A device attached to Ethernet can relay requests to the devices on its sub-buses, for
+//! example on RS485. A request for such a device is prefixed with a [`RoutingHeader`] listing
+//! the node ids of the hops from the gateway to the device, in front of any other header. Each
+//! [`Relay`] removes the first hop and forwards the remaining datagram to that node, without
+//! the header after the last hop. On the way back, each relay prefixes the response with the
+//! node it has been received from, so that the response carries the same hops as the request.
+
+use crate::{Error, Read, Write};
+
+/// The first byte of an encoded [`RoutingHeader`], which is not used by any request or
+/// response opcode
+pub const ROUTING_HEADER_MARKER: u8 = 0x51;
+
+/// The maximum number of hops of a [`RoutingHeader`]
+pub const MAX_HOPS: usize = 8;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RoutingHeader {
+    len: u8,
+    hops: [u8; MAX_HOPS],
+}
+
+impl RoutingHeader {
+    /// Fails with `Error::BufferToSmall` for more than [`MAX_HOPS`] hops
+    pub fn new(hops: &[u8]) -> Result<Self, Error> {
+        let mut header = Self {
+            len: hops.len() as u8,
+            hops: [0u8; MAX_HOPS],
+        };
+        header
+            .hops
+            .get_mut(..hops.len())
+            .ok_or(Error::BufferToSmall)?
+            .copy_from_slice(hops);
+        Ok(header)
+    }
+
+    /// The node ids of the hops, starting with the one next to the gateway
+    pub fn hops(&self) -> &[u8] {
+        &self.hops[..usize::from(self.len)]
+    }
+
+    pub fn encoded_len(&self) -> usize {
+        1 + 1 + usize::from(self.len)
+    }
+
+    /// The first hop and the header of the remaining hops, if any
+    pub fn split_first(&self) -> Option<(u8, Option<Self>)> {
+        let (first, remaining) = self.hops().split_first()?;
+        let remaining = if remaining.is_empty() {
+            None
+        } else {
+            Self::new(remaining).ok()
+        };
+        Some((*first, remaining))
+    }
+
+    /// The header with the given node in front of the hops
+    pub fn prepend(&self, node: u8) -> Result<Self, Error> {
+        let mut header = Self::new(&[node])?;
+        header
+            .hops
+            .get_mut(1..1 + usize::from(self.len))
+            .ok_or(Error::BufferToSmall)?
+            .copy_from_slice(self.hops());
+        header.len += self.len;
+        Ok(header)
+    }
+
+    pub fn write(&self, writer: &mut impl Write) -> Result<usize, Error> {
+        Ok(writer.write_u8(ROUTING_HEADER_MARKER)?
+            + writer.write_u8(self.len)?
+            + writer.write_all(self.hops())?)
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        if reader.read_u8()? != ROUTING_HEADER_MARKER {
+            return Err(Error::UnknownTypeIdentifier);
+        }
+        let len = usize::from(reader.read_u8()?);
+        let mut hops = [0u8; MAX_HOPS];
+        reader.read_all(hops.get_mut(..len).ok_or(Error::BufferToSmall)?)?;
+        Self::new(&hops[..len])
+    }
+
+    /// Reads the [`RoutingHeader`] if the given datagram starts with one. Returns the header
+    /// and the remaining datagram.
+    pub fn read_optional(datagram: &[u8]) -> Result<(Option<Self>, &[u8]), Error> {
+        if datagram.first() == Some(&ROUTING_HEADER_MARKER) {
+            let reader = &mut &*datagram;
+            let header = Self::read(reader)?;
+            Ok((Some(header), reader))
+        } else {
+            Ok((None, datagram))
+        }
+    }
+}
+
+/// Where a request received by a [`Relay`] has to go
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Forward<'a> {
+    /// The request is addressed to this device, the datagram is without the routing header
+    Local(&'a [u8]),
+    /// The datagram has to be forwarded to the node with the given id
+    Node(u8, &'a [u8]),
+}
+
+/// Rewrites the datagrams passing through a gateway, in a buffer of `N` bytes
+#[derive(Debug, Clone)]
+pub struct Relay<const N: usize> {
+    buffer: [u8; N],
+}
+
+impl<const N: usize> Default for Relay<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Relay<N> {
+    pub const fn new() -> Self {
+        Self { buffer: [0u8; N] }
+    }
+
+    /// Removes the first hop of the request, see [`Forward`]
+    pub fn forward_request<'a>(&'a mut self, datagram: &'a [u8]) -> Result<Forward<'a>, Error> {
+        let (header, remaining) = RoutingHeader::read_optional(datagram)?;
+        let (node, header) = match header.as_ref().and_then(RoutingHeader::split_first) {
+            Some(split) => split,
+            None => return Ok(Forward::Local(remaining)),
+        };
+        Ok(Forward::Node(node, self.prefix(header, remaining)?))
+    }
+
+    /// Adds the node the response has been received from in front of the hops of the response
+    pub fn forward_response(&mut self, node: u8, datagram: &[u8]) -> Result<&[u8], Error> {
+        let (header, remaining) = RoutingHeader::read_optional(datagram)?;
+        let header = match header {
+            Some(header) => header.prepend(node)?,
+            None => RoutingHeader::new(&[node])?,
+        };
+        self.prefix(Some(header), remaining)
+    }
+
+    fn prefix(&mut self, header: Option<RoutingHeader>, datagram: &[u8]) -> Result<&[u8], Error> {
+        let header_len = header.map_or(0, |header| header.encoded_len());
+        let len = header_len + datagram.len();
+        if len > N {
+            return Err(Error::BufferToSmall);
+        }
+        if let Some(header) = header {
+            header.write(&mut &mut self.buffer[..header_len])?;
+        }
+        self.buffer[header_len..len].copy_from_slice(datagram);
+        Ok(&self.buffer[..len])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_and_response_pass_two_relays() {
+        let request = [ROUTING_HEADER_MARKER, 2, 0x07, 0x03, 0x01, 8];
+        let mut gateway = Relay::<16>::new();
+        let mut master = Relay::<16>::new();
+
+        let forwarded = match gateway.forward_request(&request).unwrap() {
+            Forward::Node(node, datagram) => {
+                assert_eq!(0x07, node);
+                datagram
+            }
+            Forward::Local(_) => panic!("request not forwarded"),
+        };
+        assert_eq!(&[ROUTING_HEADER_MARKER, 1, 0x03, 0x01, 8], forwarded);
+        assert_eq!(
+            Forward::Node(0x03, &[0x01, 8]),
+            master.forward_request(forwarded).unwrap()
+        );
+
+        let response = [0xF1, 8];
+        let response = master.forward_response(0x03, &response).unwrap();
+        assert_eq!(&[ROUTING_HEADER_MARKER, 1, 0x03, 0xF1, 8], response);
+        let response = gateway.forward_response(0x07, response).unwrap();
+        assert_eq!(&[ROUTING_HEADER_MARKER, 2, 0x07, 0x03, 0xF1, 8], response);
+
+        assert_eq!(
+            Forward::Local(&[0x01, 8]),
+            gateway.forward_request(&[0x01, 8]).unwrap()
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Deferred execution of requests.
+//!
+//! A request can be prefixed with a [`ScheduleHeader`] (after an optional
+//! [`crate::replay::SequenceHeader`]) to execute it after a delay or at a unix time, for example
+//! to switch the network configuration of a whole fleet of devices at once. The device stores
+//! the request and acknowledges it with `Response::Ok(id, Format::Empty)`. The response of the
+//! request itself is discarded once it has been executed.
+//!
+//! Pending requests are identified by their request id. `Request::ListPendingCommands` is
+//! answered with `Format::AddressValuePairs(Type::U8, Type::U32)`, the id of each pending
+//! request and the seconds until it is executed. `Request::CancelPendingCommand` removes a
+//! pending request and is answered with `Response::Ok(id, Format::Empty)`, or with
+//! `Response::NotAvailable` if there is no such request.
+
+use crate::compression::AcceptCompressionHeader;
+use crate::priority::PriorityHeader;
+use crate::{Error, ErrorCode, Format, Read, Request, Response, Type, Write};
+
+/// The first byte of an encoded [`ScheduleHeader`], which is not used by any request opcode
+pub const SCHEDULE_HEADER_MARKER: u8 = 0x53;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ScheduleHeader {
+    /// Executes the request the given number of seconds after it has been received
+    Delay(u32),
+    /// Executes the request at the given seconds since the unix epoch, right away if it is in
+    /// the past
+    At(u32),
+}
+
+impl ScheduleHeader {
+    pub const ENCODED_LEN: usize = 1 + 1 + 4;
+
+    pub fn write(&self, writer: &mut impl Write) -> Result<usize, Error> {
+        let (kind, seconds) = match self {
+            ScheduleHeader::Delay(seconds) => (0x00, *seconds),
+            ScheduleHeader::At(seconds) => (0x01, *seconds),
+        };
+        Ok(writer.write_u8(SCHEDULE_HEADER_MARKER)?
+            + writer.write_u8(kind)?
+            + writer.write_u32_be(seconds)?)
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        if reader.read_u8()? != SCHEDULE_HEADER_MARKER {
+            return Err(Error::UnknownTypeIdentifier);
+        }
+        let kind = reader.read_u8()?;
+        let seconds = reader.read_u32_be()?;
+        Ok(match kind {
+            0x00 => ScheduleHeader::Delay(seconds),
+            0x01 => ScheduleHeader::At(seconds),
+            _ => return Err(Error::UnknownTypeIdentifier),
+        })
+    }
+
+    /// Reads the [`ScheduleHeader`] if the given datagram starts with one. Returns the header
+    /// and the remaining datagram.
+    pub fn read_optional(datagram: &[u8]) -> Result<(Option<Self>, &[u8]), Error> {
+        if datagram.first() == Some(&SCHEDULE_HEADER_MARKER) {
+            let reader = &mut &*datagram;
+            let header = Self::read(reader)?;
+            Ok((Some(header), reader))
+        } else {
+            Ok((None, datagram))
+        }
+    }
+
+    /// The uptime of the device in milliseconds at which the request is due. `None` for
+    /// [`ScheduleHeader::At`] if the device does not know the current unix time.
+    pub fn due_millis(&self, uptime_millis: u64, unix_seconds: Option<u32>) -> Option<u64> {
+        let delay = match *self {
+            ScheduleHeader::Delay(seconds) => seconds,
+            ScheduleHeader::At(seconds) => seconds.saturating_sub(unix_seconds?),
+        };
+        Some(uptime_millis + u64::from(delay) * 1000)
+    }
+}
+
+/// A request waiting to be executed
+#[derive(Debug, Copy, Clone)]
+pub struct PendingCommand<const LEN: usize> {
+    pub request_id: u8,
+    pub due_millis: u64,
+    len: usize,
+    datagram: [u8; LEN],
+}
+
+impl<const LEN: usize> PendingCommand<LEN> {
+    /// The request without the [`ScheduleHeader`], to be handled like a received request
+    pub fn datagram(&self) -> &[u8] {
+        &self.datagram[..self.len]
+    }
+}
+
+/// The device side storage of up to `N` pending requests of up to `LEN` bytes each
+#[derive(Debug, Clone)]
+pub struct PendingCommands<const N: usize, const LEN: usize> {
+    commands: [Option<PendingCommand<LEN>>; N],
+}
+
+impl<const N: usize, const LEN: usize> Default for PendingCommands<N, LEN> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const LEN: usize> PendingCommands<N, LEN> {
+    pub const fn new() -> Self {
+        Self {
+            commands: [None; N],
+        }
+    }
+
+    /// Stores the request that followed the header and writes the acknowledgement. Requests
+    /// that do not fit are answered with `ErrorCode::PayloadTooLarge`, requests while all slots
+    /// are in use or with the id of a pending request with `ErrorCode::BusyTryAgain`.
+    pub fn schedule(
+        &mut self,
+        header: ScheduleHeader,
+        datagram: &[u8],
+        uptime_millis: u64,
+        unix_seconds: Option<u32>,
+        response_writer: &mut impl Write,
+    ) -> Result<usize, Error> {
+        let (_, request) = AcceptCompressionHeader::read_optional(datagram)?;
+        let (_, request) = PriorityHeader::read_optional(request)?;
+        let request_id = Request::read(&mut &*request)?.id();
+        let due_millis = match header.due_millis(uptime_millis, unix_seconds) {
+            Some(due_millis) => due_millis,
+            None => return Response::NotAvailable(request_id).write(response_writer),
+        };
+
+        let response = if datagram.len() > LEN {
+            Response::Error(request_id, ErrorCode::PayloadTooLarge)
+        } else if self.find(request_id).is_some() {
+            Response::Error(request_id, ErrorCode::BusyTryAgain)
+        } else if let Some(slot) = self.commands.iter_mut().find(|slot| slot.is_none()) {
+            let mut command = PendingCommand {
+                request_id,
+                due_millis,
+                len: datagram.len(),
+                datagram: [0u8; LEN],
+            };
+            command.datagram[..datagram.len()].copy_from_slice(datagram);
+            *slot = Some(command);
+            Response::Ok(request_id, Format::Empty)
+        } else {
+            Response::Error(request_id, ErrorCode::BusyTryAgain)
+        };
+        response.write(response_writer)
+    }
+
+    /// Removes the pending request with the given id, returns whether there was one
+    pub fn cancel(&mut self, request_id: u8) -> bool {
+        match self.find(request_id) {
+            Some(index) => {
+                self.commands[index] = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes and returns the pending request that is due the longest, if any
+    pub fn pop_due(&mut self, uptime_millis: u64) -> Option<PendingCommand<LEN>> {
+        let slot = self
+            .commands
+            .iter_mut()
+            .filter(|slot| slot.is_some_and(|command| command.due_millis <= uptime_millis))
+            .min_by_key(|slot| slot.map(|command| command.due_millis))?;
+        slot.take()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PendingCommand<LEN>> {
+        self.commands.iter().flatten()
+    }
+
+    /// Answers a `Request::ListPendingCommands` or `Request::CancelPendingCommand`, returns
+    /// `None` for any other request
+    pub fn respond(
+        &mut self,
+        request: &Request,
+        uptime_millis: u64,
+        response_writer: &mut impl Write,
+    ) -> Option<Result<usize, Error>> {
+        Some(match *request {
+            Request::ListPendingCommands(id) => self.write_list(id, uptime_millis, response_writer),
+            Request::CancelPendingCommand(id, pending_id) => if self.cancel(pending_id) {
+                Response::Ok(id, Format::Empty)
+            } else {
+                Response::NotAvailable(id)
+            }
+            .write(response_writer),
+            _ => return None,
+        })
+    }
+
+    fn write_list(
+        &self,
+        request_id: u8,
+        uptime_millis: u64,
+        response_writer: &mut impl Write,
+    ) -> Result<usize, Error> {
+        let mut len = Response::Ok(request_id, Format::AddressValuePairs(Type::U8, Type::U32))
+            .write(response_writer)?;
+        for command in self.iter() {
+            let remaining = command
+                .due_millis
+                .saturating_sub(uptime_millis)
+                .div_ceil(1000);
+            len += response_writer.write_u8(command.request_id)?;
+            len += response_writer.write_u32_be(remaining.min(u64::from(u32::MAX)) as u32)?;
+        }
+        Ok(len)
+    }
+
+    fn find(&self, request_id: u8) -> Option<usize> {
+        self.commands
+            .iter()
+            .position(|slot| slot.is_some_and(|command| command.request_id == request_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+
+    #[test]
+    fn pending_commands_are_executed_when_due() {
+        let mut pending = PendingCommands::<2, 8>::new();
+        let mut buffer = [0u8; 32];
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        pending
+            .schedule(
+                ScheduleHeader::Delay(2),
+                &[0xE0, 7, 0x00],
+                500,
+                None,
+                &mut cursor,
+            )
+            .unwrap();
+        assert_eq!(&[0x00, 7, 0xFF], cursor.written());
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        pending
+            .schedule(ScheduleHeader::At(100), &[0x01, 8], 500, None, &mut cursor)
+            .unwrap();
+        assert_eq!(&[0xF1, 8], cursor.written());
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        pending
+            .respond(&Request::ListPendingCommands(9), 1_000, &mut cursor)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            &[0x00, 9, 0x02, 0xFE, 0xFA, 7, 0, 0, 0, 2],
+            cursor.written()
+        );
+
+        assert!(pending.pop_due(2_499).is_none());
+        assert_eq!(&[0xE0, 7, 0x00], pending.pop_due(2_500).unwrap().datagram());
+        assert!(!pending.cancel(7));
+    }
+}
@@ -0,0 +1,136 @@
+//! Helpers for handling requests on the device.
+
+use crate::{Error, ErrorCode, Format, Request, Response, Write};
+
+/// Whether handling the request occupies a bus for a long time, such as scanning it for
+/// devices. A `Request::Batch` counts as expensive, since any of its requests might be.
+pub const fn is_expensive(request: &Request) -> bool {
+    matches!(
+        request,
+        Request::Batch(..)
+            | Request::DiscoverAll(..)
+            | Request::DiscoverAllOnBus(..)
+            | Request::ReadAll(..)
+            | Request::ReadAllOnBus(..)
+            | Request::ReadDiagnostics(..)
+    )
+}
+
+/// Answers a `Request::Ping`, returns `None` for any other request. To be called before the
+/// request is handed to anything that accesses a bus or is rate limited, so that the answer
+/// only tells whether the device is alive.
+pub fn respond_to_ping(
+    request: &Request,
+    response_writer: &mut impl Write,
+) -> Option<Result<usize, Error>> {
+    match *request {
+        Request::Ping(id) => Some(Response::Ok(id, Format::Empty).write(response_writer)),
+        _ => None,
+    }
+}
+
+/// A token bucket of `CAPACITY` tokens, refilled by one token per interval of the millis clock
+/// of the device. It starts full, so that a burst of up to `CAPACITY` requests is handled right
+/// away.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RateLimiter<const CAPACITY: u32> {
+    tokens: u32,
+    refill_interval_millis: u64,
+    last_refill_millis: u64,
+}
+
+impl<const CAPACITY: u32> RateLimiter<CAPACITY> {
+    pub const fn new(refill_interval_millis: u64) -> Self {
+        Self {
+            tokens: CAPACITY,
+            refill_interval_millis,
+            last_refill_millis: 0,
+        }
+    }
+
+    /// The number of tokens available at the given time
+    pub fn available(&mut self, now_millis: u64) -> u32 {
+        let interval = self.refill_interval_millis.max(1);
+        let refill = now_millis.saturating_sub(self.last_refill_millis) / interval;
+        if refill > 0 {
+            self.tokens = (u64::from(self.tokens) + refill).min(u64::from(CAPACITY)) as u32;
+            self.last_refill_millis += refill * interval;
+        }
+        if self.tokens == CAPACITY {
+            // a full bucket does not accumulate time for later
+            self.last_refill_millis = now_millis;
+        }
+        self.tokens
+    }
+
+    /// Takes a token if one is available
+    pub fn try_acquire(&mut self, now_millis: u64) -> bool {
+        if self.available(now_millis) > 0 {
+            self.tokens -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Takes a token for a request that [`is_expensive`]. If none is available, the request is
+    /// answered with `ErrorCode::BusyTryAgain` and must not be handled. Returns `None` if the
+    /// request may be handled.
+    pub fn check(
+        &mut self,
+        request: &Request,
+        now_millis: u64,
+        response_writer: &mut impl Write,
+    ) -> Option<Result<usize, Error>> {
+        if !is_expensive(request) || self.try_acquire(now_millis) {
+            None
+        } else {
+            Some(Response::Error(request.id(), ErrorCode::BusyTryAgain).write(response_writer))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_refills_up_to_its_capacity() {
+        let mut limiter = RateLimiter::<2>::new(1_000);
+        assert!(limiter.try_acquire(0));
+        assert!(limiter.try_acquire(10));
+        assert!(!limiter.try_acquire(999));
+        assert!(limiter.try_acquire(1_000));
+        assert!(!limiter.try_acquire(1_500));
+        assert_eq!(2, limiter.available(10_000));
+
+        let mut buffer = [0u8; 3];
+        assert!(limiter
+            .check(
+                &Request::RetrieveDeviceInformation(1),
+                10_000,
+                &mut &mut buffer[..]
+            )
+            .is_none());
+        limiter.try_acquire(10_000);
+        limiter.try_acquire(10_000);
+        limiter
+            .check(&Request::DiscoverAll(2), 10_000, &mut &mut buffer[..])
+            .unwrap()
+            .unwrap();
+        assert_eq!([0xF2, 2, 0x01], buffer);
+        assert!(limiter
+            .check(&Request::Batch(3, 1), 10_000, &mut &mut buffer[..])
+            .is_some());
+    }
+
+    #[test]
+    fn ping_is_acknowledged() {
+        let mut buffer = [0u8; 3];
+        assert!(respond_to_ping(&Request::ReadAll(1), &mut &mut buffer[..]).is_none());
+        respond_to_ping(&Request::Ping(2), &mut &mut buffer[..])
+            .unwrap()
+            .unwrap();
+        assert_eq!([0x00, 2, 0xFF], buffer);
+    }
+}
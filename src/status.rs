@@ -0,0 +1,117 @@
+//! The outcome of reading a single sensor.
+//!
+//! A `Format::AddressStatusValue` response carries a [`SensorStatus`] between the address and
+//! the value of each sensor, so that a failing sensor does not make the readings of the other
+//! requested sensors ambiguous. The value is always present to keep the elements of a fixed
+//! size, but is meaningless (and should be zeroed) unless the status is [`SensorStatus::Ok`].
+
+use crate::{Error, Read, Write};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum SensorStatus {
+    Ok,
+    /// The checksum of the data read from the sensor did not match, for example on OneWire
+    CrcError,
+    /// The sensor did not answer in time
+    Timeout,
+    /// No sensor answered to the address
+    NotFound,
+    /// A status unknown to this version of the protocol
+    Other(u8),
+}
+
+impl SensorStatus {
+    pub const fn is_ok(&self) -> bool {
+        matches!(self, SensorStatus::Ok)
+    }
+
+    /// The status of the encoded byte, [`SensorStatus::Other`] only for bytes that are not the
+    /// code of a named status
+    pub const fn from_code(code: u8) -> Self {
+        match code {
+            0x00 => SensorStatus::Ok,
+            0x01 => SensorStatus::CrcError,
+            0x02 => SensorStatus::Timeout,
+            0x03 => SensorStatus::NotFound,
+            status => SensorStatus::Other(status),
+        }
+    }
+
+    pub const fn code(&self) -> u8 {
+        match self {
+            SensorStatus::Ok => 0x00,
+            SensorStatus::CrcError => 0x01,
+            SensorStatus::Timeout => 0x02,
+            SensorStatus::NotFound => 0x03,
+            SensorStatus::Other(status) => *status,
+        }
+    }
+
+    /// Fails with [`Error::NonCanonical`] for a [`SensorStatus::Other`] with the code of a named
+    /// status, which would be read back as the named status
+    pub fn write(&self, writer: &mut impl Write) -> Result<usize, Error> {
+        if SensorStatus::from_code(self.code()) != *self {
+            return Err(Error::NonCanonical);
+        }
+        writer.write_u8(self.code())
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        Ok(SensorStatus::from_code(reader.read_u8()?))
+    }
+}
+
+/// The error counters of a single sensor since the device started, the answer to a
+/// `Request::ReadDiagnostics` is `Format::AddressValuePairs(_, Type::SensorDiagnostics)`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct SensorDiagnostics {
+    pub crc_failures: u32,
+    pub timeouts: u32,
+    /// The uptime of the device in milliseconds when the sensor last answered, `None` if it
+    /// never did
+    pub last_seen_millis: Option<u64>,
+}
+
+impl SensorDiagnostics {
+    pub const ENCODED_LEN: usize = 4 + 4 + 8;
+
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        Ok(writer.write_u32_be(self.crc_failures)?
+            + writer.write_u32_be(self.timeouts)?
+            + writer.write_u64_be(self.last_seen_millis.unwrap_or_default())?)
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        Ok(SensorDiagnostics {
+            crc_failures: reader.read_u32_be()?,
+            timeouts: reader.read_u32_be()?,
+            last_seen_millis: Some(reader.read_u64_be()?).filter(|millis| *millis > 0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+
+    #[test]
+    fn other_with_the_code_of_a_named_status_is_rejected() {
+        let mut buffer = [0u8; 1];
+        assert_eq!(
+            Err(Error::NonCanonical),
+            SensorStatus::Other(0x00).write(&mut WriteCursor::new(&mut buffer))
+        );
+        assert_eq!(SensorStatus::Timeout, SensorStatus::from_code(0x02));
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        SensorStatus::Other(0x42).write(&mut cursor).unwrap();
+        assert_eq!(&[0x42], cursor.written());
+        assert_eq!(
+            Ok(SensorStatus::Other(0x42)),
+            SensorStatus::read(&mut &buffer[..])
+        );
+    }
+}
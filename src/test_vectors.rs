@@ -0,0 +1,411 @@
+//! Canonical encodings of every [`Request`], [`Response`], [`Format`] and [`Type`] variant.
+//!
+//! These bytes are part of the compatibility guarantee of the protocol: firmware and clients
+//! built from different versions of this crate understand each other as long as these vectors
+//! do not change. A change to an existing vector is a breaking protocol change, new variants
+//! have to be added here as well. Other implementations can use the vectors to verify their
+//! encoders and decoders (enable the `test-vectors` feature).
+
+use crate::output::OutputState;
+use crate::{Bus, ErrorCode, Format, ListElement, Request, ResetKind, Response, Type};
+
+pub const REQUESTS: &[(Request, &[u8])] = &[
+    (
+        Request::ReadSpecified(0x11, Bus::OneWire),
+        &[0x00, 0x11, 0x00],
+    ),
+    (Request::ReadAll(0x11), &[0x01, 0x11]),
+    (Request::ReadAllOnBus(0x11, Bus::I2C), &[0x02, 0x11, 0x01]),
+    (
+        Request::ReadDiagnostics(0x11, Bus::OneWire),
+        &[0x03, 0x11, 0x00],
+    ),
+    (Request::DiscoverAll(0x11), &[0x10, 0x11]),
+    (
+        Request::DiscoverAllOnBus(
+            0x11,
+            Bus::I2cN {
+                bus: 2,
+                ten_bit: true,
+            },
+        ),
+        &[0x11, 0x11, 0x02, 0x02, 0x01],
+    ),
+    (Request::ListBuses(0x11), &[0x12, 0x11]),
+    (
+        Request::SetOutput(0x11, Bus::I2C, OutputState::On),
+        &[0x20, 0x11, 0x01, 0x01, 0x00, 0x00],
+    ),
+    (
+        Request::SetOutput(0x11, Bus::I2C, OutputState::Pwm(0x8000)),
+        &[0x20, 0x11, 0x01, 0x02, 0x80, 0x00],
+    ),
+    (Request::GetOutput(0x11, Bus::I2C), &[0x21, 0x11, 0x01]),
+    (Request::ListPendingCommands(0x11), &[0x30, 0x11]),
+    (
+        Request::CancelPendingCommand(0x11, 0x22),
+        &[0x31, 0x11, 0x22],
+    ),
+    (Request::Ping(0x11), &[0x40, 0x11]),
+    (Request::GetTime(0x11), &[0x70, 0x11]),
+    (
+        Request::SetTime(0x11, 0x0102_0304_0506_0708),
+        &[0x71, 0x11, 1, 2, 3, 4, 5, 6, 7, 8],
+    ),
+    (Request::RequestChallenge(0x11), &[0x60, 0x11]),
+    (
+        Request::Pair(0x11, [1, 2, 3, 4, 5, 6, 7, 8]),
+        &[0x61, 0x11, 1, 2, 3, 4, 5, 6, 7, 8],
+    ),
+    (
+        Request::SetNetworkMac(0x11, [0x02, 0x00, 0x00, 0xAB, 0xCD, 0xEF]),
+        &[0xA0, 0x11, 0x02, 0x00, 0x00, 0xAB, 0xCD, 0xEF],
+    ),
+    (
+        Request::SetNetworkIpSubnetGateway(
+            0x11,
+            [192, 168, 0, 51],
+            [255, 255, 255, 0],
+            [192, 168, 0, 1],
+        ),
+        &[
+            0xA1, 0x11, 192, 168, 0, 51, 255, 255, 255, 0, 192, 168, 0, 1,
+        ],
+    ),
+    (
+        Request::SetNetworkDns(0x11, [192, 168, 0, 1], None),
+        &[0xA2, 0x11, 192, 168, 0, 1, 0x00],
+    ),
+    (
+        Request::SetNetworkDns(0x11, [192, 168, 0, 1], Some([8, 8, 8, 8])),
+        &[0xA2, 0x11, 192, 168, 0, 1, 0x01, 8, 8, 8, 8],
+    ),
+    (
+        Request::StageNetworkConfig(0x11, 30),
+        &[0xA3, 0x11, 0x00, 0x1E],
+    ),
+    (Request::CommitNetworkConfig(0x11), &[0xA4, 0x11]),
+    (Request::ListComponents(0x11), &[0xD0, 0x11]),
+    (Request::ListComponentsWithReportV1(0x11), &[0xD1, 0x11]),
+    (Request::ListComponentsWithReportV2(0x11), &[0xD2, 0x11]),
+    (
+        Request::ListComponentsRange(0x11, 0x0102, 0x10),
+        &[0xD3, 0x11, 0x01, 0x02, 0x10],
+    ),
+    (Request::Batch(0x11, 3), &[0xB0, 0x11, 0x03]),
+    (Request::Reset(0x11, ResetKind::Soft), &[0xE0, 0x11, 0x00]),
+    (Request::Reset(0x11, ResetKind::Hard), &[0xE0, 0x11, 0x01]),
+    (
+        Request::Reset(0x11, ResetKind::ToBootloader),
+        &[0xE0, 0x11, 0x02],
+    ),
+    (
+        Request::FactoryReset(0x11, [0xDE, 0xAD, 0xBE, 0xEF]),
+        &[0xE1, 0x11, 0xDE, 0xAD, 0xBE, 0xEF],
+    ),
+    (
+        Request::BeginFirmwareUpgrade(0x11, 0x0001_0000, 0xCBF4_3926),
+        &[0xE8, 0x11, 0x00, 0x01, 0x00, 0x00, 0xCB, 0xF4, 0x39, 0x26],
+    ),
+    (
+        Request::FirmwareChunk(0x11, 0x0000_0400),
+        &[0xE9, 0x11, 0x00, 0x00, 0x04, 0x00],
+    ),
+    (Request::CommitFirmware(0x11), &[0xEA, 0x11]),
+    (
+        Request::ReadEeprom(0x11, 0x0100, 0x20),
+        &[0xEC, 0x11, 0x01, 0x00, 0x20],
+    ),
+    (
+        Request::WriteEeprom(0x11, 0x0100),
+        &[0xED, 0x11, 0x01, 0x00],
+    ),
+    (Request::StoreProperty(0x11, 3), &[0xFA, 0x11, 0x03]),
+    (Request::RetrieveProperty(0x11, 3), &[0xFB, 0x11, 0x03]),
+    (Request::RetrieveErrorDump(0x11), &[0xFC, 0x11]),
+    (
+        Request::RetrieveErrorDumpRange(0x11, 0x0102, 0x10),
+        &[0xF8, 0x11, 0x01, 0x02, 0x10],
+    ),
+    (Request::ClearErrorDump(0x11), &[0xF9, 0x11]),
+    (
+        Request::TailLog(0x11, 0x01020304),
+        &[0xF7, 0x11, 0x01, 0x02, 0x03, 0x04],
+    ),
+    (Request::RetrieveDeviceInformation(0x11), &[0xFD, 0x11]),
+    (Request::RetrieveNetworkConfiguration(0x11), &[0xFE, 0x11]),
+    (Request::RetrieveVersionInformation(0x11), &[0xFF, 0x11]),
+];
+
+pub const BUSES: &[(Bus, &[u8])] = &[
+    (Bus::OneWire, &[0x00]),
+    (Bus::I2C, &[0x01]),
+    (
+        Bus::I2cN {
+            bus: 1,
+            ten_bit: false,
+        },
+        &[0x02, 0x01, 0x00],
+    ),
+    (Bus::ModbusRtu { unit: 7 }, &[0x03, 0x07]),
+    (Bus::Can { extended: true }, &[0x04, 0x01]),
+    (Bus::Analog, &[0x05]),
+    (Bus::Custom(0x42), &[0xFF, 0x42]),
+];
+
+pub const RESPONSES: &[(Response, &[u8])] = &[
+    (Response::NotImplemented(0x11), &[0xF0, 0x11]),
+    (Response::NotAvailable(0x11), &[0xF1, 0x11]),
+    (
+        Response::Error(0x11, ErrorCode::BusyTryAgain),
+        &[0xF2, 0x11, 0x01],
+    ),
+    (
+        Response::Error(0x11, ErrorCode::InvalidPropertyPath),
+        &[0xF2, 0x11, 0x02],
+    ),
+    (
+        Response::Error(0x11, ErrorCode::PermissionDenied),
+        &[0xF2, 0x11, 0x03],
+    ),
+    (
+        Response::Error(0x11, ErrorCode::PayloadTooLarge),
+        &[0xF2, 0x11, 0x04],
+    ),
+    (
+        Response::Error(0x11, ErrorCode::ValueOutOfRange),
+        &[0xF2, 0x11, 0x05],
+    ),
+    (
+        Response::Error(0x11, ErrorCode::Other(0x80)),
+        &[0xF2, 0x11, 0x80],
+    ),
+    (Response::Ok(0x11, Format::Empty), &[0x00, 0x11, 0xFF]),
+    (
+        Response::Announcement(0x11, Format::AddressValuePairs(Type::Bytes(8), Type::F32)),
+        &[0x01, 0x11, 0x02, 0x01, 0x08, 0x00],
+    ),
+    (
+        Response::Ok(0x11, Format::ValueOnly(Type::F32)),
+        &[0x00, 0x11, 0x00, 0x00],
+    ),
+];
+
+pub const FORMATS: &[(Format, &[u8])] = &[
+    (Format::Empty, &[0xFF]),
+    (Format::ValueOnly(Type::F32), &[0x00, 0x00]),
+    (Format::AddressOnly(Type::Bytes(8)), &[0x01, 0x01, 0x08]),
+    (
+        Format::AddressValuePairs(Type::Bytes(8), Type::F32),
+        &[0x02, 0x01, 0x08, 0x00],
+    ),
+    (Format::Echoed(Type::U16), &[0x03, 0xFC]),
+    (
+        Format::AddressValueTimestampTriples(Type::U8, Type::F32, Type::TimestampMillisU64),
+        &[0x04, 0xFE, 0x00, 0x09],
+    ),
+    (
+        Format::CountedAddressValuePairs(Type::Bytes(8), Type::F32),
+        &[0x05, 0x01, 0x08, 0x00],
+    ),
+    (
+        Format::AddressStatusValue(Type::Bytes(8), Type::F32),
+        &[0x06, 0x01, 0x08, 0x00],
+    ),
+];
+
+pub const TYPES: &[(Type, &[u8])] = &[
+    (Type::F32, &[0x00]),
+    (Type::Bytes(6), &[0x01, 0x06]),
+    (Type::String(16), &[0x02, 0x10]),
+    (Type::PropertyId, &[0x03]),
+    (Type::DynString, &[0x04]),
+    (Type::DynBytes, &[0x05]),
+    (Type::F64, &[0x06]),
+    (Type::Bool, &[0x07]),
+    (Type::ScaledI32 { exponent: -2 }, &[0x08, 0xFE]),
+    (Type::TimestampMillisU64, &[0x09]),
+    (Type::TimestampSecondsU32, &[0x0A]),
+    (Type::ListOf(ListElement::new(Type::U8)), &[0x0B, 0xFE]),
+    (
+        Type::ListOf(ListElement::new(Type::ListOf(ListElement::new(
+            Type::Bytes(4),
+        )))),
+        &[0x0B, 0x0B, 0x01, 0x04],
+    ),
+    (Type::Tlv, &[0x0C]),
+    (Type::Uuid, &[0x0D]),
+    (Type::CounterU64, &[0x0E]),
+    (Type::DynListPropertyReportV1, &[0xC0]),
+    (Type::DeviceInformation, &[0xC1]),
+    (Type::NetworkConfiguration, &[0xC2]),
+    (Type::DynListErrorDumpEntry, &[0xC3]),
+    (Type::VersionInformation, &[0xC4]),
+    (Type::Batch, &[0xC5]),
+    (Type::DynListPropertyReportV2, &[0xC6]),
+    (Type::DynListBusInformation, &[0xC7]),
+    (Type::SensorDiagnostics, &[0xC8]),
+    (Type::OutputState, &[0xC9]),
+    (Type::DynListLogRecord, &[0xCA]),
+    (Type::U128, &[0xF6]),
+    (Type::I128, &[0xF7]),
+    (Type::U64, &[0xF8]),
+    (Type::I64, &[0xF9]),
+    (Type::U32, &[0xFA]),
+    (Type::I32, &[0xFB]),
+    (Type::U16, &[0xFC]),
+    (Type::I16, &[0xFD]),
+    (Type::U8, &[0xFE]),
+    (Type::I8, &[0xFF]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, RequestOpcode};
+
+    fn assert_vectors<T: PartialEq + core::fmt::Debug>(
+        vectors: &[(T, &[u8])],
+        write: impl Fn(&T, &mut &mut [u8]) -> Result<usize, Error>,
+        read: impl Fn(&mut &[u8]) -> Result<T, Error>,
+    ) {
+        for (value, encoded) in vectors {
+            let mut buffer = [0u8; 32];
+            let len = write(value, &mut &mut buffer[..]).unwrap();
+            assert_eq!(*encoded, &buffer[..len], "encoding of {:?} changed", value);
+
+            let mut reader = *encoded;
+            assert_eq!(*value, read(&mut reader).unwrap());
+            assert!(reader.is_empty(), "{:?} was not read completely", value);
+        }
+    }
+
+    #[test]
+    fn requests() {
+        assert_vectors(REQUESTS, |r, w| r.write(w), |r| Request::read(r));
+    }
+
+    #[test]
+    fn request_opcodes() {
+        for (request, encoded) in REQUESTS {
+            assert_eq!(encoded[0], u8::from(request.opcode()), "{:?}", request);
+        }
+        for opcode in RequestOpcode::ALL {
+            assert!(
+                REQUESTS
+                    .iter()
+                    .any(|(request, _)| request.opcode() == *opcode),
+                "no vector for {}",
+                opcode.name()
+            );
+        }
+        assert!(RequestOpcode::ALL
+            .windows(2)
+            .all(|pair| u8::from(pair[0]) < u8::from(pair[1])));
+    }
+
+    /// The instance of the request with the longest encoding, the match is exhaustive so that
+    /// new requests have to be added here
+    fn largest(opcode: RequestOpcode) -> Request {
+        let bus = Bus::I2cN {
+            bus: 1,
+            ten_bit: true,
+        };
+        match opcode {
+            RequestOpcode::ReadSpecified => Request::ReadSpecified(1, bus),
+            RequestOpcode::ReadAll => Request::ReadAll(1),
+            RequestOpcode::ReadAllOnBus => Request::ReadAllOnBus(1, bus),
+            RequestOpcode::ReadDiagnostics => Request::ReadDiagnostics(1, bus),
+            RequestOpcode::DiscoverAll => Request::DiscoverAll(1),
+            RequestOpcode::DiscoverAllOnBus => Request::DiscoverAllOnBus(1, bus),
+            RequestOpcode::ListBuses => Request::ListBuses(1),
+            RequestOpcode::SetOutput => Request::SetOutput(1, bus, OutputState::Pwm(1)),
+            RequestOpcode::GetOutput => Request::GetOutput(1, bus),
+            RequestOpcode::ListPendingCommands => Request::ListPendingCommands(1),
+            RequestOpcode::CancelPendingCommand => Request::CancelPendingCommand(1, 2),
+            RequestOpcode::Ping => Request::Ping(1),
+            RequestOpcode::RequestChallenge => Request::RequestChallenge(1),
+            RequestOpcode::Pair => Request::Pair(1, [2; 8]),
+            RequestOpcode::GetTime => Request::GetTime(1),
+            RequestOpcode::SetTime => Request::SetTime(1, u64::MAX),
+            RequestOpcode::SetNetworkMac => Request::SetNetworkMac(1, [2; 6]),
+            RequestOpcode::SetNetworkIpSubnetGateway => {
+                Request::SetNetworkIpSubnetGateway(1, [2; 4], [3; 4], [4; 4])
+            }
+            RequestOpcode::SetNetworkDns => Request::SetNetworkDns(1, [2; 4], Some([3; 4])),
+            RequestOpcode::StageNetworkConfig => Request::StageNetworkConfig(1, u16::MAX),
+            RequestOpcode::CommitNetworkConfig => Request::CommitNetworkConfig(1),
+            RequestOpcode::Batch => Request::Batch(1, 2),
+            RequestOpcode::ListComponents => Request::ListComponents(1),
+            RequestOpcode::ListComponentsWithReportV1 => Request::ListComponentsWithReportV1(1),
+            RequestOpcode::ListComponentsWithReportV2 => Request::ListComponentsWithReportV2(1),
+            RequestOpcode::ListComponentsRange => Request::ListComponentsRange(1, u16::MAX, 2),
+            RequestOpcode::Reset => Request::Reset(1, ResetKind::ToBootloader),
+            RequestOpcode::FactoryReset => Request::FactoryReset(1, [2; 4]),
+            RequestOpcode::BeginFirmwareUpgrade => {
+                Request::BeginFirmwareUpgrade(1, u32::MAX, u32::MAX)
+            }
+            RequestOpcode::FirmwareChunk => Request::FirmwareChunk(1, u32::MAX),
+            RequestOpcode::CommitFirmware => Request::CommitFirmware(1),
+            RequestOpcode::ReadEeprom => Request::ReadEeprom(1, u16::MAX, 2),
+            RequestOpcode::WriteEeprom => Request::WriteEeprom(1, u16::MAX),
+            RequestOpcode::TailLog => Request::TailLog(1, u32::MAX),
+            RequestOpcode::RetrieveErrorDumpRange => {
+                Request::RetrieveErrorDumpRange(1, u16::MAX, 2)
+            }
+            RequestOpcode::ClearErrorDump => Request::ClearErrorDump(1),
+            RequestOpcode::StoreProperty => Request::StoreProperty(1, 2),
+            RequestOpcode::RetrieveProperty => Request::RetrieveProperty(1, 2),
+            RequestOpcode::RetrieveErrorDump => Request::RetrieveErrorDump(1),
+            RequestOpcode::RetrieveDeviceInformation => Request::RetrieveDeviceInformation(1),
+            RequestOpcode::RetrieveNetworkConfiguration => Request::RetrieveNetworkConfiguration(1),
+            RequestOpcode::RetrieveVersionInformation => Request::RetrieveVersionInformation(1),
+        }
+    }
+
+    #[test]
+    fn const_encoding_matches_write() {
+        let requests = REQUESTS
+            .iter()
+            .map(|(request, _)| *request)
+            .chain(RequestOpcode::ALL.iter().map(|opcode| largest(*opcode)));
+        for request in requests {
+            let mut buffer = [0u8; 32];
+            let len = request.write(&mut &mut buffer[..]).unwrap();
+            assert!(len <= Request::max_encoded_len(), "{:?}", request);
+            let (encoded, encoded_len) = request.encode();
+            assert_eq!(&buffer[..len], &encoded[..encoded_len], "{:?}", request);
+        }
+    }
+
+    #[test]
+    fn buses() {
+        assert_vectors(BUSES, |b, w| b.write(w), |r| Bus::read(r));
+    }
+
+    #[test]
+    fn responses() {
+        assert_vectors(RESPONSES, |r, w| r.write(w), |r| Response::read(r));
+    }
+
+    #[test]
+    fn other_error_codes_do_not_collide_with_named_ones() {
+        let mut buffer = [0u8; 8];
+        assert_eq!(
+            Err(Error::NonCanonical),
+            Response::Error(0x11, ErrorCode::Other(0x01)).write(&mut &mut buffer[..])
+        );
+        assert_eq!(ErrorCode::PermissionDenied, ErrorCode::from_code(0x03));
+        assert_eq!(ErrorCode::Other(0x00), ErrorCode::from_code(0x00));
+    }
+
+    #[test]
+    fn formats() {
+        assert_vectors(FORMATS, |f, w| f.write(w), |r| Format::read(r));
+    }
+
+    #[test]
+    fn types() {
+        assert_vectors(TYPES, |t, w| t.write(w), |r| Type::read(r));
+    }
+}
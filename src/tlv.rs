@@ -0,0 +1,173 @@
+//! Type-length-value encoded entries, which allow to add fields to structures without breaking
+//! older readers: unknown tags are skipped by their length.
+//!
+//! A TLV area, for example the value of a [`crate::Type::Tlv`] or the extensions of a
+//! [`crate::props::PropertyReportV2`], is prefixed by its total length in bytes as `u8`.
+
+use crate::{Error, Read, Write};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TlvEntry<'a> {
+    pub tag: u8,
+    /// At most 255 bytes
+    pub value: &'a [u8],
+}
+
+impl<'a> TlvEntry<'a> {
+    pub const fn new(tag: u8, value: &'a [u8]) -> Self {
+        Self { tag, value }
+    }
+
+    pub const fn encoded_len(&self) -> usize {
+        2 + self.value.len()
+    }
+
+    /// Fails with [`Error::ValueTooLong`] if the value exceeds 255 bytes
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        if self.value.len() > usize::from(u8::MAX) {
+            return Err(Error::ValueTooLong);
+        }
+        Ok(writer.write_u8(self.tag)? + writer.write_dyn_bytes(self.value)?)
+    }
+
+    /// Reads the next entry from the slice, which is advanced accordingly
+    pub fn read(reader: &mut &'a [u8]) -> Result<Self, Error> {
+        let tag = reader.read_u8()?;
+        let len = usize::from(reader.read_u8()?);
+        if reader.len() < len {
+            return Err(Error::UnexpectedEOF);
+        }
+        let (value, remaining) = reader.split_at(len);
+        *reader = remaining;
+        Ok(TlvEntry { tag, value })
+    }
+}
+
+/// Writes the entries as TLV area, prefixed by its length. Fails with [`Error::ValueTooLong`]
+/// if the entries exceed 255 bytes.
+pub fn write_area(entries: &[TlvEntry<'_>], writer: &mut dyn Write) -> Result<usize, Error> {
+    let len = entries.iter().map(TlvEntry::encoded_len).sum::<usize>();
+    if len > usize::from(u8::MAX) {
+        return Err(Error::ValueTooLong);
+    }
+
+    let mut written = writer.write_u8(len as u8)?;
+    for entry in entries {
+        written += entry.write(writer)?;
+    }
+    Ok(written)
+}
+
+/// Reads a TLV area from the slice, which is advanced accordingly. The entries of the returned
+/// area can be decoded with a [`TlvIter`].
+pub fn read_area<'a>(reader: &mut &'a [u8]) -> Result<&'a [u8], Error> {
+    let len = usize::from(reader.read_u8()?);
+    if reader.len() < len {
+        return Err(Error::UnexpectedEOF);
+    }
+    let (area, remaining) = reader.split_at(len);
+    *reader = remaining;
+    Ok(area)
+}
+
+/// Decodes [`TlvEntry`]s from the entries of a TLV area until it is exhausted. Stops after the
+/// first malformed entry.
+pub struct TlvIter<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> TlvIter<'a> {
+    pub const fn new(entries: &'a [u8]) -> Self {
+        Self { remaining: entries }
+    }
+
+    /// The value of the first entry with the given tag
+    pub fn find(entries: &'a [u8], tag: u8) -> Option<&'a [u8]> {
+        Self::new(entries)
+            .filter_map(Result::ok)
+            .find(|entry| entry.tag == tag)
+            .map(|entry| entry.value)
+    }
+}
+
+impl<'a> Iterator for TlvIter<'a> {
+    type Item = Result<TlvEntry<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            None
+        } else {
+            let result = TlvEntry::read(&mut self.remaining);
+            if result.is_err() {
+                self.remaining = &[];
+            }
+            Some(result)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+
+    #[test]
+    fn area_roundtrip_skips_unknown_tags() {
+        let entries = [
+            TlvEntry::new(0x01, b"abc"),
+            TlvEntry::new(0x7F, &[]),
+            TlvEntry::new(0x02, &[0xFF]),
+        ];
+        let mut buffer = [0u8; 16];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let len = write_area(&entries, &mut cursor).unwrap();
+        cursor.write_u8(0xAA).unwrap();
+        assert_eq!(1 + 5 + 2 + 3, len);
+
+        let mut reader = cursor.written();
+        let area = read_area(&mut reader).unwrap();
+        assert_eq!(&[0xAA][..], reader);
+        assert!(TlvIter::new(area)
+            .map(Result::unwrap)
+            .eq(entries.iter().copied()));
+        assert_eq!(Some(&[0xFF][..]), TlvIter::find(area, 0x02));
+        assert_eq!(None, TlvIter::find(area, 0x03));
+    }
+
+    #[test]
+    fn truncated_entries_stop_the_iteration() {
+        let area = [0x01, 0x01, 0xAB, 0x02, 0x05, 0x00];
+        let mut entries = TlvIter::new(&area);
+        assert_eq!(Some(Ok(TlvEntry::new(0x01, &[0xAB]))), entries.next());
+        assert_eq!(Some(Err(Error::UnexpectedEOF)), entries.next());
+        assert_eq!(None, entries.next());
+        assert_eq!(None, TlvIter::find(&area, 0x02));
+
+        assert_eq!(Err(Error::UnexpectedEOF), read_area(&mut &[0x02, 0x01][..]));
+        assert_eq!(Err(Error::UnexpectedEOF), read_area(&mut &[][..]));
+    }
+
+    #[test]
+    fn entries_longer_than_the_length_prefix_are_rejected() {
+        let value = [0u8; 256];
+        let mut buffer = [0u8; 300];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        assert_eq!(
+            Err(Error::ValueTooLong),
+            TlvEntry::new(0x01, &value).write(&mut cursor)
+        );
+        assert_eq!(
+            Ok(257),
+            TlvEntry::new(0x01, &value[..255]).write(&mut cursor)
+        );
+
+        let entries = [
+            TlvEntry::new(0x01, &value[..200]),
+            TlvEntry::new(0x02, &value[..52]),
+        ];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        assert_eq!(Err(Error::ValueTooLong), write_area(&entries, &mut cursor));
+        assert!(cursor.written().is_empty());
+        assert_eq!(Ok(1 + 202), write_area(&entries[..1], &mut cursor));
+    }
+}
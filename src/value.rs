@@ -0,0 +1,513 @@
+use crate::info::{DeviceInformation, NetworkConfiguration, VersionInformation};
+use crate::output::OutputState;
+use crate::status::SensorDiagnostics;
+use crate::{Error, ListElement, Read, Type, Write};
+use core::convert::TryFrom;
+
+/// A single value of any [`Type`] with a fixed layout, borrowing variable sized data from the
+/// payload it has been read from.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Value<'a> {
+    F32(f32),
+    F64(f64),
+    Bool(bool),
+    ScaledI32 {
+        raw: i32,
+        exponent: i8,
+    },
+    TimestampMillis(u64),
+    TimestampSeconds(u32),
+    /// The entries of a TLV area, see [`crate::tlv::TlvIter`]
+    Tlv(&'a [u8]),
+    Uuid([u8; 16]),
+    Counter(u64),
+    /// The encoded elements of a [`Type::ListOf`], see [`Value::elements`]
+    List {
+        element: ListElement,
+        count: u8,
+        raw: &'a [u8],
+    },
+    Bytes(&'a [u8]),
+    String(&'a str),
+    PropertyId(&'a [u8]),
+    DynString(&'a str),
+    DynBytes(&'a [u8]),
+
+    DeviceInformation(DeviceInformation),
+    NetworkConfiguration(NetworkConfiguration),
+    VersionInformation(VersionInformation),
+    SensorDiagnostics(SensorDiagnostics),
+    OutputState(OutputState),
+
+    U128(u128),
+    I128(i128),
+    U64(u64),
+    I64(i64),
+    U32(u32),
+    I32(i32),
+    U16(u16),
+    I16(i16),
+    U8(u8),
+    I8(i8),
+}
+
+impl<'a> Value<'a> {
+    pub fn ty(&self) -> Type {
+        match self {
+            Value::F32(_) => Type::F32,
+            Value::F64(_) => Type::F64,
+            Value::Bool(_) => Type::Bool,
+            Value::ScaledI32 { exponent, .. } => Type::ScaledI32 {
+                exponent: *exponent,
+            },
+            Value::TimestampMillis(_) => Type::TimestampMillisU64,
+            Value::TimestampSeconds(_) => Type::TimestampSecondsU32,
+            Value::Tlv(_) => Type::Tlv,
+            Value::Uuid(_) => Type::Uuid,
+            Value::Counter(_) => Type::CounterU64,
+            Value::List { element, .. } => Type::ListOf(*element),
+            Value::Bytes(bytes) => Type::Bytes(bytes.len().min(u8::MAX as usize) as u8),
+            Value::String(string) => Type::String(string.len().min(u8::MAX as usize) as u8),
+            Value::PropertyId(_) => Type::PropertyId,
+            Value::DynString(_) => Type::DynString,
+            Value::DynBytes(_) => Type::DynBytes,
+
+            Value::DeviceInformation(_) => Type::DeviceInformation,
+            Value::NetworkConfiguration(_) => Type::NetworkConfiguration,
+            Value::VersionInformation(_) => Type::VersionInformation,
+            Value::SensorDiagnostics(_) => Type::SensorDiagnostics,
+            Value::OutputState(_) => Type::OutputState,
+
+            Value::U128(_) => Type::U128,
+            Value::I128(_) => Type::I128,
+            Value::U64(_) => Type::U64,
+            Value::I64(_) => Type::I64,
+            Value::U32(_) => Type::U32,
+            Value::I32(_) => Type::I32,
+            Value::U16(_) => Type::U16,
+            Value::I16(_) => Type::I16,
+            Value::U8(_) => Type::U8,
+            Value::I8(_) => Type::I8,
+        }
+    }
+
+    /// Fails with [`Error::ValueTooLong`] for bytes and strings longer than 255 bytes instead of
+    /// truncating them, which could split a character of a string
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize, Error> {
+        match self {
+            Value::Tlv(bytes)
+            | Value::Bytes(bytes)
+            | Value::PropertyId(bytes)
+            | Value::DynBytes(bytes)
+                if bytes.len() > usize::from(u8::MAX) =>
+            {
+                return Err(Error::ValueTooLong)
+            }
+            Value::String(string) | Value::DynString(string)
+                if string.len() > usize::from(u8::MAX) =>
+            {
+                return Err(Error::ValueTooLong)
+            }
+            _ => {}
+        }
+        match self {
+            Value::F32(v) => writer.write_all(&v.to_be_bytes()),
+            Value::F64(v) => writer.write_all(&v.to_be_bytes()),
+            Value::Bool(v) => writer.write_u8(if *v { 0x01 } else { 0x00 }),
+            Value::ScaledI32 { raw, .. } => writer.write_all(&raw.to_be_bytes()),
+            Value::TimestampMillis(millis) => writer.write_all(&millis.to_be_bytes()),
+            Value::TimestampSeconds(seconds) => writer.write_all(&seconds.to_be_bytes()),
+            Value::Tlv(entries) => writer.write_dyn_bytes(entries),
+            Value::Uuid(uuid) => writer.write_all(uuid),
+            Value::Counter(count) => writer.write_all(&count.to_be_bytes()),
+            Value::List { count, raw, .. } => Ok(writer.write_u8(*count)? + writer.write_all(raw)?),
+            Value::Bytes(bytes) => writer.write_all(bytes),
+            Value::String(string) => writer.write_all(string.as_bytes()),
+            Value::PropertyId(id) => writer.write_dyn_bytes(id),
+            Value::DynString(string) => writer.write_dyn_string(string),
+            Value::DynBytes(bytes) => writer.write_dyn_bytes(bytes),
+
+            Value::DeviceInformation(information) => information.write(writer),
+            Value::NetworkConfiguration(configuration) => configuration.write(writer),
+            Value::VersionInformation(information) => information.write(writer),
+            Value::SensorDiagnostics(diagnostics) => diagnostics.write(writer),
+            Value::OutputState(state) => state.write(writer),
+
+            Value::U128(v) => writer.write_all(&v.to_be_bytes()),
+            Value::I128(v) => writer.write_all(&v.to_be_bytes()),
+            Value::U64(v) => writer.write_all(&v.to_be_bytes()),
+            Value::I64(v) => writer.write_all(&v.to_be_bytes()),
+            Value::U32(v) => writer.write_all(&v.to_be_bytes()),
+            Value::I32(v) => writer.write_all(&v.to_be_bytes()),
+            Value::U16(v) => writer.write_all(&v.to_be_bytes()),
+            Value::I16(v) => writer.write_all(&v.to_be_bytes()),
+            Value::U8(v) => writer.write_u8(*v),
+            Value::I8(v) => writer.write_all(&v.to_be_bytes()),
+        }
+    }
+
+    /// The elements of a list value
+    pub fn elements(&self) -> Option<ListIter<'a>> {
+        match self {
+            Value::List {
+                element,
+                count,
+                raw,
+            } => Some(ListIter {
+                element: element.ty(),
+                remaining: *count,
+                raw,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Writes the values as [`Type::ListOf`] the given element type, fails with
+    /// [`Error::UnknownTypeIdentifier`] if a value is of another type and with
+    /// [`Error::ValueTooLong`] for more than 255 values
+    pub fn write_list(
+        element: Type,
+        values: &[Value<'_>],
+        writer: &mut dyn Write,
+    ) -> Result<usize, Error> {
+        let count = u8::try_from(values.len()).map_err(|_| Error::ValueTooLong)?;
+        let mut len = writer.write_u8(count)?;
+        for value in values {
+            if value.ty() != element {
+                return Err(Error::UnknownTypeIdentifier);
+            }
+            len += value.write(writer)?;
+        }
+        Ok(len)
+    }
+
+    /// The numeric value as floating point number, booleans are `0.0` or `1.0`. Large 64 and
+    /// 128 bit integers lose precision.
+    pub fn to_f64(&self) -> Option<f64> {
+        Some(match *self {
+            Value::F32(value) => f64::from(value),
+            Value::F64(value) => value,
+            Value::Bool(value) => f64::from(u8::from(value)),
+            Value::ScaledI32 { raw, exponent } => scaled(raw, exponent),
+            Value::U128(value) => value as f64,
+            Value::I128(value) => value as f64,
+            Value::U64(value) | Value::Counter(value) => value as f64,
+            Value::I64(value) => value as f64,
+            Value::U32(value) => f64::from(value),
+            Value::I32(value) => f64::from(value),
+            Value::U16(value) => f64::from(value),
+            Value::I16(value) => f64::from(value),
+            Value::U8(value) => f64::from(value),
+            Value::I8(value) => f64::from(value),
+            _ => return None,
+        })
+    }
+
+    /// Shorter identifiers, like the 96-bit unique device id of many MCUs, are padded with zeros
+    pub fn uuid_from_slice(id: &[u8]) -> Self {
+        let mut uuid = [0u8; 16];
+        let len = id.len().min(uuid.len());
+        uuid[..len].copy_from_slice(&id[..len]);
+        Value::Uuid(uuid)
+    }
+
+    #[cfg(feature = "uuid")]
+    pub fn to_uuid(&self) -> Option<uuid::Uuid> {
+        match self {
+            Value::Uuid(uuid) => Some(uuid::Uuid::from_bytes(*uuid)),
+            _ => None,
+        }
+    }
+
+    /// The milliseconds since the unix epoch of timestamp values
+    pub fn timestamp_millis(&self) -> Option<u64> {
+        match self {
+            Value::TimestampMillis(millis) => Some(*millis),
+            Value::TimestampSeconds(seconds) => Some(u64::from(*seconds) * 1000),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn to_system_time(&self) -> Option<std::time::SystemTime> {
+        self.timestamp_millis().and_then(|millis| {
+            std::time::UNIX_EPOCH.checked_add(std::time::Duration::from_millis(millis))
+        })
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn to_date_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        use core::convert::TryFrom;
+        self.timestamp_millis()
+            .and_then(|millis| i64::try_from(millis).ok())
+            .and_then(chrono::DateTime::from_timestamp_millis)
+    }
+
+    /// Reads the next value of the given type from the slice, which is advanced accordingly.
+    /// Lists (`Type::DynList*` and `Type::Batch`) cannot be read as a single value and result
+    /// in [`Error::UnknownTypeIdentifier`].
+    pub fn read(ty: Type, reader: &mut &'a [u8]) -> Result<Self, Error> {
+        macro_rules! be {
+            ($ty:ty) => {{
+                let mut bytes = <$ty>::default().to_be_bytes();
+                reader.read_all(&mut bytes)?;
+                <$ty>::from_be_bytes(bytes)
+            }};
+        }
+
+        Ok(match ty {
+            Type::F32 => Value::F32(be!(f32)),
+            Type::F64 => Value::F64(be!(f64)),
+            Type::Bool => Value::Bool(reader.read_u8()? != 0x00),
+            Type::ScaledI32 { exponent } => Value::ScaledI32 {
+                raw: be!(i32),
+                exponent,
+            },
+            Type::TimestampMillisU64 => Value::TimestampMillis(be!(u64)),
+            Type::TimestampSecondsU32 => Value::TimestampSeconds(be!(u32)),
+            Type::Tlv => Value::Tlv(crate::tlv::read_area(reader)?),
+            Type::Uuid => {
+                let mut uuid = [0u8; 16];
+                reader.read_all(&mut uuid)?;
+                Value::Uuid(uuid)
+            }
+            Type::CounterU64 => Value::Counter(be!(u64)),
+            Type::ListOf(element) => {
+                let count = reader.read_u8()?;
+                let mut elements = *reader;
+                for _ in 0..count {
+                    Value::read(element.ty(), &mut elements)?;
+                }
+                Value::List {
+                    element,
+                    count,
+                    raw: take(reader, reader.len() - elements.len())?,
+                }
+            }
+            Type::Bytes(len) => Value::Bytes(take(reader, usize::from(len))?),
+            Type::String(len) => Value::String(utf8(take(reader, usize::from(len))?)?),
+            Type::PropertyId => Value::PropertyId(take_dyn(reader)?),
+            Type::DynString => Value::DynString(utf8(take_dyn(reader)?)?),
+            Type::DynBytes => Value::DynBytes(take_dyn(reader)?),
+
+            Type::DeviceInformation => Value::DeviceInformation(DeviceInformation::read(reader)?),
+            Type::NetworkConfiguration => {
+                Value::NetworkConfiguration(NetworkConfiguration::read(reader)?)
+            }
+            Type::VersionInformation => {
+                Value::VersionInformation(VersionInformation::read(reader)?)
+            }
+            Type::SensorDiagnostics => Value::SensorDiagnostics(SensorDiagnostics::read(reader)?),
+            Type::OutputState => Value::OutputState(OutputState::read(reader)?),
+            Type::DynListPropertyReportV1
+            | Type::DynListPropertyReportV2
+            | Type::DynListBusInformation
+            | Type::DynListErrorDumpEntry
+            | Type::DynListLogRecord
+            | Type::Batch => return Err(Error::UnknownTypeIdentifier),
+
+            Type::U128 => Value::U128(be!(u128)),
+            Type::I128 => Value::I128(be!(i128)),
+            Type::U64 => Value::U64(be!(u64)),
+            Type::I64 => Value::I64(be!(i64)),
+            Type::U32 => Value::U32(be!(u32)),
+            Type::I32 => Value::I32(be!(i32)),
+            Type::U16 => Value::U16(be!(u16)),
+            Type::I16 => Value::I16(be!(i16)),
+            Type::U8 => Value::U8(reader.read_u8()?),
+            Type::I8 => Value::I8(be!(i8)),
+        })
+    }
+}
+
+macro_rules! from_primitive {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for Value<'_> {
+                fn from(value: $ty) -> Self {
+                    Value::$variant(value)
+                }
+            }
+        )*
+    };
+}
+
+from_primitive!(
+    f32 => F32,
+    f64 => F64,
+    bool => Bool,
+    u128 => U128,
+    i128 => I128,
+    u64 => U64,
+    i64 => I64,
+    u32 => U32,
+    i32 => I32,
+    u16 => U16,
+    i16 => I16,
+    u8 => U8,
+    i8 => I8,
+);
+
+#[cfg(feature = "uuid")]
+impl From<uuid::Uuid> for Value<'_> {
+    fn from(uuid: uuid::Uuid) -> Self {
+        Value::Uuid(uuid.into_bytes())
+    }
+}
+
+/// Decodes the elements of a [`Value::List`]
+pub struct ListIter<'a> {
+    element: Type,
+    remaining: u8,
+    raw: &'a [u8],
+}
+
+impl<'a> Iterator for ListIter<'a> {
+    type Item = Result<Value<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            None
+        } else {
+            self.remaining -= 1;
+            Some(Value::read(self.element, &mut self.raw))
+        }
+    }
+}
+
+/// The value of a [`Type::ScaledI32`] as floating point number
+pub fn scaled(raw: i32, exponent: i8) -> f64 {
+    let mut factor = 1.0_f64;
+    for _ in 0..exponent.unsigned_abs() {
+        factor *= 10.0;
+    }
+    if exponent < 0 {
+        f64::from(raw) / factor
+    } else {
+        f64::from(raw) * factor
+    }
+}
+
+/// The number of events between two readings of a [`Type::CounterU64`], across a wrap around
+pub const fn counter_delta(previous: u64, current: u64) -> u64 {
+    current.wrapping_sub(previous)
+}
+
+fn take<'a>(reader: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+    if reader.len() < len {
+        return Err(Error::UnexpectedEOF);
+    }
+    let (value, remaining) = reader.split_at(len);
+    *reader = remaining;
+    Ok(value)
+}
+
+fn take_dyn<'a>(reader: &mut &'a [u8]) -> Result<&'a [u8], Error> {
+    let len = usize::from(reader.read_u8()?);
+    take(reader, len)
+}
+
+fn utf8(bytes: &[u8]) -> Result<&str, Error> {
+    core::str::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::WriteCursor;
+
+    #[test]
+    fn nested_list_roundtrip() {
+        let inner = Type::ListOf(ListElement::new(Type::U16));
+        let outer = Type::ListOf(ListElement::new(inner));
+
+        let mut buffer = [0u8; 32];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        outer.write(&mut cursor).unwrap();
+        assert_eq!(&[0x0B, 0x0B, 0xFC], cursor.written());
+        assert_eq!(outer, Type::read(&mut cursor.written()).unwrap());
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let a = [0x00, 0x01, 0x00, 0x02];
+        let b = [];
+        let lists = [
+            Value::List {
+                element: ListElement::new(Type::U16),
+                count: 2,
+                raw: &a,
+            },
+            Value::List {
+                element: ListElement::new(Type::U16),
+                count: 0,
+                raw: &b,
+            },
+        ];
+        Value::write_list(inner, &lists, &mut cursor).unwrap();
+
+        let mut payload = cursor.written();
+        let value = Value::read(outer, &mut payload).unwrap();
+        assert!(payload.is_empty());
+
+        let mut lists = value.elements().unwrap().map(Result::unwrap);
+        let mut first = lists.next().unwrap().elements().unwrap();
+        assert_eq!(Some(Ok(Value::U16(1))), first.next());
+        assert_eq!(Some(Ok(Value::U16(2))), first.next());
+        assert_eq!(None, first.next());
+        assert_eq!(0, lists.next().unwrap().elements().unwrap().count());
+        assert!(lists.next().is_none());
+    }
+
+    #[test]
+    fn values_longer_than_the_length_prefix_are_rejected() {
+        let long = [b'a'; 256];
+        let long = core::str::from_utf8(&long).unwrap();
+        let mut buffer = [0u8; 300];
+        for value in [
+            Value::DynString(long),
+            Value::String(long),
+            Value::DynBytes(long.as_bytes()),
+            Value::Bytes(long.as_bytes()),
+        ]
+        .iter()
+        {
+            let mut cursor = WriteCursor::new(&mut buffer);
+            assert_eq!(Err(Error::ValueTooLong), value.write(&mut cursor));
+            assert_eq!(0, cursor.position());
+        }
+
+        let mut cursor = WriteCursor::new(&mut buffer);
+        assert_eq!(
+            256,
+            Value::DynString(&long[1..]).write(&mut cursor).unwrap()
+        );
+
+        let values = [Value::U8(0); 256];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        assert_eq!(
+            Err(Error::ValueTooLong),
+            Value::write_list(Type::U8, &values, &mut cursor)
+        );
+        assert_eq!(
+            Ok(256),
+            Value::write_list(Type::U8, &values[1..], &mut cursor)
+        );
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        let encoded = [0x0B, 0x0B, 0x0B, 0x0B, 0x0B, 0x0B, 0x0B, 0xFC];
+        assert!(Type::read(&mut &encoded[1..]).is_ok());
+        assert_eq!(Err(Error::NestingTooDeep), Type::read(&mut &encoded[..]));
+
+        // the innermost type has a parameter and the element no longer fits
+        let encoded = [0x0B, 0x0B, 0x0B, 0x0B, 0x0B, 0x0B, 0x01, 0x08];
+        assert!(Type::read(&mut &encoded[1..]).is_ok());
+        assert_eq!(Err(Error::NestingTooDeep), Type::read(&mut &encoded[..]));
+    }
+
+    #[test]
+    fn counter_delta_wraps_around() {
+        assert_eq!(5, counter_delta(10, 15));
+        assert_eq!(3, counter_delta(u64::MAX - 1, 1));
+    }
+}